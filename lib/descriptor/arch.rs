@@ -84,7 +84,7 @@ impl Arch {
             })
         }) {
             return Some(arch);
-        };
+        }
 
         /*
             HACK: If nothing else matched, but the search string contains "universal",
@@ -114,6 +114,18 @@ impl Arch {
         Some(parse_executable(binary_contents)?.1)
     }
 
+    /**
+        Get all keywords that may identify this architecture in an artifact name.
+    */
+    pub(crate) fn keywords(self) -> impl Iterator<Item = &'static str> {
+        ARCH_SUBSTRINGS
+            .into_iter()
+            .chain(ARCH_FULL_WORDS)
+            .filter(move |(arch, _)| *arch == self)
+            .flat_map(|(_, keywords)| keywords.iter().copied())
+            .chain(std::iter::once(self.as_str()))
+    }
+
     /**
         Get the architecture as a string, such as "x64" or "arm64".
     */
@@ -137,10 +149,7 @@ mod tests {
 
     #[test]
     fn substrings_and_words_are_lowercase() {
-        for (arch, keywords) in ARCH_SUBSTRINGS
-            .into_iter()
-            .chain(ARCH_FULL_WORDS.into_iter())
-        {
+        for (arch, keywords) in ARCH_SUBSTRINGS.into_iter().chain(ARCH_FULL_WORDS) {
             for keyword in keywords {
                 assert_eq!(
                     keyword.to_string(),
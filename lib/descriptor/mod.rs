@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, str::FromStr};
+use std::{cmp::Ordering, fmt, str::FromStr};
 
 use thiserror::Error;
 
@@ -132,6 +132,22 @@ impl Descriptor {
             )
     }
 
+    /**
+        Check if running an executable with the other description on this system
+        requires emulation, which is currently only the case for x64 (Intel)
+        executables running on macOS Apple Silicon through Rosetta 2.
+
+        Emulated executables are compatible, but will generally run slower
+        than native ones, and are sorted last by [`Descriptor::sort_by_preferred_compat`].
+    */
+    #[must_use]
+    pub fn requires_emulation(&self, other: &Descriptor) -> bool {
+        matches!(
+            (self.os, other.os, self.arch, other.arch),
+            (OS::MacOS, OS::MacOS, Some(Arch::Arm64), Some(Arch::X64))
+        )
+    }
+
     /**
         Sort two descriptions by their preferred order, compared to this description.
 
@@ -153,6 +169,16 @@ impl Descriptor {
             return Ordering::Greater;
         }
 
+        // Prefer binaries built for the same libc as the current system, since
+        // musl binaries on glibc systems (and vice versa) may fail to run
+        if let Some(current) = self.toolchain.filter(|t| t.is_libc()) {
+            let a_libc = libc_preference(current, a.toolchain);
+            let b_libc = libc_preference(current, b.toolchain);
+            if a_libc != b_libc {
+                return a_libc.cmp(&b_libc);
+            }
+        }
+
         // Sort by preferred architecture or toolchain
         if a.arch != b.arch {
             return a.arch.cmp(&b.arch);
@@ -166,6 +192,15 @@ impl Descriptor {
     }
 }
 
+// Lower is better - matching libc first, then unknown, then any other libc
+fn libc_preference(current: Toolchain, other: Option<Toolchain>) -> u8 {
+    match other {
+        Some(t) if t == current => 0,
+        Some(t) if t.is_libc() => 2,
+        _ => 1,
+    }
+}
+
 impl FromStr for Descriptor {
     type Err = DescriptionParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -181,6 +216,19 @@ impl FromStr for Descriptor {
     }
 }
 
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.os.as_str())?;
+        if let Some(arch) = self.arch {
+            write!(f, "-{}", arch.as_str())?;
+        }
+        if let Some(toolchain) = self.toolchain {
+            write!(f, "-{}", toolchain.as_str())?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,6 +241,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_description() {
+        let display = |s: &str| s.parse::<Descriptor>().unwrap().to_string();
+        assert_eq!(display("x86_64-pc-windows-msvc"), "windows-x64-msvc");
+        assert_eq!(display("aarch64-apple-darwin"), "macos-arm64");
+        assert_eq!(display("linux"), "linux");
+    }
+
     #[test]
     fn current_description() {
         let current = Descriptor::current_system();
@@ -296,6 +352,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rosetta_compatibility() {
+        let desc = |os, arch| Descriptor {
+            os,
+            arch: Some(arch),
+            toolchain: None,
+        };
+        let mac_arm = desc(OS::MacOS, Arch::Arm64);
+        let mac_x64 = desc(OS::MacOS, Arch::X64);
+        let linux_arm = desc(OS::Linux, Arch::Arm64);
+        let linux_x64 = desc(OS::Linux, Arch::X64);
+
+        // x64 binaries can run on Apple Silicon, but only through emulation
+        assert!(mac_arm.is_compatible_with(&mac_x64));
+        assert!(mac_arm.requires_emulation(&mac_x64));
+        assert!(!mac_arm.requires_emulation(&mac_arm));
+        assert!(!mac_x64.is_compatible_with(&mac_arm));
+
+        // Native binaries must always be preferred over emulated ones
+        assert_eq!(
+            mac_arm.sort_by_preferred_compat(&mac_x64, &mac_arm),
+            Ordering::Greater
+        );
+
+        // Linux has no such fallback
+        assert!(!linux_arm.is_compatible_with(&linux_x64));
+        assert!(!linux_arm.requires_emulation(&linux_x64));
+    }
+
+    #[test]
+    fn sort_by_libc_preference() {
+        let system = |toolchain| Descriptor {
+            os: OS::Linux,
+            arch: Some(Arch::X64),
+            toolchain: Some(toolchain),
+        };
+        let gnu = system(Toolchain::Gnu);
+        let musl = system(Toolchain::Musl);
+        let unknown = Descriptor {
+            toolchain: None,
+            ..gnu
+        };
+
+        // musl systems should prefer musl binaries
+        assert_eq!(musl.sort_by_preferred_compat(&musl, &gnu), Ordering::Less);
+        assert_eq!(
+            musl.sort_by_preferred_compat(&unknown, &musl),
+            Ordering::Greater
+        );
+
+        // glibc systems should avoid musl binaries
+        assert_eq!(gnu.sort_by_preferred_compat(&musl, &gnu), Ordering::Greater);
+        assert_eq!(
+            gnu.sort_by_preferred_compat(&musl, &unknown),
+            Ordering::Greater
+        );
+        assert_eq!(gnu.sort_by_preferred_compat(&gnu, &unknown), Ordering::Less);
+    }
+
     #[test]
     fn detect_description_universal() {
         // macOS universal binaries should parse as x64 (most compatible)
@@ -74,7 +74,7 @@ impl OS {
             })
         }) {
             return Some(os);
-        };
+        }
 
         None
     }
@@ -89,6 +89,18 @@ impl OS {
         Some(parse_executable(binary_contents)?.0)
     }
 
+    /**
+        Get all keywords that may identify this operating system in an artifact name.
+    */
+    pub(crate) fn keywords(self) -> impl Iterator<Item = &'static str> {
+        OS_SUBSTRINGS
+            .into_iter()
+            .chain(OS_FULL_WORDS)
+            .filter(move |(os, _)| *os == self)
+            .flat_map(|(_, keywords)| keywords.iter().copied())
+            .chain(std::iter::once(self.as_str()))
+    }
+
     /**
         Get the name of the operating system as a string.
     */
@@ -111,7 +123,7 @@ mod tests {
 
     #[test]
     fn substrings_and_words_are_lowercase() {
-        for (os, keywords) in OS_SUBSTRINGS.into_iter().chain(OS_FULL_WORDS.into_iter()) {
+        for (os, keywords) in OS_SUBSTRINGS.into_iter().chain(OS_FULL_WORDS) {
             for keyword in keywords {
                 assert_eq!(
                     keyword.to_string(),
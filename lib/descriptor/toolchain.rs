@@ -1,3 +1,9 @@
+use std::{fs, path::Path, process::Command};
+
+use once_cell::sync::OnceCell;
+
+static CURRENT_TOOLCHAIN: OnceCell<Option<Toolchain>> = OnceCell::new();
+
 #[rustfmt::skip]
 const TOOLCHAIN_KEYWORDS: [(Toolchain, &[&str]); 3] = [
     (Toolchain::Msvc, &["msvc"]),
@@ -19,10 +25,30 @@ pub enum Toolchain {
 impl Toolchain {
     /**
         Get the toolchain of the current host system.
+
+        On Linux, this detects the host libc, returning either [`Toolchain::Musl`]
+        or [`Toolchain::Gnu`]. Detection is only done once, and then cached.
+
+        Other operating systems currently have no detected toolchain.
     */
     #[must_use]
     pub fn current_system() -> Option<Self> {
-        None // TODO: Implement detection of the host toolchain
+        *CURRENT_TOOLCHAIN.get_or_init(|| {
+            if cfg!(target_os = "linux") {
+                Some(detect_linux_libc())
+            } else {
+                None // TODO: Implement detection of the host toolchain
+            }
+        })
+    }
+
+    /**
+        Check if this toolchain is a libc implementation, meaning
+        binaries built for it can only run on systems using it.
+    */
+    #[must_use]
+    pub fn is_libc(self) -> bool {
+        matches!(self, Self::Gnu | Self::Musl)
     }
 
     /**
@@ -53,6 +79,44 @@ impl Toolchain {
     }
 }
 
+/*
+    Detects the libc used by the current Linux system, checking the
+    cheapest indicators first before spawning `ldd` as a last resort.
+
+    If no musl indicators were found, we assume the system uses glibc,
+    which is by far the most common, and is what `ldd` reports otherwise.
+*/
+fn detect_linux_libc() -> Toolchain {
+    if Path::new("/etc/alpine-release").exists() {
+        return Toolchain::Musl;
+    }
+
+    // The musl dynamic loader is named after the architecture, such as ld-musl-x86_64.so.1
+    let has_musl_loader = fs::read_dir("/lib").is_ok_and(|entries| {
+        entries
+            .filter_map(Result::ok)
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("ld-musl-"))
+    });
+    if has_musl_loader {
+        return Toolchain::Musl;
+    }
+
+    // NOTE: musl ldd prints its version to stderr, and exits with a non-zero code
+    let ldd_mentions_musl = Command::new("ldd")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            stdout.to_lowercase().contains("musl") || stderr.to_lowercase().contains("musl")
+        });
+    if ldd_mentions_musl {
+        Toolchain::Musl
+    } else {
+        Toolchain::Gnu
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::uninlined_format_args)]
@@ -84,6 +148,18 @@ mod tests {
         assert_eq!(Toolchain::detect("musl-gcc"), Some(Toolchain::Musl));
     }
 
+    #[test]
+    fn current_toolchain() {
+        let toolchain = Toolchain::current_system();
+        if cfg!(target_os = "linux") {
+            assert!(toolchain.is_some_and(Toolchain::is_libc));
+        } else {
+            assert_eq!(toolchain, None);
+        }
+        // Detection is cached, and must be consistent
+        assert_eq!(toolchain, Toolchain::current_system());
+    }
+
     #[test]
     fn detect_toolchain_invalid() {
         assert_eq!(Toolchain::detect("unknown"), None);
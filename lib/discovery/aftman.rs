@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use serde::Deserialize;
 
-use crate::tool::{ToolAlias, ToolSpec};
+use crate::tool::{ToolAlias, ToolSpec, ToolSpecReq};
 
 use super::Manifest;
 
@@ -35,10 +35,10 @@ impl Manifest for AftmanManifest {
         toml::from_str(contents).ok()
     }
 
-    fn into_tools(self) -> HashMap<ToolAlias, ToolSpec> {
+    fn into_tools(self) -> HashMap<ToolAlias, ToolSpecReq> {
         self.tools
             .into_iter()
-            .map(|(alias, tool)| (alias.0, tool.0))
+            .map(|(alias, tool)| (alias.0, tool.0.into()))
             .collect()
     }
 }
@@ -0,0 +1,121 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::BuildHasher,
+};
+
+use crate::tool::{ToolAlias, ToolSpec, ToolSpecReq};
+
+use super::{DiscoveredManifest, ToolSource};
+
+/**
+    Groups the aliases of all tools in the given manifests by the tool spec
+    that they use, so that each tool spec only needs to be downloaded and
+    installed once, no matter how many aliases - in one or several manifests -
+    use it, while still knowing every alias that must be linked for it.
+
+    Manifests are ordered by precedence, and only the first tool found for each alias
+    is used. Version requirements use the tool spec they were resolved to, and aliases
+    with unresolved requirements, or for locally built tools, are not included.
+*/
+#[must_use]
+pub fn group_aliases_by_spec<S: BuildHasher>(
+    manifests: &[DiscoveredManifest],
+    resolved: &HashMap<ToolSpecReq, ToolSpec, S>,
+) -> BTreeMap<ToolSpec, BTreeSet<ToolAlias>> {
+    let mut seen = HashSet::new();
+    let mut grouped = BTreeMap::<ToolSpec, BTreeSet<ToolAlias>>::new();
+    for manifest in manifests {
+        for (alias, entry) in &manifest.entries {
+            if !seen.insert(alias) {
+                continue;
+            }
+            let spec = match &entry.source {
+                ToolSource::Spec(spec) => Some(spec),
+                ToolSource::Requirement(requirement) => resolved.get(requirement),
+                ToolSource::Local(_) => None,
+            };
+            if let Some(spec) = spec {
+                grouped
+                    .entry(spec.clone())
+                    .or_default()
+                    .insert(alias.clone());
+            }
+        }
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    use crate::discovery::{ManifestKind, ToolEntry};
+
+    fn entry(requirement: &str) -> ToolEntry {
+        ToolEntry::from_requirement(requirement.parse().unwrap())
+    }
+
+    fn manifest(tools: &[(&str, &str)], requirements: &[(&str, &str)]) -> DiscoveredManifest {
+        DiscoveredManifest {
+            _kind: ManifestKind::Rokit,
+            path: PathBuf::from("rokit.toml"),
+            aliases: Vec::new(),
+            entries: tools
+                .iter()
+                .chain(requirements)
+                .map(|(alias, req)| (alias.parse().unwrap(), entry(req)))
+                .collect(),
+        }
+    }
+
+    fn aliases(names: &[&str]) -> BTreeSet<ToolAlias> {
+        names.iter().map(|name| name.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn multiple_aliases_share_one_spec() {
+        let manifests = [
+            manifest(
+                &[("tool", "author/tool@1.0.0"), ("t", "author/tool@1.0.0")],
+                &[("tool-latest", "author/tool@^1")],
+            ),
+            manifest(&[("other", "author/tool@1.0.0")], &[]),
+        ];
+        let resolved = HashMap::from([(
+            "author/tool@^1".parse().unwrap(),
+            "author/tool@1.0.0".parse().unwrap(),
+        )]);
+        let grouped = group_aliases_by_spec(&manifests, &resolved);
+        assert_eq!(grouped.len(), 1);
+        assert_eq!(
+            grouped[&"author/tool@1.0.0".parse::<ToolSpec>().unwrap()],
+            aliases(&["other", "t", "tool", "tool-latest"])
+        );
+    }
+
+    #[test]
+    fn closest_manifest_decides_spec() {
+        let manifests = [
+            manifest(
+                &[("tool", "author/tool@2.0.0")],
+                &[("pending", "author/x@^1")],
+            ),
+            manifest(
+                &[("tool", "author/tool@1.0.0"), ("old", "author/tool@1.0.0")],
+                &[],
+            ),
+        ];
+        let grouped = group_aliases_by_spec(&manifests, &HashMap::new());
+        assert_eq!(
+            grouped[&"author/tool@2.0.0".parse::<ToolSpec>().unwrap()],
+            aliases(&["tool"])
+        );
+        assert_eq!(
+            grouped[&"author/tool@1.0.0".parse::<ToolSpec>().unwrap()],
+            aliases(&["old"])
+        );
+        assert_eq!(grouped.len(), 2, "unresolved requirements are not included");
+    }
+}
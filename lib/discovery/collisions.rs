@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+use thiserror::Error;
+
+use crate::tool::ToolAlias;
+
+use super::DiscoveredManifest;
+
+/**
+    Tool aliases that differ only by case, such as `Tool` and `tool`.
+
+    Aliases are not case sensitive, but links to tools are created using the
+    original casing of their alias. On case-insensitive file systems, which are
+    the default on macOS and Windows, these aliases would use the same link,
+    and one tool would silently replace the other.
+*/
+#[derive(Debug, Clone, Error)]
+pub struct AliasCollision {
+    pub aliases: Vec<(ToolAlias, PathBuf)>,
+}
+
+impl fmt::Display for AliasCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tool aliases differ only by case:")?;
+        for (alias, path) in &self.aliases {
+            write!(f, "\n  - '{}' in '{}'", alias.name(), path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/**
+    Finds all tool aliases in the given manifests that differ only by case,
+    both within a single manifest and across several manifests.
+
+    Aliases with the exact same casing in several manifests are not
+    collisions, since the closest manifest simply takes precedence.
+*/
+#[must_use]
+pub fn find_alias_collisions(manifests: &[DiscoveredManifest]) -> Vec<AliasCollision> {
+    let mut by_uncased = BTreeMap::<String, Vec<(ToolAlias, PathBuf)>>::new();
+    for manifest in manifests {
+        for alias in &manifest.aliases {
+            let entries = by_uncased
+                .entry(alias.name().to_ascii_lowercase())
+                .or_default();
+            let is_duplicate = entries
+                .iter()
+                .any(|(a, path)| a.name() == alias.name() && path == &manifest.path);
+            if !is_duplicate {
+                entries.push((alias.clone(), manifest.path.clone()));
+            }
+        }
+    }
+    by_uncased
+        .into_values()
+        .filter(|entries| {
+            entries
+                .iter()
+                .any(|(alias, _)| alias.name() != entries[0].0.name())
+        })
+        .map(|aliases| AliasCollision { aliases })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::discovery::load_manifests;
+
+    #[tokio::test]
+    async fn finds_aliases_differing_by_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("rokit.toml");
+        let global = dir.path().join("global.toml");
+        std::fs::write(
+            &project,
+            "[tools]\nTool = \"author/tool@1.0.0\"\ntool = \"author/other@1.0.0\"\nsame = \"author/same@1.0.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &global,
+            "[tools]\nsame = \"author/same@2.0.0\"\nOTHER = \"author/x@1.0.0\"\nother = \"author/x@1.0.0\"\n",
+        )
+        .unwrap();
+
+        let manifests = load_manifests([&project, &global]).await.unwrap();
+        let collisions = find_alias_collisions(&manifests);
+        assert_eq!(collisions.len(), 2);
+        let names = |collision: &AliasCollision| {
+            collision
+                .aliases
+                .iter()
+                .map(|(alias, _)| alias.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&collisions[0]), ["OTHER", "other"]);
+        assert_eq!(names(&collisions[1]), ["Tool", "tool"]);
+        assert!(collisions[1]
+            .aliases
+            .iter()
+            .all(|(_, path)| path == &project));
+    }
+}
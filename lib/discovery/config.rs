@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use crate::{
+    manifests::{RokitConfig, ROKIT_PROJECT_CONFIG_DIR_NAME},
+    result::RokitResult,
+    storage::Home,
+    system::current_dir,
+};
+
+/**
+    Discovers the config to use, by merging any `.rokit/config.toml` files in the
+    current directory and its ancestors with the config in the Rokit home directory.
+
+    Configs closer to the current directory take precedence, and the
+    config in the Rokit home directory has the lowest precedence.
+
+    # Errors
+
+    - If any config file could not be read, or contains unknown keys or invalid values.
+*/
+pub async fn discover_config(home: &Home) -> RokitResult<RokitConfig> {
+    let cwd = current_dir().await;
+
+    let mut dirs = cwd
+        .ancestors()
+        .map(|dir| dir.join(ROKIT_PROJECT_CONFIG_DIR_NAME))
+        .filter(|dir| dir.as_path() != home.path())
+        .collect::<Vec<PathBuf>>();
+    dirs.push(home.path().to_path_buf());
+
+    let mut config = RokitConfig::default();
+    for dir in dirs {
+        if let Some(found) = RokitConfig::load(&dir).await? {
+            config = config.merge_lower_precedence(found);
+        }
+    }
+
+    Ok(config)
+}
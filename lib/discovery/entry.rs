@@ -0,0 +1,106 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    manifests::{ToolEnvVar, ToolPostInstall, ToolVerify},
+    tool::{ToolSpec, ToolSpecReq},
+};
+
+/**
+    Where the binary for a tool in a manifest comes from.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolSource {
+    /// A release with an exact version, such as `author/name@1.2.3`.
+    Spec(ToolSpec),
+    /// A release with a version requirement, such as `^1.2` or `latest`,
+    /// which must first be resolved to an exact version before being used.
+    Requirement(ToolSpecReq),
+    /// A locally built binary, such as during development of the tool itself.
+    /// The path is already resolved relative to the manifest it was found in.
+    Local(PathBuf),
+}
+
+/**
+    A tool in a discovered manifest, along with all of its settings.
+
+    Settings that are only used when installing a release, such as the binary
+    to extract or the minisign key to verify signatures with, are always `None`
+    for locally built tools, as well as for tools in manifests that do not
+    support any settings, such as Aftman and Foreman manifests.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolEntry {
+    pub source: ToolSource,
+    pub binary: Option<String>,
+    pub minisign_key: Option<String>,
+    pub artifact_pattern: Option<String>,
+    pub tag_prefix: Option<String>,
+    pub verify: Option<ToolVerify>,
+    pub post_install: Option<ToolPostInstall>,
+    pub env: BTreeMap<String, ToolEnvVar>,
+}
+
+impl ToolEntry {
+    pub(super) fn new(source: ToolSource) -> Self {
+        Self {
+            source,
+            binary: None,
+            minisign_key: None,
+            artifact_pattern: None,
+            tag_prefix: None,
+            verify: None,
+            post_install: None,
+            env: BTreeMap::new(),
+        }
+    }
+
+    /*
+        Exact versions are split out from other requirements
+        since they can be used directly, without resolving them.
+    */
+    pub(super) fn from_requirement(requirement: ToolSpecReq) -> Self {
+        Self::new(match requirement.exact() {
+            Some(spec) => ToolSource::Spec(spec),
+            None => ToolSource::Requirement(requirement),
+        })
+    }
+
+    /**
+        Gets the exact tool spec, if the tool is a release with an exact version.
+    */
+    #[must_use]
+    pub fn spec(&self) -> Option<&ToolSpec> {
+        match &self.source {
+            ToolSource::Spec(spec) => Some(spec),
+            _ => None,
+        }
+    }
+
+    /**
+        Gets the version requirement, if the tool is a release.
+
+        Releases with exact versions are returned as exact requirements.
+    */
+    #[must_use]
+    pub fn requirement(&self) -> Option<ToolSpecReq> {
+        match &self.source {
+            ToolSource::Spec(spec) => Some(spec.clone().into()),
+            ToolSource::Requirement(requirement) => Some(requirement.clone()),
+            ToolSource::Local(_) => None,
+        }
+    }
+
+    /**
+        Gets the path to the locally built binary, if the tool uses one.
+    */
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        match &self.source {
+            ToolSource::Local(path) => Some(path),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{manifests::ToolEnvVar, tool::ToolAlias};
+
+/**
+    Environment variables to set when running a tool, merged from all manifests.
+
+    Variables are applied with the following precedence, highest first:
+
+    1. Variables declared with `override = true`, in any manifest
+    2. Variables already set in the environment Rokit is running in
+    3. Variables declared in project manifests, closest to the current directory first
+    4. Variables declared in the global manifest
+*/
+#[derive(Debug, Clone)]
+pub struct ToolEnv {
+    alias: ToolAlias,
+    vars: BTreeMap<String, (ToolEnvVar, PathBuf)>,
+}
+
+impl ToolEnv {
+    pub(super) fn new(alias: ToolAlias) -> Self {
+        Self {
+            alias,
+            vars: BTreeMap::new(),
+        }
+    }
+
+    /*
+        Manifests are merged in order of precedence, so any
+        variable that is already present must not be replaced.
+    */
+    pub(super) fn merge_lower_precedence(
+        &mut self,
+        manifest_path: &Path,
+        vars: BTreeMap<String, ToolEnvVar>,
+    ) {
+        for (name, var) in vars {
+            self.vars
+                .entry(name)
+                .or_insert_with(|| (var, manifest_path.to_path_buf()));
+        }
+    }
+
+    /**
+        Checks if no environment variables were declared for the tool.
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    /**
+        Resolves the variables to set for the tool, given a function
+        to read variables from the existing environment.
+
+        Returns the variables to set, along with any conflicts where the existing
+        environment has a different value than a manifest and therefore takes precedence.
+    */
+    #[must_use]
+    pub fn resolve<F>(&self, get_existing: F) -> (Vec<(String, String)>, Vec<ToolEnvConflict>)
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        let mut vars = Vec::new();
+        let mut conflicts = Vec::new();
+        for (name, (var, manifest_path)) in &self.vars {
+            match get_existing(name) {
+                Some(existing) if !var.override_existing => {
+                    if existing != OsString::from(&var.value) {
+                        conflicts.push(ToolEnvConflict {
+                            alias: self.alias.clone(),
+                            name: name.clone(),
+                            manifest_path: manifest_path.clone(),
+                        });
+                    }
+                }
+                _ => vars.push((name.clone(), var.value.clone())),
+            }
+        }
+        (vars, conflicts)
+    }
+}
+
+/**
+    A conflict between an environment variable declared for a tool
+    in a manifest, and the same variable in the existing environment.
+*/
+#[derive(Debug, Clone, Error)]
+#[error(
+    "environment variable '{name}' for tool '{alias}' is declared in '{}', \
+    but is already set to a different value - the existing value will be used\
+    \nprecedence, highest first: manifest variables with `override = true`, \
+    the existing environment, project manifests, the global manifest\
+    \nto always use the manifest value, declare it as `{name} = {{ value = \"...\", override = true }}`",
+    manifest_path.display()
+)]
+pub struct ToolEnvConflict {
+    pub alias: ToolAlias,
+    pub name: String,
+    pub manifest_path: PathBuf,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(value: &str, override_existing: bool) -> ToolEnvVar {
+        ToolEnvVar {
+            value: value.to_string(),
+            override_existing,
+        }
+    }
+
+    fn tool_env() -> ToolEnv {
+        let mut env = ToolEnv::new("tool".parse().unwrap());
+        env.merge_lower_precedence(
+            Path::new("project/rokit.toml"),
+            BTreeMap::from([
+                (String::from("CONFIG"), var("project.toml", false)),
+                (String::from("MODE"), var("ci", true)),
+            ]),
+        );
+        env.merge_lower_precedence(
+            Path::new("global/rokit.toml"),
+            BTreeMap::from([
+                (String::from("CONFIG"), var("global.toml", false)),
+                (String::from("GLOBAL"), var("yes", false)),
+            ]),
+        );
+        env
+    }
+
+    #[test]
+    fn project_overrides_global() {
+        let (vars, conflicts) = tool_env().resolve(|_| None);
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            vars,
+            vec![
+                (String::from("CONFIG"), String::from("project.toml")),
+                (String::from("GLOBAL"), String::from("yes")),
+                (String::from("MODE"), String::from("ci")),
+            ]
+        );
+    }
+
+    #[test]
+    fn existing_env_wins_unless_overridden() {
+        let (vars, conflicts) = tool_env().resolve(|name| match name {
+            "CONFIG" | "MODE" => Some(OsString::from("existing")),
+            "GLOBAL" => Some(OsString::from("yes")),
+            _ => None,
+        });
+        assert_eq!(vars, vec![(String::from("MODE"), String::from("ci"))]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].name, "CONFIG");
+        assert!(conflicts[0].to_string().contains("precedence"));
+    }
+}
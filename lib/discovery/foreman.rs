@@ -3,7 +3,10 @@ use std::{collections::HashMap, str::FromStr};
 use semver::Version;
 use toml_edit::{DocumentMut, InlineTable, Table};
 
-use crate::tool::{ToolAlias, ToolId, ToolSpec};
+use crate::{
+    sources::ArtifactProvider,
+    tool::{ToolAlias, ToolId, ToolSpec, ToolSpecReq},
+};
 
 use super::Manifest;
 
@@ -35,7 +38,7 @@ impl Manifest for ForemanManifest {
             .ok()
     }
 
-    fn into_tools(self) -> HashMap<ToolAlias, ToolSpec> {
+    fn into_tools(self) -> HashMap<ToolAlias, ToolSpecReq> {
         let mut tools = HashMap::new();
         if let Some(map) = self.document.get("tools").and_then(|t| t.as_table()) {
             for (alias, tool_def) in map {
@@ -54,7 +57,7 @@ impl Manifest for ForemanManifest {
                 };
 
                 if let (Some(alias), Some(spec)) = (tool_alias, tool_spec) {
-                    tools.insert(alias, spec);
+                    tools.insert(alias, spec.into());
                 }
             }
         }
@@ -73,11 +76,20 @@ fn parse_foreman_tool_definition(map: SpecType) -> Option<ToolSpec> {
         let without_prefix = v.trim_start_matches('=').trim_start_matches('^');
         without_prefix.parse::<Version>().ok()
     })?;
-    // TODO: Support gitlab tool ids
     let github_tool_id = map
         .get("github")
         .or(map.get("source"))
         .and_then(|t| t.as_str())
         .and_then(|s| s.parse::<ToolId>().ok());
-    github_tool_id.map(|id| (id, version).into())
+    let gitlab_tool_id = map
+        .get("gitlab")
+        .and_then(|t| t.as_str())
+        .and_then(|s| s.parse::<ToolId>().ok())
+        .map(|mut id| {
+            id.provider = ArtifactProvider::GitLab;
+            id
+        });
+    github_tool_id
+        .or(gitlab_tool_id)
+        .map(|id| (id, version).into())
 }
@@ -1,24 +1,39 @@
 use std::{
     collections::HashMap,
     env::var_os,
+    io::ErrorKind,
     path::{Path, PathBuf},
 };
 
 use futures::{stream::FuturesOrdered, StreamExt};
-use tokio::fs::read_to_string;
+use tokio::fs::{metadata, read_to_string};
 
 use crate::{
     manifests::RokitManifest,
+    result::{RokitError, RokitResult},
     storage::Home,
     system::current_dir,
-    tool::{ToolAlias, ToolSpec},
+    tool::{ToolAlias, ToolSpec, ToolSpecReq},
 };
 
 use self::{aftman::AftmanManifest, foreman::ForemanManifest};
 
 mod aftman;
+mod aliases;
+mod collisions;
+mod config;
+mod entry;
+mod env;
 mod foreman;
 mod rokit;
+mod shadowed;
+
+pub use self::aliases::group_aliases_by_spec;
+pub use self::collisions::{find_alias_collisions, AliasCollision};
+pub use self::config::discover_config;
+pub use self::entry::{ToolEntry, ToolSource};
+pub use self::env::{ToolEnv, ToolEnvConflict};
+pub use self::shadowed::{find_shadowed_tools, ShadowedTool};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum ManifestKind {
@@ -34,19 +49,113 @@ where
     fn home_dir() -> &'static str;
     fn manifest_file_name() -> &'static str;
     fn parse_manifest(contents: &str) -> Option<Self>;
-    fn into_tools(self) -> HashMap<ToolAlias, ToolSpec>;
+    fn into_tools(self) -> HashMap<ToolAlias, ToolSpecReq>;
+    fn tool_aliases(&self) -> Vec<ToolAlias> {
+        Vec::new()
+    }
+    fn into_entries(self) -> HashMap<ToolAlias, ToolEntry> {
+        self.into_tools()
+            .into_iter()
+            .map(|(alias, requirement)| (alias, ToolEntry::from_requirement(requirement)))
+            .collect()
+    }
+}
+
+struct ParsedTools {
+    aliases: Vec<ToolAlias>,
+    entries: HashMap<ToolAlias, ToolEntry>,
+}
+
+fn parse_tools<M: Manifest>(manifest_path: &Path, contents: &str) -> Option<ParsedTools> {
+    let manifest = M::parse_manifest(contents)?;
+    let aliases = manifest.tool_aliases();
+    let mut entries = manifest.into_entries();
+    for entry in entries.values_mut() {
+        if let ToolSource::Local(local) = &mut entry.source {
+            *local = resolve_local_path(manifest_path, local);
+        }
+    }
+    // NOTE: Manifests that do not keep aliases differing only by case
+    // have already merged them, so their tool keys are used instead
+    let aliases = if aliases.is_empty() {
+        entries.keys().cloned().collect()
+    } else {
+        aliases
+    };
+    Some(ParsedTools { aliases, entries })
+}
+
+/*
+    Paths to locally built tools are relative to the directory containing
+    the manifest, so that manifests can be moved along with their projects.
+*/
+fn resolve_local_path(manifest_path: &Path, local_path: &Path) -> PathBuf {
+    match manifest_path.parent() {
+        Some(dir) => dir.join(local_path),
+        None => local_path.to_path_buf(),
+    }
+}
+
+fn parse_manifest_tools(kind: ManifestKind, path: &Path, contents: &str) -> Option<ParsedTools> {
+    match kind {
+        ManifestKind::Rokit => parse_tools::<RokitManifest>(path, contents),
+        ManifestKind::Aftman => parse_tools::<AftmanManifest>(path, contents),
+        ManifestKind::Foreman => parse_tools::<ForemanManifest>(path, contents),
+    }
 }
 
 /**
     A discovered manifest.
 
     Contains tools as well as the path where the manifest was found.
+
+    Each tool is stored in `entries` along with all of its settings,
+    see [`ToolEntry`] for more information. Paths to locally built
+    tools are already resolved relative to the manifest.
+
+    The aliases of all tools are also stored in `aliases`, in their original
+    casing, including any aliases that differ only by case - which are
+    otherwise considered the same alias, and only one of them is kept.
 */
 #[derive(Debug, Clone)]
 pub struct DiscoveredManifest {
     _kind: ManifestKind,
     pub path: PathBuf,
-    pub tools: HashMap<ToolAlias, ToolSpec>,
+    pub aliases: Vec<ToolAlias>,
+    pub entries: HashMap<ToolAlias, ToolEntry>,
+}
+
+impl DiscoveredManifest {
+    /**
+        Checks if the manifest contains a tool with the given alias,
+        either with an exact version, a version requirement, or a local path.
+    */
+    #[must_use]
+    pub fn has_alias(&self, alias: &ToolAlias) -> bool {
+        self.entries.contains_key(alias)
+    }
+
+    /**
+        Gets all tools in the manifest with exact versions.
+    */
+    pub fn tools(&self) -> impl Iterator<Item = (&ToolAlias, &ToolSpec)> {
+        self.entries
+            .iter()
+            .filter_map(|(alias, entry)| Some((alias, entry.spec()?)))
+    }
+
+    /**
+        Gets all tools in the manifest with version requirements
+        such as `^1.2` or `latest`, which are not exact versions.
+    */
+    pub fn requirements(&self) -> impl Iterator<Item = (&ToolAlias, &ToolSpecReq)> {
+        self.entries
+            .iter()
+            .filter_map(|(alias, entry)| match &entry.source {
+                ToolSource::Requirement(requirement) => Some((alias, requirement)),
+                _ => None,
+            })
+    }
 }
 
 fn search_paths(cwd: &Path, rokit_only: bool, skip_home: bool) -> Vec<(ManifestKind, PathBuf)> {
@@ -72,26 +181,26 @@ fn search_paths(cwd: &Path, rokit_only: bool, skip_home: bool) -> Vec<(ManifestK
         current = dir.parent();
     }
 
-    // Gather paths from program-specific home directories, if desired
+    // Gather paths from program-specific home directories, if desired - the global
+    // Rokit manifest is the lowest priority Rokit manifest, and respects ROKIT_ROOT
     if !skip_home {
-        if let Some(home) = dirs::home_dir() {
+        if let Ok(rokit_home) = Home::path_from_env() {
             ordered_paths.push((
                 ManifestKind::Rokit,
-                home.join(RokitManifest::home_dir())
-                    .join(RokitManifest::manifest_file_name()),
+                rokit_home.join(RokitManifest::manifest_file_name()),
+            ));
+        }
+        if let Some(home) = dirs::home_dir().filter(|_| !rokit_only) {
+            ordered_paths.push((
+                ManifestKind::Aftman,
+                home.join(AftmanManifest::home_dir())
+                    .join(AftmanManifest::manifest_file_name()),
+            ));
+            ordered_paths.push((
+                ManifestKind::Foreman,
+                home.join(ForemanManifest::home_dir())
+                    .join(ForemanManifest::manifest_file_name()),
             ));
-            if !rokit_only {
-                ordered_paths.push((
-                    ManifestKind::Aftman,
-                    home.join(AftmanManifest::home_dir())
-                        .join(AftmanManifest::manifest_file_name()),
-                ));
-                ordered_paths.push((
-                    ManifestKind::Foreman,
-                    home.join(ForemanManifest::home_dir())
-                        .join(ForemanManifest::manifest_file_name()),
-                ));
-            }
         }
     }
 
@@ -101,7 +210,7 @@ fn search_paths(cwd: &Path, rokit_only: bool, skip_home: bool) -> Vec<(ManifestK
 /**
     Discovers all known tool manifests in the current directory and its ancestors, as well as home directories.
 
-    This is a slow operation that reads many potential files - use `discover_tool_spec` if possible.
+    This is a slow operation that reads many potential files - use [`discover_tool_entry`] if possible.
 */
 pub async fn discover_all_manifests(rokit_only: bool, skip_home: bool) -> Vec<DiscoveredManifest> {
     let cwd = current_dir().await;
@@ -121,31 +230,88 @@ pub async fn discover_all_manifests(rokit_only: bool, skip_home: bool) -> Vec<Di
 
     found_manifest_contents
         .into_iter()
-        .filter_map(|(kind, path, contents)| {
-            let tools = match kind {
-                ManifestKind::Rokit => RokitManifest::parse_manifest(&contents)?.into_tools(),
-                ManifestKind::Aftman => AftmanManifest::parse_manifest(&contents)?.into_tools(),
-                ManifestKind::Foreman => ForemanManifest::parse_manifest(&contents)?.into_tools(),
-            };
-            Some(DiscoveredManifest {
-                _kind: kind,
-                path,
-                tools,
-            })
-        })
+        .filter_map(|(kind, path, contents)| parse_discovered_manifest(kind, path, &contents))
         .collect()
 }
 
 /**
-    Discovers a tool spec by searching for manifests in the current directory and its ancestors.
+    Loads the tool manifests at the given paths, instead of discovering them.
+
+    Manifests are returned in the same order as the given paths, meaning that tools
+    in earlier manifests take priority over tools with the same alias in later ones,
+    same as for [`discover_all_manifests`]. Relative paths are resolved against the
+    current directory, and manifests named `aftman.toml` or `foreman.toml` are read
+    as such, while any other file name is read as a Rokit manifest.
+
+    # Errors
+
+    - If any of the manifests could not be read or parsed.
+*/
+pub async fn load_manifests(
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+) -> RokitResult<Vec<DiscoveredManifest>> {
+    let cwd = current_dir().await;
+
+    let mut manifests = Vec::new();
+    for path in paths {
+        let path = cwd.join(path.as_ref());
+        let contents = match read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(RokitError::FileNotFound(path));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let kind = manifest_kind_for_path(&path);
+        let manifest = parse_discovered_manifest(kind, path.clone(), &contents)
+            .ok_or(RokitError::InvalidManifest(path))?;
+        manifests.push(manifest);
+    }
+
+    Ok(manifests)
+}
+
+fn manifest_kind_for_path(path: &Path) -> ManifestKind {
+    let file_name = path.file_name().and_then(|name| name.to_str());
+    if file_name == Some(AftmanManifest::manifest_file_name()) {
+        ManifestKind::Aftman
+    } else if file_name == Some(ForemanManifest::manifest_file_name()) {
+        ManifestKind::Foreman
+    } else {
+        ManifestKind::Rokit
+    }
+}
+
+fn parse_discovered_manifest(
+    kind: ManifestKind,
+    path: PathBuf,
+    contents: &str,
+) -> Option<DiscoveredManifest> {
+    let parsed = parse_manifest_tools(kind, &path, contents)?;
+    Some(DiscoveredManifest {
+        _kind: kind,
+        path,
+        aliases: parsed.aliases,
+        entries: parsed.entries,
+    })
+}
+
+/**
+    Discovers a tool, along with all of its settings, by searching
+    for manifests in the current directory and its ancestors.
+
+    Returns the tool from the nearest manifest containing it, or `None`
+    if no manifest contains the tool. Note that environment variables
+    for the tool are merged from all manifests, which this does not do -
+    use [`discover_tool_env`] for those.
 
     This is a fast operation that reads only the necessary files.
 */
-pub async fn discover_tool_spec(
+pub async fn discover_tool_entry(
     alias: &ToolAlias,
     rokit_only: bool,
     skip_home: bool,
-) -> Option<ToolSpec> {
+) -> Option<ToolEntry> {
     let cwd = current_dir().await;
 
     for (kind, path) in search_paths(&cwd, rokit_only, skip_home) {
@@ -153,14 +319,56 @@ pub async fn discover_tool_spec(
             continue;
         };
 
-        let tools = match kind {
-            ManifestKind::Rokit => RokitManifest::parse_manifest(&contents)?.into_tools(),
-            ManifestKind::Aftman => AftmanManifest::parse_manifest(&contents)?.into_tools(),
-            ManifestKind::Foreman => ForemanManifest::parse_manifest(&contents)?.into_tools(),
+        let mut parsed = parse_manifest_tools(kind, &path, &contents)?;
+        if let Some(entry) = parsed.entries.remove(alias) {
+            return Some(entry);
+        }
+    }
+
+    None
+}
+
+/**
+    Discovers the environment variables to set when running a tool, by
+    searching for manifests in the current directory and its ancestors.
+
+    Variables from all manifests that declare them for the tool are merged,
+    with variables from manifests closer to the current directory taking
+    precedence, meaning that project manifests override the global manifest.
+
+    See [`ToolEnv`] for how these are merged with the current environment.
+*/
+pub async fn discover_tool_env(alias: &ToolAlias, rokit_only: bool, skip_home: bool) -> ToolEnv {
+    let cwd = current_dir().await;
+
+    let mut env = ToolEnv::new(alias.clone());
+    for (kind, path) in search_paths(&cwd, rokit_only, skip_home) {
+        let Ok(contents) = read_to_string(&path).await else {
+            continue;
+        };
+        let Some(mut parsed) = parse_manifest_tools(kind, &path, &contents) else {
+            continue;
         };
+        if let Some(entry) = parsed.entries.remove(alias) {
+            env.merge_lower_precedence(&path, entry.env);
+        }
+    }
+
+    env
+}
+
+/**
+    Discovers the directory that the lockfile for the current directory is stored
+    in, which is the directory of the closest Rokit manifest, if there is one.
 
-        if let Some(spec) = tools.get(alias) {
-            return Some(spec.clone());
+    Note that the lockfile itself may not exist yet, even if a manifest was found.
+*/
+pub async fn discover_lockfile_dir(skip_home: bool) -> Option<PathBuf> {
+    let cwd = current_dir().await;
+
+    for (_, path) in search_paths(&cwd, true, skip_home) {
+        if metadata(&path).await.is_ok_and(|meta| meta.is_file()) {
+            return path.parent().map(Path::to_path_buf);
         }
     }
 
@@ -190,3 +398,66 @@ pub async fn discover_non_rokit_tool(home: &Home, alias: &ToolAlias) -> Option<P
 
     found_tool_paths.next()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn load_manifests_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let rokit = dir.path().join("tools.toml");
+        let aftman = dir.path().join("aftman.toml");
+        std::fs::write(&rokit, "[tools]\ntool = \"author/tool@1.0.0\"\n").unwrap();
+        std::fs::write(&aftman, "[tools]\nother = \"author/other@2.0.0\"\n").unwrap();
+
+        let manifests = load_manifests([&rokit, &aftman]).await.unwrap();
+        assert_eq!(manifests.len(), 2);
+        assert_eq!(manifests[0].path, rokit);
+        assert_eq!(manifest_kind_for_path(&rokit), ManifestKind::Rokit);
+        assert_eq!(manifest_kind_for_path(&aftman), ManifestKind::Aftman);
+        assert!(manifests[1].has_alias(&"other".parse().unwrap()));
+
+        let missing = dir.path().join("missing.toml");
+        assert!(matches!(
+            load_manifests([&missing]).await,
+            Err(RokitError::FileNotFound(path)) if path == missing
+        ));
+        std::fs::write(&rokit, "[tools\n").unwrap();
+        assert!(matches!(
+            load_manifests([&rokit]).await,
+            Err(RokitError::InvalidManifest(_))
+        ));
+    }
+
+    #[test]
+    fn entries_keep_settings_and_resolve_paths() {
+        let contents = "[tools]\n\
+            exact = { spec = \"author/exact@1.0.0\", bin = \"name\", tag_prefix = \"cli-\" }\n\
+            range = \"author/range@^1\"\n\
+            local = { path = \"bin/local\", bin = \"ignored\" }\n";
+        let path = Path::new("project").join("rokit.toml");
+        let manifest = parse_discovered_manifest(ManifestKind::Rokit, path, contents).unwrap();
+        assert_eq!(manifest.entries.len(), 3);
+
+        let exact = &manifest.entries[&"exact".parse().unwrap()];
+        assert_eq!(exact.spec().unwrap().to_string(), "author/exact@1.0.0");
+        assert_eq!(exact.binary.as_deref(), Some("name"));
+        assert_eq!(exact.tag_prefix.as_deref(), Some("cli-"));
+
+        let range = &manifest.entries[&"range".parse().unwrap()];
+        assert!(range.spec().is_none());
+        assert_eq!(range.requirement().unwrap().to_string(), "author/range@^1");
+
+        let local = &manifest.entries[&"local".parse().unwrap()];
+        assert_eq!(
+            local.path(),
+            Some(Path::new("project").join("bin/local").as_path())
+        );
+        assert!(local.requirement().is_none());
+        assert!(local.binary.is_none());
+
+        assert_eq!(manifest.tools().count(), 1);
+        assert_eq!(manifest.requirements().count(), 1);
+    }
+}
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
 use crate::{
     manifests::RokitManifest,
-    tool::{ToolAlias, ToolSpec},
+    tool::{ToolAlias, ToolSpecReq},
 };
 
-use super::Manifest;
+use super::{Manifest, ToolEntry, ToolSource};
 
 impl Manifest for RokitManifest {
     fn home_dir() -> &'static str {
@@ -23,7 +23,187 @@ impl Manifest for RokitManifest {
         contents.parse().ok()
     }
 
-    fn into_tools(self) -> HashMap<ToolAlias, ToolSpec> {
-        self.tool_specs().into_iter().collect()
+    fn into_tools(self) -> HashMap<ToolAlias, ToolSpecReq> {
+        self.tool_requirements().into_iter().collect()
+    }
+
+    fn tool_aliases(&self) -> Vec<ToolAlias> {
+        RokitManifest::tool_aliases(self)
+    }
+
+    fn into_entries(self) -> HashMap<ToolAlias, ToolEntry> {
+        RokitManifest::tool_aliases(&self)
+            .into_iter()
+            .filter_map(|alias| {
+                let entry = tool_entry(&self, &alias)?;
+                Some((alias, entry))
+            })
+            .collect()
+    }
+}
+
+/*
+    Locally built tools take precedence over any spec, and
+    ignore all settings that are only used for installing.
+*/
+fn tool_entry(manifest: &RokitManifest, alias: &ToolAlias) -> Option<ToolEntry> {
+    let mut entry = if let Some(path) = manifest.get_tool_path(alias) {
+        ToolEntry::new(ToolSource::Local(PathBuf::from(path)))
+    } else {
+        let mut entry = ToolEntry::from_requirement(manifest.get_tool_requirement(alias)?);
+        entry.binary = manifest.get_tool_binary(alias);
+        entry.minisign_key = manifest.get_tool_minisign_key(alias);
+        entry.artifact_pattern = manifest.get_tool_artifact_pattern(alias);
+        entry.tag_prefix = manifest.get_tool_tag_prefix(alias);
+        entry.verify = manifest.get_tool_verify(alias);
+        entry.post_install = manifest.get_tool_post_install(alias);
+        entry
+    };
+    entry.env = manifest.get_tool_env(alias);
+    Some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::manifests::{ToolEnvVar, ToolPostInstall, ToolVerify};
+
+    use super::*;
+
+    const SPEC: &str = "author/tool@1.0.0";
+
+    fn parse_entry(tool: &str) -> Option<ToolEntry> {
+        let manifest = format!("[tools]\ntool = {tool}\n")
+            .parse::<RokitManifest>()
+            .unwrap();
+        tool_entry(&manifest, &"tool".parse().unwrap())
+    }
+
+    fn spec_entry() -> ToolEntry {
+        ToolEntry::from_requirement(SPEC.parse().unwrap())
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn tool_entry_forms() {
+        let cases = [
+            (r#""author/tool@1.0.0""#, spec_entry()),
+            (
+                r#""author/tool@^1.2""#,
+                ToolEntry::new(ToolSource::Requirement("author/tool@^1.2".parse().unwrap())),
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", bin = "tool-cli" }"#,
+                ToolEntry {
+                    binary: Some(String::from("tool-cli")),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", minisign = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3" }"#,
+                ToolEntry {
+                    minisign_key: Some(String::from(
+                        "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3",
+                    )),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", artifact = "tool-{os}64.zip" }"#,
+                ToolEntry {
+                    artifact_pattern: Some(String::from("tool-{os}64.zip")),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", tag_prefix = "cli-" }"#,
+                ToolEntry {
+                    tag_prefix: Some(String::from("cli-")),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", verify = true }"#,
+                ToolEntry {
+                    verify: Some(ToolVerify::default()),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", verify = false }"#,
+                ToolEntry {
+                    verify: Some(ToolVerify::Disabled),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", verify = "version --short" }"#,
+                ToolEntry {
+                    verify: Some(ToolVerify::Args(args(&["version", "--short"]))),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", post_install = "completions  --install" }"#,
+                ToolEntry {
+                    post_install: Some(ToolPostInstall {
+                        args: args(&["completions", "--install"]),
+                        required: false,
+                    }),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", post_install = { args = "setup", required = true } }"#,
+                ToolEntry {
+                    post_install: Some(ToolPostInstall {
+                        args: args(&["setup"]),
+                        required: true,
+                    }),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", post_install = { required = true } }"#,
+                spec_entry(),
+            ),
+            (
+                r#"{ spec = "author/tool@1.0.0", env = { CONFIG = "tool.toml", MODE = { value = "ci", override = true } } }"#,
+                ToolEntry {
+                    env: BTreeMap::from([
+                        (
+                            String::from("CONFIG"),
+                            ToolEnvVar {
+                                value: String::from("tool.toml"),
+                                override_existing: false,
+                            },
+                        ),
+                        (
+                            String::from("MODE"),
+                            ToolEnvVar {
+                                value: String::from("ci"),
+                                override_existing: true,
+                            },
+                        ),
+                    ]),
+                    ..spec_entry()
+                },
+            ),
+            (
+                r#"{ path = "../tool/target/release/tool", bin = "tool-cli" }"#,
+                ToolEntry::new(ToolSource::Local(PathBuf::from(
+                    "../tool/target/release/tool",
+                ))),
+            ),
+        ];
+
+        for (tool, expected) in cases {
+            assert_eq!(parse_entry(tool), Some(expected), "tool = {tool}");
+        }
+        assert_eq!(parse_entry(r#"{ bin = "tool-cli" }"#), None);
     }
 }
@@ -0,0 +1,135 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use thiserror::Error;
+
+use crate::tool::ToolAlias;
+
+use super::{DiscoveredManifest, ToolEntry, ToolSource};
+
+/**
+    A tool in a manifest that is shadowed by a tool with the same
+    alias in a manifest with higher precedence, and a different target.
+
+    Manifests are ordered by precedence, closest to the current
+    directory first, and only the first tool found for each alias
+    is used - meaning that project manifests override the global manifest.
+*/
+#[derive(Debug, Clone, Error)]
+#[error(
+    "tool '{alias}' is declared as '{shadowed}' in '{}', but '{used}' from '{}' will be used instead\
+    \nthe closest manifest always takes precedence, remove one of the declarations to silence this warning",
+    manifest_path.display(),
+    used_manifest_path.display()
+)]
+pub struct ShadowedTool {
+    pub alias: ToolAlias,
+    pub shadowed: String,
+    pub manifest_path: PathBuf,
+    pub used: String,
+    pub used_manifest_path: PathBuf,
+}
+
+/**
+    Finds all tools in the given manifests that are shadowed by a tool with
+    the same alias in an earlier manifest, but with a different version,
+    version requirement, or path.
+
+    Tools declared identically in several manifests are not considered shadowed.
+*/
+#[must_use]
+pub fn find_shadowed_tools(manifests: &[DiscoveredManifest]) -> Vec<ShadowedTool> {
+    let mut used = HashMap::<&ToolAlias, (String, &DiscoveredManifest)>::new();
+    let mut shadowed_tools = Vec::new();
+    for manifest in manifests {
+        let mut entries = manifest.entries.iter().collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| a.name().cmp(b.name()));
+        for (alias, entry) in entries {
+            let target = entry_target(entry);
+            match used.get(alias) {
+                None => {
+                    used.insert(alias, (target, manifest));
+                }
+                Some((used_target, used_manifest)) if *used_target != target => {
+                    shadowed_tools.push(ShadowedTool {
+                        alias: alias.clone(),
+                        shadowed: target,
+                        manifest_path: manifest.path.clone(),
+                        used: used_target.clone(),
+                        used_manifest_path: used_manifest.path.clone(),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    shadowed_tools
+}
+
+// Gets the tool that an entry points to, which is either
+// an exact version, a version requirement, or a local path
+fn entry_target(entry: &ToolEntry) -> String {
+    match &entry.source {
+        ToolSource::Spec(spec) => spec.to_string(),
+        ToolSource::Requirement(requirement) => requirement.to_string(),
+        ToolSource::Local(path) => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    use crate::discovery::ManifestKind;
+
+    fn manifest(path: &str, tools: &[(&str, &str)]) -> DiscoveredManifest {
+        DiscoveredManifest {
+            _kind: ManifestKind::Rokit,
+            path: PathBuf::from(path),
+            aliases: tools
+                .iter()
+                .map(|(alias, _)| alias.parse().unwrap())
+                .collect(),
+            entries: tools
+                .iter()
+                .map(|(alias, spec)| {
+                    let entry = ToolEntry::from_requirement(spec.parse().unwrap());
+                    (alias.parse().unwrap(), entry)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn closest_manifest_takes_precedence() {
+        let manifests = [
+            manifest("project/rokit.toml", &[("tool", "author/tool@2.0.0")]),
+            manifest(
+                "home/rokit.toml",
+                &[
+                    ("tool", "author/tool@1.0.0"),
+                    ("other", "author/other@1.0.0"),
+                ],
+            ),
+            manifest("other/rokit.toml", &[("tool", "author/tool@3.0.0")]),
+        ];
+        let shadowed = find_shadowed_tools(&manifests);
+        assert_eq!(shadowed.len(), 2);
+        for (tool, version) in shadowed.iter().zip(["1.0.0", "3.0.0"]) {
+            assert_eq!(tool.alias.name(), "tool");
+            assert_eq!(tool.shadowed, format!("author/tool@{version}"));
+            assert_eq!(tool.used, "author/tool@2.0.0");
+            assert_eq!(tool.used_manifest_path, Path::new("project/rokit.toml"));
+        }
+    }
+
+    #[test]
+    fn identical_tools_are_not_shadowed() {
+        let manifests = [
+            manifest("project/rokit.toml", &[("tool", "author/tool@1.0.0")]),
+            manifest("home/rokit.toml", &[("tool", "author/tool@1.0.0")]),
+        ];
+        assert!(find_shadowed_tools(&manifests).is_empty());
+    }
+}
@@ -0,0 +1,585 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use tokio::{process::Command, time::timeout};
+use tracing::{debug, instrument};
+use url::Url;
+
+use crate::{
+    manifests::ToolPostInstall,
+    result::{RokitError, RokitResult},
+    sources::{Artifact, ArtifactPattern, ArtifactSource, MinisignPublicKey},
+    storage::Home,
+    tool::{ToolAlias, ToolId, ToolSpec},
+};
+
+/**
+    Options for installing a tool, see [`install_spec_with_options`].
+
+    The default options install a tool using the same
+    heuristics that Rokit uses for tools in manifests.
+*/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InstallOptions<'a> {
+    /**
+        The name or path of the binary to extract from the artifact.
+    */
+    pub binary: Option<&'a str>,
+    /**
+        An alias for the tool, used as an additional name when looking for the binary.
+    */
+    pub alias: Option<&'a ToolAlias>,
+    /**
+        A pattern for the file name of the artifact to install, instead of detecting it.
+    */
+    pub artifact_pattern: Option<&'a ArtifactPattern>,
+    /**
+        A minisign public key that the artifact must be signed with.
+    */
+    pub minisign_key: Option<&'a MinisignPublicKey>,
+    /**
+        A prefix for the release tags of the tool, such as `cli-` for `cli-1.2.3`,
+        which is tried before the common `v1.2.3` and `1.2.3` release tags.
+    */
+    pub tag_prefix: Option<&'a str>,
+    /**
+        Arguments to run the installed tool with, to verify that it runs,
+        see [`verify_tool_runs`]. No verification is done if not given.
+    */
+    pub verify_args: Option<&'a [String]>,
+    /**
+        A command to run once the tool has been installed, see [`run_post_install`].
+    */
+    pub post_install: Option<&'a ToolPostInstall>,
+    /**
+        The artifacts of the release of the tool, if already fetched,
+        such as from a cached release, instead of fetching them again.
+    */
+    pub release: Option<&'a [Artifact]>,
+    /**
+        The artifact to install, if already selected, such as an artifact from a
+        lockfile, instead of finding the most compatible artifact in the release.
+
+        Artifacts that already have a checksum keep it, and are verified using it.
+    */
+    pub artifact: Option<&'a Artifact>,
+}
+
+/**
+    Progress of installing a tool, see [`install_spec_with_options`].
+
+    Stages are reported in order, once each has finished,
+    and every stage other than downloading is reported once.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallProgress {
+    /// The release of the tool was fetched.
+    FetchedRelease,
+    /// An artifact was found, and is about to be downloaded.
+    FoundArtifact,
+    /// Bytes downloaded so far, and the total number of bytes, if known.
+    Downloading { downloaded: u64, total: Option<u64> },
+    /// The artifact was downloaded, and is about to be verified.
+    Downloaded,
+    /// The artifact was verified, and is about to be extracted.
+    Verified,
+    /// The tool binary was extracted, and is about to be stored.
+    Extracted,
+    /// The tool was stored, and its post-install command is about to run.
+    RunningPostInstall,
+    /// The tool was fully installed.
+    Installed,
+}
+
+/**
+    A tool version that was installed, see [`install_spec_with_options`].
+*/
+#[derive(Debug)]
+pub struct InstalledArtifact {
+    /**
+        The path to the installed tool binary.
+    */
+    pub path: PathBuf,
+    /**
+        The URL of the artifact that was installed, if it has one.
+    */
+    pub url: Option<Url>,
+    /**
+        The SHA-256 checksum of the downloaded artifact contents.
+    */
+    pub checksum: String,
+    /**
+        The number of bytes that were downloaded.
+    */
+    pub bytes: u64,
+    /**
+        The error from running the post-install command, if it failed
+        without being required to succeed - the tool is still installed.
+    */
+    pub post_install_error: Option<RokitError>,
+}
+
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/**
+    A summary of installing tools, separate from how it is presented.
+
+    Contains tools that were downloaded and installed, tools that
+    were already installed, tools that were skipped since they could
+    not be installed on the current system, and any tools that were
+    trusted for the first time as part of installing them, along
+    with the aliases that links were created for.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallSummary {
+    /**
+        Tools that were downloaded and installed, in no particular order.
+    */
+    pub installed: Vec<InstalledTool>,
+    /**
+        Tools that were already installed, and were not downloaded again.
+    */
+    pub already_installed: Vec<ToolSpec>,
+    /**
+        Tools that were not installed, since they can not be installed on the current system.
+    */
+    pub skipped: Vec<SkippedTool>,
+    /**
+        Tools that had not been trusted before, and were trusted during this install.
+    */
+    pub newly_trusted: Vec<ToolId>,
+    /**
+        Aliases that links were created for, which may be more than the number
+        of tools, since several aliases can use the same tool.
+    */
+    pub linked: Vec<ToolAlias>,
+}
+
+/**
+    A tool that was downloaded and installed, see [`InstallSummary`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledTool {
+    /**
+        The tool that was installed.
+    */
+    pub spec: ToolSpec,
+    /**
+        The number of bytes that were downloaded for the tool,
+        which is zero if its artifact was already cached.
+    */
+    pub bytes: u64,
+    /**
+        How long it took to download and install the tool.
+    */
+    pub elapsed: Duration,
+}
+
+/**
+    A tool that was skipped instead of installed, see [`InstallSummary`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedTool {
+    /**
+        The tool that was skipped.
+    */
+    pub spec: ToolSpec,
+    /**
+        Why the tool was skipped, such as there being no compatible artifact.
+    */
+    pub reason: String,
+}
+
+impl InstallSummary {
+    /**
+        Gets the total number of tools, both installed and already installed.
+        Tools that were skipped are not counted.
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.installed.len() + self.already_installed.len()
+    }
+
+    /**
+        Checks if there were no tools to install at all.
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+        Gets all tools, both installed and already installed.
+    */
+    pub fn specs(&self) -> impl Iterator<Item = &ToolSpec> {
+        self.installed
+            .iter()
+            .map(|tool| &tool.spec)
+            .chain(self.already_installed.iter())
+    }
+
+    /**
+        Gets the total number of bytes downloaded for all installed tools.
+    */
+    #[must_use]
+    pub fn downloaded_bytes(&self) -> u64 {
+        self.installed.iter().map(|tool| tool.bytes).sum()
+    }
+
+    /**
+        Gets the tool that took the longest to download and install, if any.
+    */
+    #[must_use]
+    pub fn slowest(&self) -> Option<&InstalledTool> {
+        self.installed.iter().max_by_key(|tool| tool.elapsed)
+    }
+}
+
+/**
+    Installs the given tool version into the tool storage of the given home,
+    and marks it as installed in the tool cache, returning the installed path.
+
+    This finds the most compatible artifact for the current system,
+    downloads it, verifies its checksum if the release publishes one,
+    and extracts the tool binary from it.
+
+    Note that this does not check if the tool is trusted, does not create
+    any links, and does not save the home - see [`Home::save`].
+
+    # Errors
+
+    - If no compatible artifact was found.
+    - If the artifact could not be downloaded, verified, or extracted.
+    - If the tool could not be written to storage.
+*/
+pub async fn install_spec(
+    home: &Home,
+    source: &ArtifactSource,
+    spec: &ToolSpec,
+) -> RokitResult<PathBuf> {
+    let installed =
+        install_spec_with_options(home, source, spec, InstallOptions::default(), |_| {}).await?;
+    Ok(installed.path)
+}
+
+/**
+    Installs the given tool version, using the given options,
+    and reporting progress to the given callback as it installs.
+
+    If the options contain a command to run after installing, it runs once
+    the tool has been stored. A failing command that is required to succeed
+    fails the install, without marking the tool as installed, so that the
+    command runs again the next time the tool is installed.
+
+    See [`install_spec`] for more information.
+
+    # Errors
+
+    - If no compatible artifact was found, or no artifact matched the artifact pattern.
+    - If the artifact could not be downloaded, verified, or extracted.
+    - If the tool could not be written to storage.
+    - If the tool did not run, or a required post-install command failed.
+*/
+#[instrument(skip(home, source, options, on_progress), level = "debug")]
+pub async fn install_spec_with_options<F>(
+    home: &Home,
+    source: &ArtifactSource,
+    spec: &ToolSpec,
+    options: InstallOptions<'_>,
+    on_progress: F,
+) -> RokitResult<InstalledArtifact>
+where
+    F: Fn(InstallProgress),
+{
+    let fetched;
+    let artifacts = if let Some(artifacts) = options.release {
+        artifacts
+    } else {
+        fetched = source
+            .get_specific_release_with_tag_prefix(spec, options.tag_prefix)
+            .await?;
+        &fetched
+    };
+    on_progress(InstallProgress::FetchedRelease);
+
+    let artifact = match options.artifact {
+        Some(artifact) => artifact.clone(),
+        None => find_artifact(artifacts, options.artifact_pattern)
+            .ok_or_else(|| RokitError::NoCompatibleArtifact(spec.clone().into()))?,
+    };
+    debug!(name = ?artifact.file_name, "selected artifact");
+
+    let artifact = if artifact.checksum.is_some() {
+        artifact
+    } else {
+        source.attach_checksum(&artifact, artifacts).await?
+    };
+    let artifact = if options.minisign_key.is_some() {
+        source.attach_signature(&artifact, artifacts).await?
+    } else {
+        artifact
+    };
+    on_progress(InstallProgress::FoundArtifact);
+
+    let contents = source
+        .download_artifact_contents_with_progress(&artifact, |downloaded, total| {
+            on_progress(InstallProgress::Downloading { downloaded, total });
+        })
+        .await?;
+    on_progress(InstallProgress::Downloaded);
+
+    artifact.verify_checksum(&contents)?;
+    if let Some(key) = options.minisign_key {
+        artifact.verify_signature(&contents, key)?;
+    }
+    let checksum = Artifact::checksum_of(&contents);
+    let bytes = contents.len() as u64;
+    on_progress(InstallProgress::Verified);
+
+    let extracted = artifact
+        .extract_binary(contents, options.binary, options.alias)
+        .await?;
+    on_progress(InstallProgress::Extracted);
+
+    let tool_storage = home.tool_storage();
+    tool_storage.replace_tool_contents(spec, extracted).await?;
+    let tool_path = tool_storage.tool_path(spec);
+    if let Some(args) = options.verify_args {
+        if let Err(e) = verify_tool_runs(spec, &tool_path, args).await {
+            tool_storage.remove_tool_contents(spec).await?;
+            return Err(e);
+        }
+    }
+
+    let mut post_install_error = None;
+    if let Some(post_install) = options.post_install {
+        on_progress(InstallProgress::RunningPostInstall);
+        match run_post_install(spec, &tool_path, &post_install.args).await {
+            Ok(()) => {}
+            // NOTE: Tools that failed a required post-install command are not
+            // installed, so they must not be left in storage either, same as
+            // for tools that failed verification
+            Err(e) if post_install.required => {
+                tool_storage.remove_tool_contents(spec).await?;
+                return Err(e);
+            }
+            Err(e) => post_install_error = Some(e),
+        }
+    }
+
+    let _ = home.tool_cache().add_installed(spec.clone());
+    on_progress(InstallProgress::Installed);
+
+    Ok(InstalledArtifact {
+        path: tool_path,
+        url: artifact.url,
+        checksum,
+        bytes,
+        post_install_error,
+    })
+}
+
+/**
+    Verifies that an installed tool binary actually runs, by running
+    it with the given arguments, such as `--version`.
+
+    This catches tools that can not run on the current system at all, such
+    as when an artifact for the wrong architecture was installed. The tool
+    exiting with an error code is not considered a failure, since not every
+    tool supports the given arguments, but failing to start or crashing is.
+    Tools that are still running after a few seconds are stopped, and pass.
+
+    # Errors
+
+    - If the tool could not be started, or crashed.
+*/
+#[instrument(skip(spec, path), fields(%spec), level = "debug")]
+pub async fn verify_tool_runs(spec: &ToolSpec, path: &Path, args: &[String]) -> RokitResult<()> {
+    let failed = |reason: String| RokitError::ToolVerificationFailed {
+        tool_spec: spec.clone().into(),
+        reason,
+    };
+
+    let mut child = Command::new(path)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| failed(format!("failed to start - {e}")))?;
+
+    match timeout(VERIFY_TIMEOUT, child.wait()).await {
+        Err(_) => {
+            debug!("tool is still running, stopping it");
+            let _ = child.kill().await;
+            Ok(())
+        }
+        Ok(Err(e)) => Err(failed(format!("failed to run - {e}"))),
+        Ok(Ok(status)) if is_crash(status) => Err(failed(format!("crashed - {status}"))),
+        Ok(Ok(status)) => {
+            debug!(%status, "tool ran successfully");
+            Ok(())
+        }
+    }
+}
+
+/**
+    Runs the post-install command of an installed tool, which runs the
+    tool binary with the given arguments, such as to generate completions.
+
+    This should only be run once the tool has actually been installed
+    or reinstalled, and never for tools that were already installed.
+    Any output of the tool is logged at the debug level.
+
+    # Errors
+
+    - If the tool could not be started, or exited with an error code.
+*/
+#[instrument(skip(spec, path), fields(%spec), level = "debug")]
+pub async fn run_post_install(spec: &ToolSpec, path: &Path, args: &[String]) -> RokitResult<()> {
+    let failed = |reason: String| RokitError::PostInstallFailed {
+        tool_spec: spec.clone().into(),
+        reason,
+    };
+
+    let output = Command::new(path)
+        .args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| failed(format!("failed to start - {e}")))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stdout.lines().chain(stderr.lines()) {
+        debug!("{line}");
+    }
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(failed(format!("failed - {}", output.status)))
+    }
+}
+
+// Unix processes that crash are killed by a signal and have no exit code,
+// while crashes on Windows have an NTSTATUS exit code with the high bit set
+fn is_crash(status: std::process::ExitStatus) -> bool {
+    match status.code() {
+        None => true,
+        Some(code) => cfg!(windows) && code < 0,
+    }
+}
+
+fn find_artifact(artifacts: &[Artifact], pattern: Option<&ArtifactPattern>) -> Option<Artifact> {
+    if let Some(pattern) = pattern {
+        return Artifact::filter_by_pattern(artifacts, pattern)
+            .into_iter()
+            .next();
+    }
+    Artifact::sort_by_system_compatibility(artifacts)
+        .into_iter()
+        .next()
+        .or_else(|| Artifact::find_partially_compatible_fallback(artifacts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(spec: &str, bytes: u64, millis: u64) -> InstalledTool {
+        InstalledTool {
+            spec: spec.parse().unwrap(),
+            bytes,
+            elapsed: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn summarizes_installed_tools() {
+        let summary = InstallSummary {
+            installed: vec![
+                installed("author/fast@1.0.0", 100, 10),
+                installed("author/slow@1.0.0", 50, 500),
+            ],
+            already_installed: vec!["author/cached@1.0.0".parse().unwrap()],
+            skipped: vec![SkippedTool {
+                spec: "author/windows-only@1.0.0".parse().unwrap(),
+                reason: String::from("no compatible artifact"),
+            }],
+            newly_trusted: Vec::new(),
+            linked: Vec::new(),
+        };
+        assert_eq!(summary.len(), 3);
+        assert_eq!(summary.downloaded_bytes(), 150);
+        assert_eq!(
+            summary.slowest().unwrap().spec.to_string(),
+            "author/slow@1.0.0"
+        );
+        assert_eq!(summary.specs().count(), 3);
+    }
+
+    #[test]
+    fn empty_summary() {
+        let summary = InstallSummary::default();
+        assert!(summary.is_empty());
+        assert_eq!(summary.downloaded_bytes(), 0);
+        assert!(summary.slowest().is_none());
+    }
+
+    #[cfg(unix)]
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn verify_allows_error_codes() {
+        let spec = "author/tool@1.0.0".parse().unwrap();
+        let sh = Path::new("/bin/sh");
+        assert!(verify_tool_runs(&spec, sh, &args(&["-c", "exit 0"]))
+            .await
+            .is_ok());
+        assert!(verify_tool_runs(&spec, sh, &args(&["-c", "exit 2"]))
+            .await
+            .is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn verify_fails_on_crashes() {
+        let spec = "author/tool@1.0.0".parse().unwrap();
+        let sh = Path::new("/bin/sh");
+        let result = verify_tool_runs(&spec, sh, &args(&["-c", "kill -SEGV $$"])).await;
+        assert!(matches!(
+            result,
+            Err(RokitError::ToolVerificationFailed { .. })
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let not_executable = dir.path().join("tool");
+        std::fs::write(&not_executable, b"not a binary").unwrap();
+        let result = verify_tool_runs(&spec, &not_executable, &args(&["--version"])).await;
+        assert!(matches!(
+            result,
+            Err(RokitError::ToolVerificationFailed { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn post_install_fails_on_error_codes() {
+        let spec = "author/tool@1.0.0".parse().unwrap();
+        let sh = Path::new("/bin/sh");
+        assert!(run_post_install(&spec, sh, &args(&["-c", "echo done"]))
+            .await
+            .is_ok());
+        let result = run_post_install(&spec, sh, &args(&["-c", "exit 2"])).await;
+        assert!(matches!(result, Err(RokitError::PostInstallFailed { .. })));
+    }
+}
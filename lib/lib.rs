@@ -2,6 +2,7 @@ pub(crate) mod util;
 
 pub mod descriptor;
 pub mod discovery;
+pub mod install;
 pub mod manifests;
 pub mod result;
 pub mod sources;
@@ -3,23 +3,40 @@
 // make library consumers think that auth manifests are meant
 // to be displayed - they are only meant to be stringified.
 
-use std::{collections::HashMap, path::Path, str::FromStr};
+use std::{collections::HashMap, env::var, path::Path, str::FromStr};
 
-use toml_edit::{DocumentMut, Formatted, Item, Value};
+use toml_edit::{table, DocumentMut, Formatted, Item, Value};
 use tracing::warn;
 
 use crate::{
     result::{RokitError, RokitResult},
     sources::ArtifactProvider,
-    util::fs::{load_from_file, save_to_file},
+    system::Keyring,
+    util::{
+        fs::{load_from_file, save_to_file},
+        str::normalize_host,
+    },
 };
 
 pub const MANIFEST_FILE_NAME: &str = "auth.toml";
+const KEYRING_REFERENCE_PREFIX: &str = "keyring:";
 pub(super) const MANIFEST_DEFAULT_CONTENTS: &str = "
 # This file lists authentication tokens managed by Rokit, a toolchain manager for Roblox projects.
 # For more information, see <|REPOSITORY_URL|>
 
 # github = \"ghp_tokenabcdef1234567890\"
+
+# Tokens may also be given using environment variables such as ROKIT_GITHUB_TOKEN,
+# GITHUB_TOKEN, or GH_TOKEN, which take precedence over tokens in this file.
+
+# Tokens for providers with several hosts, such as GitHub Enterprise or Gitea, are listed per host:
+# [ghe]
+# \"github.mycorp.com\" = \"ghp_tokenabcdef1234567890\"
+# [gitea]
+# \"codeberg.org\" = \"tokenabcdef1234567890\"
+
+# Tokens stored in the system keyring using `rokit auth login --keyring` are only referenced here:
+# github = \"keyring:github\"
 ";
 
 /**
@@ -100,27 +117,179 @@ impl AuthManifest {
     /**
         Gets the authentication token for the given artifact provider.
 
-        Returns `None` if the token is not present.
+        Tokens stored in the system keyring are read from it.
+
+        Returns `None` if the token is not present, or could not be read from the keyring.
     */
-    #[must_use]
-    pub fn get_token(&self, artifact_provider: ArtifactProvider) -> Option<String> {
+    pub async fn get_token(&self, artifact_provider: ArtifactProvider) -> Option<String> {
         let token = self.document.get(artifact_provider.as_str())?;
-        token.as_str().map(ToString::to_string)
+        let token = token.as_str()?.to_string();
+        resolve_token(&token, read_from_keyring).await
     }
 
     /**
         Gets all authentication tokens found in the manifest.
+
+        Tokens stored in the system keyring are read from it, and any
+        tokens that could not be read from the keyring are skipped.
+
+        Tokens for providers that require a host are not included,
+        use [`AuthManifest::get_all_host_tokens`] to get those.
     */
-    #[must_use]
-    pub fn get_all_tokens(&self) -> HashMap<ArtifactProvider, String> {
-        self.document
+    pub async fn get_all_tokens(&self) -> HashMap<ArtifactProvider, String> {
+        self.get_all_tokens_from(read_from_keyring).await
+    }
+
+    async fn get_all_tokens_from(
+        &self,
+        keyring: impl AsyncFn(&str) -> Option<String>,
+    ) -> HashMap<ArtifactProvider, String> {
+        let stored = self
+            .document
             .iter()
             .filter_map(|(key, value)| {
                 let provider = ArtifactProvider::from_str(key).ok()?;
                 let token = value.as_str()?.to_string();
                 Some((provider, token))
             })
-            .collect()
+            .collect::<Vec<_>>();
+
+        let mut tokens = HashMap::new();
+        for (provider, token) in stored {
+            if let Some(token) = resolve_token(&token, &keyring).await {
+                tokens.insert(provider, token);
+            }
+        }
+        tokens
+    }
+
+    /**
+        Gets all authentication tokens found in the manifest, merged with any
+        tokens given using environment variables, such as `ROKIT_GITHUB_TOKEN`.
+
+        Tokens from environment variables take precedence over tokens in the
+        manifest, and are never saved to the manifest. For each provider, the
+        first environment variable found is used, in the following order:
+
+        - GitHub: `ROKIT_GITHUB_TOKEN`, `GITHUB_TOKEN`, `GH_TOKEN`
+        - GitLab: `ROKIT_GITLAB_TOKEN`, `GITLAB_TOKEN`
+    */
+    pub async fn get_all_tokens_with_env(&self) -> HashMap<ArtifactProvider, String> {
+        merge_env_tokens(self.get_all_tokens().await, |name| var(name).ok())
+    }
+
+    /**
+        Checks if the manifest contains an authentication token
+        for the given host of an artifact provider.
+    */
+    #[must_use]
+    pub fn has_host_token(&self, artifact_provider: ArtifactProvider, host: &str) -> bool {
+        self.document
+            .get(artifact_provider.as_str())
+            .and_then(Item::as_table_like)
+            .is_some_and(|hosts| hosts.contains_key(&normalize_host(host)))
+    }
+
+    /**
+        Gets all authentication tokens for providers that require a host,
+        keyed by provider, and then by the host that each token belongs to.
+
+        Tokens stored in the system keyring are read from it, and any
+        tokens that could not be read from the keyring are skipped.
+    */
+    pub async fn get_all_host_tokens(&self) -> HashMap<ArtifactProvider, HashMap<String, String>> {
+        self.get_all_host_tokens_from(read_from_keyring).await
+    }
+
+    async fn get_all_host_tokens_from(
+        &self,
+        keyring: impl AsyncFn(&str) -> Option<String>,
+    ) -> HashMap<ArtifactProvider, HashMap<String, String>> {
+        let stored = self
+            .document
+            .iter()
+            .filter_map(|(key, value)| {
+                let provider = ArtifactProvider::from_str(key)
+                    .ok()
+                    .filter(|provider| provider.requires_host())?;
+                let hosts = value
+                    .as_table_like()?
+                    .iter()
+                    .filter_map(|(host, token)| {
+                        Some((normalize_host(host), token.as_str()?.to_string()))
+                    })
+                    .collect::<Vec<_>>();
+                Some((provider, hosts))
+            })
+            .collect::<Vec<_>>();
+
+        let mut tokens = HashMap::new();
+        for (provider, hosts) in stored {
+            let mut resolved = HashMap::new();
+            for (host, token) in hosts {
+                if let Some(token) = resolve_token(&token, &keyring).await {
+                    resolved.insert(host, token);
+                }
+            }
+            tokens.insert(provider, resolved);
+        }
+        tokens
+    }
+
+    /**
+        Gets the name of the account used to store the token for the
+        given artifact provider, and optional host, in the system keyring.
+    */
+    #[must_use]
+    pub fn keyring_account(artifact_provider: ArtifactProvider, host: Option<&str>) -> String {
+        match host {
+            Some(host) => format!("{}/{}", artifact_provider.as_str(), normalize_host(host)),
+            None => artifact_provider.as_str().to_string(),
+        }
+    }
+
+    /**
+        Gets the account that the token for the given artifact provider, and
+        optional host, is stored under in the system keyring, if any.
+
+        Returns `None` if the token is not present, or is stored in the manifest itself.
+    */
+    #[must_use]
+    pub fn get_keyring_account(
+        &self,
+        artifact_provider: ArtifactProvider,
+        host: Option<&str>,
+    ) -> Option<String> {
+        let item = self.document.get(artifact_provider.as_str())?;
+        let item = match host {
+            Some(host) => item.as_table_like()?.get(&normalize_host(host))?,
+            None => item,
+        };
+        parse_keyring_reference(item.as_str()?).map(ToString::to_string)
+    }
+
+    /**
+        Sets the authentication token for the given artifact provider, and optional
+        host, to a reference to the token stored in the system keyring.
+
+        The token itself must be stored in the keyring separately, using the
+        account given by [`AuthManifest::keyring_account`].
+
+        Returns `true` if the reference replaced an older
+        token, `false` if an older token was not present.
+    */
+    #[must_use]
+    pub fn set_keyring_token(
+        &mut self,
+        artifact_provider: ArtifactProvider,
+        host: Option<&str>,
+    ) -> bool {
+        let account = Self::keyring_account(artifact_provider, host);
+        let reference = format!("{KEYRING_REFERENCE_PREFIX}{account}");
+        match host {
+            Some(host) => self.set_host_token(artifact_provider, host, reference),
+            None => self.set_token(artifact_provider, reference),
+        }
     }
 
     /**
@@ -153,6 +322,134 @@ impl AuthManifest {
         let tab = self.document.as_table_mut();
         tab.remove(artifact_provider.as_str()).is_some()
     }
+
+    /**
+        Sets the authentication token for the given host of an artifact provider.
+
+        Returns `true` if the token replaced an older
+        one, `false` if an older token was not present.
+    */
+    #[must_use]
+    pub fn set_host_token(
+        &mut self,
+        artifact_provider: ArtifactProvider,
+        host: &str,
+        token: impl Into<String>,
+    ) -> bool {
+        let tab = self.document.as_table_mut();
+        if !tab.contains_table(artifact_provider.as_str()) {
+            tab.insert(artifact_provider.as_str(), table());
+        }
+        let hosts = tab[artifact_provider.as_str()].as_table_mut().unwrap();
+        let old = hosts.insert(
+            &normalize_host(host),
+            Item::Value(Value::String(Formatted::new(token.into()))),
+        );
+        old.is_some()
+    }
+
+    /**
+        Unsets the authentication token for the given host of an artifact provider.
+
+        Returns `true` if the token was removed, `false` if it was not present.
+    */
+    #[must_use]
+    pub fn unset_host_token(&mut self, artifact_provider: ArtifactProvider, host: &str) -> bool {
+        self.document
+            .get_mut(artifact_provider.as_str())
+            .and_then(Item::as_table_like_mut)
+            .is_some_and(|hosts| hosts.remove(&normalize_host(host)).is_some())
+    }
+}
+
+/*
+    Environment variables to read tokens from, in order of precedence.
+
+    Providers with several hosts, such as Gitea, have no environment variables,
+    since a single variable can not say which host its token belongs to.
+*/
+const TOKEN_ENV_VARS: &[(ArtifactProvider, &[&str])] = &[
+    (
+        ArtifactProvider::GitHub,
+        &["ROKIT_GITHUB_TOKEN", "GITHUB_TOKEN", "GH_TOKEN"],
+    ),
+    (
+        ArtifactProvider::GitLab,
+        &["ROKIT_GITLAB_TOKEN", "GITLAB_TOKEN"],
+    ),
+];
+
+fn merge_env_tokens(
+    mut tokens: HashMap<ArtifactProvider, String>,
+    get_var: impl Fn(&str) -> Option<String>,
+) -> HashMap<ArtifactProvider, String> {
+    for (provider, names) in TOKEN_ENV_VARS {
+        let env_token = names.iter().find_map(|name| {
+            let token = get_var(name)?.trim().to_string();
+            (!token.is_empty()).then_some(token)
+        });
+        if let Some(token) = env_token {
+            tokens.insert(*provider, token);
+        }
+    }
+    tokens
+}
+
+fn parse_keyring_reference(token: &str) -> Option<&str> {
+    let account = token.strip_prefix(KEYRING_REFERENCE_PREFIX)?.trim();
+    (!account.is_empty()).then_some(account)
+}
+
+/*
+    Resolves a token stored in the manifest, reading it from the given
+    keyring if the manifest only contains a reference to it.
+
+    The keyring is given separately so that tests never touch the real one.
+*/
+async fn resolve_token(
+    token: &str,
+    keyring: impl AsyncFn(&str) -> Option<String>,
+) -> Option<String> {
+    match parse_keyring_reference(token) {
+        Some(account) => keyring(account).await,
+        None => Some(token.to_string()),
+    }
+}
+
+/*
+    Reads a token from the system keyring.
+
+    Tokens that can not be read are skipped with a warning, instead of
+    failing, so that tools not requiring authentication can still be used.
+*/
+async fn read_from_keyring(account: &str) -> Option<String> {
+    let Some(keyring) = Keyring::detect() else {
+        warn!(
+            "The token for '{account}' is stored in the system keyring, \
+            but no supported keyring is available on this system!\
+            \nRun `rokit auth login` to store the token in the auth manifest instead."
+        );
+        return None;
+    };
+    match keyring.get(account).await {
+        Ok(Some(token)) => Some(token),
+        Ok(None) => {
+            warn!(
+                "The token for '{account}' was not found in the {}!\
+                \nRun `rokit auth login --keyring` to store it again.",
+                keyring.name()
+            );
+            None
+        }
+        Err(e) => {
+            warn!(
+                "Failed to read the token for '{account}' from the {}!\
+                \nError: {e}",
+                keyring.name()
+            );
+            None
+        }
+    }
 }
 
 impl FromStr for AuthManifest {
@@ -173,7 +470,22 @@ impl FromStr for AuthManifest {
                     key
                 );
             }
-            if !value.is_str() {
+            let is_host_provider =
+                ArtifactProvider::from_str(key).is_ok_and(ArtifactProvider::requires_host);
+            if is_host_provider {
+                let all_hosts_valid = value
+                    .as_table_like()
+                    .is_some_and(|hosts| hosts.iter().all(|(_, token)| token.is_str()));
+                if !all_hosts_valid {
+                    warn!(
+                        "Encountered invalid value for artifact provider '{}' in auth manifest!\
+                        \nExpected: Table of hosts and their tokens\
+                        \nActual: {}",
+                        key,
+                        value.type_name()
+                    );
+                }
+            } else if !value.is_str() {
                 warn!(
                     "Encountered invalid value for artifact provider '{}' in auth manifest!\
                     \nExpected: String\
@@ -202,3 +514,86 @@ impl Default for AuthManifest {
         Self { document }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn host_tokens() {
+        let mut manifest = AuthManifest::default();
+        assert!(!manifest.has_host_token(ArtifactProvider::Gitea, "codeberg.org"));
+        assert!(!manifest.set_host_token(ArtifactProvider::Gitea, "Codeberg.org", "abc"));
+        assert!(!manifest.set_host_token(ArtifactProvider::Gitea, "localhost:3000", "def"));
+        assert!(manifest.has_host_token(ArtifactProvider::Gitea, "codeberg.org"));
+
+        let manifest = manifest.to_string().parse::<AuthManifest>().unwrap();
+        let tokens = manifest.get_all_host_tokens().await;
+        let gitea = tokens.get(&ArtifactProvider::Gitea).unwrap();
+        assert_eq!(gitea.get("codeberg.org").map(String::as_str), Some("abc"));
+        assert_eq!(gitea.get("localhost:3000").map(String::as_str), Some("def"));
+        assert!(manifest.get_all_tokens().await.is_empty());
+
+        let mut manifest = manifest;
+        assert!(manifest.unset_host_token(ArtifactProvider::Gitea, "codeberg.org"));
+        assert!(!manifest.unset_host_token(ArtifactProvider::Gitea, "codeberg.org"));
+        assert!(!manifest.has_host_token(ArtifactProvider::Gitea, "codeberg.org"));
+    }
+
+    #[tokio::test]
+    async fn env_tokens() {
+        let mut manifest = AuthManifest::default();
+        let _ = manifest.set_token(ArtifactProvider::GitHub, "file-github");
+        let _ = manifest.set_token(ArtifactProvider::GitLab, "file-gitlab");
+
+        let env = HashMap::from([("GITHUB_TOKEN", "env-github"), ("GH_TOKEN", "env-gh")]);
+        let tokens = merge_env_tokens(manifest.get_all_tokens().await, |name| {
+            env.get(name).map(ToString::to_string)
+        });
+        assert_eq!(tokens[&ArtifactProvider::GitHub], "env-github");
+        assert_eq!(tokens[&ArtifactProvider::GitLab], "file-gitlab");
+
+        let env = HashMap::from([("ROKIT_GITHUB_TOKEN", " "), ("GH_TOKEN", "env-gh")]);
+        let tokens = merge_env_tokens(HashMap::new(), |name| {
+            env.get(name).map(ToString::to_string)
+        });
+        assert_eq!(tokens[&ArtifactProvider::GitHub], "env-gh");
+        assert!(!tokens.contains_key(&ArtifactProvider::GitLab));
+
+        // Environment tokens must never end up in the manifest itself
+        assert!(!manifest.to_string().contains("env-github"));
+    }
+
+    #[tokio::test]
+    async fn keyring_references() {
+        let mut manifest = AuthManifest::default();
+        let _ = manifest.set_token(ArtifactProvider::GitLab, "file-gitlab");
+        assert!(!manifest.set_keyring_token(ArtifactProvider::GitHub, None));
+        assert!(!manifest.set_keyring_token(ArtifactProvider::Gitea, Some("Codeberg.org")));
+
+        let manifest = manifest.to_string().parse::<AuthManifest>().unwrap();
+        assert!(manifest.to_string().contains("\"keyring:github\""));
+        assert_eq!(
+            manifest.get_keyring_account(ArtifactProvider::GitHub, None),
+            Some(String::from("github"))
+        );
+        assert_eq!(
+            manifest.get_keyring_account(ArtifactProvider::Gitea, Some("codeberg.org")),
+            Some(String::from("gitea/codeberg.org"))
+        );
+        assert_eq!(
+            manifest.get_keyring_account(ArtifactProvider::GitLab, None),
+            None
+        );
+
+        // References are read from the keyring, and must never be used as tokens,
+        // even if the keyring doesn't contain them
+        let keyring = HashMap::from([("github", "keyring-github")]);
+        let read = async |account: &str| keyring.get(account).map(ToString::to_string);
+        let tokens = manifest.get_all_tokens_from(read).await;
+        assert_eq!(tokens[&ArtifactProvider::GitHub], "keyring-github");
+        assert_eq!(tokens[&ArtifactProvider::GitLab], "file-gitlab");
+        let host_tokens = manifest.get_all_host_tokens_from(read).await;
+        assert!(host_tokens[&ArtifactProvider::Gitea].is_empty());
+    }
+}
@@ -0,0 +1,506 @@
+use std::{
+    collections::BTreeMap,
+    io::ErrorKind,
+    num::{NonZeroU64, NonZeroUsize},
+    path::Path,
+    str::FromStr,
+};
+
+use thiserror::Error;
+use tokio::fs::read_to_string;
+use toml_edit::{DocumentMut, Item, TomlError};
+use url::Url;
+
+use crate::{
+    result::{RokitError, RokitResult},
+    sources::ArtifactProvider,
+    util::str::normalize_host,
+};
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+pub const PROJECT_CONFIG_DIR_NAME: &str = ".rokit";
+
+const NO_TRUST_CHECK_KEY: &str = "no_trust_check";
+const JOBS_KEY: &str = "jobs";
+const TIMEOUT_KEY: &str = "timeout";
+const PROXY_KEY: &str = "proxy";
+const PATH_FALLBACK_KEY: &str = "path_fallback";
+const PROVIDERS_KEY: &str = "providers";
+const MIRRORS_KEY: &str = "mirrors";
+const MAX_RETRIES_KEY: &str = "max_retries";
+
+const KNOWN_KEYS: [&str; 7] = [
+    NO_TRUST_CHECK_KEY,
+    JOBS_KEY,
+    TIMEOUT_KEY,
+    PROXY_KEY,
+    PATH_FALLBACK_KEY,
+    PROVIDERS_KEY,
+    MIRRORS_KEY,
+];
+const KNOWN_PROVIDER_KEYS: [&str; 2] = [JOBS_KEY, MAX_RETRIES_KEY];
+
+/**
+    Error type representing the possible errors that can occur when parsing a config file.
+*/
+#[derive(Debug, Error)]
+pub enum RokitConfigError {
+    #[error("{0}")]
+    Toml(Box<TomlError>),
+    #[error("unknown key '{0}', expected one of: {}", KNOWN_KEYS.join(", "))]
+    UnknownKey(String),
+    #[error("unknown key '{0}', expected one of: {}", KNOWN_PROVIDER_KEYS.join(", "))]
+    UnknownProviderKey(String),
+    #[error("invalid value for '{key}': expected {expected}, got {actual}")]
+    InvalidValue {
+        key: &'static str,
+        expected: &'static str,
+        actual: String,
+    },
+}
+
+impl From<TomlError> for RokitConfigError {
+    fn from(err: TomlError) -> Self {
+        RokitConfigError::Toml(err.into())
+    }
+}
+
+/**
+    Default settings for Rokit, which CLI flags and environment variables take precedence over.
+
+    Loaded from a `config.toml` file in the Rokit home directory, and
+    from `.rokit/config.toml` files in project directories, which
+    take precedence over the config in the home directory.
+
+    All settings are optional, and unset settings use Rokit's defaults.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RokitConfig {
+    /**
+        If trust checks should be skipped when installing tools.
+    */
+    pub no_trust_check: Option<bool>,
+    /**
+        The maximum number of tools to download and install at the same time.
+    */
+    pub jobs: Option<NonZeroUsize>,
+    /**
+        The maximum number of seconds that installing tools may take, in total.
+    */
+    pub timeout: Option<NonZeroU64>,
+    /**
+        A proxy to use for all network requests. Just like the `--proxy` flag, this is
+        used instead of the `HTTP_PROXY` and `HTTPS_PROXY` environment variables.
+    */
+    pub proxy: Option<Url>,
+    /**
+        If running a tool that is not in any manifest should fall back to running
+        a binary with the same name found on the system PATH, which is not managed
+        by Rokit, instead of failing. Useful for partially migrated setups.
+    */
+    pub path_fallback: Option<bool>,
+    /**
+        Settings for specific artifact providers, set in `[providers.<name>]`
+        tables, such as `[providers.gitea]`, which take precedence over the
+        global settings for tools from that provider.
+    */
+    pub providers: BTreeMap<ArtifactProvider, ProviderConfig>,
+    /**
+        Mirrors to download artifacts from, set in a `[mirrors]` table that maps
+        the host to rewrite to the mirror, such as `"api.github.com" = "mirror.corp.net"`.
+
+        Useful in restricted networks, where a provider is blocked but an internal
+        mirror serves the same assets. Release metadata is still fetched from providers.
+    */
+    pub mirrors: BTreeMap<String, Url>,
+}
+
+/**
+    Settings for a specific artifact provider, such as GitHub or Gitea.
+
+    Useful for self-hosted instances with stricter rate limits than
+    larger providers, which should not get as many concurrent requests.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProviderConfig {
+    /**
+        The maximum number of tools from this provider to download
+        and install at the same time, in addition to the global limit.
+    */
+    pub jobs: Option<NonZeroUsize>,
+    /**
+        The maximum number of times to retry failed requests to this provider.
+        The `ROKIT_MAX_RETRIES` environment variable takes precedence over this.
+    */
+    pub max_retries: Option<u32>,
+}
+
+impl RokitConfig {
+    /**
+        Loads the config from the given directory, if it exists.
+
+        This will search for a file named `config.toml` in the given directory.
+
+        # Errors
+
+        - If the config file could not be read.
+        - If the config file contains unknown keys or invalid values.
+    */
+    #[tracing::instrument(skip(dir), level = "trace")]
+    pub async fn load(dir: impl AsRef<Path>) -> RokitResult<Option<Self>> {
+        let path = dir.as_ref().join(CONFIG_FILE_NAME);
+        tracing::trace!(?path, "Loading config");
+        let contents = match read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        match contents.parse() {
+            Ok(config) => Ok(Some(config)),
+            Err(e) => Err(RokitError::Config {
+                path,
+                error: Box::new(e),
+            }),
+        }
+    }
+
+    /**
+        Merges another config into this one, keeping any settings
+        in this config and filling in those that are unset.
+    */
+    #[must_use]
+    pub fn merge_lower_precedence(self, other: Self) -> Self {
+        let mut providers = other.providers;
+        for (provider, config) in self.providers {
+            let lower = providers.remove(&provider).unwrap_or_default();
+            providers.insert(
+                provider,
+                ProviderConfig {
+                    jobs: config.jobs.or(lower.jobs),
+                    max_retries: config.max_retries.or(lower.max_retries),
+                },
+            );
+        }
+        let mut mirrors = other.mirrors;
+        mirrors.extend(self.mirrors);
+        Self {
+            no_trust_check: self.no_trust_check.or(other.no_trust_check),
+            jobs: self.jobs.or(other.jobs),
+            timeout: self.timeout.or(other.timeout),
+            proxy: self.proxy.or(other.proxy),
+            path_fallback: self.path_fallback.or(other.path_fallback),
+            providers,
+            mirrors,
+        }
+    }
+}
+
+impl FromStr for RokitConfig {
+    type Err = RokitConfigError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = s.parse::<DocumentMut>()?;
+
+        /*
+            Just like trust manifests, invalid settings are hard errors - a
+            mistyped setting silently being ignored would be very confusing.
+        */
+        let mut config = Self::default();
+        for (key, item) in document.iter() {
+            match key {
+                NO_TRUST_CHECK_KEY => {
+                    config.no_trust_check = Some(parse_value(
+                        NO_TRUST_CHECK_KEY,
+                        item,
+                        "a boolean",
+                        Item::as_bool,
+                    )?);
+                }
+                JOBS_KEY => {
+                    config.jobs = Some(parse_jobs(item)?);
+                }
+                TIMEOUT_KEY => {
+                    config.timeout = Some(parse_value(
+                        TIMEOUT_KEY,
+                        item,
+                        "a positive number of seconds",
+                        |item| {
+                            item.as_integer()
+                                .and_then(|i| u64::try_from(i).ok())
+                                .and_then(NonZeroU64::new)
+                        },
+                    )?);
+                }
+                PROXY_KEY => {
+                    config.proxy = Some(parse_value(
+                        PROXY_KEY,
+                        item,
+                        "a URL, such as \"http://proxy:8080\"",
+                        |item| item.as_str().and_then(|s| Url::parse(s).ok()),
+                    )?);
+                }
+                PATH_FALLBACK_KEY => {
+                    config.path_fallback = Some(parse_value(
+                        PATH_FALLBACK_KEY,
+                        item,
+                        "a boolean",
+                        Item::as_bool,
+                    )?);
+                }
+                PROVIDERS_KEY => {
+                    config.providers = parse_providers(item)?;
+                }
+                MIRRORS_KEY => {
+                    config.mirrors = parse_mirrors(item)?;
+                }
+                _ => return Err(RokitConfigError::UnknownKey(key.to_string())),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn parse_jobs(item: &Item) -> Result<NonZeroUsize, RokitConfigError> {
+    parse_value(JOBS_KEY, item, "a positive integer", |item| {
+        item.as_integer()
+            .and_then(|i| usize::try_from(i).ok())
+            .and_then(NonZeroUsize::new)
+    })
+}
+
+fn parse_providers(
+    item: &Item,
+) -> Result<BTreeMap<ArtifactProvider, ProviderConfig>, RokitConfigError> {
+    let tables = parse_value(
+        PROVIDERS_KEY,
+        item,
+        "a table of providers, such as [providers.github]",
+        Item::as_table_like,
+    )?;
+
+    let mut providers = BTreeMap::new();
+    for (name, item) in tables.iter() {
+        let provider = parse_value(
+            PROVIDERS_KEY,
+            &toml_edit::value(name),
+            "a known provider, such as github, ghe, gitlab, or gitea",
+            |name| name.as_str().and_then(|s| s.parse().ok()),
+        )?;
+        let table = parse_value(
+            PROVIDERS_KEY,
+            item,
+            "a table of provider settings",
+            Item::as_table_like,
+        )?;
+
+        let mut provider_config = ProviderConfig::default();
+        for (key, item) in table.iter() {
+            match key {
+                JOBS_KEY => {
+                    provider_config.jobs = Some(parse_jobs(item)?);
+                }
+                MAX_RETRIES_KEY => {
+                    provider_config.max_retries = Some(parse_value(
+                        MAX_RETRIES_KEY,
+                        item,
+                        "a non-negative integer",
+                        |item| item.as_integer().and_then(|i| u32::try_from(i).ok()),
+                    )?);
+                }
+                _ => {
+                    return Err(RokitConfigError::UnknownProviderKey(format!(
+                        "{PROVIDERS_KEY}.{name}.{key}"
+                    )))
+                }
+            }
+        }
+        providers.insert(provider, provider_config);
+    }
+
+    Ok(providers)
+}
+
+fn parse_mirrors(item: &Item) -> Result<BTreeMap<String, Url>, RokitConfigError> {
+    let table = parse_value(
+        MIRRORS_KEY,
+        item,
+        "a table of hosts and mirrors, such as \"api.github.com\" = \"mirror.corp.net\"",
+        Item::as_table_like,
+    )?;
+
+    let mut mirrors = BTreeMap::new();
+    for (host, item) in table.iter() {
+        /*
+            Mirrors may be given as just a host, or as a full URL with a port or
+            path - all requests use HTTPS, so other schemes are rejected here.
+        */
+        let mirror = parse_value(
+            MIRRORS_KEY,
+            item,
+            "a host or HTTPS URL, such as \"mirror.corp.net\" or \"https://mirror.corp.net/github\"",
+            |item| {
+                let mirror = item.as_str()?.trim();
+                let url = if mirror.contains("://") {
+                    Url::parse(mirror).ok()?
+                } else {
+                    Url::parse(&format!("https://{mirror}")).ok()?
+                };
+                (url.scheme() == "https" && url.host_str().is_some()).then_some(url)
+            },
+        )?;
+        mirrors.insert(normalize_host(host), mirror);
+    }
+
+    Ok(mirrors)
+}
+
+fn parse_value<'a, T>(
+    key: &'static str,
+    item: &'a Item,
+    expected: &'static str,
+    parse: impl FnOnce(&'a Item) -> Option<T>,
+) -> Result<T, RokitConfigError> {
+    parse(item).ok_or_else(|| RokitConfigError::InvalidValue {
+        key,
+        expected,
+        actual: match item.as_value() {
+            Some(value) => value.to_string().trim().to_string(),
+            None => item.type_name().to_string(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(s: &str) -> Result<RokitConfig, RokitConfigError> {
+        s.parse::<RokitConfig>()
+    }
+
+    #[test]
+    fn parses_all_settings() {
+        let config = config(
+            r#"
+            no_trust_check = true
+            jobs = 4
+            timeout = 300
+            proxy = "http://proxy:8080"
+            path_fallback = true
+
+            [providers.gitea]
+            jobs = 2
+            max_retries = 5
+
+            [mirrors]
+            "api.github.com" = "mirror.corp.net"
+            "GitLab.com" = "https://mirror.corp.net:8443/gitlab"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.no_trust_check, Some(true));
+        assert_eq!(config.jobs, NonZeroUsize::new(4));
+        assert_eq!(config.timeout, NonZeroU64::new(300));
+        assert_eq!(config.proxy, Some(Url::parse("http://proxy:8080").unwrap()));
+        assert_eq!(config.path_fallback, Some(true));
+        assert_eq!(
+            config.providers.get(&ArtifactProvider::Gitea),
+            Some(&ProviderConfig {
+                jobs: NonZeroUsize::new(2),
+                max_retries: Some(5),
+            })
+        );
+        assert_eq!(
+            config.mirrors.get("api.github.com"),
+            Some(&Url::parse("https://mirror.corp.net").unwrap())
+        );
+        assert_eq!(
+            config.mirrors.get("gitlab.com"),
+            Some(&Url::parse("https://mirror.corp.net:8443/gitlab").unwrap())
+        );
+    }
+
+    #[test]
+    fn empty_config_is_default() {
+        assert_eq!(config("").unwrap(), RokitConfig::default());
+    }
+
+    #[test]
+    fn errors_point_at_key() {
+        let err = config("jobs = 0").unwrap_err();
+        assert!(matches!(
+            err,
+            RokitConfigError::InvalidValue { key: JOBS_KEY, .. }
+        ));
+        assert!(err.to_string().contains("'jobs'"));
+
+        let err = config("no_trust_check = \"yes\"").unwrap_err();
+        assert!(err.to_string().contains("'no_trust_check'"));
+        assert!(err.to_string().contains("\"yes\""));
+
+        let err = config("no_trust_checks = true").unwrap_err();
+        assert!(matches!(err, RokitConfigError::UnknownKey(key) if key == "no_trust_checks"));
+
+        let err = config("[providers.gitbucket]\njobs = 2").unwrap_err();
+        assert!(err.to_string().contains("gitbucket"));
+
+        let err = config("[providers.gitlab]\nretries = 2").unwrap_err();
+        assert!(
+            matches!(err, RokitConfigError::UnknownProviderKey(key) if key == "providers.gitlab.retries")
+        );
+
+        for mirror in ["1", "\"http://mirror.corp.net\""] {
+            let err = config(&format!("[mirrors]\n\"github.com\" = {mirror}")).unwrap_err();
+            assert!(matches!(
+                err,
+                RokitConfigError::InvalidValue {
+                    key: MIRRORS_KEY,
+                    ..
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn merges_by_precedence() {
+        let project = config("jobs = 2").unwrap();
+        let home = config("jobs = 8\nno_trust_check = true").unwrap();
+        let merged = project.merge_lower_precedence(home);
+        assert_eq!(merged.jobs, NonZeroUsize::new(2));
+        assert_eq!(merged.no_trust_check, Some(true));
+    }
+
+    #[test]
+    fn merges_provider_settings_by_key() {
+        let project = config("[providers.github]\njobs = 2").unwrap();
+        let home =
+            config("[providers.github]\njobs = 8\nmax_retries = 1\n[providers.gitea]\njobs = 1")
+                .unwrap();
+        let merged = project.merge_lower_precedence(home);
+        assert_eq!(
+            merged.providers[&ArtifactProvider::GitHub],
+            ProviderConfig {
+                jobs: NonZeroUsize::new(2),
+                max_retries: Some(1),
+            }
+        );
+        assert_eq!(
+            merged.providers[&ArtifactProvider::Gitea].jobs,
+            NonZeroUsize::new(1)
+        );
+    }
+
+    #[test]
+    fn merges_mirrors_by_host() {
+        let project = config("[mirrors]\n\"github.com\" = \"project.mirror\"").unwrap();
+        let home =
+            config("[mirrors]\n\"github.com\" = \"home.mirror\"\n\"gitlab.com\" = \"home.mirror\"")
+                .unwrap();
+        let merged = project.merge_lower_precedence(home);
+        assert_eq!(
+            merged.mirrors["github.com"].host_str(),
+            Some("project.mirror")
+        );
+        assert_eq!(merged.mirrors["gitlab.com"].host_str(), Some("home.mirror"));
+    }
+}
@@ -0,0 +1,362 @@
+use std::{collections::HashSet, io::ErrorKind, path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::fs::{read_to_string, write};
+use url::Url;
+
+use crate::{
+    descriptor::{Arch, Toolchain, OS},
+    result::RokitResult,
+    tool::{ToolSpec, ToolSpecReq},
+};
+
+pub const LOCKFILE_NAME: &str = "rokit.lock";
+const LOCKFILE_VERSION: u32 = 1;
+const LOCKFILE_HEADER: &str = "\
+# This file is automatically generated by Rokit.
+# It is not intended for manual editing.
+";
+
+/**
+    Error type representing the possible errors that can occur when reading or writing a lockfile.
+*/
+#[derive(Debug, Error)]
+pub enum RokitLockfileError {
+    #[error("{0}")]
+    Deserialize(Box<toml::de::Error>),
+    #[error("{0}")]
+    Serialize(Box<toml::ser::Error>),
+    #[error("unsupported lockfile version {0}, expected {LOCKFILE_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+impl From<toml::de::Error> for RokitLockfileError {
+    fn from(err: toml::de::Error) -> Self {
+        RokitLockfileError::Deserialize(err.into())
+    }
+}
+
+impl From<toml::ser::Error> for RokitLockfileError {
+    fn from(err: toml::ser::Error) -> Self {
+        RokitLockfileError::Serialize(err.into())
+    }
+}
+
+/**
+    An artifact that was installed for a locked tool, on a specific system.
+
+    Systems are named by their operating system, architecture, and toolchain,
+    if any, such as `linux-x64-gnu` or `macos-arm64`, since artifacts built for
+    one libc may not run on systems using another, such as musl and glibc.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedArtifact {
+    pub system: String,
+    pub url: Url,
+    pub checksum: String,
+}
+
+/**
+    A tool requirement from a manifest, and the exact version it was resolved to.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedTool {
+    pub requirement: ToolSpecReq,
+    pub spec: ToolSpec,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<LockedArtifact>,
+}
+
+/**
+    Lockfile, stored next to the closest `rokit.toml` manifest.
+
+    Contains the exact versions that tool requirements were resolved to, as
+    well as the URLs and SHA-256 checksums of the artifacts that were installed
+    for each system, so that installs are reproducible, similar to a `Cargo.lock`.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RokitLockfile {
+    version: u32,
+    #[serde(default, rename = "tool")]
+    tools: Vec<LockedTool>,
+}
+
+impl RokitLockfile {
+    /**
+        Loads the lockfile from the given directory, if it exists.
+
+        This will search for a file named `rokit.lock` in the given directory.
+
+        # Errors
+
+        - If the lockfile could not be read, or is not a valid lockfile.
+    */
+    #[tracing::instrument(skip(dir), level = "trace")]
+    pub async fn load(dir: impl AsRef<Path>) -> RokitResult<Option<Self>> {
+        let path = dir.as_ref().join(LOCKFILE_NAME);
+        tracing::trace!(?path, "Loading lockfile");
+        match read_to_string(&path).await {
+            Ok(contents) => Ok(Some(contents.parse()?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /**
+        Saves the lockfile to the given directory.
+
+        This will write the lockfile to a file named `rokit.lock` in the given directory.
+
+        # Errors
+
+        - If the lockfile could not be serialized or saved.
+    */
+    #[tracing::instrument(skip(self, dir), level = "trace")]
+    pub async fn save(&self, dir: impl AsRef<Path>) -> RokitResult<()> {
+        let path = dir.as_ref().join(LOCKFILE_NAME);
+        tracing::trace!(?path, "Saving lockfile");
+        let contents = toml::to_string(self).map_err(RokitLockfileError::from)?;
+        write(path, format!("{LOCKFILE_HEADER}\n{contents}")).await?;
+        Ok(())
+    }
+
+    /**
+        Gets the locked tool version for the given requirement, if
+        it has been locked and still satisfies the requirement.
+    */
+    #[must_use]
+    pub fn get_resolved(&self, requirement: &ToolSpecReq) -> Option<&ToolSpec> {
+        self.tools
+            .iter()
+            .find(|tool| &tool.requirement == requirement)
+            .map(|tool| &tool.spec)
+            .filter(|spec| requirement.matches(spec))
+    }
+
+    /**
+        Gets the locked artifact for the given tool version, on the current system.
+    */
+    #[must_use]
+    pub fn get_artifact(&self, spec: &ToolSpec) -> Option<&LockedArtifact> {
+        let system = current_system();
+        self.tools
+            .iter()
+            .filter(|tool| &tool.spec == spec)
+            .flat_map(|tool| &tool.artifacts)
+            .find(|artifact| artifact.system == system)
+    }
+
+    /**
+        Locks the given requirement to the given tool version.
+
+        Any artifacts previously locked for the requirement
+        are removed, if it now resolves to a different version.
+    */
+    pub fn lock_resolved(&mut self, requirement: ToolSpecReq, spec: ToolSpec) {
+        if let Some(tool) = self
+            .tools
+            .iter_mut()
+            .find(|tool| tool.requirement == requirement)
+        {
+            if tool.spec != spec {
+                tool.spec = spec;
+                tool.artifacts.clear();
+            }
+        } else {
+            self.tools.push(LockedTool {
+                requirement,
+                spec,
+                artifacts: Vec::new(),
+            });
+        }
+        self.tools
+            .sort_by_cached_key(|tool| tool.requirement.to_string());
+    }
+
+    /**
+        Locks the artifact installed for the given tool version, on the current system.
+    */
+    pub fn lock_artifact(&mut self, spec: &ToolSpec, url: &Url, checksum: &str) {
+        let system = current_system();
+        // NOTE: Artifacts locked before toolchains were part of system
+        // names are replaced too, since they are for the same system
+        let legacy_system = system_name(OS::current_system(), Arch::current_system(), None);
+        for tool in self.tools.iter_mut().filter(|tool| &tool.spec == spec) {
+            tool.artifacts
+                .retain(|artifact| artifact.system != system && artifact.system != legacy_system);
+            tool.artifacts.push(LockedArtifact {
+                system: system.clone(),
+                url: url.clone(),
+                checksum: checksum.to_string(),
+            });
+            tool.artifacts.sort_by(|a, b| a.system.cmp(&b.system));
+        }
+    }
+
+    /**
+        Removes the locked tool for the given requirement, if any, such as
+        when a requirement in a manifest has been replaced with another.
+    */
+    pub fn unlock(&mut self, requirement: &ToolSpecReq) {
+        self.tools.retain(|tool| &tool.requirement != requirement);
+    }
+
+    /**
+        Removes any locked tools with requirements that are not in the given set.
+    */
+    pub fn retain_requirements(&mut self, requirements: &HashSet<ToolSpecReq>) {
+        self.tools
+            .retain(|tool| requirements.contains(&tool.requirement));
+    }
+}
+
+impl FromStr for RokitLockfile {
+    type Err = RokitLockfileError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lockfile = toml::from_str::<Self>(s)?;
+        if lockfile.version == LOCKFILE_VERSION {
+            Ok(lockfile)
+        } else {
+            Err(RokitLockfileError::UnsupportedVersion(lockfile.version))
+        }
+    }
+}
+
+impl Default for RokitLockfile {
+    fn default() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            tools: Vec::new(),
+        }
+    }
+}
+
+// Artifacts differ between systems, so they are locked separately for each
+fn current_system() -> String {
+    system_name(
+        OS::current_system(),
+        Arch::current_system(),
+        Toolchain::current_system(),
+    )
+}
+
+fn system_name(os: OS, arch: Arch, toolchain: Option<Toolchain>) -> String {
+    match toolchain {
+        Some(toolchain) => format!("{}-{}-{}", os.as_str(), arch.as_str(), toolchain.as_str()),
+        None => format!("{}-{}", os.as_str(), arch.as_str()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> ToolSpecReq {
+        s.parse().unwrap()
+    }
+
+    fn spec(s: &str) -> ToolSpec {
+        s.parse().unwrap()
+    }
+
+    fn url() -> Url {
+        "https://example.com/tool.zip".parse().unwrap()
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut lockfile = RokitLockfile::default();
+        lockfile.lock_resolved(req("rojo-rbx/rojo@^7"), spec("rojo-rbx/rojo@7.4.1"));
+        lockfile.lock_artifact(&spec("rojo-rbx/rojo@7.4.1"), &url(), "abc");
+
+        let contents = toml::to_string(&lockfile).unwrap();
+        let parsed = contents.parse::<RokitLockfile>().unwrap();
+        assert_eq!(parsed, lockfile);
+        assert_eq!(
+            parsed.get_resolved(&req("rojo-rbx/rojo@^7")),
+            Some(&spec("rojo-rbx/rojo@7.4.1"))
+        );
+        assert_eq!(
+            parsed
+                .get_artifact(&spec("rojo-rbx/rojo@7.4.1"))
+                .map(|artifact| artifact.checksum.as_str()),
+            Some("abc")
+        );
+    }
+
+    #[test]
+    fn relocking_clears_artifacts() {
+        let mut lockfile = RokitLockfile::default();
+        lockfile.lock_resolved(req("rojo-rbx/rojo@^7"), spec("rojo-rbx/rojo@7.4.1"));
+        lockfile.lock_artifact(&spec("rojo-rbx/rojo@7.4.1"), &url(), "abc");
+
+        lockfile.lock_resolved(req("rojo-rbx/rojo@^7"), spec("rojo-rbx/rojo@7.4.1"));
+        assert!(lockfile
+            .get_artifact(&spec("rojo-rbx/rojo@7.4.1"))
+            .is_some());
+
+        lockfile.lock_resolved(req("rojo-rbx/rojo@^7"), spec("rojo-rbx/rojo@7.5.0"));
+        assert!(lockfile
+            .get_artifact(&spec("rojo-rbx/rojo@7.5.0"))
+            .is_none());
+    }
+
+    #[test]
+    fn ignores_outdated_requirements() {
+        let mut lockfile = RokitLockfile::default();
+        lockfile.lock_resolved(req("rojo-rbx/rojo@^7"), spec("rojo-rbx/rojo@7.4.1"));
+        assert_eq!(lockfile.get_resolved(&req("rojo-rbx/rojo@^6")), None);
+
+        lockfile.retain_requirements(&HashSet::from([req("rojo-rbx/rojo@^6")]));
+        assert_eq!(lockfile, RokitLockfile::default());
+    }
+
+    #[test]
+    fn unlocks_replaced_requirements() {
+        let mut lockfile = RokitLockfile::default();
+        lockfile.lock_resolved(req("rojo-rbx/rojo@7.4.1"), spec("rojo-rbx/rojo@7.4.1"));
+        lockfile.lock_resolved(req("rojo-rbx/rojo@7.5.0"), spec("rojo-rbx/rojo@7.5.0"));
+
+        lockfile.unlock(&req("rojo-rbx/rojo@7.4.1"));
+        assert_eq!(lockfile.get_resolved(&req("rojo-rbx/rojo@7.4.1")), None);
+        assert_eq!(
+            lockfile.get_resolved(&req("rojo-rbx/rojo@7.5.0")),
+            Some(&spec("rojo-rbx/rojo@7.5.0"))
+        );
+    }
+
+    #[test]
+    fn system_names_include_toolchain() {
+        assert_eq!(
+            system_name(OS::Linux, Arch::X64, Some(Toolchain::Gnu)),
+            "linux-x64-gnu"
+        );
+        assert_eq!(
+            system_name(OS::Linux, Arch::X64, Some(Toolchain::Musl)),
+            "linux-x64-musl"
+        );
+        assert_eq!(system_name(OS::MacOS, Arch::Arm64, None), "macos-arm64");
+    }
+
+    #[test]
+    fn relocking_replaces_legacy_systems() {
+        let mut lockfile = RokitLockfile::default();
+        lockfile.lock_resolved(req("rojo-rbx/rojo@^7"), spec("rojo-rbx/rojo@7.4.1"));
+        lockfile.tools[0].artifacts.push(LockedArtifact {
+            system: system_name(OS::current_system(), Arch::current_system(), None),
+            url: url(),
+            checksum: String::from("old"),
+        });
+
+        lockfile.lock_artifact(&spec("rojo-rbx/rojo@7.4.1"), &url(), "new");
+        assert_eq!(lockfile.tools[0].artifacts.len(), 1);
+        assert_eq!(lockfile.tools[0].artifacts[0].system, current_system());
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let err = "version = 2".parse::<RokitLockfile>().unwrap_err();
+        assert!(matches!(err, RokitLockfileError::UnsupportedVersion(2)));
+    }
+}
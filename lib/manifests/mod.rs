@@ -1,8 +1,25 @@
 mod auth;
+mod config;
+mod lock;
 mod rokit;
+mod trust;
 
 pub use self::auth::{AuthManifest, MANIFEST_FILE_NAME as AUTH_MANIFEST_FILE_NAME};
-pub use self::rokit::{RokitManifest, MANIFEST_FILE_NAME as ROKIT_MANIFEST_FILE_NAME};
+pub use self::config::{
+    ProviderConfig, RokitConfig, RokitConfigError, CONFIG_FILE_NAME as ROKIT_CONFIG_FILE_NAME,
+    PROJECT_CONFIG_DIR_NAME as ROKIT_PROJECT_CONFIG_DIR_NAME,
+};
+pub use self::lock::{
+    LockedArtifact, LockedTool, RokitLockfile, RokitLockfileError,
+    LOCKFILE_NAME as ROKIT_LOCKFILE_NAME,
+};
+pub use self::rokit::{
+    RokitManifest, ToolEnvVar, ToolPostInstall, ToolVerify,
+    MANIFEST_FILE_NAME as ROKIT_MANIFEST_FILE_NAME,
+};
+pub use self::trust::{
+    TrustManifest, TrustManifestError, MANIFEST_FILE_NAME as TRUST_MANIFEST_FILE_NAME,
+};
 
 /**
     Helper function to make sure our authored manifest templates
@@ -25,32 +42,40 @@ mod tests {
     fn has_no_indentation() {
         let auth_contents = make_manifest_template(auth::MANIFEST_DEFAULT_CONTENTS);
         let rokit_contents = make_manifest_template(rokit::MANIFEST_DEFAULT_CONTENTS);
+        let trust_contents = make_manifest_template(trust::MANIFEST_DEFAULT_CONTENTS);
 
         assert!(!auth_contents.contains('\t'));
         assert!(!rokit_contents.contains('\t'));
+        assert!(!trust_contents.contains('\t'));
 
         assert!(!auth_contents.contains("\n  "));
         assert!(!rokit_contents.contains("\n  "));
+        assert!(!trust_contents.contains("\n  "));
 
         assert!(!auth_contents.contains("    "));
         assert!(!rokit_contents.contains("    "));
+        assert!(!trust_contents.contains("    "));
     }
 
     #[test]
     fn ends_with_newline() {
         assert!(make_manifest_template(auth::MANIFEST_DEFAULT_CONTENTS).ends_with('\n'));
         assert!(make_manifest_template(rokit::MANIFEST_DEFAULT_CONTENTS).ends_with('\n'));
+        assert!(make_manifest_template(trust::MANIFEST_DEFAULT_CONTENTS).ends_with('\n'));
     }
 
     #[test]
     fn contains_repo_url() {
         let auth_contents = make_manifest_template(auth::MANIFEST_DEFAULT_CONTENTS);
         let rokit_contents = make_manifest_template(rokit::MANIFEST_DEFAULT_CONTENTS);
+        let trust_contents = make_manifest_template(trust::MANIFEST_DEFAULT_CONTENTS);
 
         assert!(auth_contents.contains(env!("CARGO_PKG_REPOSITORY")));
         assert!(rokit_contents.contains(env!("CARGO_PKG_REPOSITORY")));
+        assert!(trust_contents.contains(env!("CARGO_PKG_REPOSITORY")));
 
         assert!(!auth_contents.contains("REPOSITORY_URL"));
         assert!(!rokit_contents.contains("REPOSITORY_URL"));
+        assert!(!trust_contents.contains("REPOSITORY_URL"));
     }
 }
@@ -3,18 +3,32 @@
 // make library consumers think that Rokit manifests are meant
 // to be displayed - they are only meant to be stringified.
 
-use std::{path::Path, str::FromStr};
+use std::{collections::BTreeMap, path::Path, str::FromStr};
 
-use toml_edit::{DocumentMut, Formatted, Item, Value};
+use toml_edit::{DocumentMut, Formatted, InlineTable, Item, Value};
 use tracing::warn;
 
 use crate::{
     result::{RokitError, RokitResult},
-    tool::{ToolAlias, ToolSpec},
+    tool::{ToolAlias, ToolId, ToolSpec, ToolSpecReq},
     util::fs::{load_from_file, save_to_file},
 };
 
 pub const MANIFEST_FILE_NAME: &str = "rokit.toml";
+const TOOL_SPEC_KEY: &str = "spec";
+const TOOL_BINARY_KEY: &str = "bin";
+const TOOL_PATH_KEY: &str = "path";
+const TOOL_MINISIGN_KEY: &str = "minisign";
+const TOOL_ARTIFACT_KEY: &str = "artifact";
+const TOOL_TAG_PREFIX_KEY: &str = "tag_prefix";
+const TOOL_ENV_KEY: &str = "env";
+const TOOL_VERIFY_KEY: &str = "verify";
+const TOOL_VERIFY_DEFAULT_ARGS: &[&str] = &["--version"];
+const TOOL_ENV_VALUE_KEY: &str = "value";
+const TOOL_POST_INSTALL_KEY: &str = "post_install";
+const TOOL_POST_INSTALL_ARGS_KEY: &str = "args";
+const TOOL_POST_INSTALL_REQUIRED_KEY: &str = "required";
+const TOOL_ENV_OVERRIDE_KEY: &str = "override";
 pub(super) const MANIFEST_DEFAULT_CONTENTS: &str = "
 # This file lists tools managed by Rokit, a toolchain manager for Roblox projects.
 # For more information, see <|REPOSITORY_URL|>
@@ -24,6 +38,79 @@ pub(super) const MANIFEST_DEFAULT_CONTENTS: &str = "
 [tools]
 ";
 
+/**
+    An environment variable declared for a tool in a Rokit manifest.
+
+    Tools may declare environment variables using the inline table form, for example
+    `tool = { spec = "author/name@1.0.0", env = { CONFIG = "tool.toml" } }`.
+
+    By default, a variable that is already set in the environment Rokit is running in
+    keeps its existing value. Variables declared in the form `{ value = "...", override = true }`
+    always use the value from the manifest instead.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolEnvVar {
+    pub value: String,
+    pub override_existing: bool,
+}
+
+/**
+    How a tool is verified to run after being installed, as declared in a Rokit manifest.
+
+    Tools may declare this using the inline table form, for example
+    `tool = { spec = "author/name@1.0.0", verify = "-V" }` to run the tool
+    with the given arguments, `verify = true` to run it with `--version`,
+    or `verify = false` to never verify it, even if asked to.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolVerify {
+    Disabled,
+    Args(Vec<String>),
+}
+
+impl ToolVerify {
+    /**
+        Gets the arguments to run the tool with, or `None` if verification is disabled.
+    */
+    #[must_use]
+    pub fn args(&self) -> Option<&[String]> {
+        match self {
+            Self::Disabled => None,
+            Self::Args(args) => Some(args),
+        }
+    }
+}
+
+/**
+    The default verification, which runs the tool with `--version`.
+*/
+impl Default for ToolVerify {
+    fn default() -> Self {
+        Self::Args(
+            TOOL_VERIFY_DEFAULT_ARGS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        )
+    }
+}
+
+/**
+    A command to run once after a tool has been installed, as declared in a Rokit manifest,
+    such as for generating shell completions or other one-time setup of the tool.
+
+    Tools may declare this using the inline table form, for example
+    `tool = { spec = "author/name@1.0.0", post_install = "completions --install" }`
+    to run the installed tool with the given arguments. A failing command only
+    warns by default, unless declared in the form `{ args = "...", required = true }`,
+    which makes the whole install fail instead.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolPostInstall {
+    pub args: Vec<String>,
+    pub required: bool,
+}
+
 /**
     Rokit manifest file.
 
@@ -106,10 +193,139 @@ impl RokitManifest {
     #[must_use]
     pub fn get_tool(&self, alias: &ToolAlias) -> Option<ToolSpec> {
         let tools = self.document.get("tools")?.as_table()?;
-        let tool_str = tools.get(alias.name())?.as_str()?;
+        let tool_str = tool_spec_str(tools.get(alias.name())?.as_value()?)?;
         tool_str.parse::<ToolSpec>().ok()
     }
 
+    /**
+        Gets the name of the binary to extract for a tool by its alias, if one was specified.
+
+        Tools may specify a binary using the inline table form in the manifest,
+        for example `tool = { spec = "author/name@1.0.0", bin = "name" }`.
+    */
+    #[must_use]
+    pub fn get_tool_binary(&self, alias: &ToolAlias) -> Option<String> {
+        let tools = self.document.get("tools")?.as_table()?;
+        tool_binary_str(tools.get(alias.name())?.as_value()?).map(ToString::to_string)
+    }
+
+    /**
+        Gets the path to a locally built binary for a tool by its alias, if one was specified.
+
+        Local tools use the inline table form in the manifest, for example
+        `tool = { path = "../tool/target/release/tool" }`, and the path
+        is relative to the directory containing the manifest.
+    */
+    #[must_use]
+    pub fn get_tool_path(&self, alias: &ToolAlias) -> Option<String> {
+        let tools = self.document.get("tools")?.as_table()?;
+        tool_path_str(tools.get(alias.name())?.as_value()?).map(ToString::to_string)
+    }
+
+    /**
+        Gets the minisign public key used to verify a tool by its alias, if one was specified.
+
+        Tools may specify a key using the inline table form in the manifest, for example
+        `tool = { spec = "author/name@1.0.0", minisign = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3" }`.
+        Installing such a tool requires a valid signature to be published alongside its artifact.
+    */
+    #[must_use]
+    pub fn get_tool_minisign_key(&self, alias: &ToolAlias) -> Option<String> {
+        let tools = self.document.get("tools")?.as_table()?;
+        tool_minisign_key_str(tools.get(alias.name())?.as_value()?).map(ToString::to_string)
+    }
+
+    /**
+        Gets the artifact name pattern used to select an artifact for a tool by its alias, if one was specified.
+
+        Tools may specify a pattern using the inline table form in the manifest, for
+        example `tool = { spec = "author/name@1.0.0", artifact = "tool-{os}64.zip" }`.
+        See [`crate::sources::ArtifactPattern`] for the supported placeholders.
+    */
+    #[must_use]
+    pub fn get_tool_artifact_pattern(&self, alias: &ToolAlias) -> Option<String> {
+        let tools = self.document.get("tools")?.as_table()?;
+        tool_artifact_pattern_str(tools.get(alias.name())?.as_value()?).map(ToString::to_string)
+    }
+
+    /**
+        Gets the prefix used for the release tags of a tool by its alias, if one was specified.
+
+        Tools may specify a prefix using the inline table form in the manifest, for
+        example `tool = { spec = "author/name@1.0.0", tag_prefix = "cli-" }`, which
+        looks for the release tagged `cli-1.0.0` before trying `v1.0.0` and `1.0.0`.
+    */
+    #[must_use]
+    pub fn get_tool_tag_prefix(&self, alias: &ToolAlias) -> Option<String> {
+        let tools = self.document.get("tools")?.as_table()?;
+        tool_tag_prefix_str(tools.get(alias.name())?.as_value()?).map(ToString::to_string)
+    }
+
+    /**
+        Gets how a tool should be verified to run after being installed, by its alias, if specified.
+
+        See [`ToolVerify`] for more information.
+    */
+    #[must_use]
+    pub fn get_tool_verify(&self, alias: &ToolAlias) -> Option<ToolVerify> {
+        let tools = self.document.get("tools")?.as_table()?;
+        tool_verify(tools.get(alias.name())?.as_value()?)
+    }
+
+    /**
+        Gets the command to run after a tool has been installed, by its alias, if specified.
+
+        See [`ToolPostInstall`] for more information.
+    */
+    #[must_use]
+    pub fn get_tool_post_install(&self, alias: &ToolAlias) -> Option<ToolPostInstall> {
+        let tools = self.document.get("tools")?.as_table()?;
+        tool_post_install(tools.get(alias.name())?.as_value()?)
+    }
+
+    /**
+        Gets the environment variables declared for a tool by its alias.
+
+        Returns an empty map if the tool does not exist or does not declare any variables.
+        See [`ToolEnvVar`] for more information.
+    */
+    #[must_use]
+    pub fn get_tool_env(&self, alias: &ToolAlias) -> BTreeMap<String, ToolEnvVar> {
+        let tools = self.document.get("tools").and_then(|v| v.as_table());
+        tools
+            .and_then(|t| t.get(alias.name()))
+            .and_then(|item| item.as_value())
+            .map(tool_env_vars)
+            .unwrap_or_default()
+    }
+
+    /**
+        Sets the name of the binary to extract for a tool in the manifest.
+
+        If the tool doesn't exist, this will return `false` and do nothing.
+    */
+    pub fn set_tool_binary(&mut self, alias: &ToolAlias, binary: &str) -> bool {
+        let Some(spec) = self.get_tool_requirement(alias) else {
+            return false;
+        };
+        let tools = self.document["tools"].as_table_mut().unwrap();
+        let mut table = InlineTable::new();
+        table.insert(TOOL_SPEC_KEY, Value::from(spec.to_string()));
+        table.insert(TOOL_BINARY_KEY, Value::from(binary));
+        tools.insert(alias.name(), Item::Value(Value::InlineTable(table)));
+        true
+    }
+
+    /**
+        Gets a tool specification or version requirement from the manifest by its alias, if it exists.
+    */
+    #[must_use]
+    pub fn get_tool_requirement(&self, alias: &ToolAlias) -> Option<ToolSpecReq> {
+        let tools = self.document.get("tools")?.as_table()?;
+        let tool_str = tool_spec_str(tools.get(alias.name())?.as_value()?)?;
+        tool_str.parse::<ToolSpecReq>().ok()
+    }
+
     /**
         Adds a tool to the manifest.
 
@@ -143,21 +359,112 @@ impl RokitManifest {
             return false;
         }
         let tools = doc["tools"].as_table_mut().unwrap();
-        if tools.contains_value(alias.name()) {
-            tools.insert(
-                alias.name(),
-                Item::Value(Value::String(Formatted::new(spec.to_string()))),
-            );
-            true
+        if !tools.contains_value(alias.name()) {
+            return false;
+        }
+        // NOTE: Keep any other keys such as the binary name when using the table form,
+        // as well as any surrounding whitespace and comments for the existing value
+        let value = if let Some(table) = tools[alias.name()].as_inline_table_mut() {
+            table.get_mut(TOOL_SPEC_KEY)
         } else {
-            false
+            tools[alias.name()].as_value_mut()
+        };
+        if let Some(value) = value {
+            let decor = value.decor().clone();
+            *value = Value::from(spec.to_string());
+            *value.decor_mut() = decor;
+        } else if let Some(table) = tools[alias.name()].as_inline_table_mut() {
+            table.insert(TOOL_SPEC_KEY, Value::from(spec.to_string()));
         }
+        true
+    }
+
+    /**
+        Normalizes the tool specifications in the manifest, rewriting each spec
+        and version requirement in its canonical form, such as `"author/name@1.2.3"`.
+
+        The ids of tools may also be replaced using the given function, such as to
+        use the casing of their author and name as on their provider. Only the specs
+        themselves are rewritten, keeping comments, ordering, and any other keys.
+
+        Returns the aliases of all tools with specs that were changed.
+    */
+    pub fn normalize_tool_specs(
+        &mut self,
+        canonical_id: impl Fn(&ToolId) -> Option<ToolId>,
+    ) -> Vec<ToolAlias> {
+        let Some(tools) = self.document.get_mut("tools").and_then(Item::as_table_mut) else {
+            return Vec::new();
+        };
+        let mut changed = Vec::new();
+        for (key, item) in tools.iter_mut() {
+            let Ok(alias) = key.get().parse::<ToolAlias>() else {
+                continue;
+            };
+            let value = if item.is_inline_table() {
+                item.as_inline_table_mut()
+                    .and_then(|table| table.get_mut(TOOL_SPEC_KEY))
+            } else {
+                item.as_value_mut()
+            };
+            let Some(Value::String(spec_str)) = value else {
+                continue;
+            };
+            let Ok(mut requirement) = spec_str.value().parse::<ToolSpecReq>() else {
+                continue;
+            };
+            if let Some(id) = canonical_id(&requirement.id) {
+                requirement.id = id;
+            }
+            // NOTE: Comparing the raw strings also catches differences in quoting,
+            // and any surrounding whitespace and comments are kept as they are
+            let normalized = Formatted::new(requirement.to_string());
+            if normalized.display_repr() != spec_str.display_repr() {
+                let decor = spec_str.decor().clone();
+                *spec_str = normalized;
+                *spec_str.decor_mut() = decor;
+                changed.push(alias);
+            }
+        }
+        changed
+    }
+
+    /**
+        Removes a tool from the manifest.
+
+        If the tool doesn't exist, this will return `false` and do nothing.
+    */
+    pub fn remove_tool(&mut self, alias: &ToolAlias) -> bool {
+        let doc = self.document.as_table_mut();
+        if !doc.contains_table("tools") {
+            return false;
+        }
+        let tools = doc["tools"].as_table_mut().unwrap();
+        tools.remove(alias.name()).is_some()
+    }
+
+    /**
+        Returns the aliases of all tools in the manifest, in their original casing.
+
+        Unlike other methods, this includes aliases that differ only by case, which
+        are otherwise considered to be the same alias - see [`ToolAlias`].
+    */
+    #[must_use]
+    pub fn tool_aliases(&self) -> Vec<ToolAlias> {
+        let tools = self.document.get("tools").and_then(|v| v.as_table());
+        let tool_kv_pairs = tools.map(|t| t.get_values()).unwrap_or_default();
+        tool_kv_pairs
+            .into_iter()
+            .filter_map(|(keys, _)| keys.last()?.parse::<ToolAlias>().ok())
+            .collect()
     }
 
     /**
         Returns all valid tool specifications in the manifest.
 
-        This will ignore any tools that are not valid tool specifications.
+        This will ignore any tools that are not valid tool specifications,
+        including tools using version requirements instead of exact versions.
+        Use [`RokitManifest::tool_requirements`] to also get those.
     */
     #[must_use]
     pub fn tool_specs(&self) -> Vec<(ToolAlias, ToolSpec)> {
@@ -167,11 +474,176 @@ impl RokitManifest {
             .into_iter()
             .filter_map(|(keys, value)| {
                 let alias = keys.last()?.parse::<ToolAlias>().ok()?;
-                let spec = value.as_str()?.parse::<ToolSpec>().ok()?;
+                let spec = tool_spec_str(value)?.parse::<ToolSpec>().ok()?;
                 Some((alias, spec))
             })
             .collect()
     }
+
+    /**
+        Returns all valid tool specifications and version requirements in the manifest.
+
+        This will ignore any tools that are not valid tool specifications.
+    */
+    #[must_use]
+    pub fn tool_requirements(&self) -> Vec<(ToolAlias, ToolSpecReq)> {
+        let tools = self.document.get("tools").and_then(|v| v.as_table());
+        let tool_kv_pairs = tools.map(|t| t.get_values()).unwrap_or_default();
+        tool_kv_pairs
+            .into_iter()
+            .filter_map(|(keys, value)| {
+                let alias = keys.last()?.parse::<ToolAlias>().ok()?;
+                let spec = tool_spec_str(value)?.parse::<ToolSpecReq>().ok()?;
+                Some((alias, spec))
+            })
+            .collect()
+    }
+
+    /**
+        Returns all tools in the manifest that use a locally built binary.
+    */
+    #[must_use]
+    pub fn tool_paths(&self) -> Vec<(ToolAlias, String)> {
+        let tools = self.document.get("tools").and_then(|v| v.as_table());
+        let tool_kv_pairs = tools.map(|t| t.get_values()).unwrap_or_default();
+        tool_kv_pairs
+            .into_iter()
+            .filter_map(|(keys, value)| {
+                let alias = keys.last()?.parse::<ToolAlias>().ok()?;
+                let path = tool_path_str(value)?;
+                Some((alias, path.to_string()))
+            })
+            .collect()
+    }
+}
+
+/*
+    Tools are either a plain tool spec string, an inline table containing
+    the tool spec and the binary to extract, or an inline table containing
+    the path to a locally built binary, which takes precedence over any spec.
+*/
+
+fn tool_spec_str(value: &Value) -> Option<&str> {
+    match value.as_inline_table() {
+        Some(table) if table.contains_key(TOOL_PATH_KEY) => None,
+        Some(table) => table.get(TOOL_SPEC_KEY)?.as_str(),
+        None => value.as_str(),
+    }
+}
+
+fn tool_path_str(value: &Value) -> Option<&str> {
+    value.as_inline_table()?.get(TOOL_PATH_KEY)?.as_str()
+}
+
+fn tool_binary_str(value: &Value) -> Option<&str> {
+    value.as_inline_table()?.get(TOOL_BINARY_KEY)?.as_str()
+}
+
+fn tool_minisign_key_str(value: &Value) -> Option<&str> {
+    value.as_inline_table()?.get(TOOL_MINISIGN_KEY)?.as_str()
+}
+
+fn tool_artifact_pattern_str(value: &Value) -> Option<&str> {
+    value.as_inline_table()?.get(TOOL_ARTIFACT_KEY)?.as_str()
+}
+
+fn tool_tag_prefix_str(value: &Value) -> Option<&str> {
+    value.as_inline_table()?.get(TOOL_TAG_PREFIX_KEY)?.as_str()
+}
+
+/*
+    Verification is either a boolean to enable or disable it, or a
+    string of whitespace-separated arguments to run the tool with.
+*/
+
+fn tool_verify(value: &Value) -> Option<ToolVerify> {
+    let verify = value.as_inline_table()?.get(TOOL_VERIFY_KEY)?;
+    if let Some(enabled) = verify.as_bool() {
+        return Some(if enabled {
+            ToolVerify::default()
+        } else {
+            ToolVerify::Disabled
+        });
+    }
+    let args = verify
+        .as_str()?
+        .split_whitespace()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    Some(if args.is_empty() {
+        ToolVerify::default()
+    } else {
+        ToolVerify::Args(args)
+    })
+}
+
+/*
+    Post-install commands are either a string of whitespace-separated
+    arguments, or an inline table with such a string and an optional
+    flag for failing the install if the command fails.
+*/
+
+fn tool_post_install(value: &Value) -> Option<ToolPostInstall> {
+    let post_install = value.as_inline_table()?.get(TOOL_POST_INSTALL_KEY)?;
+    let (args, required) = match post_install.as_inline_table() {
+        Some(table) => (
+            table.get(TOOL_POST_INSTALL_ARGS_KEY)?.as_str()?,
+            table
+                .get(TOOL_POST_INSTALL_REQUIRED_KEY)
+                .and_then(Value::as_bool)
+                .unwrap_or_default(),
+        ),
+        None => (post_install.as_str()?, false),
+    };
+    Some(ToolPostInstall {
+        args: args.split_whitespace().map(ToString::to_string).collect(),
+        required,
+    })
+}
+
+/*
+    Environment variables are either a plain string value, or an inline
+    table with a string value and an optional override flag - any
+    variables that are not in one of these forms are ignored.
+*/
+
+fn tool_env_vars(value: &Value) -> BTreeMap<String, ToolEnvVar> {
+    let Some(env) = value
+        .as_inline_table()
+        .and_then(|t| t.get(TOOL_ENV_KEY))
+        .and_then(Value::as_inline_table)
+    else {
+        return BTreeMap::new();
+    };
+    env.iter()
+        .filter_map(|(name, value)| {
+            let var = tool_env_var(name, value)?;
+            Some((name.to_string(), var))
+        })
+        .collect()
+}
+
+fn tool_env_var(name: &str, value: &Value) -> Option<ToolEnvVar> {
+    if !is_valid_env_name(name) {
+        return None;
+    }
+    match value.as_inline_table() {
+        Some(table) => Some(ToolEnvVar {
+            value: table.get(TOOL_ENV_VALUE_KEY)?.as_str()?.to_string(),
+            override_existing: match table.get(TOOL_ENV_OVERRIDE_KEY) {
+                Some(value) => value.as_bool()?,
+                None => false,
+            },
+        }),
+        None => Some(ToolEnvVar {
+            value: value.as_str()?.to_string(),
+            override_existing: false,
+        }),
+    }
+}
+
+fn is_valid_env_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['=', '\0'])
 }
 
 impl FromStr for RokitManifest {
@@ -220,32 +692,62 @@ impl FromStr for RokitManifest {
                     \nThe tool will be ignored and may not be available.\
                     \nError: {e}",
                 );
-            };
-            let Some(spec_str) = value.as_str() else {
+            }
+            warn_about_invalid_env_vars(keys.last().unwrap().get(), value);
+            if tool_path_str(value).is_some() {
+                continue;
+            }
+            let Some(spec_str) = tool_spec_str(value) else {
                 warn!(
                     "A tool spec with alias '{}' could not be parsed!\
                     \nThe tool will be ignored and may not be available.\
-                    \nExpected: String\
+                    \nExpected: String, or table with a '{TOOL_SPEC_KEY}' or '{TOOL_PATH_KEY}' string\
                     \nActual: {}",
                     keys.into_iter().last().unwrap(),
                     value.type_name()
                 );
                 continue;
             };
-            if let Err(e) = spec_str.parse::<ToolSpec>() {
+            if let Err(e) = spec_str.parse::<ToolSpecReq>() {
                 warn!(
                     "A tool spec with alias '{}' could not be parsed!\
                     \nThe tool will be ignored and may not be available.\
                     \nError: {e}",
                     keys.into_iter().last().unwrap(),
                 );
-            };
+            }
         }
 
         Ok(Self { document })
     }
 }
 
+fn warn_about_invalid_env_vars(alias: &str, value: &Value) {
+    let Some(env) = value.as_inline_table().and_then(|t| t.get(TOOL_ENV_KEY)) else {
+        return;
+    };
+    let Some(env) = env.as_inline_table() else {
+        warn!(
+            "Environment variables for tool '{alias}' could not be parsed!\
+            \nThe variables will be ignored.\
+            \nExpected: Table\
+            \nActual: {}",
+            env.type_name()
+        );
+        return;
+    };
+    for (name, value) in env {
+        if tool_env_var(name, value).is_none() {
+            warn!(
+                "Environment variable '{name}' for tool '{alias}' could not be parsed!\
+                \nThe variable will be ignored.\
+                \nExpected: String, or table with a '{TOOL_ENV_VALUE_KEY}' string \
+                and an optional '{TOOL_ENV_OVERRIDE_KEY}' boolean"
+            );
+        }
+    }
+}
+
 impl ToString for RokitManifest {
     fn to_string(&self) -> String {
         self.document.to_string()
@@ -260,3 +762,138 @@ impl Default for RokitManifest {
         Self { document }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+[tools]
+plain = "author/plain@1.0.0"
+multi = { spec = "author/multi@2.0.0", bin = "multi-cli" }
+local = { path = "../local/target/release/local" }
+"#;
+
+    fn alias(s: &str) -> ToolAlias {
+        s.parse().unwrap()
+    }
+
+    fn spec(s: &str) -> ToolSpec {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn tool_table_form() {
+        let manifest = MANIFEST.parse::<RokitManifest>().unwrap();
+        assert_eq!(
+            manifest.get_tool(&alias("multi")),
+            Some(spec("author/multi@2.0.0"))
+        );
+        assert_eq!(
+            manifest.get_tool_binary(&alias("multi")),
+            Some(String::from("multi-cli"))
+        );
+        assert_eq!(manifest.get_tool_binary(&alias("plain")), None);
+        assert_eq!(manifest.tool_specs().len(), 2);
+    }
+
+    #[test]
+    fn tool_path_form() {
+        let manifest = MANIFEST.parse::<RokitManifest>().unwrap();
+        assert!(manifest.has_tool(&alias("local")));
+        assert_eq!(manifest.get_tool(&alias("local")), None);
+        assert_eq!(manifest.get_tool_requirement(&alias("local")), None);
+        assert_eq!(
+            manifest.get_tool_path(&alias("local")),
+            Some(String::from("../local/target/release/local"))
+        );
+        assert_eq!(manifest.get_tool_path(&alias("plain")), None);
+        assert_eq!(
+            manifest.tool_paths(),
+            vec![(
+                alias("local"),
+                String::from("../local/target/release/local")
+            )]
+        );
+        assert_eq!(manifest.tool_requirements().len(), 2);
+    }
+
+    #[test]
+    fn update_keeps_binary() {
+        let mut manifest = MANIFEST.parse::<RokitManifest>().unwrap();
+        assert!(manifest.update_tool(&alias("multi"), &spec("author/multi@3.0.0")));
+        assert_eq!(
+            manifest.get_tool(&alias("multi")),
+            Some(spec("author/multi@3.0.0"))
+        );
+        assert_eq!(
+            manifest.get_tool_binary(&alias("multi")),
+            Some(String::from("multi-cli"))
+        );
+    }
+
+    #[test]
+    fn update_keeps_comments() {
+        let mut manifest = "[tools]\nplain = \"author/plain@^1.0\" # Pinned below 2.0\n"
+            .parse::<RokitManifest>()
+            .unwrap();
+        assert!(manifest.update_tool(&alias("plain"), &spec("author/plain@1.2.3")));
+        assert_eq!(
+            manifest.to_string(),
+            "[tools]\nplain = \"author/plain@1.2.3\" # Pinned below 2.0\n"
+        );
+    }
+
+    #[test]
+    fn normalize_specs() {
+        let mut manifest = r#"
+[tools]
+# Tools used by this project
+quoted = 'author/quoted@1.0.0' # Literal string
+prefixed = "github:author/prefixed@1.0.0"
+table = { spec = "author/table @ 1.2", bin = "table" }
+normal = "author/normal@1.0.0"
+local = { path = "./local" }
+"#
+        .parse::<RokitManifest>()
+        .unwrap();
+
+        let changed = manifest.normalize_tool_specs(|id| {
+            (id.name() == "table").then(|| id.with_casing_of("Author/Table"))
+        });
+        assert_eq!(
+            changed,
+            vec![alias("quoted"), alias("prefixed"), alias("table")]
+        );
+        assert_eq!(
+            manifest.to_string(),
+            r#"
+[tools]
+# Tools used by this project
+quoted = "author/quoted@1.0.0" # Literal string
+prefixed = "author/prefixed@1.0.0"
+table = { spec = "Author/Table@^1.2", bin = "table" }
+normal = "author/normal@1.0.0"
+local = { path = "./local" }
+"#
+        );
+
+        // Normalizing again must not change anything
+        assert!(manifest.normalize_tool_specs(|_| None).is_empty());
+    }
+
+    #[test]
+    fn set_binary() {
+        let mut manifest = MANIFEST.parse::<RokitManifest>().unwrap();
+        assert!(manifest.set_tool_binary(&alias("plain"), "plain-cli"));
+        assert!(!manifest.set_tool_binary(&alias("missing"), "missing-cli"));
+        assert_eq!(
+            manifest.get_tool(&alias("plain")),
+            Some(spec("author/plain@1.0.0"))
+        );
+        assert_eq!(
+            manifest.get_tool_binary(&alias("plain")),
+            Some(String::from("plain-cli"))
+        );
+    }
+}
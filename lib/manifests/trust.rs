@@ -0,0 +1,303 @@
+#![allow(clippy::to_string_trait_impl)]
+// NOTE: We don't want to implement Display here since it may
+// make library consumers think that trust manifests are meant
+// to be displayed - they are only meant to be stringified.
+
+use std::{path::Path, str::FromStr};
+
+use thiserror::Error;
+use toml_edit::{DocumentMut, TomlError};
+
+use crate::{
+    result::{RokitError, RokitResult},
+    tool::ToolId,
+    util::fs::{load_from_file, save_to_file},
+};
+
+pub const MANIFEST_FILE_NAME: &str = "trust.toml";
+pub(super) const MANIFEST_DEFAULT_CONTENTS: &str = "
+# This file lists trust policies for Rokit, a toolchain manager for Roblox projects.
+# For more information, see <|REPOSITORY_URL|>
+
+# Tools matching any of these patterns are trusted automatically, without prompting.
+# Use \"author/*\" to trust all tools from an author or organization, or \"author/name\"
+# to trust a single tool. Patterns may be prefixed with a provider, like \"gitlab:group/*\".
+
+trusted = []
+";
+
+const TRUSTED_KEY: &str = "trusted";
+const WILDCARD: &str = "*";
+
+/**
+    Error type representing the possible errors that can occur when parsing a trust manifest.
+*/
+#[derive(Debug, Error)]
+pub enum TrustManifestError {
+    #[error("{0}")]
+    Toml(Box<TomlError>),
+    #[error("unknown key '{0}', expected only '{TRUSTED_KEY}'")]
+    UnknownKey(String),
+    #[error("'{TRUSTED_KEY}' must be an array of strings, got {0}")]
+    InvalidType(&'static str),
+    #[error(
+        "invalid pattern '{pattern}': {reason}\
+        \nexpected 'author/name' or 'author/*', optionally prefixed with a provider"
+    )]
+    InvalidPattern { pattern: String, reason: String },
+}
+
+impl From<TomlError> for TrustManifestError {
+    fn from(err: TomlError) -> Self {
+        TrustManifestError::Toml(err.into())
+    }
+}
+
+/**
+    A pattern matching tool ids - either a single tool, or all tools from an author.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrustPattern {
+    id: ToolId,
+    any_name: bool,
+}
+
+impl TrustPattern {
+    fn matches(&self, id: &ToolId) -> bool {
+        if self.any_name {
+            self.id.provider == id.provider
+                && self.id.host == id.host
+                && self.id.author == id.author
+        } else {
+            self.id == *id
+        }
+    }
+}
+
+impl FromStr for TrustPattern {
+    type Err = TrustManifestError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        // NOTE: A trailing slash is the same as a wildcard, "author/" == "author/*"
+        let full = if s.ends_with('/') {
+            format!("{s}{WILDCARD}")
+        } else {
+            s.to_string()
+        };
+
+        let id = full
+            .parse::<ToolId>()
+            .map_err(|e| TrustManifestError::InvalidPattern {
+                pattern: s.to_string(),
+                reason: e.to_string(),
+            })?;
+
+        let any_name = id.name() == WILDCARD;
+        if (!any_name && id.name().contains(WILDCARD)) || id.author().contains(WILDCARD) {
+            return Err(TrustManifestError::InvalidPattern {
+                pattern: s.to_string(),
+                reason: String::from("wildcards may only be used for the whole tool name"),
+            });
+        }
+
+        Ok(Self { id, any_name })
+    }
+}
+
+/**
+    Trust manifest file.
+
+    Contains trust policies for automatically trusting tools, for example
+    all tools from a specific author or organization, which is useful on
+    CI machines where prompting for trust is not possible.
+*/
+#[derive(Debug, Clone)]
+pub struct TrustManifest {
+    document: DocumentMut,
+    patterns: Vec<TrustPattern>,
+}
+
+impl TrustManifest {
+    /**
+        Loads the manifest from the given directory, or creates a new one if it doesn't exist.
+
+        If the manifest doesn't exist, a new one will be created with default contents and saved.
+
+        See [`TrustManifest::load`] and [`TrustManifest::save`] for more information.
+
+        # Errors
+
+        - If the manifest file could not be loaded or created.
+        - If the manifest file contains invalid trust policies.
+    */
+    pub async fn load_or_create(dir: impl AsRef<Path>) -> RokitResult<Self> {
+        let path = dir.as_ref().join(MANIFEST_FILE_NAME);
+        match load_from_file(path).await {
+            Ok(manifest) => Ok(manifest),
+            Err(RokitError::FileNotFound(_)) => {
+                let new = Self::default();
+                new.save(dir).await?;
+                Ok(new)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+        Loads the manifest from the given directory.
+
+        This will search for a file named `trust.toml` in the given directory.
+
+        # Errors
+
+        - If the manifest file could not be loaded.
+        - If the manifest file contains invalid trust policies.
+    */
+    #[tracing::instrument(skip(dir), level = "trace")]
+    pub async fn load(dir: impl AsRef<Path>) -> RokitResult<Self> {
+        let path = dir.as_ref().join(MANIFEST_FILE_NAME);
+        tracing::trace!(?path, "Loading manifest");
+        load_from_file(path).await
+    }
+
+    /**
+        Saves the manifest to the given directory.
+
+        This will write the manifest to a file named `trust.toml` in the given directory.
+
+        # Errors
+
+        - If the manifest file could not be saved.
+    */
+    #[tracing::instrument(skip(self, dir), level = "trace")]
+    pub async fn save(&self, dir: impl AsRef<Path>) -> RokitResult<()> {
+        let path = dir.as_ref().join(MANIFEST_FILE_NAME);
+        tracing::trace!(?path, "Saving manifest");
+        save_to_file(path, self.clone()).await
+    }
+
+    /**
+        Checks if the given tool is trusted by any of the trust policies in this manifest.
+    */
+    #[must_use]
+    pub fn is_trusted(&self, id: &ToolId) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(id))
+    }
+}
+
+impl FromStr for TrustManifest {
+    type Err = TrustManifestError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = s.parse::<DocumentMut>()?;
+
+        /*
+            Unlike other manifests, we do not just warn about invalid
+            entries here - silently ignoring a mistyped trust policy
+            would lead to confusing prompts, or worse, in CI.
+        */
+        let mut patterns = Vec::new();
+        for (key, value) in document.iter() {
+            if key != TRUSTED_KEY {
+                return Err(TrustManifestError::UnknownKey(key.to_string()));
+            }
+            let array = value
+                .as_array()
+                .ok_or(TrustManifestError::InvalidType(value.type_name()))?;
+            for item in array {
+                let pattern = item
+                    .as_str()
+                    .ok_or(TrustManifestError::InvalidType(item.type_name()))?;
+                patterns.push(pattern.parse()?);
+            }
+        }
+
+        Ok(Self { document, patterns })
+    }
+}
+
+impl ToString for TrustManifest {
+    fn to_string(&self) -> String {
+        self.document.to_string()
+    }
+}
+
+impl Default for TrustManifest {
+    fn default() -> Self {
+        let document = super::make_manifest_template(MANIFEST_DEFAULT_CONTENTS)
+            .parse::<DocumentMut>()
+            .expect("default manifest template should be valid");
+        Self {
+            document,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(s: &str) -> Result<TrustManifest, TrustManifestError> {
+        s.parse::<TrustManifest>()
+    }
+
+    fn id(s: &str) -> ToolId {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn trusts_matching_patterns() {
+        let trust =
+            manifest(r#"trusted = ["my-org/*", "other-org/", "rojo-rbx/rojo", "gitlab:group/*"]"#)
+                .unwrap();
+        assert!(trust.is_trusted(&id("my-org/tool")));
+        assert!(trust.is_trusted(&id("MY-ORG/Tool")));
+        assert!(trust.is_trusted(&id("other-org/tool")));
+        assert!(trust.is_trusted(&id("rojo-rbx/rojo")));
+        assert!(trust.is_trusted(&id("gitlab:group/tool")));
+        assert!(!trust.is_trusted(&id("rojo-rbx/remodel")));
+        assert!(!trust.is_trusted(&id("group/tool")));
+        assert!(!trust.is_trusted(&id("someone/tool")));
+    }
+
+    #[test]
+    fn default_trusts_nothing() {
+        let trust = TrustManifest::default();
+        assert!(!trust.is_trusted(&id("my-org/tool")));
+        let reparsed = manifest(&trust.to_string()).unwrap();
+        assert!(!reparsed.is_trusted(&id("my-org/tool")));
+    }
+
+    #[test]
+    fn rejects_invalid_policies() {
+        assert!(matches!(
+            manifest(r#"trust = ["my-org/*"]"#),
+            Err(TrustManifestError::UnknownKey(_))
+        ));
+        assert!(matches!(
+            manifest(r#"trusted = "my-org/*""#),
+            Err(TrustManifestError::InvalidType(_))
+        ));
+        assert!(matches!(
+            manifest("trusted = [1]"),
+            Err(TrustManifestError::InvalidType(_))
+        ));
+        assert!(matches!(
+            manifest(r#"trusted = ["my-org"]"#),
+            Err(TrustManifestError::InvalidPattern { .. })
+        ));
+        assert!(matches!(
+            manifest(r#"trusted = ["*/tool"]"#),
+            Err(TrustManifestError::InvalidPattern { .. })
+        ));
+        assert!(matches!(
+            manifest(r#"trusted = ["my-org/tool-*"]"#),
+            Err(TrustManifestError::InvalidPattern { .. })
+        ));
+        assert!(matches!(
+            manifest(r#"trusted = ["unknown:my-org/*"]"#),
+            Err(TrustManifestError::InvalidPattern { .. })
+        ));
+    }
+}
@@ -2,13 +2,23 @@ use std::io::Error as IoError;
 use std::path::PathBuf;
 
 use postcard::Error as PostcardError;
+use semver::Version;
 use serde_json::Error as JsonError;
 use thiserror::Error;
 use tokio::task::JoinError;
 use toml_edit::TomlError;
 use zip::result::ZipError;
 
-use crate::sources::{github::GithubError, ExtractError};
+use crate::{
+    manifests::{
+        RokitConfigError, RokitLockfileError, TrustManifestError, ROKIT_LOCKFILE_NAME,
+        TRUST_MANIFEST_FILE_NAME,
+    },
+    sources::{
+        gitea::GiteaError, github::GithubError, gitlab::GitlabError, ExtractError, SignatureError,
+    },
+    tool::{ToolSpec, ToolSpecReq},
+};
 
 #[derive(Debug, Error)]
 pub enum RokitError {
@@ -16,10 +26,78 @@ pub enum RokitError {
     HomeNotFound,
     #[error("file not found: {0}")]
     FileNotFound(PathBuf),
+    #[error("manifest at '{}' could not be parsed", .0.display())]
+    InvalidManifest(PathBuf),
     #[error("unexpected invalid UTF-8")]
     InvalidUtf8,
+    #[error("file at '{}' could not be made executable: {reason}", path.display())]
+    NotExecutable { path: PathBuf, reason: String },
+    #[error(
+        "checksum mismatch for tool '{tool_spec}'\
+        \nExpected SHA-256: {expected}\
+        \nActual SHA-256:   {actual}"
+    )]
+    ChecksumMismatch {
+        tool_spec: Box<ToolSpec>,
+        expected: String,
+        actual: String,
+    },
+    #[error("tool '{tool_spec}' {reason}")]
+    ToolVerificationFailed {
+        tool_spec: Box<ToolSpec>,
+        reason: String,
+    },
+    #[error("post-install command for tool '{tool_spec}' {reason}")]
+    PostInstallFailed {
+        tool_spec: Box<ToolSpec>,
+        reason: String,
+    },
+    #[error("incomplete download for tool '{tool_spec}' - {reason}")]
+    IncompleteDownload {
+        tool_spec: Box<ToolSpec>,
+        reason: String,
+    },
+    #[error("no compatible artifact found for tool '{0}'")]
+    NoCompatibleArtifact(Box<ToolSpec>),
+    #[error(
+        "no release of '{}' matches '{}'{}",
+        requirement.id(),
+        requirement.version_req(),
+        format_closest_versions(closest)
+    )]
+    NoMatchingVersion {
+        requirement: Box<ToolSpecReq>,
+        closest: Vec<Version>,
+    },
+    #[error(
+        "failed to recreate {} link(s), other links were recreated:\n{}",
+        .0.len(),
+        format_link_failures(.0)
+    )]
+    LinksFailed(Vec<(PathBuf, RokitError)>),
+    #[error("invalid trust policy in '{TRUST_MANIFEST_FILE_NAME}': {0}")]
+    TrustManifest(Box<TrustManifestError>),
+    #[error("invalid config in '{}': {error}", path.display())]
+    Config {
+        path: PathBuf,
+        error: Box<RokitConfigError>,
+    },
+    #[error("invalid lockfile '{ROKIT_LOCKFILE_NAME}': {0}")]
+    Lockfile(Box<RokitLockfileError>),
+    #[error("failed to verify signature: {0}")]
+    Signature(Box<SignatureError>),
+    #[error("failed to load CA certificates from '{}': {reason}", path.display())]
+    InvalidCaBundle { path: PathBuf, reason: String },
+    #[error("failed to access the system keyring: {reason}")]
+    Keyring { reason: String },
     #[error("failed to extract artifact: {0}")]
     Extract(Box<ExtractError>),
+    #[error(
+        "Rokit couldn't reach the network - check your internet connection and try again.\
+        \nTools that are already installed can be used without network access with \
+        `rokit install --locked`, and previously downloaded tools are reused from the cache."
+    )]
+    NetworkUnavailable(#[source] Box<RokitError>),
     #[error("task join error: {0}")]
     TaskJoinError(Box<JoinError>),
     #[error("TOML parse error: {0}")]
@@ -32,12 +110,91 @@ pub enum RokitError {
     Postcard(Box<PostcardError>),
     #[error("Zip file error: {0}")]
     Zip(Box<ZipError>),
+    #[error("download error: {0}")]
+    Download(Box<reqwest::Error>),
     #[error("GitHub error: {0}")]
     GitHub(Box<GithubError>),
+    #[error("GitLab error: {0}")]
+    GitLab(Box<GitlabError>),
+    #[error("Gitea error: {0}")]
+    Gitea(Box<GiteaError>),
 }
 
 pub type RokitResult<T> = Result<T, RokitError>;
 
+impl RokitError {
+    /**
+        Checks if this error was caused by a network request timing out,
+        either while connecting, or while waiting to receive any data.
+    */
+    #[must_use]
+    pub fn is_timeout(&self) -> bool {
+        self.reqwest_error().is_some_and(reqwest::Error::is_timeout)
+    }
+
+    /**
+        Checks if this error was caused by a network request failing to
+        connect, such as when DNS lookups fail or there is no network at all.
+    */
+    #[must_use]
+    pub fn is_connect(&self) -> bool {
+        match self {
+            Self::NetworkUnavailable(_) => true,
+            _ => self.reqwest_error().is_some_and(reqwest::Error::is_connect),
+        }
+    }
+
+    fn reqwest_error(&self) -> Option<&reqwest::Error> {
+        match self {
+            Self::GitHub(e) => match e.as_ref() {
+                GithubError::Reqwest(e) => Some(e.as_ref()),
+                GithubError::ReqwestMiddleware(e) => middleware_reqwest_error(e),
+                _ => None,
+            },
+            Self::GitLab(e) => match e.as_ref() {
+                GitlabError::Reqwest(e) => Some(e.as_ref()),
+                GitlabError::ReqwestMiddleware(e) => middleware_reqwest_error(e),
+                _ => None,
+            },
+            Self::Gitea(e) => match e.as_ref() {
+                GiteaError::Reqwest(e) => Some(e.as_ref()),
+                GiteaError::ReqwestMiddleware(e) => middleware_reqwest_error(e),
+                _ => None,
+            },
+            Self::Download(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+fn middleware_reqwest_error(err: &reqwest_middleware::Error) -> Option<&reqwest::Error> {
+    match err {
+        reqwest_middleware::Error::Reqwest(e) => Some(e),
+        reqwest_middleware::Error::Middleware(_) => None,
+    }
+}
+
+fn format_closest_versions(closest: &[Version]) -> String {
+    if closest.is_empty() {
+        String::from("\nNo releases were found for this tool.")
+    } else {
+        let versions = closest
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("\nClosest available versions: {versions}")
+    }
+}
+
+fn format_link_failures(failures: &[(PathBuf, RokitError)]) -> String {
+    failures
+        .iter()
+        .map(|(path, err)| format!("- {}: {err}", path.display()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 // FUTURE: Figure out some way to reduce this boxing boilerplate
 
 impl From<ExtractError> for RokitError {
@@ -46,6 +203,24 @@ impl From<ExtractError> for RokitError {
     }
 }
 
+impl From<SignatureError> for RokitError {
+    fn from(err: SignatureError) -> Self {
+        RokitError::Signature(err.into())
+    }
+}
+
+impl From<RokitLockfileError> for RokitError {
+    fn from(err: RokitLockfileError) -> Self {
+        RokitError::Lockfile(err.into())
+    }
+}
+
+impl From<TrustManifestError> for RokitError {
+    fn from(err: TrustManifestError) -> Self {
+        RokitError::TrustManifest(err.into())
+    }
+}
+
 impl From<JoinError> for RokitError {
     fn from(err: JoinError) -> Self {
         RokitError::TaskJoinError(err.into())
@@ -82,8 +257,26 @@ impl From<ZipError> for RokitError {
     }
 }
 
+impl From<reqwest::Error> for RokitError {
+    fn from(err: reqwest::Error) -> Self {
+        RokitError::Download(err.into())
+    }
+}
+
 impl From<GithubError> for RokitError {
     fn from(err: GithubError) -> Self {
         RokitError::GitHub(err.into())
     }
 }
+
+impl From<GitlabError> for RokitError {
+    fn from(err: GitlabError) -> Self {
+        RokitError::GitLab(err.into())
+    }
+}
+
+impl From<GiteaError> for RokitError {
+    fn from(err: GiteaError) -> Self {
+        RokitError::Gitea(err.into())
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt::Write;
+
+use ring::digest::{digest, SHA256};
+
+const CHECKSUM_EXTENSIONS: [&str; 2] = [".sha256", ".sha256sum"];
+const CHECKSUM_LIST_NAMES: [&str; 4] = [
+    "checksums.txt",
+    "sha256sums",
+    "sha256sums.txt",
+    "checksums.sha256",
+];
+
+/**
+    Checks if the given file name is a checksum file for exactly one other file.
+
+    These are named after the file they contain a checksum for, such as `file.zip.sha256`.
+*/
+pub(super) fn is_sibling_checksum_file_name(checksum_file_name: &str, file_name: &str) -> bool {
+    let checksum_file_name = checksum_file_name.to_ascii_lowercase();
+    let file_name = file_name.to_ascii_lowercase();
+    CHECKSUM_EXTENSIONS.iter().any(|ext| {
+        checksum_file_name
+            .strip_suffix(ext)
+            .is_some_and(|stem| stem == file_name)
+    })
+}
+
+/**
+    Checks if the given file name is a checksum file listing checksums for many files.
+
+    These have common names such as `checksums.txt` or `SHA256SUMS`,
+    and may also be prefixed by the tool name, such as `tool-1.0.0-checksums.txt`.
+*/
+pub(super) fn is_checksum_list_file_name(file_name: &str) -> bool {
+    let file_name = file_name.to_ascii_lowercase();
+    CHECKSUM_LIST_NAMES.iter().any(|name| {
+        file_name == *name
+            || file_name
+                .strip_suffix(name)
+                .is_some_and(|prefix| prefix.ends_with(['-', '_', '.']))
+    })
+}
+
+/**
+    Checks if the given file name is any kind of checksum file.
+*/
+pub(super) fn is_checksum_file_name(file_name: &str) -> bool {
+    let lower = file_name.to_ascii_lowercase();
+    CHECKSUM_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+        || is_checksum_list_file_name(file_name)
+}
+
+/**
+    Parses the SHA-256 checksum for the given file name out of checksum file contents.
+
+    Supports both files containing only a single digest, as well
+    as the `<digest>  <file name>` format used by `sha256sum`.
+*/
+pub(super) fn parse_checksum_contents(contents: &str, file_name: &str) -> Option<String> {
+    let lines = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>();
+
+    for line in &lines {
+        let mut parts = line.splitn(2, char::is_whitespace);
+        let digest = parts.next().unwrap_or_default();
+        let name = parts.next().unwrap_or_default().trim();
+        let name = name.trim_start_matches(['*', ' ']);
+        let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+
+        if !is_sha256_hex(digest) {
+            continue;
+        }
+
+        // A single digest with no file name is the format used by sibling checksum files
+        if name.is_empty() && lines.len() == 1 {
+            return Some(digest.to_ascii_lowercase());
+        }
+
+        if name.eq_ignore_ascii_case(file_name) {
+            return Some(digest.to_ascii_lowercase());
+        }
+    }
+
+    None
+}
+
+/**
+    Computes the SHA-256 digest of the given bytes, as a lowercase hex string.
+*/
+pub(super) fn sha256_hex(bytes: &[u8]) -> String {
+    digest(&SHA256, bytes)
+        .as_ref()
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+fn is_sha256_hex(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    const OTHER: &str = "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae";
+
+    #[test]
+    fn sha256_basic() {
+        assert_eq!(sha256_hex(b""), DIGEST);
+        assert_eq!(sha256_hex(b"foo"), OTHER);
+    }
+
+    #[test]
+    fn checksum_file_names() {
+        assert!(is_sibling_checksum_file_name("tool.zip.sha256", "tool.zip"));
+        assert!(is_sibling_checksum_file_name("Tool.ZIP.SHA256", "tool.zip"));
+        assert!(is_sibling_checksum_file_name(
+            "tool.zip.sha256sum",
+            "tool.zip"
+        ));
+        assert!(!is_sibling_checksum_file_name(
+            "tool.zip.sha256",
+            "tool.tar.gz"
+        ));
+        assert!(!is_sibling_checksum_file_name("tool.zip", "tool.zip"));
+
+        assert!(is_checksum_list_file_name("checksums.txt"));
+        assert!(is_checksum_list_file_name("SHA256SUMS"));
+        assert!(is_checksum_list_file_name("tool-1.0.0-checksums.txt"));
+        assert!(!is_checksum_list_file_name("notchecksums.txt"));
+        assert!(!is_checksum_list_file_name("tool-1.0.0-linux-x86_64.zip"));
+
+        assert!(is_checksum_file_name("tool.zip.sha256"));
+        assert!(is_checksum_file_name("checksums.txt"));
+        assert!(!is_checksum_file_name("tool.zip"));
+    }
+
+    #[test]
+    fn parse_single_digest() {
+        assert_eq!(
+            parse_checksum_contents(&format!("{DIGEST}\n"), "tool.zip"),
+            Some(DIGEST.to_string())
+        );
+        assert_eq!(
+            parse_checksum_contents(&DIGEST.to_ascii_uppercase(), "tool.zip"),
+            Some(DIGEST.to_string())
+        );
+        assert_eq!(parse_checksum_contents("not a digest", "tool.zip"), None);
+    }
+
+    #[test]
+    fn parse_checksum_list() {
+        let contents = format!(
+            "# comment\n{OTHER}  tool-linux.zip\n{DIGEST} *tool-windows.zip\n{OTHER}  ./dir/tool-macos.zip\n"
+        );
+        assert_eq!(
+            parse_checksum_contents(&contents, "tool-windows.zip"),
+            Some(DIGEST.to_string())
+        );
+        assert_eq!(
+            parse_checksum_contents(&contents, "tool-linux.zip"),
+            Some(OTHER.to_string())
+        );
+        assert_eq!(
+            parse_checksum_contents(&contents, "tool-macos.zip"),
+            Some(OTHER.to_string())
+        );
+        assert_eq!(parse_checksum_contents(&contents, "tool-other.zip"), None);
+    }
+}
@@ -2,6 +2,13 @@ use std::{fmt, str::FromStr};
 
 use super::util::split_filename_and_extensions;
 
+const MAGIC_ZIP: &[u8] = b"PK\x03\x04";
+const MAGIC_GZIP: &[u8] = &[0x1F, 0x8B];
+const MAGIC_XZ: &[u8] = &[0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00];
+const MAGIC_ZSTD: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+const MAGIC_TAR: &[u8] = b"ustar";
+const MAGIC_TAR_OFFSET: usize = 257;
+
 /**
     An artifact format supported by Rokit.
 */
@@ -10,6 +17,8 @@ pub enum ArtifactFormat {
     Zip,
     Tar,
     TarGz,
+    TarXz,
+    TarZst,
 }
 
 impl ArtifactFormat {
@@ -19,6 +28,8 @@ impl ArtifactFormat {
             Self::Zip => "zip",
             Self::Tar => "tar",
             Self::TarGz => "tar.gz",
+            Self::TarXz => "tar.xz",
+            Self::TarZst => "tar.zst",
         }
     }
 
@@ -28,15 +39,53 @@ impl ArtifactFormat {
             [.., ext] if ext.eq_ignore_ascii_case("zip") => Some(Self::Zip),
             [.., ext] if ext.eq_ignore_ascii_case("tar") => Some(Self::Tar),
             [.., ext] if ext.eq_ignore_ascii_case("tgz") => Some(Self::TarGz),
+            [.., ext] if ext.eq_ignore_ascii_case("txz") => Some(Self::TarXz),
+            [.., ext] if ext.eq_ignore_ascii_case("tzst") => Some(Self::TarZst),
             [.., ext1, ext2]
                 if ext1.eq_ignore_ascii_case("tar") && ext2.eq_ignore_ascii_case("gz") =>
             {
                 Some(Self::TarGz)
             }
+            [.., ext1, ext2]
+                if ext1.eq_ignore_ascii_case("tar") && ext2.eq_ignore_ascii_case("xz") =>
+            {
+                Some(Self::TarXz)
+            }
+            [.., ext1, ext2]
+                if ext1.eq_ignore_ascii_case("tar") && ext2.eq_ignore_ascii_case("zst") =>
+            {
+                Some(Self::TarZst)
+            }
             _ => None,
         }
     }
 
+    /**
+        Detects the format of an artifact from the magic bytes at the start of its contents.
+
+        Note that any compressed format is assumed to contain a tar archive.
+    */
+    #[must_use]
+    pub fn from_magic_bytes(contents: impl AsRef<[u8]>) -> Option<Self> {
+        let contents = contents.as_ref();
+        if contents.starts_with(MAGIC_ZIP) {
+            Some(Self::Zip)
+        } else if contents.starts_with(MAGIC_GZIP) {
+            Some(Self::TarGz)
+        } else if contents.starts_with(MAGIC_XZ) {
+            Some(Self::TarXz)
+        } else if contents.starts_with(MAGIC_ZSTD) {
+            Some(Self::TarZst)
+        } else if contents
+            .get(MAGIC_TAR_OFFSET..)
+            .is_some_and(|c| c.starts_with(MAGIC_TAR))
+        {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
     #[must_use]
     pub fn from_path_or_url(path_or_url: impl AsRef<str>) -> Option<Self> {
         let path_or_url = path_or_url.as_ref();
@@ -53,6 +102,8 @@ impl FromStr for ArtifactFormat {
             "zip" => Ok(Self::Zip),
             "tar" => Ok(Self::Tar),
             "tar.gz" | "tgz" => Ok(Self::TarGz),
+            "tar.xz" | "txz" => Ok(Self::TarXz),
+            "tar.zst" | "tzst" => Ok(Self::TarZst),
             _ => Err(format!("unknown artifact format '{l}'")),
         }
     }
@@ -99,6 +150,53 @@ mod tests {
         assert_eq!(format_from_str("really.long.file.name"), None);
     }
 
+    #[test]
+    fn format_from_compressed_tar_extensions() {
+        assert_eq!(format_from_str("file.tar.xz"), Some(ArtifactFormat::TarXz));
+        assert_eq!(format_from_str("file.txz"), Some(ArtifactFormat::TarXz));
+        assert_eq!(
+            format_from_str("file.tar.zst"),
+            Some(ArtifactFormat::TarZst)
+        );
+        assert_eq!(format_from_str("file.tzst"), Some(ArtifactFormat::TarZst));
+        assert_eq!(
+            format_from_str("file.TAR.ZST"),
+            Some(ArtifactFormat::TarZst)
+        );
+        assert_eq!(
+            format_from_str("tool-1.0.0-x86_64-unknown-linux-musl.tar.xz"),
+            Some(ArtifactFormat::TarXz)
+        );
+    }
+
+    #[test]
+    fn format_from_magic_bytes() {
+        let mut tar = vec![0u8; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(
+            ArtifactFormat::from_magic_bytes(b"PK\x03\x04rest"),
+            Some(ArtifactFormat::Zip)
+        );
+        assert_eq!(
+            ArtifactFormat::from_magic_bytes([0x1F, 0x8B, 0x08]),
+            Some(ArtifactFormat::TarGz)
+        );
+        assert_eq!(
+            ArtifactFormat::from_magic_bytes([0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00, 0x00]),
+            Some(ArtifactFormat::TarXz)
+        );
+        assert_eq!(
+            ArtifactFormat::from_magic_bytes([0x28, 0xB5, 0x2F, 0xFD, 0x00]),
+            Some(ArtifactFormat::TarZst)
+        );
+        assert_eq!(
+            ArtifactFormat::from_magic_bytes(&tar),
+            Some(ArtifactFormat::Tar)
+        );
+        assert_eq!(ArtifactFormat::from_magic_bytes(b"#!/bin/sh"), None);
+        assert_eq!(ArtifactFormat::from_magic_bytes([]), None);
+    }
+
     #[test]
     fn format_from_real_tools() {
         assert_eq!(
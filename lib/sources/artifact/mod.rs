@@ -1,29 +1,43 @@
-use tracing::instrument;
+use tracing::{debug, instrument};
 use url::Url;
 
 use crate::{
     descriptor::{Descriptor, OS},
-    result::RokitResult,
-    tool::ToolSpec,
+    result::{RokitError, RokitResult},
+    tool::{ToolAlias, ToolSpec},
 };
 
 use super::{
-    decompression::decompress_gzip,
-    extraction::{extract_tar_file, extract_zip_file},
+    decompression::{decompress_gzip, decompress_xz, decompress_zstd},
+    extraction::{extract_tar_file, extract_zip_file, ExtractedFile},
+    gitea::models::Asset as GiteaAsset,
     github::models::Asset,
+    gitlab::models::AssetLink,
     ExtractError,
 };
 
+mod checksum;
 mod format;
+mod pattern;
 mod provider;
+mod signature;
 mod sorting;
 mod util;
 
-use self::sorting::sort_preferred_artifact;
+use self::checksum::{
+    is_checksum_file_name, is_checksum_list_file_name, is_sibling_checksum_file_name,
+    parse_checksum_contents, sha256_hex,
+};
+use self::signature::{is_sibling_signature_file_name, is_signature_file_name};
+use self::sorting::{
+    artifact_format_score, artifact_name_score, sort_preferred_artifact, sort_preferred_format,
+};
 use self::util::split_filename_and_extensions;
 
 pub use self::format::ArtifactFormat;
+pub use self::pattern::{ArtifactPattern, ArtifactPatternError};
 pub use self::provider::ArtifactProvider;
+pub use self::signature::{KeyId, MinisignPublicKey, SignatureError};
 
 /**
     An artifact found by Rokit, to be downloaded and installed.
@@ -35,6 +49,10 @@ pub struct Artifact {
     pub id: Option<String>,
     pub url: Option<Url>,
     pub name: Option<String>,
+    pub file_name: Option<String>,
+    pub checksum: Option<String>,
+    pub signature: Option<String>,
+    pub size: Option<u64>,
     pub tool_spec: ToolSpec,
 }
 
@@ -43,15 +61,189 @@ impl Artifact {
         let (name, extensions) = split_filename_and_extensions(&asset.name);
         let format = ArtifactFormat::from_extensions(extensions);
         Self {
-            provider: ArtifactProvider::GitHub,
+            provider: spec.provider(),
             format,
             id: Some(asset.id.to_string()),
             url: Some(asset.url.clone()),
             name: Some(name.to_string()),
+            file_name: Some(asset.name.clone()),
+            checksum: None,
+            signature: None,
+            size: asset.size,
             tool_spec: spec.clone(),
         }
     }
 
+    pub(crate) fn from_gitlab_release_link(link: &AssetLink, spec: &ToolSpec) -> Self {
+        let (name, extensions) = split_filename_and_extensions(&link.name);
+        let format = ArtifactFormat::from_extensions(extensions);
+        Self {
+            provider: ArtifactProvider::GitLab,
+            format,
+            id: Some(link.id.to_string()),
+            url: Some(link.download_url().clone()),
+            name: Some(name.to_string()),
+            file_name: Some(link.name.clone()),
+            checksum: None,
+            signature: None,
+            size: None,
+            tool_spec: spec.clone(),
+        }
+    }
+
+    pub(crate) fn from_gitea_release_asset(asset: &GiteaAsset, spec: &ToolSpec) -> Self {
+        let (name, extensions) = split_filename_and_extensions(&asset.name);
+        let format = ArtifactFormat::from_extensions(extensions);
+        Self {
+            provider: ArtifactProvider::Gitea,
+            format,
+            id: Some(asset.id.to_string()),
+            url: Some(asset.browser_download_url.clone()),
+            name: Some(name.to_string()),
+            file_name: Some(asset.name.clone()),
+            checksum: None,
+            signature: None,
+            size: asset.size,
+            tool_spec: spec.clone(),
+        }
+    }
+
+    /**
+        Checks if this artifact is a checksum file, such as
+        `tool.zip.sha256` or `checksums.txt`, and not a tool.
+    */
+    #[must_use]
+    pub fn is_checksum_file(&self) -> bool {
+        self.file_name.as_deref().is_some_and(is_checksum_file_name)
+    }
+
+    /**
+        Finds artifacts in the given list that may contain a checksum for this artifact.
+
+        Checksum files specific to this artifact are returned
+        first, followed by any checksum files for the release.
+    */
+    pub(crate) fn find_checksum_artifacts<'a>(&self, artifacts: &'a [Self]) -> Vec<&'a Self> {
+        let Some(file_name) = self.file_name.as_deref() else {
+            return Vec::new();
+        };
+
+        let siblings = artifacts.iter().filter(|a| {
+            a.file_name
+                .as_deref()
+                .is_some_and(|name| is_sibling_checksum_file_name(name, file_name))
+        });
+        let lists = artifacts.iter().filter(|a| {
+            a.file_name
+                .as_deref()
+                .is_some_and(is_checksum_list_file_name)
+        });
+
+        siblings.chain(lists).collect()
+    }
+
+    /**
+        Checks if this artifact is a signature file, such as `tool.zip.minisig`, and not a tool.
+    */
+    #[must_use]
+    pub fn is_signature_file(&self) -> bool {
+        self.file_name
+            .as_deref()
+            .is_some_and(is_signature_file_name)
+    }
+
+    /**
+        Finds the artifact in the given list that contains a signature for this artifact.
+    */
+    pub(crate) fn find_signature_artifact<'a>(&self, artifacts: &'a [Self]) -> Option<&'a Self> {
+        let file_name = self.file_name.as_deref()?;
+        artifacts.iter().find(|a| {
+            a.file_name
+                .as_deref()
+                .is_some_and(|name| is_sibling_signature_file_name(name, file_name))
+        })
+    }
+
+    /**
+        Verifies the given contents against the signature for this artifact, using the given key.
+
+        The given contents must be the raw bytes of the artifact, as downloaded.
+
+        Unlike [`Artifact::verify_checksum`], this fails if the artifact has no known
+        signature, since verification was explicitly requested by providing a key.
+
+        # Errors
+
+        - If the artifact has no signature, or the signature is not valid for the contents.
+    */
+    pub fn verify_signature(
+        &self,
+        contents: impl AsRef<[u8]>,
+        key: &MinisignPublicKey,
+    ) -> RokitResult<()> {
+        let file_name = self
+            .file_name
+            .clone()
+            .unwrap_or_else(|| self.tool_spec.to_string());
+        let Some(signature) = self.signature.as_deref() else {
+            return Err(SignatureError::Missing(file_name).into());
+        };
+        key.verify(&file_name, contents.as_ref(), signature)?;
+        debug!(spec = %self.tool_spec, "verified artifact signature");
+        Ok(())
+    }
+
+    /**
+        Tries to parse a checksum for this artifact from the contents of a checksum file.
+    */
+    pub(crate) fn parse_checksum(&self, checksum_contents: &[u8]) -> Option<String> {
+        let file_name = self.file_name.as_deref()?;
+        let contents = String::from_utf8_lossy(checksum_contents);
+        parse_checksum_contents(&contents, file_name)
+    }
+
+    /**
+        Computes the SHA-256 checksum of the given artifact contents, as a lowercase hex string.
+    */
+    #[must_use]
+    pub fn checksum_of(contents: impl AsRef<[u8]>) -> String {
+        sha256_hex(contents.as_ref())
+    }
+
+    /**
+        Verifies the given contents against the checksum for this artifact.
+
+        The given contents must be the raw bytes of the artifact, as downloaded.
+
+        If the artifact has no known checksum, this does nothing.
+
+        # Errors
+
+        - If the artifact has a checksum and the contents do not match it.
+    */
+    pub fn verify_checksum(&self, contents: impl AsRef<[u8]>) -> RokitResult<()> {
+        let Some(expected) = self.checksum.as_deref() else {
+            debug!(
+                spec = %self.tool_spec,
+                name = ?self.file_name,
+                "no checksum found for artifact, skipping verification"
+            );
+            return Ok(());
+        };
+
+        let actual = sha256_hex(contents.as_ref());
+        if actual.eq_ignore_ascii_case(expected) {
+            debug!(spec = %self.tool_spec, "verified artifact checksum");
+            Ok(())
+        } else {
+            Err(RokitError::ChecksumMismatch {
+                tool_spec: self.tool_spec.clone().into(),
+                expected: expected.to_string(),
+                actual,
+            })
+        }
+    }
+
     /**
         Extract the contents of the artifact.
 
@@ -61,24 +253,99 @@ impl Artifact {
         This generally means that, as long as the same artifact provider
         is used to both create and download the artifact, the format
         should be known and the contents should be in the correct format.
+
+        # Errors
+
+        - If the artifact could not be extracted, or did not contain the tool binary.
     */
-    #[instrument(skip(self, contents), level = "debug")]
     pub async fn extract_contents(&self, contents: Vec<u8>) -> RokitResult<Vec<u8>> {
-        let format = self.format.ok_or(ExtractError::UnknownFormat)?;
+        self.extract_binary(contents, None, None).await
+    }
+
+    /**
+        Extract a specific binary from the contents of the artifact.
+
+        If a `binary` name is given, only a file with that name (or path) will be extracted.
+        Otherwise, a file matching the tool name or the given `alias` will be extracted,
+        falling back to the only executable in the artifact if nothing matched by name.
+
+        See [`Artifact::extract_contents`] for more information.
+
+        # Errors
+
+        - If the binary was not found, or if several binaries
+          were found and it was ambiguous which one to use.
+        - If the artifact could not be extracted.
+    */
+    #[instrument(skip(self, contents), level = "debug")]
+    pub async fn extract_binary(
+        &self,
+        contents: Vec<u8>,
+        binary: Option<&str>,
+        alias: Option<&ToolAlias>,
+    ) -> RokitResult<Vec<u8>> {
+        // NOTE: Some artifacts may not have a file extension, in which
+        // case we fall back to detecting the format from the contents
+        let format = self
+            .format
+            .or_else(|| ArtifactFormat::from_magic_bytes(&contents));
+
+        // NOTE: Some artifacts are not archives at all, but the binary itself,
+        // which is only assumed if the contents are not in any known archive
+        // format, and actually parse as an executable for some known OS
+        let Some(format) = format else {
+            if OS::detect_from_executable(&contents).is_none() {
+                return Err(ExtractError::UnknownFormat.into());
+            }
+            debug!("artifact is not an archive, using its contents as the binary");
+            let file_name = self.name.clone().unwrap_or_default();
+            self.check_binary_os(&contents, file_name)?;
+            return Ok(contents);
+        };
+
+        let explicit = binary.is_some();
+        let file_names = if let Some(binary) = binary {
+            vec![binary.to_string()]
+        } else {
+            let mut names = vec![self.tool_spec.name().to_string()];
+            if let Some(alias) = alias.filter(|a| a.name() != self.tool_spec.name()) {
+                names.push(alias.name().to_string());
+            }
+            names
+        };
+        let file_name = file_names[0].clone();
 
-        let file_name = self.tool_spec.name().to_string();
         let file_res = match format {
-            ArtifactFormat::Zip => extract_zip_file(&contents, &file_name).await,
-            ArtifactFormat::Tar => extract_tar_file(&contents, &file_name).await,
-            ArtifactFormat::TarGz => {
-                let tar = decompress_gzip(&contents).await?;
-                extract_tar_file(&tar, &file_name).await
+            ArtifactFormat::Zip => extract_zip_file(&contents, file_names, explicit).await,
+            ArtifactFormat::Tar => extract_tar_file(&contents, file_names, explicit).await,
+            ArtifactFormat::TarGz | ArtifactFormat::TarXz | ArtifactFormat::TarZst => {
+                match decompress(format, &contents).await {
+                    // NOTE: Some artifacts are a single compressed binary, such as
+                    // `tool-linux-x64.gz`, instead of a compressed tar archive, which
+                    // can only be told apart using the decompressed contents
+                    Ok(decompressed) if is_compressed_binary(&decompressed) => {
+                        debug!("artifact is a compressed binary, not a tar archive");
+                        let file_name = self.name.clone().unwrap_or_default();
+                        self.check_binary_os(&decompressed, file_name)?;
+                        return Ok(decompressed);
+                    }
+                    Ok(tar) => extract_tar_file(&tar, file_names, explicit).await,
+                    Err(e) => Err(e),
+                }
             }
         };
 
         // Make sure we got back the file we need ...
 
-        let file_opt = file_res.map_err(|err| ExtractError::Generic {
+        // NOTE: Extraction errors are already specific enough, and the archive
+        // contents would only add noise to them, so only wrap other errors
+        let file_res = match file_res {
+            Err(RokitError::Extract(err)) => return Err(RokitError::Extract(err)),
+            other => other,
+        };
+        let extracted = file_res.map_err(|err| ExtractError::Generic {
+            format,
+            archive_name: self.name.clone().unwrap_or_default(),
             source: err.into(),
             body: {
                 if contents.len() > 128 + 6 {
@@ -90,27 +357,103 @@ impl Artifact {
             },
         })?;
 
-        let file_bytes = file_opt.ok_or_else(|| ExtractError::FileMissing {
-            format,
-            file_name: self.tool_spec.name().to_string(),
-            archive_name: self.name.clone().unwrap_or_default(),
-        })?;
+        let file_bytes = match extracted {
+            ExtractedFile::Found(bytes) => bytes,
+            ExtractedFile::Missing => Err(ExtractError::FileMissing {
+                format,
+                file_name: file_name.clone(),
+                archive_name: self.name.clone().unwrap_or_default(),
+            })?,
+            ExtractedFile::Ambiguous(candidates) => Err(ExtractError::AmbiguousBinary {
+                format,
+                archive_name: self.name.clone().unwrap_or_default(),
+                candidates,
+            })?,
+        };
 
         // ... and parse the OS from the executable binary, or error,
         // to ensure that the user will actually be able to run it
 
+        self.check_binary_os(&file_bytes, file_name)?;
+
+        Ok(file_bytes)
+    }
+
+    fn check_binary_os(&self, file_bytes: &[u8], file_name: String) -> RokitResult<()> {
         let os_current = OS::current_system();
-        let os_file = OS::detect_from_executable(&file_bytes);
+        let os_file = OS::detect_from_executable(file_bytes);
         if os_file.is_some_and(|os| os != os_current) {
             Err(ExtractError::OSMismatch {
                 current_os: os_current,
                 file_os: os_file.unwrap(),
-                file_name: self.tool_spec.name().to_string(),
+                file_name,
                 archive_name: self.name.clone().unwrap_or_default(),
             })?;
         }
+        Ok(())
+    }
 
-        Ok(file_bytes)
+    /**
+        Checks if this artifact would need to be emulated to run on the current
+        system, such as x64 macOS artifacts on Apple Silicon, using Rosetta 2.
+
+        See [`Descriptor::requires_emulation`] for more information.
+    */
+    #[must_use]
+    pub fn is_emulated_on_current_system(&self) -> bool {
+        let Some(name) = self.name.as_deref() else {
+            return false;
+        };
+        // NOTE: Universal binaries are detected as x64, but contain native arm64 code too
+        if name.to_ascii_lowercase().contains("universal") {
+            return false;
+        }
+        Descriptor::detect(name)
+            .is_some_and(|desc| Descriptor::current_system().requires_emulation(&desc))
+    }
+
+    /**
+        Checks if this artifact can run on the given target system, meaning that
+        its operating system and architecture are compatible with the target, and
+        that it was not built for a different libc than the one the target uses.
+
+        Artifacts without a detectable system are assumed to be compatible, since
+        those can only have been chosen explicitly, such as using an artifact pattern.
+    */
+    #[must_use]
+    pub fn is_compatible_with(&self, target: Descriptor) -> bool {
+        let Some(desc) = self.name.as_deref().and_then(Descriptor::detect) else {
+            return true;
+        };
+        let libc_mismatch = matches!(
+            (target.toolchain(), desc.toolchain()),
+            (Some(a), Some(b)) if a.is_libc() && b.is_libc() && a != b
+        );
+        target.is_compatible_with(&desc) && !libc_mismatch
+    }
+
+    /**
+        Scores how compatible this artifact is with the given target system.
+
+        Artifacts without a known operating system, or with a different operating
+        system than the target, always score zero. Otherwise, the score increases
+        for a compatible architecture, and for a matching toolchain, if any.
+
+        This is only meant to explain why artifacts were _not_ picked - see
+        [`Artifact::sort_by_compatibility_with`] for how artifacts are picked.
+    */
+    #[must_use]
+    pub fn compatibility_score_with(&self, target: Descriptor) -> u8 {
+        let Some(desc) = self.name.as_deref().and_then(Descriptor::detect) else {
+            return 0;
+        };
+        if desc.os() != target.os() {
+            return 0;
+        }
+        let arch_score = u8::from(target.is_compatible_with(&desc));
+        let toolchain_score =
+            u8::from(desc.toolchain().is_some() && desc.toolchain() == target.toolchain());
+        1 + arch_score + toolchain_score
     }
 
     /**
@@ -123,7 +466,60 @@ impl Artifact {
         - [`Descriptor::sort_by_preferred_compat`]
     */
     pub fn sort_by_system_compatibility(artifacts: impl AsRef<[Self]>) -> Vec<Self> {
-        Self::sort_by_system_compatibility_inner(artifacts, false)
+        Self::sort_by_compatibility_with(artifacts, Descriptor::current_system())
+    }
+
+    /**
+        Sorts the given artifacts by their compatibility with the given target
+        system, which may be a different system than the current one, such as
+        when downloading artifacts to be used on another platform.
+
+        See [`Artifact::sort_by_system_compatibility`] for more information.
+    */
+    pub fn sort_by_compatibility_with(
+        artifacts: impl AsRef<[Self]>,
+        target: Descriptor,
+    ) -> Vec<Self> {
+        Self::sort_by_compatibility_inner(artifacts, target, false)
+    }
+
+    /**
+        Finds all artifacts with file names matching the given pattern, on the current system.
+
+        Matching artifacts are sorted by their compatibility with the current system, so
+        that the heuristics are only used to choose between several matching artifacts.
+    */
+    pub fn filter_by_pattern(
+        artifacts: impl AsRef<[Self]>,
+        pattern: &ArtifactPattern,
+    ) -> Vec<Self> {
+        Self::filter_by_pattern_for(artifacts, pattern, Descriptor::current_system())
+    }
+
+    /**
+        Finds all artifacts with file names matching the given pattern, on the given target system.
+
+        See [`Artifact::filter_by_pattern`] for more information.
+    */
+    pub fn filter_by_pattern_for(
+        artifacts: impl AsRef<[Self]>,
+        pattern: &ArtifactPattern,
+        target: Descriptor,
+    ) -> Vec<Self> {
+        let mut matching = artifacts
+            .as_ref()
+            .iter()
+            .filter(|artifact| !artifact.is_checksum_file() && !artifact.is_signature_file())
+            .filter(|artifact| {
+                artifact.file_name.as_deref().is_some_and(|name| {
+                    pattern.matches_target(name, artifact.tool_spec.version(), target)
+                })
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let compatible = Self::sort_by_compatibility_with(&matching, target);
+        matching.retain(|artifact| !compatible.contains(artifact));
+        compatible.into_iter().chain(matching).collect()
     }
 
     /**
@@ -135,45 +531,229 @@ impl Artifact {
         system, the contents of the artifact should be checked before use.
     */
     pub fn find_partially_compatible_fallback(artifacts: impl AsRef<[Self]>) -> Option<Self> {
-        Self::sort_by_system_compatibility_inner(artifacts, true)
+        Self::find_partially_compatible_fallback_for(artifacts, Descriptor::current_system())
+    }
+
+    /**
+        Tries to find a partially compatible artifact for the given target system.
+
+        See [`Artifact::find_partially_compatible_fallback`] for more information.
+    */
+    pub fn find_partially_compatible_fallback_for(
+        artifacts: impl AsRef<[Self]>,
+        target: Descriptor,
+    ) -> Option<Self> {
+        Self::sort_by_compatibility_inner(artifacts, target, true)
             .into_iter()
             .next()
     }
 
-    fn sort_by_system_compatibility_inner(
+    fn sort_by_compatibility_inner(
         artifacts: impl AsRef<[Self]>,
+        target_desc: Descriptor,
         allow_partial_compatibility: bool,
     ) -> Vec<Self> {
-        let current_desc = Descriptor::current_system();
-
         let mut compatible_artifacts = artifacts
             .as_ref()
             .iter()
+            .filter(|artifact| !artifact.is_checksum_file() && !artifact.is_signature_file())
             .filter_map(|artifact| {
                 let name = artifact.name.as_deref()?;
                 if let Some(asset_desc) = Descriptor::detect(name) {
-                    let is_fully_compatible = current_desc.is_compatible_with(&asset_desc);
-                    let is_os_compatible = current_desc.os() == asset_desc.os();
+                    let is_fully_compatible = target_desc.is_compatible_with(&asset_desc);
+                    let is_os_compatible = target_desc.os() == asset_desc.os();
+                    debug!(
+                        name,
+                        ?asset_desc,
+                        is_fully_compatible,
+                        is_os_compatible,
+                        "checked artifact compatibility"
+                    );
                     if is_fully_compatible || (allow_partial_compatibility && is_os_compatible) {
                         Some((asset_desc, artifact))
                     } else {
                         None
                     }
                 } else {
+                    debug!(name, "skipped artifact without a known OS or architecture");
                     None
                 }
             })
             .collect::<Vec<_>>();
 
         compatible_artifacts.sort_by(|(desc_a, artifact_a), (desc_b, artifact_b)| {
-            current_desc
+            target_desc
                 .sort_by_preferred_compat(desc_a, desc_b)
                 .then_with(|| sort_preferred_artifact(artifact_a, artifact_b))
+                .then_with(|| sort_preferred_format(target_desc.os(), artifact_a, artifact_b))
+                .then_with(|| artifact_a.name.cmp(&artifact_b.name))
         });
 
-        compatible_artifacts
+        for (rank, (desc, artifact)) in compatible_artifacts.iter().enumerate() {
+            debug!(
+                rank = rank + 1,
+                name = artifact.name.as_deref().unwrap_or_default(),
+                ?desc,
+                name_score = artifact_name_score(artifact),
+                format_score = artifact_format_score(target_desc.os(), artifact),
+                "ranked compatible artifact"
+            );
+        }
+
+        let sorted = compatible_artifacts
             .into_iter()
             .map(|(_, artifact)| artifact.clone())
-            .collect()
+            .collect::<Vec<_>>();
+        debug!(
+            allow_partial_compatibility,
+            ranking = ?sorted
+                .iter()
+                .map(|artifact| artifact.name.as_deref().unwrap_or_default())
+                .collect::<Vec<_>>(),
+            "ranked compatible artifacts"
+        );
+        sorted
+    }
+}
+
+async fn decompress(format: ArtifactFormat, contents: &[u8]) -> RokitResult<Vec<u8>> {
+    match format {
+        ArtifactFormat::TarXz => decompress_xz(contents).await,
+        ArtifactFormat::TarZst => decompress_zstd(contents).await,
+        _ => decompress_gzip(contents).await,
+    }
+}
+
+// Tar archives without the standard header are still extracted as archives,
+// unless the contents are clearly an executable instead of an archive
+fn is_compressed_binary(decompressed: &[u8]) -> bool {
+    ArtifactFormat::from_magic_bytes(decompressed) != Some(ArtifactFormat::Tar)
+        && OS::detect_from_executable(decompressed).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(format: Option<ArtifactFormat>) -> Artifact {
+        Artifact {
+            provider: ArtifactProvider::GitHub,
+            format,
+            id: None,
+            url: None,
+            name: Some(String::from("tool")),
+            file_name: Some(String::from("tool")),
+            checksum: None,
+            signature: None,
+            size: None,
+            tool_spec: "author/tool@1.0.0".parse().unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn extract_raw_binary() {
+        // NOTE: The test binary itself is a real executable for the current system
+        let contents = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let extracted = artifact(None)
+            .extract_contents(contents.clone())
+            .await
+            .unwrap();
+        assert_eq!(extracted, contents);
+    }
+
+    #[tokio::test]
+    async fn extract_raw_binary_requires_executable() {
+        let contents = b"definitely not an executable".to_vec();
+        assert!(artifact(None).extract_contents(contents).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn extract_raw_binary_respects_known_format() {
+        let contents = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let artifact = artifact(Some(ArtifactFormat::Zip));
+        assert!(artifact.extract_contents(contents).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn extract_compressed_binary() {
+        use std::io::Write;
+
+        // NOTE: The test binary is too large to compress quickly, so use a smaller one
+        let contents = std::fs::read("/bin/sh").unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&contents).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        // NOTE: Both the `.gz` extension and no extension at all are
+        // detected as a compressed tar archive, before decompressing
+        for format in [Some(ArtifactFormat::TarGz), None] {
+            let extracted = artifact(format)
+                .extract_contents(compressed.clone())
+                .await
+                .unwrap();
+            assert_eq!(extracted, contents);
+        }
+    }
+
+    #[test]
+    fn sort_by_compatibility_with_target() {
+        let named = |name: &str| Artifact {
+            name: Some(name.to_string()),
+            file_name: Some(name.to_string()),
+            format: ArtifactFormat::from_path_or_url(name),
+            ..artifact(None)
+        };
+        let artifacts = vec![
+            named("tool-linux-x86_64.tar.gz"),
+            named("tool-windows-x86_64.zip"),
+            named("tool-macos-aarch64.tar.gz"),
+        ];
+        let name_for = |target: &str| {
+            let target = target.parse::<Descriptor>().unwrap();
+            Artifact::sort_by_compatibility_with(&artifacts, target)
+                .first()
+                .and_then(|artifact| artifact.name.clone())
+        };
+        assert_eq!(
+            name_for("x86_64-pc-windows-msvc").as_deref(),
+            Some("tool-windows-x86_64.zip")
+        );
+        assert_eq!(
+            name_for("aarch64-apple-darwin").as_deref(),
+            Some("tool-macos-aarch64.tar.gz")
+        );
+        assert_eq!(name_for("linux-arm"), None);
+    }
+
+    #[test]
+    fn compatibility_scores() {
+        let named = |name: &str| Artifact {
+            name: Some(name.to_string()),
+            ..artifact(None)
+        };
+        let target = "x86_64-unknown-linux-gnu".parse::<Descriptor>().unwrap();
+        let score_for = |name: &str| named(name).compatibility_score_with(target);
+        assert_eq!(score_for("tool-source.tar.gz"), 0);
+        assert_eq!(score_for("tool-windows-x86_64.zip"), 0);
+        assert_eq!(score_for("tool-linux-aarch64.tar.gz"), 1);
+        assert_eq!(score_for("tool-linux-x86_64-musl.tar.gz"), 2);
+        assert_eq!(score_for("tool-linux-x86_64-gnu.tar.gz"), 3);
+    }
+
+    #[test]
+    fn compatibility_with_libc() {
+        let named = |name: &str| Artifact {
+            name: Some(name.to_string()),
+            ..artifact(None)
+        };
+        let musl = "x86_64-unknown-linux-musl".parse::<Descriptor>().unwrap();
+        let compatible = |name: &str| named(name).is_compatible_with(musl);
+        assert!(compatible("tool-linux-x86_64-musl.tar.gz"));
+        assert!(compatible("tool-linux-x86_64.tar.gz"));
+        assert!(compatible("tool-custom-name.tar.gz"));
+        assert!(!compatible("tool-linux-x86_64-gnu.tar.gz"));
+        assert!(!compatible("tool-linux-aarch64-musl.tar.gz"));
+        assert!(!compatible("tool-macos-x86_64.tar.gz"));
     }
 }
@@ -0,0 +1,253 @@
+use std::{fmt, str::FromStr};
+
+use semver::Version;
+use thiserror::Error;
+
+use crate::descriptor::{Arch, Descriptor, OS};
+
+/**
+    Error type representing the possible errors that can occur when parsing an artifact pattern.
+*/
+#[derive(Debug, Error)]
+pub enum ArtifactPatternError {
+    #[error("artifact pattern is empty")]
+    Empty,
+    #[error(
+        "unknown placeholder '{{{0}}}', expected one of '{{os}}', '{{arch}}', or '{{version}}'"
+    )]
+    UnknownPlaceholder(String),
+    #[error("unclosed placeholder in artifact pattern '{0}'")]
+    UnclosedPlaceholder(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Os,
+    Arch,
+    Version,
+    Wildcard,
+}
+
+/**
+    A pattern for the file name of an artifact, used to select an artifact
+    for tools with names that can not be detected automatically.
+
+    Patterns must match the whole file name, are not case sensitive, and
+    may contain the following placeholders and wildcards:
+
+    - `{os}` - the current operating system, such as `linux`, `macos`, `darwin`, `windows`, or `win`
+    - `{arch}` - the current architecture, such as `x86_64`, `x64`, `amd64`, `aarch64`, or `arm64`
+    - `{version}` - the version of the tool, such as `1.2.3`
+    - `*` - any sequence of characters, including none
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactPattern {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl ArtifactPattern {
+    /**
+        Checks if the given artifact file name and version match this pattern, on the current system.
+    */
+    #[must_use]
+    pub fn matches(&self, file_name: &str, version: &Version) -> bool {
+        self.matches_system(
+            file_name,
+            version,
+            OS::current_system(),
+            Arch::current_system(),
+        )
+    }
+
+    /**
+        Checks if the given artifact file name and version match this pattern, on the given target system.
+
+        Note that the `{arch}` placeholder never matches if the target has no known architecture.
+    */
+    #[must_use]
+    pub fn matches_target(&self, file_name: &str, version: &Version, target: Descriptor) -> bool {
+        self.matches_keywords(file_name, version, target.os(), target.arch())
+    }
+
+    fn matches_system(&self, file_name: &str, version: &Version, os: OS, arch: Arch) -> bool {
+        self.matches_keywords(file_name, version, os, Some(arch))
+    }
+
+    fn matches_keywords(
+        &self,
+        file_name: &str,
+        version: &Version,
+        os: OS,
+        arch: Option<Arch>,
+    ) -> bool {
+        let context = MatchContext {
+            os: os.keywords().collect(),
+            arch: arch
+                .map(|arch| arch.keywords().collect())
+                .unwrap_or_default(),
+            version: version.to_string(),
+        };
+        context.matches(&self.segments, &file_name.to_ascii_lowercase())
+    }
+}
+
+struct MatchContext {
+    os: Vec<&'static str>,
+    arch: Vec<&'static str>,
+    version: String,
+}
+
+impl MatchContext {
+    fn matches(&self, segments: &[Segment], remaining: &str) -> bool {
+        let Some((segment, rest)) = segments.split_first() else {
+            return remaining.is_empty();
+        };
+        let matches_prefix = |prefix: &str| {
+            remaining
+                .strip_prefix(prefix)
+                .is_some_and(|after| self.matches(rest, after))
+        };
+        match segment {
+            Segment::Literal(literal) => matches_prefix(literal),
+            Segment::Os => self.os.iter().any(|keyword| matches_prefix(keyword)),
+            Segment::Arch => self.arch.iter().any(|keyword| matches_prefix(keyword)),
+            Segment::Version => matches_prefix(&self.version),
+            Segment::Wildcard => (0..=remaining.len())
+                .filter(|index| remaining.is_char_boundary(*index))
+                .any(|index| self.matches(rest, &remaining[index..])),
+        }
+    }
+}
+
+impl FromStr for ArtifactPattern {
+    type Err = ArtifactPatternError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let source = s.trim();
+        if source.is_empty() {
+            return Err(ArtifactPatternError::Empty);
+        }
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars();
+        while let Some(c) = chars.next() {
+            let segment = match c {
+                '*' => Segment::Wildcard,
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(ArtifactPatternError::UnclosedPlaceholder(
+                                    source.to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    match name.trim().to_ascii_lowercase().as_str() {
+                        "os" => Segment::Os,
+                        "arch" => Segment::Arch,
+                        "version" => Segment::Version,
+                        _ => return Err(ArtifactPatternError::UnknownPlaceholder(name)),
+                    }
+                }
+                c => {
+                    literal.push(c.to_ascii_lowercase());
+                    continue;
+                }
+            };
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(segment);
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+}
+
+impl fmt::Display for ArtifactPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> ArtifactPattern {
+        s.parse().unwrap()
+    }
+
+    fn version() -> Version {
+        Version::new(1, 2, 3)
+    }
+
+    #[test]
+    fn matches_os_and_arch_keywords() {
+        let pattern = parse("tool-{os}64.zip");
+        let matches = |name, os| pattern.matches_system(name, &version(), os, Arch::X64);
+        assert!(matches("tool-linux64.zip", OS::Linux));
+        assert!(matches("tool-win64.zip", OS::Windows));
+        assert!(matches("Tool-MacOS64.zip", OS::MacOS));
+        assert!(!matches("tool-linux64.zip", OS::Windows));
+        assert!(!matches("tool-linux64.zip.sha256", OS::Linux));
+
+        let pattern = parse("tool-{arch}-{os}.tar.gz");
+        assert!(pattern.matches_system(
+            "tool-amd64-linux.tar.gz",
+            &version(),
+            OS::Linux,
+            Arch::X64
+        ));
+        assert!(pattern.matches_system(
+            "tool-aarch64-darwin.tar.gz",
+            &version(),
+            OS::MacOS,
+            Arch::Arm64
+        ));
+        assert!(!pattern.matches_system(
+            "tool-aarch64-darwin.tar.gz",
+            &version(),
+            OS::MacOS,
+            Arch::X64
+        ));
+    }
+
+    #[test]
+    fn matches_version_and_wildcards() {
+        let pattern = parse("tool-v{version}-*-{os}.zip");
+        let matches = |name| pattern.matches_system(name, &version(), OS::Linux, Arch::X64);
+        assert!(matches("tool-v1.2.3-gnu-linux.zip"));
+        assert!(matches("tool-v1.2.3--linux.zip"));
+        assert!(!matches("tool-v1.2.4-gnu-linux.zip"));
+        assert!(!matches("tool-v1.2.3-gnu-windows.zip"));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(matches!(
+            "".parse::<ArtifactPattern>(),
+            Err(ArtifactPatternError::Empty)
+        ));
+        assert!(matches!(
+            "tool-{platform}.zip".parse::<ArtifactPattern>(),
+            Err(ArtifactPatternError::UnknownPlaceholder(name)) if name == "platform"
+        ));
+        assert!(matches!(
+            "tool-{os.zip".parse::<ArtifactPattern>(),
+            Err(ArtifactPatternError::UnclosedPlaceholder(_))
+        ));
+    }
+}
@@ -5,10 +5,13 @@ use std::{fmt, str::FromStr};
 
     The default provider is [`ArtifactProvider::GitHub`].
 */
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ArtifactProvider {
     #[default]
     GitHub,
+    GitHubEnterprise,
+    GitLab,
+    Gitea,
 }
 
 impl ArtifactProvider {
@@ -16,6 +19,9 @@ impl ArtifactProvider {
     pub fn as_str(self) -> &'static str {
         match self {
             Self::GitHub => "github",
+            Self::GitHubEnterprise => "ghe",
+            Self::GitLab => "gitlab",
+            Self::Gitea => "gitea",
         }
     }
 
@@ -23,8 +29,21 @@ impl ArtifactProvider {
     pub fn display_name(self) -> &'static str {
         match self {
             Self::GitHub => "GitHub",
+            Self::GitHubEnterprise => "GitHub Enterprise",
+            Self::GitLab => "GitLab",
+            Self::Gitea => "Gitea",
         }
     }
+
+    /**
+        Checks if tool ids for this provider must include the host of the
+        instance to use, such as `gitea:codeberg.org/author/name`, since
+        there is no single default instance for the provider.
+    */
+    #[must_use]
+    pub fn requires_host(self) -> bool {
+        matches!(self, Self::GitHubEnterprise | Self::Gitea)
+    }
 }
 
 impl FromStr for ArtifactProvider {
@@ -33,6 +52,9 @@ impl FromStr for ArtifactProvider {
         let l = s.trim().to_lowercase();
         match l.as_str() {
             "github" => Ok(Self::GitHub),
+            "ghe" | "github-enterprise" => Ok(Self::GitHubEnterprise),
+            "gitlab" => Ok(Self::GitLab),
+            "gitea" | "forgejo" => Ok(Self::Gitea),
             _ => Err(format!("unknown artifact provider '{l}'")),
         }
     }
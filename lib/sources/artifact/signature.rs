@@ -0,0 +1,446 @@
+use std::{fmt, str::FromStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use thiserror::Error;
+
+const SIGNATURE_EXTENSIONS: [&str; 1] = [".minisig"];
+
+const ALGORITHM_PURE: [u8; 2] = *b"Ed";
+const ALGORITHM_HASHED: [u8; 2] = *b"ED";
+const KEY_ID_LEN: usize = 8;
+const PUBLIC_KEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+
+const UNTRUSTED_COMMENT_PREFIX: &str = "untrusted comment:";
+const TRUSTED_COMMENT_PREFIX: &str = "trusted comment:";
+
+#[derive(Debug, Error)]
+pub enum SignatureError {
+    #[error("invalid minisign public key - {0}")]
+    InvalidPublicKey(&'static str),
+    #[error("invalid minisign signature file - {0}")]
+    InvalidSignatureFile(&'static str),
+    #[error(
+        "no minisign signature was found for '{0}'\
+        \nsignature verification is configured for this tool, so it can not be installed without one"
+    )]
+    Missing(String),
+    #[error(
+        "signature for '{file_name}' was made with key {actual}, but key {expected} was expected"
+    )]
+    KeyMismatch {
+        file_name: String,
+        expected: KeyId,
+        actual: KeyId,
+    },
+    #[error("signature for '{0}' is not valid - its contents may have been tampered with")]
+    Invalid(String),
+}
+
+/**
+    The id of a minisign key, displayed as uppercase hex, the same way minisign does.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyId([u8; KEY_ID_LEN]);
+
+impl fmt::Display for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // NOTE: Minisign stores key ids in little endian, but displays them as numbers
+        for byte in self.0.iter().rev() {
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+/**
+    A minisign public key, used to verify signatures of artifacts.
+
+    May be parsed from either the base64-encoded key, such as
+    `RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3`,
+    or the full contents of a minisign public key file.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinisignPublicKey {
+    key_id: KeyId,
+    key: [u8; PUBLIC_KEY_LEN],
+}
+
+impl MinisignPublicKey {
+    /**
+        Verifies the given contents against the contents of a minisign signature file.
+
+        Both the signature of the contents, and the global signature
+        covering the trusted comment of the signature file, are verified.
+
+        # Errors
+
+        - If the signature file is invalid, was made using a
+          different key, or does not match the given contents.
+    */
+    pub fn verify(
+        &self,
+        file_name: &str,
+        contents: &[u8],
+        signature_file: &str,
+    ) -> Result<(), SignatureError> {
+        let signature = MinisignSignature::from_str(signature_file)?;
+        if signature.key_id != self.key_id {
+            return Err(SignatureError::KeyMismatch {
+                file_name: file_name.to_string(),
+                expected: self.key_id,
+                actual: signature.key_id,
+            });
+        }
+
+        let key = UnparsedPublicKey::new(&ED25519, &self.key);
+        let invalid = |_| SignatureError::Invalid(file_name.to_string());
+
+        if signature.hashed {
+            key.verify(&blake2b_512(contents), &signature.signature)
+                .map_err(invalid)?;
+        } else {
+            key.verify(contents, &signature.signature)
+                .map_err(invalid)?;
+        }
+
+        let mut global_message = signature.signature.to_vec();
+        global_message.extend_from_slice(signature.trusted_comment.as_bytes());
+        key.verify(&global_message, &signature.global_signature)
+            .map_err(invalid)?;
+
+        Ok(())
+    }
+}
+
+impl FromStr for MinisignPublicKey {
+    type Err = SignatureError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with(UNTRUSTED_COMMENT_PREFIX))
+            .ok_or(SignatureError::InvalidPublicKey("key is empty"))?;
+        let bytes = STANDARD
+            .decode(encoded)
+            .map_err(|_| SignatureError::InvalidPublicKey("key is not valid base64"))?;
+        if bytes.len() != 2 + KEY_ID_LEN + PUBLIC_KEY_LEN {
+            return Err(SignatureError::InvalidPublicKey(
+                "key has an invalid length",
+            ));
+        }
+        if bytes[..2] != ALGORITHM_PURE {
+            return Err(SignatureError::InvalidPublicKey(
+                "key uses an unsupported algorithm",
+            ));
+        }
+        Ok(Self {
+            key_id: KeyId(bytes[2..2 + KEY_ID_LEN].try_into().unwrap()),
+            key: bytes[2 + KEY_ID_LEN..].try_into().unwrap(),
+        })
+    }
+}
+
+struct MinisignSignature {
+    hashed: bool,
+    key_id: KeyId,
+    signature: [u8; SIGNATURE_LEN],
+    trusted_comment: String,
+    global_signature: [u8; SIGNATURE_LEN],
+}
+
+impl FromStr for MinisignSignature {
+    type Err = SignatureError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = SignatureError::InvalidSignatureFile;
+
+        // NOTE: Lines are not trimmed, since the trusted comment is signed exactly as written
+        let mut lines = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .skip_while(|line| line.starts_with(UNTRUSTED_COMMENT_PREFIX));
+        let signature_line = lines.next().ok_or(invalid("signature is missing"))?;
+        let trusted_comment = lines
+            .next()
+            .and_then(|line| line.strip_prefix(TRUSTED_COMMENT_PREFIX))
+            .ok_or(invalid("trusted comment is missing"))?;
+        let global_line = lines.next().ok_or(invalid("global signature is missing"))?;
+
+        let bytes = STANDARD
+            .decode(signature_line.trim())
+            .map_err(|_| invalid("signature is not valid base64"))?;
+        if bytes.len() != 2 + KEY_ID_LEN + SIGNATURE_LEN {
+            return Err(invalid("signature has an invalid length"));
+        }
+        let hashed = match [bytes[0], bytes[1]] {
+            ALGORITHM_PURE => false,
+            ALGORITHM_HASHED => true,
+            _ => return Err(invalid("signature uses an unsupported algorithm")),
+        };
+
+        let global_signature = STANDARD
+            .decode(global_line.trim())
+            .map_err(|_| invalid("global signature is not valid base64"))?
+            .try_into()
+            .map_err(|_| invalid("global signature has an invalid length"))?;
+
+        Ok(Self {
+            hashed,
+            key_id: KeyId(bytes[2..2 + KEY_ID_LEN].try_into().unwrap()),
+            signature: bytes[2 + KEY_ID_LEN..].try_into().unwrap(),
+            // NOTE: The single space following the prefix is not part of the comment
+            trusted_comment: trusted_comment
+                .strip_prefix(' ')
+                .unwrap_or(trusted_comment)
+                .to_string(),
+            global_signature,
+        })
+    }
+}
+
+/**
+    Checks if the given file name is a signature file for the given file,
+    which are named after the file they sign, such as `file.zip.minisig`.
+*/
+pub(super) fn is_sibling_signature_file_name(signature_file_name: &str, file_name: &str) -> bool {
+    let signature_file_name = signature_file_name.to_ascii_lowercase();
+    let file_name = file_name.to_ascii_lowercase();
+    SIGNATURE_EXTENSIONS.iter().any(|ext| {
+        signature_file_name
+            .strip_suffix(ext)
+            .is_some_and(|stem| stem == file_name)
+    })
+}
+
+/**
+    Checks if the given file name is any kind of signature file.
+*/
+pub(super) fn is_signature_file_name(file_name: &str) -> bool {
+    let lower = file_name.to_ascii_lowercase();
+    SIGNATURE_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+}
+
+/*
+    Minisign prehashes contents using BLAKE2b-512, which ring does not
+    provide, so a minimal implementation of it (RFC 7693) is included here.
+*/
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09_e667_f3bc_c908,
+    0xbb67_ae85_84ca_a73b,
+    0x3c6e_f372_fe94_f82b,
+    0xa54f_f53a_5f1d_36f1,
+    0x510e_527f_ade6_82d1,
+    0x9b05_688c_2b3e_6c1f,
+    0x1f83_d9ab_fb41_bd6b,
+    0x5be0_cd19_137e_2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const BLAKE2B_BLOCK_LEN: usize = 128;
+
+fn blake2b_512(data: &[u8]) -> [u8; 64] {
+    let mut h = BLAKE2B_IV;
+    h[0] ^= 0x0101_0040; // No key, 64 byte digest
+
+    let block_count = data.len().div_ceil(BLAKE2B_BLOCK_LEN).max(1);
+    for (index, chunk) in data
+        .chunks(BLAKE2B_BLOCK_LEN)
+        .chain((data.is_empty()).then_some(&[][..]))
+        .enumerate()
+    {
+        let mut block = [0u8; BLAKE2B_BLOCK_LEN];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let counter = (index * BLAKE2B_BLOCK_LEN + chunk.len()) as u128;
+        blake2b_compress(&mut h, &block, counter, index + 1 == block_count);
+    }
+
+    let mut out = [0u8; 64];
+    for (word, bytes) in h.iter().zip(out.chunks_exact_mut(8)) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+#[allow(clippy::many_single_char_names)]
+fn blake2b_compress(h: &mut [u64; 8], block: &[u8; BLAKE2B_BLOCK_LEN], t: u128, last: bool) {
+    let mut m = [0u64; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+    #[allow(clippy::cast_possible_truncation)]
+    {
+        v[12] ^= t as u64;
+        v[13] ^= (t >> 64) as u64;
+    }
+    if last {
+        v[14] = !v[14];
+    }
+
+    let mut g = |a: usize, b: usize, c: usize, d: usize, x: u64, y: u64| {
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+        v[d] = (v[d] ^ v[a]).rotate_right(32);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(24);
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+        v[d] = (v[d] ^ v[a]).rotate_right(16);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(63);
+    };
+
+    for round in 0..12 {
+        let s = &BLAKE2B_SIGMA[round % 10];
+        g(0, 4, 8, 12, m[s[0]], m[s[1]]);
+        g(1, 5, 9, 13, m[s[2]], m[s[3]]);
+        g(2, 6, 10, 14, m[s[4]], m[s[5]]);
+        g(3, 7, 11, 15, m[s[6]], m[s[7]]);
+        g(0, 5, 10, 15, m[s[8]], m[s[9]]);
+        g(1, 6, 11, 12, m[s[10]], m[s[11]]);
+        g(2, 7, 8, 13, m[s[12]], m[s[13]]);
+        g(3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use ring::{
+        rand::SystemRandom,
+        signature::{Ed25519KeyPair, KeyPair},
+    };
+
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+    }
+
+    #[test]
+    fn blake2b_test_vectors() {
+        assert_eq!(
+            hex(&blake2b_512(b"")),
+            "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+            d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce"
+        );
+        assert_eq!(
+            hex(&blake2b_512(b"abc")),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+            7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+    }
+
+    // Creates a key pair along with its public key and a
+    // signature file for the given contents, in minisign format
+    fn sign(contents: &[u8], key_id: [u8; 8]) -> (MinisignPublicKey, String) {
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+
+        let mut public = ALGORITHM_PURE.to_vec();
+        public.extend_from_slice(&key_id);
+        public.extend_from_slice(pair.public_key().as_ref());
+        let public = format!("untrusted comment: test key\n{}\n", STANDARD.encode(public));
+
+        let signature = pair.sign(&blake2b_512(contents));
+        let mut signature_line = ALGORITHM_HASHED.to_vec();
+        signature_line.extend_from_slice(&key_id);
+        signature_line.extend_from_slice(signature.as_ref());
+
+        let trusted_comment = "timestamp:1700000000\tfile:tool.zip\thashed";
+        let mut global = signature.as_ref().to_vec();
+        global.extend_from_slice(trusted_comment.as_bytes());
+        let global = pair.sign(&global);
+
+        let file = format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {trusted_comment}\n{}\n",
+            STANDARD.encode(signature_line),
+            STANDARD.encode(global.as_ref()),
+        );
+        (public.parse().unwrap(), file)
+    }
+
+    #[test]
+    fn verifies_valid_signature() {
+        let (key, file) = sign(b"tool contents", [1; 8]);
+        key.verify("tool.zip", b"tool contents", &file).unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_signatures() {
+        let (key, file) = sign(b"tool contents", [1; 8]);
+        assert!(matches!(
+            key.verify("tool.zip", b"tampered contents", &file),
+            Err(SignatureError::Invalid(_))
+        ));
+
+        let tampered_comment = file.replace("hashed", "modified");
+        assert!(matches!(
+            key.verify("tool.zip", b"tool contents", &tampered_comment),
+            Err(SignatureError::Invalid(_))
+        ));
+
+        let (other_key, _) = sign(b"tool contents", [2; 8]);
+        assert!(matches!(
+            other_key.verify("tool.zip", b"tool contents", &file),
+            Err(SignatureError::KeyMismatch { .. })
+        ));
+
+        assert!(matches!(
+            key.verify("tool.zip", b"tool contents", "not a signature"),
+            Err(SignatureError::InvalidSignatureFile(_))
+        ));
+    }
+
+    #[test]
+    fn parses_public_keys() {
+        let key = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3"
+            .parse::<MinisignPublicKey>()
+            .unwrap();
+        assert_eq!(key.key_id.to_string(), "E7620F1842B4E81F");
+        assert!("not a key".parse::<MinisignPublicKey>().is_err());
+        assert!("".parse::<MinisignPublicKey>().is_err());
+    }
+
+    #[test]
+    fn signature_file_names() {
+        assert!(is_sibling_signature_file_name(
+            "tool.zip.minisig",
+            "tool.zip"
+        ));
+        assert!(is_sibling_signature_file_name(
+            "Tool.ZIP.MINISIG",
+            "tool.zip"
+        ));
+        assert!(!is_sibling_signature_file_name(
+            "tool.zip.minisig",
+            "tool.tar.gz"
+        ));
+        assert!(is_signature_file_name("tool.zip.minisig"));
+        assert!(!is_signature_file_name("tool.zip"));
+    }
+}
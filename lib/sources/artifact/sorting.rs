@@ -8,7 +8,7 @@ use crate::{
     util::str::char_is_word_separator,
 };
 
-use super::Artifact;
+use super::{Artifact, ArtifactFormat};
 
 /**
     Helper function to sort which artifact is preferred, based on
@@ -28,15 +28,60 @@ use super::Artifact;
     and should not be directly exposed in a public API.
 */
 pub(super) fn sort_preferred_artifact(artifact_a: &Artifact, artifact_b: &Artifact) -> Ordering {
-    let count_a = count_non_tool_mentions(
-        artifact_a.name.as_deref().unwrap_or_default(),
-        artifact_a.tool_spec.id(),
-    );
-    let count_b = count_non_tool_mentions(
-        artifact_b.name.as_deref().unwrap_or_default(),
-        artifact_b.tool_spec.id(),
-    );
-    count_a.cmp(&count_b)
+    artifact_name_score(artifact_a).cmp(&artifact_name_score(artifact_b))
+}
+
+/**
+    Gets the score used by [`sort_preferred_artifact`] for an artifact,
+    where lower scores are preferred. Mainly useful for debugging.
+*/
+pub(super) fn artifact_name_score(artifact: &Artifact) -> usize {
+    count_non_tool_mentions(
+        artifact.name.as_deref().unwrap_or_default(),
+        artifact.tool_spec.id(),
+    )
+}
+
+/**
+    Helper function to sort which artifact is preferred, based on
+    which archive format is most common for the given platform.
+
+    On Windows, `.zip` archives and plain `.exe` binaries are preferred,
+    and on other platforms, `.tar.gz` and `.tar.xz` archives are preferred.
+
+    This is only meant to be used as a tiebreaker between artifacts
+    that are equally compatible with the target system, and
+    should not be directly exposed in a public API.
+*/
+pub(super) fn sort_preferred_format(
+    os: OS,
+    artifact_a: &Artifact,
+    artifact_b: &Artifact,
+) -> Ordering {
+    artifact_format_score(os, artifact_a).cmp(&artifact_format_score(os, artifact_b))
+}
+
+/**
+    Gets the score used by [`sort_preferred_format`] for an artifact,
+    where lower scores are preferred. Mainly useful for debugging.
+*/
+pub(super) fn artifact_format_score(os: OS, artifact: &Artifact) -> u8 {
+    format_preference_rank(os, artifact.format)
+}
+
+// Lower ranks are preferred, artifacts without a known format are plain binaries
+fn format_preference_rank(os: OS, format: Option<ArtifactFormat>) -> u8 {
+    let is_windows = os == OS::Windows;
+    match format {
+        Some(ArtifactFormat::Zip) if is_windows => 0,
+        None if is_windows => 1,
+        Some(ArtifactFormat::TarGz) => 2,
+        Some(ArtifactFormat::TarXz) => 3,
+        Some(ArtifactFormat::TarZst) => 4,
+        Some(ArtifactFormat::Tar) => 5,
+        Some(ArtifactFormat::Zip) => 6,
+        None => 7,
+    }
 }
 
 fn count_non_tool_mentions(name: impl AsRef<str>, tool_id: &ToolId) -> usize {
@@ -151,6 +196,24 @@ mod tests {
         test_some_mentions("TOOLING-x86_64-linux", "tool");
     }
 
+    #[test]
+    fn format_preference_windows() {
+        let rank = |format| format_preference_rank(OS::Windows, format);
+        assert!(rank(Some(ArtifactFormat::Zip)) < rank(None));
+        assert!(rank(None) < rank(Some(ArtifactFormat::TarGz)));
+        assert!(rank(Some(ArtifactFormat::TarGz)) < rank(Some(ArtifactFormat::Tar)));
+    }
+
+    #[test]
+    fn format_preference_unix() {
+        for os in [OS::Linux, OS::MacOS] {
+            let rank = |format| format_preference_rank(os, format);
+            assert!(rank(Some(ArtifactFormat::TarGz)) < rank(Some(ArtifactFormat::TarXz)));
+            assert!(rank(Some(ArtifactFormat::TarXz)) < rank(Some(ArtifactFormat::Zip)));
+            assert!(rank(Some(ArtifactFormat::Zip)) < rank(None));
+        }
+    }
+
     #[test]
     fn name_mention_check_real_tools() {
         // Valid
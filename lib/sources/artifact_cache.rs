@@ -0,0 +1,266 @@
+use std::{
+    cmp::Reverse,
+    fs::File,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tokio::{
+    fs::{create_dir_all, read, read_dir, remove_dir_all, remove_file, rename, write},
+    task::spawn_blocking,
+};
+use tracing::debug;
+
+use crate::result::RokitResult;
+
+use super::Artifact;
+
+const PARTIAL_EXTENSION: &str = "partial";
+
+/**
+    The result of cleaning an artifact cache, see [`ArtifactCache::clean`].
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArtifactCacheCleanup {
+    /// The number of cached artifacts that were removed.
+    pub removed: usize,
+    /// The total size of the cached artifacts that were removed, in bytes.
+    pub removed_bytes: u64,
+    /// The total size of the cached artifacts that were kept, in bytes.
+    pub remaining_bytes: u64,
+}
+
+/**
+    An on-disk cache for downloaded artifact contents, keyed by artifact URL.
+
+    Cached contents are verified against the checksum of the artifact, when
+    one is known, and entries that do not match are removed and downloaded again.
+
+    Any errors reading or writing the cache during downloads are ignored,
+    since the cache is only an optimization and must never break installs.
+*/
+#[derive(Debug, Clone)]
+pub struct ArtifactCache {
+    dir: PathBuf,
+}
+
+impl ArtifactCache {
+    /**
+        Creates a new artifact cache, storing artifacts in the given directory.
+    */
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, artifact: &Artifact) -> Option<PathBuf> {
+        let url = artifact.url.as_ref()?;
+        Some(self.dir.join(Artifact::checksum_of(url.as_str())))
+    }
+
+    pub(crate) async fn get(&self, artifact: &Artifact) -> Option<Vec<u8>> {
+        let path = self.path(artifact)?;
+        let contents = read(&path).await.ok()?;
+        if let Some(expected) = &artifact.checksum {
+            if !Artifact::checksum_of(&contents).eq_ignore_ascii_case(expected) {
+                debug!(path = %path.display(), "removing cached artifact with mismatched checksum");
+                let _ = remove_file(&path).await;
+                return None;
+            }
+        }
+        // NOTE: The modification time is used to remove the least
+        // recently used artifacts first when cleaning the cache
+        let _ = spawn_blocking(move || {
+            File::options()
+                .write(true)
+                .open(path)
+                .and_then(|file| file.set_modified(SystemTime::now()))
+        })
+        .await;
+        Some(contents)
+    }
+
+    pub(crate) async fn put(&self, artifact: &Artifact, contents: &[u8]) {
+        let Some(path) = self.path(artifact) else {
+            return;
+        };
+        // NOTE: Contents are written to a partial file first and then renamed,
+        // so that an interrupted write never leaves a truncated cached artifact
+        let partial = path.with_extension(PARTIAL_EXTENSION);
+        let result = async {
+            create_dir_all(&self.dir).await?;
+            write(&partial, contents).await?;
+            rename(&partial, &path).await
+        }
+        .await;
+        if let Err(e) = result {
+            debug!(path = %path.display(), "failed to write artifact cache: {e}");
+            let _ = remove_file(&partial).await;
+        }
+    }
+
+    /**
+        Gets the total size of all cached artifacts, in bytes.
+
+        # Errors
+
+        - If the cache directory could not be read.
+    */
+    pub async fn size(&self) -> RokitResult<u64> {
+        let entries = self.entries().await?;
+        Ok(entries.iter().map(|entry| entry.size).sum())
+    }
+
+    /**
+        Removes cached artifacts, least recently used first, until the
+        total size of the cache is at most `max_size` bytes.
+
+        If no maximum size is given, all cached artifacts are removed.
+
+        # Errors
+
+        - If the cache directory could not be read.
+        - If any cached artifact could not be removed.
+    */
+    pub async fn clean(&self, max_size: Option<u64>) -> RokitResult<ArtifactCacheCleanup> {
+        let mut entries = self.entries().await?;
+        let Some(max_size) = max_size else {
+            let cleanup = ArtifactCacheCleanup {
+                removed: entries.len(),
+                removed_bytes: entries.iter().map(|entry| entry.size).sum(),
+                remaining_bytes: 0,
+            };
+            match remove_dir_all(&self.dir).await {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => return Ok(cleanup),
+            }
+        };
+
+        // Most recently used first, so that those are kept
+        entries.sort_by_key(|entry| Reverse(entry.modified));
+
+        let mut cleanup = ArtifactCacheCleanup::default();
+        for entry in entries {
+            if !entry.is_partial && cleanup.remaining_bytes + entry.size <= max_size {
+                cleanup.remaining_bytes += entry.size;
+            } else {
+                remove_file(&entry.path).await?;
+                cleanup.removed += 1;
+                cleanup.removed_bytes += entry.size;
+            }
+        }
+        Ok(cleanup)
+    }
+
+    async fn entries(&self) -> RokitResult<Vec<CacheEntry>> {
+        let mut reader = match read_dir(&self.dir).await {
+            Ok(reader) => reader,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries = Vec::new();
+        while let Some(entry) = reader.next_entry().await? {
+            let meta = entry.metadata().await?;
+            if !meta.is_file() {
+                continue;
+            }
+            let path = entry.path();
+            entries.push(CacheEntry {
+                is_partial: is_partial(&path),
+                path,
+                size: meta.len(),
+                modified: meta.modified().unwrap_or(UNIX_EPOCH),
+            });
+        }
+        Ok(entries)
+    }
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+    is_partial: bool,
+}
+
+fn is_partial(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == PARTIAL_EXTENSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sources::ArtifactProvider;
+
+    fn artifact(url: &str, checksum: Option<&str>) -> Artifact {
+        Artifact {
+            provider: ArtifactProvider::GitHub,
+            format: None,
+            id: None,
+            url: Some(url.parse().unwrap()),
+            name: None,
+            file_name: None,
+            checksum: checksum.map(ToString::to_string),
+            signature: None,
+            size: None,
+            tool_spec: "author/tool@1.0.0".parse().unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_by_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::new(dir.path());
+        let a = artifact("https://example.com/a.zip", None);
+        let b = artifact("https://example.com/b.zip", None);
+
+        assert_eq!(cache.get(&a).await, None);
+        cache.put(&a, b"contents").await;
+        assert_eq!(cache.get(&a).await.as_deref(), Some(&b"contents"[..]));
+        assert_eq!(cache.get(&b).await, None);
+        assert_eq!(cache.size().await.unwrap(), 8);
+    }
+
+    #[tokio::test]
+    async fn verifies_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::new(dir.path());
+        let checksum = Artifact::checksum_of(b"contents");
+        let valid = artifact("https://example.com/a.zip", Some(&checksum));
+        let invalid = artifact("https://example.com/a.zip", Some("abc"));
+
+        cache.put(&valid, b"contents").await;
+        assert!(cache.get(&valid).await.is_some());
+        assert_eq!(cache.get(&invalid).await, None);
+        assert_eq!(cache.get(&valid).await, None);
+    }
+
+    #[tokio::test]
+    async fn cleans_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::new(dir.path());
+        let a = artifact("https://example.com/a.zip", None);
+        let b = artifact("https://example.com/b.zip", None);
+
+        cache.put(&a, b"aaaa").await;
+        cache.put(&b, b"bbbb").await;
+        let old = SystemTime::now() - std::time::Duration::from_mins(1);
+        File::options()
+            .write(true)
+            .open(cache.path(&a).unwrap())
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let cleanup = cache.clean(Some(6)).await.unwrap();
+        assert_eq!(cleanup.removed, 1);
+        assert_eq!(cleanup.remaining_bytes, 4);
+        assert_eq!(cache.get(&a).await, None);
+        assert!(cache.get(&b).await.is_some());
+
+        let cleanup = cache.clean(None).await.unwrap();
+        assert_eq!(cleanup.removed, 1);
+        assert_eq!(cache.size().await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,71 @@
+use std::{env::var_os, path::Path, path::PathBuf};
+
+use reqwest::Certificate;
+use tokio::fs::read;
+
+use crate::result::{RokitError, RokitResult};
+
+const CA_BUNDLE_ENV_VAR: &str = "ROKIT_CA_BUNDLE";
+const SSL_CERT_FILE_ENV_VAR: &str = "SSL_CERT_FILE";
+
+/**
+    Gets the path to a CA certificate bundle from the `ROKIT_CA_BUNDLE`
+    environment variable, falling back to the standard `SSL_CERT_FILE`.
+*/
+#[must_use]
+pub(crate) fn ca_bundle_from_env() -> Option<PathBuf> {
+    [CA_BUNDLE_ENV_VAR, SSL_CERT_FILE_ENV_VAR]
+        .into_iter()
+        .filter_map(var_os)
+        .find(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/**
+    Loads all PEM-encoded certificates from the CA bundle at the given path.
+
+    These are trusted in addition to the built-in root certificates, and never
+    replace them, so that certificate verification always stays enabled.
+
+    # Errors
+
+    - If the bundle could not be read.
+    - If the bundle contains invalid certificates, or no certificates at all.
+*/
+pub(crate) async fn load_ca_certificates(path: &Path) -> RokitResult<Vec<Certificate>> {
+    let invalid = |reason: String| RokitError::InvalidCaBundle {
+        path: path.to_path_buf(),
+        reason,
+    };
+
+    let contents = read(path).await.map_err(|e| invalid(e.to_string()))?;
+    let certificates = Certificate::from_pem_bundle(&contents)
+        .map_err(|e| invalid(format!("invalid certificate - {e}")))?;
+    if certificates.is_empty() {
+        return Err(invalid(String::from(
+            "no PEM-encoded certificates were found",
+        )));
+    }
+
+    Ok(certificates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn load_errors_are_explicit() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let missing = dir.path().join("missing.pem");
+        let err = load_ca_certificates(&missing).await.unwrap_err();
+        assert!(matches!(err, RokitError::InvalidCaBundle { .. }));
+        assert!(err.to_string().contains("missing.pem"));
+
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "not a certificate").unwrap();
+        let err = load_ca_certificates(&empty).await.unwrap_err();
+        assert!(err.to_string().contains("no PEM-encoded certificates"));
+    }
+}
@@ -1,38 +1,128 @@
-use std::time::Duration;
+use std::{
+    env::var,
+    time::{Duration, Instant},
+};
 
+use async_trait::async_trait;
+use http::Extensions;
 use reqwest::{
     header::{HeaderMap, USER_AGENT},
-    Client, Error,
+    redirect::Policy,
+    Certificate, Client, Error, NoProxy, Proxy, Request, Response,
 };
+use tracing::{debug, warn};
+use url::Url;
 
-use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
 use reqwest_tracing::TracingMiddleware;
 
+use super::retry::RetryMiddleware;
+
+const TIMEOUT_ENV_VAR: &str = "ROKIT_TIMEOUT";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 10;
+
 /*
     Adds middleware for:
 
-    - Retrying failed requests with exponential backoff
+    - Retrying failed requests with exponential backoff and jitter
+    - Logging of each HTTP request attempt, at the debug level
     - Tracing of HTTP requests
 */
 fn add_client_middleware(client: Client) -> ClientWithMiddleware {
     ClientBuilder::new(client)
-        .with(RetryTransientMiddleware::new_with_policy(
-            ExponentialBackoff::builder().build_with_max_retries(3),
-        ))
+        .with(RetryMiddleware::from_env())
+        .with(LoggingMiddleware)
         .with(TracingMiddleware::default())
         .build()
 }
 
+/*
+    Middleware that logs the method, url, status, and duration of requests.
+
+    Note that tokens are only ever sent in headers, which are never logged.
+*/
+#[derive(Debug, Clone, Copy)]
+struct LoggingMiddleware;
+
+#[async_trait]
+impl Middleware for LoggingMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let method = req.method().clone();
+        let url = req.url().clone();
+        let start = Instant::now();
+
+        let result = next.run(req, extensions).await;
+        match &result {
+            Ok(response) => debug!(
+                %method,
+                %url,
+                status = %response.status(),
+                elapsed = ?start.elapsed(),
+                "sent request"
+            ),
+            Err(err) => debug!(
+                %method,
+                %url,
+                elapsed = ?start.elapsed(),
+                "request failed: {err}"
+            ),
+        }
+
+        result
+    }
+}
+
+/*
+    Follows redirects, such as from release asset URLs to external storage or CDNs.
+
+    Reqwest always drops the `Authorization` header when a redirect changes the
+    host or port, which is why tokens must only ever be sent using that header,
+    and redirects to plain HTTP are rejected since the client is HTTPS only.
+*/
+fn redirect_policy() -> Policy {
+    Policy::custom(|attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        if let Some(previous) = attempt.previous().last() {
+            let next = attempt.url();
+            if previous.host_str() != next.host_str() {
+                debug!(
+                    from = previous.host_str(),
+                    to = next.host_str(),
+                    "following redirect to another host"
+                );
+            }
+        }
+        attempt.follow()
+    })
+}
+
 /**
     Creates a client with:
 
     - HTTPS only
-    - Timeouts for connection and response
+    - Timeouts for connecting, and for receiving any data, which aborts
+      stalled requests and downloads without limiting their total duration
     - All common compression algorithms enabled
     - User agent set to `<crate_name>/<crate_version> (<repository_url>)`
+    - Proxies from the standard `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY`
+      environment variables, unless an explicit proxy is given, in which case that proxy
+      is used for all requests, while still honoring any `NO_PROXY` exclusions
+    - Any given root certificates trusted in addition to the built-in ones
+    - Redirects followed across hosts, without forwarding the `Authorization` header
 */
-pub fn create_client(mut default_headers: HeaderMap) -> Result<ClientWithMiddleware, Error> {
+pub fn create_client(
+    mut default_headers: HeaderMap,
+    proxy: Option<&Url>,
+    root_certificates: &[Certificate],
+) -> Result<ClientWithMiddleware, Error> {
     let user_agent = format!(
         "{}/{} ({})",
         env!("CARGO_PKG_NAME"),
@@ -42,11 +132,20 @@ pub fn create_client(mut default_headers: HeaderMap) -> Result<ClientWithMiddlew
 
     default_headers.insert(USER_AGENT, user_agent.parse().unwrap());
 
-    let client = Client::builder()
+    let mut builder = Client::builder();
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(Proxy::all(proxy.as_str())?.no_proxy(NoProxy::from_env()));
+    }
+    for certificate in root_certificates {
+        builder = builder.add_root_certificate(certificate.clone());
+    }
+
+    let client = builder
         .default_headers(default_headers)
         .https_only(true)
+        .redirect(redirect_policy())
         .connect_timeout(Duration::from_secs(15))
-        .timeout(Duration::from_secs(60))
+        .read_timeout(timeout_from_env())
         .gzip(true)
         .brotli(true)
         .deflate(true)
@@ -54,3 +153,48 @@ pub fn create_client(mut default_headers: HeaderMap) -> Result<ClientWithMiddlew
 
     Ok(add_client_middleware(client))
 }
+
+/*
+    Reads the timeout for receiving data from the `ROKIT_TIMEOUT` environment
+    variable, in seconds, falling back to the default of 30 if unset or invalid.
+*/
+fn timeout_from_env() -> Duration {
+    let Ok(value) = var(TIMEOUT_ENV_VAR) else {
+        return DEFAULT_TIMEOUT;
+    };
+    match value.trim().parse::<u64>() {
+        Ok(seconds) if seconds > 0 => Duration::from_secs(seconds),
+        _ => {
+            warn!(
+                "Invalid value for {TIMEOUT_ENV_VAR} - expected a positive number of seconds, got '{value}'\
+                \nUsing the default of {} seconds instead.",
+                DEFAULT_TIMEOUT.as_secs()
+            );
+            DEFAULT_TIMEOUT
+        }
+    }
+}
+
+/**
+    Reads the full body of a response, chunk by chunk, calling the given
+    progress callback with the number of bytes downloaded so far, as well
+    as the total number of bytes if the server sent a `Content-Length`.
+*/
+pub async fn read_body_with_progress<F>(
+    mut response: Response,
+    on_progress: F,
+) -> Result<Vec<u8>, Error>
+where
+    F: Fn(u64, Option<u64>),
+{
+    let total = response.content_length();
+    let mut bytes = Vec::with_capacity(total.and_then(|t| usize::try_from(t).ok()).unwrap_or(0));
+
+    on_progress(0, total);
+    while let Some(chunk) = response.chunk().await? {
+        bytes.extend_from_slice(&chunk);
+        on_progress(bytes.len() as u64, total);
+    }
+
+    Ok(bytes)
+}
@@ -1,7 +1,8 @@
-use std::io::Read;
+use std::io::{self, Read};
 
 use flate2::read::GzDecoder;
 use tokio::{task::spawn_blocking, time::Instant};
+use zstd::stream::read::Decoder as ZstdDecoder;
 
 use crate::result::RokitResult;
 
@@ -26,3 +27,48 @@ pub async fn decompress_gzip(gz_contents: impl AsRef<[u8]>) -> RokitResult<Vec<u
     })
     .await?
 }
+
+pub async fn decompress_xz(xz_contents: impl AsRef<[u8]>) -> RokitResult<Vec<u8>> {
+    let xz_contents = xz_contents.as_ref().to_vec();
+    let num_kilobytes = xz_contents.len() / 1024;
+    let start = Instant::now();
+
+    // Decompressing xz is a potentially expensive operation, so
+    // spawn it as a blocking task and use the tokio thread pool.
+    spawn_blocking(move || {
+        let mut reader = io::BufReader::new(xz_contents.as_slice());
+        let mut contents = Vec::new();
+        lzma_rs::xz_decompress(&mut reader, &mut contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        tracing::trace!(
+            num_kilobytes,
+            elapsed = ?start.elapsed(),
+            "decompressed xz"
+        );
+        Ok(contents)
+    })
+    .await?
+}
+
+pub async fn decompress_zstd(zst_contents: impl AsRef<[u8]>) -> RokitResult<Vec<u8>> {
+    let zst_contents = zst_contents.as_ref().to_vec();
+    let num_kilobytes = zst_contents.len() / 1024;
+    let start = Instant::now();
+
+    // Decompressing zstd is a potentially expensive operation, so
+    // spawn it as a blocking task and use the tokio thread pool.
+    spawn_blocking(move || {
+        let mut decoder = ZstdDecoder::new(zst_contents.as_slice())?;
+        let mut contents = Vec::new();
+        decoder.read_to_end(&mut contents)?;
+
+        tracing::trace!(
+            num_kilobytes,
+            elapsed = ?start.elapsed(),
+            "decompressed zstd"
+        );
+        Ok(contents)
+    })
+    .await?
+}
@@ -0,0 +1,272 @@
+use std::io::SeekFrom;
+
+use reqwest::{
+    header::{HeaderMap, ACCEPT_RANGES, CONTENT_RANGE},
+    Response, StatusCode,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+use tracing::debug;
+
+use crate::result::{RokitError, RokitResult};
+
+use super::Artifact;
+
+/**
+    The contents of an artifact that are being downloaded, streamed to a
+    temporary file so that an interrupted download can be resumed using an
+    HTTP range request, if the server supports it, instead of starting over.
+
+    The temporary file is removed when this is dropped.
+*/
+pub(super) struct PartialDownload {
+    file: File,
+    downloaded: u64,
+    total: Option<u64>,
+    resumable: bool,
+    resumed: bool,
+}
+
+impl PartialDownload {
+    pub(super) fn new() -> RokitResult<Self> {
+        Ok(Self {
+            file: File::from_std(tempfile::tempfile()?),
+            downloaded: 0,
+            total: None,
+            resumable: false,
+            resumed: false,
+        })
+    }
+
+    /**
+        Gets the byte offset to resume the download from, if any
+        contents were already downloaded and the server supports it.
+    */
+    pub(super) fn resume_from(&self) -> Option<u64> {
+        let is_partial = self
+            .total
+            .is_some_and(|total| self.downloaded > 0 && self.downloaded < total);
+        (self.resumable && is_partial).then_some(self.downloaded)
+    }
+
+    /**
+        Discards any downloaded contents, and makes sure that
+        the next download starts over instead of resuming.
+    */
+    pub(super) async fn restart(&mut self) -> RokitResult<()> {
+        self.resumable = false;
+        self.reset().await
+    }
+
+    async fn reset(&mut self) -> RokitResult<()> {
+        self.file.set_len(0).await?;
+        self.file.seek(SeekFrom::Start(0)).await?;
+        self.downloaded = 0;
+        self.total = None;
+        self.resumed = false;
+        Ok(())
+    }
+
+    /**
+        Reads the body of a response into the temporary file, either appending
+        to the existing contents if the response is for the requested range,
+        or replacing them if the server sent the full contents instead.
+
+        Contents that were read before any error are kept, so that the
+        download can be resumed from where it left off.
+
+        # Errors
+
+        - If the server sent a range other than the one that was requested.
+        - If the response body could not be read or written to the file.
+    */
+    pub(super) async fn read_response<F>(
+        &mut self,
+        artifact: &Artifact,
+        mut response: Response,
+        on_progress: F,
+    ) -> RokitResult<()>
+    where
+        F: Fn(u64, Option<u64>),
+    {
+        if response.status() == StatusCode::PARTIAL_CONTENT {
+            let range = content_range(response.headers());
+            if range != Some((self.downloaded, self.total)) {
+                return Err(RokitError::IncompleteDownload {
+                    tool_spec: artifact.tool_spec.clone().into(),
+                    reason: format!(
+                        "server sent an unexpected range when resuming from byte {}",
+                        self.downloaded
+                    ),
+                });
+            }
+            debug!(from = self.downloaded, total = ?self.total, "resuming download");
+            self.resumed = true;
+        } else {
+            self.reset().await?;
+            // NOTE: Decompressed responses have no known content length,
+            // and byte ranges would refer to the compressed contents, so
+            // a known content length is also required for resuming downloads
+            self.total = response.content_length();
+            self.resumable = self.total.is_some() && accepts_ranges(response.headers());
+        }
+
+        on_progress(self.downloaded, self.total);
+        while let Some(chunk) = response.chunk().await? {
+            self.file.write_all(&chunk).await?;
+            self.downloaded += chunk.len() as u64;
+            on_progress(self.downloaded, self.total);
+        }
+        self.file.flush().await?;
+
+        Ok(())
+    }
+
+    /**
+        Reads the full downloaded contents, after validating them against the
+        content length sent by the server, as well as the checksum of the
+        artifact if the download was resumed and a checksum is known.
+
+        # Errors
+
+        - If the contents are incomplete or do not match the checksum.
+        - If the contents could not be read from the file.
+    */
+    pub(super) async fn finish(&mut self, artifact: &Artifact) -> RokitResult<Vec<u8>> {
+        if let Some(total) = self.total.filter(|total| *total != self.downloaded) {
+            return Err(RokitError::IncompleteDownload {
+                tool_spec: artifact.tool_spec.clone().into(),
+                reason: format!("expected {total} bytes, got {}", self.downloaded),
+            });
+        }
+
+        let mut contents = Vec::with_capacity(usize::try_from(self.downloaded).unwrap_or_default());
+        self.file.seek(SeekFrom::Start(0)).await?;
+        self.file.read_to_end(&mut contents).await?;
+
+        // NOTE: Contents that were not resumed are verified when installing,
+        // but resumed contents must be checked here, so that we can start
+        // over if the server sent different contents for the second part
+        if self.resumed {
+            artifact.verify_checksum(&contents)?;
+        }
+
+        Ok(contents)
+    }
+}
+
+fn accepts_ranges(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT_RANGES)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("bytes"))
+}
+
+// Parses the start and total length from a `Content-Range` header,
+// such as `bytes 100-999/1000`, where the total may also be unknown (`*`)
+fn content_range(headers: &HeaderMap) -> Option<(u64, Option<u64>)> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+    let start = start.trim().parse().ok()?;
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sources::ArtifactProvider;
+
+    fn artifact(checksum: Option<String>) -> Artifact {
+        Artifact {
+            provider: ArtifactProvider::GitHub,
+            format: None,
+            id: None,
+            url: None,
+            name: None,
+            file_name: None,
+            checksum,
+            signature: None,
+            size: None,
+            tool_spec: "author/tool@1.0.0".parse().unwrap(),
+        }
+    }
+
+    fn partial_response(range: &str, body: &'static str) -> Response {
+        http::Response::builder()
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(CONTENT_RANGE, range)
+            .body(body)
+            .unwrap()
+            .into()
+    }
+
+    async fn interrupted_download(contents: &[u8], total: u64) -> PartialDownload {
+        let mut download = PartialDownload::new().unwrap();
+        download.file.write_all(contents).await.unwrap();
+        download.downloaded = contents.len() as u64;
+        download.total = Some(total);
+        download.resumable = true;
+        download
+    }
+
+    #[test]
+    fn parses_content_ranges() {
+        let range = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_RANGE, value.parse().unwrap());
+            content_range(&headers)
+        };
+        assert_eq!(range("bytes 100-999/1000"), Some((100, Some(1000))));
+        assert_eq!(range("bytes 0-9/*"), Some((0, None)));
+        assert_eq!(range("bytes */1000"), None);
+        assert_eq!(range("items 0-9/10"), None);
+    }
+
+    #[tokio::test]
+    async fn resumes_from_matching_range() {
+        let checksum = Artifact::checksum_of(b"helloworld");
+        let artifact = artifact(Some(checksum));
+        let mut download = interrupted_download(b"hello", 10).await;
+        assert_eq!(download.resume_from(), Some(5));
+
+        let response = partial_response("bytes 5-9/10", "world");
+        download
+            .read_response(&artifact, response, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(download.finish(&artifact).await.unwrap(), b"helloworld");
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_resumed_contents() {
+        let artifact = artifact(None);
+        let mut download = interrupted_download(b"hello", 10).await;
+        let response = partial_response("bytes 0-9/10", "helloworld");
+        assert!(download
+            .read_response(&artifact, response, |_, _| {})
+            .await
+            .is_err());
+
+        let artifact = self::artifact(Some(Artifact::checksum_of(b"helloworld")));
+        let response = partial_response("bytes 5-9/10", "there");
+        download
+            .read_response(&artifact, response, |_, _| {})
+            .await
+            .unwrap();
+        assert!(matches!(
+            download.finish(&artifact).await,
+            Err(RokitError::ChecksumMismatch { .. })
+        ));
+
+        download.restart().await.unwrap();
+        assert_eq!(download.resume_from(), None);
+    }
+}
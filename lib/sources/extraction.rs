@@ -1,15 +1,16 @@
 #![allow(clippy::struct_excessive_bools)]
 
 use std::{
+    cmp::Reverse,
     env::consts::{EXE_EXTENSION, EXE_SUFFIX},
     io::{self, Read},
-    path::{Path, PathBuf, MAIN_SEPARATOR_STR},
+    path::{Path, PathBuf},
 };
 
 use tar::Archive as TarArchive;
 use thiserror::Error;
 use tokio::{task::spawn_blocking, time::Instant};
-use zip::ZipArchive;
+use zip::{ZipArchive, SUPPORTED_COMPRESSION_METHODS};
 
 use crate::{descriptor::OS, result::RokitResult, sources::ArtifactFormat};
 
@@ -26,6 +27,16 @@ pub enum ExtractError {
         file_name: String,
         archive_name: String,
     },
+    #[error(
+        "found multiple binaries in {format} file '{archive_name}': {}\
+        \nuse the 'bin' key for the tool in your manifest to choose one",
+        .candidates.join(", ")
+    )]
+    AmbiguousBinary {
+        format: ArtifactFormat,
+        archive_name: String,
+        candidates: Vec<String>,
+    },
     #[error(
         "mismatch in OS for binary '{file_name}' in archive '{archive_name}'\
         \ncurrent OS is {current_os:?}, binary is {file_os:?}"
@@ -37,16 +48,51 @@ pub enum ExtractError {
         archive_name: String,
     },
     #[error(
-        "{source}\
+        "unsupported compression method '{method}' for binary '{file_name}' in zip file\
+        \nsupported compression methods are: {}",
+        zip::SUPPORTED_COMPRESSION_METHODS
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )]
+    UnsupportedCompression { method: String, file_name: String },
+    #[error(
+        "binary '{file_name}' in zip file is truncated\
+        \nexpected {expected} bytes, but only {actual} bytes could be extracted"
+    )]
+    Truncated {
+        file_name: String,
+        expected: u64,
+        actual: u64,
+    },
+    #[error(
+        "failed to extract {format} file '{archive_name}': {source}\
         \nresponse body first bytes:\
         \n{body}"
     )]
     Generic {
+        format: ArtifactFormat,
+        archive_name: String,
         source: Box<dyn std::error::Error + Send + Sync>,
         body: String,
     },
 }
 
+/**
+    The result of searching an archive for a desired binary.
+*/
+#[derive(Debug, Clone)]
+pub enum ExtractedFile {
+    /// The desired binary was found and extracted.
+    Found(Vec<u8>),
+    /// No matching binary was found in the archive.
+    Missing,
+    /// Several binaries were found, but none of them matched any of
+    /// the desired names, so we could not decide which one to use.
+    Ambiguous(Vec<String>),
+}
+
 /**
     A candidate for extraction from an archive.
 
@@ -57,81 +103,177 @@ pub enum ExtractError {
 struct Candidate {
     path: PathBuf,
     matched_full_path: bool,
-    matched_file_exact: bool,   // Case-sensitive filename match
+    desired_is_subpath: bool, // Desired path has several components, such as `bin/tool`
+    matched_path_suffix: bool, // Path ends with all components of the desired path
+    matched_file_exact: bool, // Case-sensitive filename match
     matched_file_inexact: bool, // Case-insensitive filename match
-    has_exec_perms: bool,       // Has executable permissions (UNIX only)
-    has_exec_suffix: bool,      // Has an executable suffix (e.g. `.exe`)
+    has_exec_perms: bool,     // Has executable permissions (UNIX only)
+    has_exec_suffix: bool,    // Has an executable suffix (e.g. `.exe`)
 }
 
 impl Candidate {
+    fn new(path: &Path, perms: Option<u32>, desired_file_path: &Path) -> Self {
+        let file_name = path.file_name().and_then(|name| name.to_str());
+        let desired_file_name = desired_file_path.file_name().and_then(|name| name.to_str());
+
+        let matched_full_path = path == desired_file_path;
+        let desired_is_subpath = desired_file_path.components().count() > 1;
+        let matched_path_suffix = desired_is_subpath && path.ends_with(desired_file_path);
+        let matched_file_exact = file_name.is_some() && file_name == desired_file_name;
+        let matched_file_inexact = file_name
+            .zip(desired_file_name)
+            .is_some_and(|(name, desired)| name.eq_ignore_ascii_case(desired));
+
+        let has_exec_perms = perms.is_some_and(|perms| (perms & 0o111) != 0);
+        let has_exec_suffix = path.extension().is_some_and(|ext| ext == EXE_EXTENSION);
+
+        Self {
+            path: path.to_path_buf(),
+            matched_full_path,
+            desired_is_subpath,
+            matched_path_suffix,
+            matched_file_exact,
+            matched_file_inexact,
+            has_exec_perms,
+            has_exec_suffix,
+        }
+    }
+
     fn priority(&self) -> u32 {
         u32::from(self.matched_full_path)
+            + u32::from(self.matched_path_suffix)
             + u32::from(self.matched_file_exact)
             + u32::from(self.matched_file_inexact)
             + u32::from(self.has_exec_perms)
             + u32::from(self.has_exec_suffix)
     }
 
+    // Desired subpaths, such as `bin/tool`, must match all of their
+    // components, otherwise any file named `tool` would be a match
+    fn is_name_match(&self) -> bool {
+        if self.desired_is_subpath {
+            self.matched_full_path || self.matched_path_suffix
+        } else {
+            self.matched_full_path || self.matched_file_inexact
+        }
+    }
+
+    fn is_executable(&self) -> bool {
+        self.has_exec_perms || self.has_exec_suffix
+    }
+
+    fn depth(&self) -> usize {
+        self.path.components().count()
+    }
+
+    /**
+        Finds the best candidate for the desired files, in order of preference.
+
+        Files are matched at any depth in the archive, such as `tool-1.2.3/bin/tool`,
+        and when several files match equally well, the shallowest one is preferred.
+
+        If no file matches any of the desired names, and the selection is not `explicit`,
+        a single executable in the archive will be used - if there are several executables,
+        their paths will be returned as an error, since we can not know which one to use.
+    */
     fn find_best(
         entry_paths: impl AsRef<[(PathBuf, Option<u32>)]>,
-        desired_file_path: impl AsRef<Path>,
-    ) -> Option<Self> {
-        let entry_paths = entry_paths.as_ref();
-        let desired_file_path = desired_file_path.as_ref();
-        let desired_file_name = desired_file_path.file_name()?.to_str()?;
-
-        // Gather all candidates
-        let mut candidates = entry_paths
+        desired_file_paths: impl AsRef<[PathBuf]>,
+        explicit: bool,
+    ) -> Result<Option<Self>, Vec<PathBuf>> {
+        let entry_paths = entry_paths
+            .as_ref()
             .iter()
-            .filter_map(|(path, perms)| {
-                if path.ends_with(MAIN_SEPARATOR_STR) {
-                    return None;
-                }
-
-                let file_name = path.file_name().and_then(|name| name.to_str());
+            .filter(|(path, _)| !is_dir_path(path))
+            .collect::<Vec<_>>();
 
-                let matched_full_path = path == desired_file_path;
-                let matched_file_exact = file_name == Some(desired_file_name);
-                let matched_file_inexact =
-                    file_name.is_some_and(|name| name.eq_ignore_ascii_case(desired_file_name));
+        // Try each of the desired files in order, and pick the best match for the first one found
+        for desired_file_path in desired_file_paths.as_ref() {
+            let best = entry_paths
+                .iter()
+                .map(|(path, perms)| Self::new(path, *perms, desired_file_path))
+                .filter(Candidate::is_name_match)
+                .max_by_key(|candidate| (candidate.priority(), Reverse(candidate.depth())));
+            if let Some(candidate) = best {
+                tracing::debug!(
+                    path = ?candidate.path,
+                    desired = ?desired_file_path,
+                    "found binary matching desired name"
+                );
+                return Ok(Some(candidate));
+            }
+        }
 
-                let has_exec_perms = perms.map_or(false, |perms| (perms & 0o111) != 0);
-                let has_exec_suffix = path.extension().map_or(false, |ext| ext == EXE_EXTENSION);
+        if explicit {
+            return Ok(None);
+        }
 
-                Some(Self {
-                    path: path.clone(),
-                    matched_full_path,
-                    matched_file_exact,
-                    matched_file_inexact,
-                    has_exec_perms,
-                    has_exec_suffix,
-                })
-            })
-            .filter(|c| c.priority() > 0) // Filter out candidates with no matches at all
+        // Nothing matched by name, fall back to the only executable, if there is only one
+        let mut executables = entry_paths
+            .iter()
+            .map(|(path, perms)| Self::new(path, *perms, Path::new("")))
+            .filter(Candidate::is_executable)
             .collect::<Vec<_>>();
+        match executables.len() {
+            0 => Ok(None),
+            1 => {
+                let candidate = executables.remove(0);
+                tracing::debug!(
+                    path = ?candidate.path,
+                    "no binary matched by name, using the only executable"
+                );
+                Ok(Some(candidate))
+            }
+            _ => Err(executables.into_iter().map(|c| c.path).collect()),
+        }
+    }
+}
 
-        // Sort by their priority, best first
-        candidates.sort_by_key(Candidate::priority);
-        candidates.reverse();
+// Directory entries in archives end with a separator, and must never be
+// picked as a binary, even when named the same as the binary inside them
+fn is_dir_path(path: &Path) -> bool {
+    path.as_os_str()
+        .to_str()
+        .is_some_and(|path| path.ends_with(['/', '\\']))
+}
 
-        // The first candidate, if one exists, should now be the best one
-        let candidate = candidates.into_iter().next()?;
-        tracing::trace!(path = ?candidate.path, "found candidate");
-        Some(candidate)
-    }
+/**
+    Creates the list of desired file paths to search for in an archive,
+    adding an executable suffix (e.g. `.exe`) to names that lack one.
+*/
+fn desired_file_paths(desired_file_names: Vec<String>) -> Vec<PathBuf> {
+    desired_file_names
+        .into_iter()
+        .map(|name| {
+            if EXE_SUFFIX.is_empty() || name.ends_with(EXE_SUFFIX) {
+                PathBuf::from(name)
+            } else {
+                PathBuf::from(format!("{name}{EXE_SUFFIX}"))
+            }
+        })
+        .collect()
+}
+
+fn ambiguous_names(paths: Vec<PathBuf>) -> Vec<String> {
+    let mut names = paths
+        .into_iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
 }
 
 /**
     Searches for and extracts the best matching file from a zip archive.
 
-    May return `None` if no desired file was found in the archive.
+    Desired file names are tried in order - see [`ExtractedFile`] for possible results.
 */
 pub async fn extract_zip_file(
     zip_contents: impl AsRef<[u8]>,
-    desired_file_name: impl Into<String>,
-) -> RokitResult<Option<Vec<u8>>> {
-    let desired_file_name = format!("{}{EXE_SUFFIX}", desired_file_name.into());
-    let desired_file_path = PathBuf::from(&desired_file_name);
+    desired_file_names: Vec<String>,
+    explicit: bool,
+) -> RokitResult<ExtractedFile> {
+    let desired_file_paths = desired_file_paths(desired_file_names);
 
     let zip_contents = zip_contents.as_ref().to_vec();
     let num_kilobytes = zip_contents.len() / 1024;
@@ -156,14 +298,14 @@ pub async fn extract_zip_file(
             .collect::<Vec<_>>();
 
         // Find the best candidate to extract, if any
-        let best = Candidate::find_best(entry_paths, &desired_file_path);
+        let best = match Candidate::find_best(entry_paths, &desired_file_paths, explicit) {
+            Ok(best) => best,
+            Err(paths) => return Ok(ExtractedFile::Ambiguous(ambiguous_names(paths))),
+        };
         if let Some(candidate) = best {
-            if let Some(path_str) = candidate.path.to_str() {
-                if let Ok(mut entry) = zip.by_name(path_str) {
-                    let mut bytes = Vec::new();
-                    entry.read_to_end(&mut bytes)?;
-                    found = Some(bytes);
-                }
+            let index = candidate.path.to_str().and_then(|p| zip.index_for_name(p));
+            if let Some(index) = index {
+                found = Some(read_zip_entry(&mut zip, index)?);
             }
             if found.is_none() {
                 tracing::warn!(
@@ -179,22 +321,59 @@ pub async fn extract_zip_file(
             found = found.is_some(),
             "extracted zip file"
         );
-        Ok(found)
+        Ok(found.map_or(ExtractedFile::Missing, ExtractedFile::Found))
     })
     .await?
 }
 
+// Reads the full contents of a zip entry, making sure that its compression
+// method is supported, and that the entry was not silently truncated, which
+// would otherwise result in a corrupt binary that crashes when it is run
+fn read_zip_entry<R: io::Read + io::Seek>(
+    zip: &mut ZipArchive<R>,
+    index: usize,
+) -> RokitResult<Vec<u8>> {
+    let (method, file_name) = {
+        let raw = zip.by_index_raw(index)?;
+        (raw.compression(), raw.name().to_string())
+    };
+    if !SUPPORTED_COMPRESSION_METHODS.contains(&method) {
+        return Err(ExtractError::UnsupportedCompression {
+            method: method.to_string(),
+            file_name,
+        }
+        .into());
+    }
+
+    let mut entry = zip.by_index(index)?;
+    let expected = entry.size();
+    let mut bytes = Vec::with_capacity(usize::try_from(expected).unwrap_or_default());
+    entry.read_to_end(&mut bytes)?;
+
+    let actual = bytes.len() as u64;
+    if actual != expected {
+        return Err(ExtractError::Truncated {
+            file_name,
+            expected,
+            actual,
+        }
+        .into());
+    }
+
+    Ok(bytes)
+}
+
 /**
     Searches for and extracts the best matching file from a tar archive.
 
-    May return `None` if no desired file was found in the archive.
+    Desired file names are tried in order - see [`ExtractedFile`] for possible results.
 */
 pub async fn extract_tar_file(
     tar_contents: impl AsRef<[u8]>,
-    desired_file_name: impl Into<String>,
-) -> RokitResult<Option<Vec<u8>>> {
-    let desired_file_name = format!("{}{EXE_SUFFIX}", desired_file_name.into());
-    let desired_file_path = PathBuf::from(&desired_file_name);
+    desired_file_names: Vec<String>,
+    explicit: bool,
+) -> RokitResult<ExtractedFile> {
+    let desired_file_paths = desired_file_paths(desired_file_names);
 
     let tar_contents = tar_contents.as_ref().to_vec();
     let num_kilobytes = tar_contents.len() / 1024;
@@ -229,7 +408,10 @@ pub async fn extract_tar_file(
             .collect::<Vec<_>>();
 
         // Find the best candidate to extract, if any
-        let best = Candidate::find_best(entry_paths, &desired_file_path);
+        let best = match Candidate::find_best(entry_paths, &desired_file_paths, explicit) {
+            Ok(best) => best,
+            Err(paths) => return Ok(ExtractedFile::Ambiguous(ambiguous_names(paths))),
+        };
         if let Some(candidate) = best {
             let contents_cursor = io::Cursor::new(&tar_contents);
             let mut contents_reader = TarArchive::new(contents_cursor);
@@ -257,7 +439,201 @@ pub async fn extract_tar_file(
             found = found.is_some(),
             "extracted tar file"
         );
-        Ok(found)
+        Ok(found.map_or(ExtractedFile::Missing, ExtractedFile::Found))
     })
     .await?
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::result::RokitError;
+
+    fn entries(paths: &[(&str, Option<u32>)]) -> Vec<(PathBuf, Option<u32>)> {
+        paths
+            .iter()
+            .map(|(path, perms)| (PathBuf::from(path), *perms))
+            .collect()
+    }
+
+    fn find_best(
+        entry_paths: &[(&str, Option<u32>)],
+        desired: &[&str],
+        explicit: bool,
+    ) -> Result<Option<String>, Vec<PathBuf>> {
+        let desired = desired.iter().map(PathBuf::from).collect::<Vec<_>>();
+        Candidate::find_best(entries(entry_paths), desired, explicit)
+            .map(|c| c.map(|c| c.path.display().to_string()))
+    }
+
+    const ARCHIVE: &[(&str, Option<u32>)] = &[
+        ("tool/README.md", Some(0o644)),
+        ("tool/main-cli", Some(0o755)),
+        ("tool/helper", Some(0o755)),
+    ];
+
+    #[test]
+    fn prefers_desired_names_in_order() {
+        assert_eq!(
+            find_best(ARCHIVE, &["helper", "main-cli"], false),
+            Ok(Some(String::from("tool/helper")))
+        );
+        assert_eq!(
+            find_best(ARCHIVE, &["missing", "MAIN-CLI"], false),
+            Ok(Some(String::from("tool/main-cli")))
+        );
+        assert_eq!(
+            find_best(ARCHIVE, &["tool/helper"], true),
+            Ok(Some(String::from("tool/helper")))
+        );
+    }
+
+    #[test]
+    fn finds_nested_binaries() {
+        let archive = &[
+            ("tool-1.2.3/", None),
+            ("tool-1.2.3/README.md", Some(0o644)),
+            ("tool-1.2.3/bin/", None),
+            ("tool-1.2.3/bin/tool", Some(0o755)),
+            ("tool-1.2.3/share/extras/tool", Some(0o755)),
+        ];
+        assert_eq!(
+            find_best(archive, &["tool"], false),
+            Ok(Some(String::from("tool-1.2.3/bin/tool")))
+        );
+        assert_eq!(
+            find_best(archive, &["extras/tool"], true),
+            Ok(Some(String::from("tool-1.2.3/share/extras/tool")))
+        );
+    }
+
+    #[test]
+    fn directories_are_never_binaries() {
+        let archive = &[("tool/", None), ("tool/tool", Some(0o755))];
+        assert_eq!(
+            find_best(archive, &["tool"], true),
+            Ok(Some(String::from("tool/tool")))
+        );
+    }
+
+    #[test]
+    fn subpaths_match_all_components() {
+        let archive = &[
+            ("tool-1.2.3/share/bar", Some(0o755)),
+            ("tool-1.2.3/bin/bar", Some(0o755)),
+        ];
+        assert_eq!(
+            find_best(archive, &["bin/bar"], true),
+            Ok(Some(String::from("tool-1.2.3/bin/bar")))
+        );
+        assert_eq!(find_best(archive, &["lib/bar"], true), Ok(None));
+    }
+
+    #[test]
+    fn explicit_selection_never_falls_back() {
+        assert_eq!(find_best(ARCHIVE, &["missing"], true), Ok(None));
+    }
+
+    #[test]
+    fn single_executable_fallback() {
+        let archive = &[("README.md", Some(0o644)), ("cli", Some(0o755))];
+        assert_eq!(
+            find_best(archive, &["missing"], false),
+            Ok(Some(String::from("cli")))
+        );
+    }
+
+    #[test]
+    fn multiple_executables_are_ambiguous() {
+        assert_eq!(
+            find_best(ARCHIVE, &["missing"], false),
+            Err(vec![
+                PathBuf::from("tool/main-cli"),
+                PathBuf::from("tool/helper")
+            ])
+        );
+    }
+
+    fn zip_archive(
+        files: &[(&str, &[u8])],
+        method: zip::CompressionMethod,
+        large_file: bool,
+    ) -> Vec<u8> {
+        use std::io::Write;
+
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(large_file);
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    async fn extract_zip(contents: Vec<u8>, desired: &str) -> RokitResult<ExtractedFile> {
+        extract_zip_file(contents, vec![desired.to_string()], false).await
+    }
+
+    #[tokio::test]
+    async fn extracts_stored_and_zip64_entries() {
+        let binary = (0..=255u8).cycle().take(256 * 1024).collect::<Vec<_>>();
+        let files: &[(&str, &[u8])] = &[("README.md", b"readme"), ("tool/cli", &binary)];
+        for method in [
+            zip::CompressionMethod::Stored,
+            zip::CompressionMethod::Deflated,
+        ] {
+            for large_file in [false, true] {
+                let archive = zip_archive(files, method, large_file);
+                match extract_zip(archive, "cli").await.unwrap() {
+                    ExtractedFile::Found(bytes) => assert_eq!(bytes, binary),
+                    other => panic!("expected binary for {method}, got {other:?}"),
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn extracts_zip64_archives_with_many_entries() {
+        let names = (0..=zip::ZIP64_ENTRY_THR)
+            .map(|i| format!("docs/{i}.txt"))
+            .collect::<Vec<_>>();
+        let mut files = names
+            .iter()
+            .map(|name| (name.as_str(), &b""[..]))
+            .collect::<Vec<_>>();
+        files.push(("tool/cli", b"binary"));
+
+        let archive = zip_archive(&files, zip::CompressionMethod::Stored, false);
+        match extract_zip(archive, "cli").await.unwrap() {
+            ExtractedFile::Found(bytes) => assert_eq!(bytes, b"binary"),
+            other => panic!("expected binary, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn unsupported_compression_is_explicit() {
+        let mut archive = zip_archive(&[("cli", b"binary")], zip::CompressionMethod::Stored, false);
+
+        // NOTE: Patch the compression method in both the local file header
+        // and the central directory to "imploded", which is not supported
+        let patch = |archive: &mut Vec<u8>, signature: &[u8], offset: usize| {
+            let start = archive
+                .windows(signature.len())
+                .position(|window| window == signature)
+                .unwrap();
+            archive[start + offset] = 6;
+        };
+        patch(&mut archive, b"PK\x03\x04", 8);
+        patch(&mut archive, b"PK\x01\x02", 10);
+
+        let err = extract_zip(archive, "cli").await.unwrap_err();
+        assert!(matches!(
+            err,
+            RokitError::Extract(ref e) if matches!(**e, ExtractError::UnsupportedCompression { .. })
+        ));
+        assert!(err.to_string().contains("unsupported compression method"));
+    }
+}
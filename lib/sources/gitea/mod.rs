@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+
+use reqwest_middleware::{ClientWithMiddleware, RequestBuilder};
+use semver::Version;
+use serde::de::DeserializeOwned;
+use tracing::{debug, instrument};
+use url::{form_urlencoded::byte_serialize, Url};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, RANGE},
+    Certificate, Response, StatusCode,
+};
+
+use crate::{
+    tool::{ToolId, ToolSpec},
+    util::str::normalize_host,
+};
+
+use super::{
+    client::{create_client, read_body_with_progress},
+    mirrors::DownloadMirrors,
+    release_tags,
+    retry::MaxRetries,
+    Artifact, ArtifactProvider, ReleaseInfo,
+};
+
+const RELEASES_PER_PAGE: usize = 50;
+const MAX_RELEASE_PAGES: usize = 10;
+
+pub mod models;
+mod result;
+
+use self::models::Release;
+
+pub use self::result::{GiteaError, GiteaResult};
+
+/**
+    A provider for releases on Gitea instances, including Forgejo and Codeberg.
+
+    Since there is no single Gitea instance, tool ids for this provider
+    must include the host of the instance, and tokens are stored per host.
+*/
+#[derive(Debug, Clone)]
+pub struct GiteaProvider {
+    client: ClientWithMiddleware,
+    tokens: HashMap<String, HeaderValue>,
+    max_retries: Option<u32>,
+}
+
+impl GiteaProvider {
+    pub(crate) fn new_inner(
+        tokens: HashMap<String, String>,
+        proxy: Option<&Url>,
+        root_certificates: &[Certificate],
+    ) -> GiteaResult<Self> {
+        let tokens = tokens
+            .into_iter()
+            .map(|(host, pat)| {
+                let mut value = HeaderValue::from_str(&format!("token {}", pat.trim()))?;
+                value.set_sensitive(true);
+                Ok((normalize_host(&host), value))
+            })
+            .collect::<GiteaResult<HashMap<_, _>>>()?;
+
+        // NOTE: Tokens are intentionally not added as default headers here,
+        // since each token must only ever be sent to the host it belongs to.
+        let client = create_client(HeaderMap::new(), proxy, root_certificates)?;
+
+        Ok(Self {
+            client,
+            tokens,
+            max_retries: None,
+        })
+    }
+
+    fn api_url(host: &str, path: &str) -> String {
+        format!("https://{host}/api/v1/{}", path.trim_start_matches('/'))
+    }
+
+    fn repo_url(tool_id: &ToolId, path: &str) -> GiteaResult<String> {
+        let host = tool_id
+            .host()
+            .ok_or_else(|| GiteaError::MissingHost(tool_id.clone().into()))?;
+        Ok(Self::api_url(
+            host,
+            &format!(
+                "repos/{}/{}/{}",
+                tool_id.author(),
+                tool_id.name(),
+                path.trim_start_matches('/')
+            ),
+        ))
+    }
+
+    fn token_for(&self, url: &str) -> Option<&HeaderValue> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        let host = match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+        self.tokens.get(&normalize_host(&host))
+    }
+
+    /**
+        Sets the maximum number of retries for requests to this provider.
+    */
+    #[must_use]
+    pub(crate) fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    fn request(&self, url: &str, accept: &'static str) -> RequestBuilder {
+        let mut request = self
+            .client
+            .get(url)
+            .header(ACCEPT, HeaderValue::from_static(accept));
+        if let Some(max_retries) = self.max_retries {
+            request = request.with_extension(MaxRetries(max_retries));
+        }
+        if let Some(token) = self.token_for(url) {
+            request = request.header(AUTHORIZATION, token.clone());
+        }
+        request
+    }
+
+    async fn get(&self, url: &str, accept: &'static str) -> GiteaResult<Response> {
+        Ok(self.request(url, accept).send().await?.error_for_status()?)
+    }
+
+    async fn get_contents(&self, url: &str, range_start: Option<u64>) -> GiteaResult<Response> {
+        let mut request = self.request(url, "application/octet-stream");
+        if let Some(start) = range_start {
+            request = request.header(RANGE, format!("bytes={start}-"));
+        }
+        Ok(request.send().await?.error_for_status()?)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> GiteaResult<T> {
+        let response = self.get(url, "application/json").await?;
+        Ok(response.json().await?)
+    }
+
+    /**
+        Creates a new Gitea source instance.
+
+        # Errors
+
+        - If the Gitea API client could not be created.
+    */
+    pub fn new() -> GiteaResult<Self> {
+        Self::new_inner(HashMap::new(), None, &[])
+    }
+
+    /**
+        Creates a new authenticated Gitea source instance with a token for the given host.
+
+        Note that this does not verify the formatting or validity of the token,
+        use the `verify_authentication` method for checking with the Gitea API.
+
+        # Errors
+
+        - If the Gitea API client could not be created.
+    */
+    pub fn new_authenticated(host: impl AsRef<str>, pat: impl AsRef<str>) -> GiteaResult<Self> {
+        let tokens = HashMap::from([(host.as_ref().to_string(), pat.as_ref().to_string())]);
+        Self::new_inner(tokens, None, &[])
+    }
+
+    /**
+        Verifies that the current authentication token for the given host is valid.
+
+        Returns `true` if the token is valid, `false` if it is not.
+
+        Always returns `false` if the source is not authenticated for the host.
+
+        # Errors
+
+        - If the request to the Gitea API failed.
+    */
+    pub async fn verify_authentication(&self, host: &str) -> GiteaResult<bool> {
+        if !self.tokens.contains_key(&normalize_host(host)) {
+            return Ok(false);
+        }
+
+        let url = Self::api_url(host, "user");
+        let res = self.get_json::<serde_json::Value>(&url).await;
+
+        match res {
+            Ok(_) => Ok(true),
+            Err(e) if is_unauthenticated(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+        Fetches the latest release for a given tool.
+
+        # Errors
+
+        - If the latest release could not be fetched.
+    */
+    pub async fn get_latest_release(&self, tool_id: &ToolId) -> GiteaResult<Vec<Artifact>> {
+        Ok(self.get_latest_release_info(tool_id).await?.artifacts)
+    }
+
+    /**
+        Fetches the latest release for a given tool, including details
+        such as when it was published and its release notes.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_latest_release_info(&self, tool_id: &ToolId) -> GiteaResult<ReleaseInfo> {
+        debug!(id = %tool_id, "fetching latest release for tool");
+
+        let url = Self::repo_url(tool_id, "releases/latest")?;
+        let release: Release = match self.get_json(&url).await {
+            Err(e) if is_404(&e) => {
+                return Err(GiteaError::LatestReleaseNotFound(tool_id.clone().into()));
+            }
+            Err(e) => return Err(e),
+            Ok(r) => r,
+        };
+
+        let version = release
+            .tag_name
+            .trim_start_matches('v')
+            .parse::<Version>()
+            .map_err(|e| GiteaError::Other(e.to_string()))?;
+
+        let tool_spec: ToolSpec = (tool_id.clone(), version).into();
+        let artifacts = artifacts_from_release(&release, &tool_spec);
+        Ok(ReleaseInfo::new(
+            tool_spec,
+            artifacts,
+            release.published_at,
+            release.body,
+        ))
+    }
+
+    /**
+        Fetches all available release versions for a given tool, sorted in ascending order.
+
+        Releases with tags that are not valid versions are ignored, as are
+        drafts and prereleases, unless `include_prereleases` is `true`.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_available_versions(
+        &self,
+        tool_id: &ToolId,
+        include_prereleases: bool,
+    ) -> GiteaResult<Vec<Version>> {
+        debug!(id = %tool_id, "fetching available versions for tool");
+
+        let mut versions = Vec::new();
+        for page in 1..=MAX_RELEASE_PAGES {
+            let url = Self::repo_url(
+                tool_id,
+                &format!("releases?limit={RELEASES_PER_PAGE}&page={page}"),
+            )?;
+
+            let releases: Vec<Release> = match self.get_json(&url).await {
+                Err(e) if is_404(&e) => {
+                    return Err(GiteaError::LatestReleaseNotFound(tool_id.clone().into()));
+                }
+                Err(e) => return Err(e),
+                Ok(r) => r,
+            };
+
+            let page_len = releases.len();
+            versions.extend(releases.into_iter().filter_map(|release| {
+                if release.draft || (release.prerelease && !include_prereleases) {
+                    return None;
+                }
+                let version = release.tag_name.trim_start_matches('v').parse::<Version>();
+                let version = version.ok()?;
+                if !version.pre.is_empty() && !include_prereleases {
+                    None
+                } else {
+                    Some(version)
+                }
+            }));
+
+            if page_len < RELEASES_PER_PAGE {
+                break;
+            }
+        }
+
+        versions.sort();
+        versions.dedup();
+        Ok(versions)
+    }
+
+    /**
+        Checks if the repository for a given tool exists, and can be accessed.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn repository_exists(&self, tool_id: &ToolId) -> GiteaResult<bool> {
+        debug!(id = %tool_id, "checking if repository exists");
+        Ok(self.get_canonical_id(tool_id).await?.is_some())
+    }
+
+    /**
+        Fetches the id of a given tool with the casing of its author and
+        name as on the Gitea instance, or `None` if the repository does not exist.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_canonical_id(&self, tool_id: &ToolId) -> GiteaResult<Option<ToolId>> {
+        let url = Self::repo_url(tool_id, "")?;
+        let url = url.trim_end_matches('/');
+
+        match self.get_json::<serde_json::Value>(url).await {
+            Ok(repository) => Ok(Some(
+                match repository.get("full_name").and_then(|n| n.as_str()) {
+                    Some(full_name) => tool_id.with_casing_of(full_name),
+                    None => tool_id.clone(),
+                },
+            )),
+            Err(e) if is_404(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+        Fetches a specific release for a given tool.
+
+        # Errors
+
+        - If the release could not be fetched, or does not exist.
+    */
+    pub async fn get_specific_release(&self, tool_spec: &ToolSpec) -> GiteaResult<Vec<Artifact>> {
+        self.get_specific_release_with_tag_prefix(tool_spec, None)
+            .await
+    }
+
+    /**
+        Fetches a specific release for a given tool, also trying
+        release tags with the given prefix, such as `cli-1.2.3`.
+
+        # Errors
+
+        - If the release could not be fetched, or does not exist.
+    */
+    #[instrument(skip(self), fields(%tool_spec), level = "debug")]
+    pub async fn get_specific_release_with_tag_prefix(
+        &self,
+        tool_spec: &ToolSpec,
+        tag_prefix: Option<&str>,
+    ) -> GiteaResult<Vec<Artifact>> {
+        debug!(spec = %tool_spec, "fetching release for tool");
+
+        let tool_id = tool_spec.id();
+        let tags = release_tags(tool_spec.version(), tag_prefix);
+        let tag_url = |tag: &str| {
+            let tag = byte_serialize(tag.as_bytes()).collect::<String>();
+            Self::repo_url(tool_id, &format!("releases/tags/{tag}"))
+        };
+
+        let mut found = None;
+        for tag in &tags {
+            match self.get_json::<Release>(&tag_url(tag)?).await {
+                Err(e) if is_404(&e) => debug!(spec = %tool_spec, tag, "release tag not found"),
+                Err(e) => return Err(e),
+                Ok(r) => {
+                    found = Some(r);
+                    break;
+                }
+            }
+        }
+        let Some(release) = found else {
+            return Err(GiteaError::ReleaseNotFound {
+                tool_spec: tool_spec.clone().into(),
+                tags,
+            });
+        };
+
+        Ok(artifacts_from_release(&release, tool_spec))
+    }
+
+    /**
+        Downloads the contents of the given artifact.
+
+        # Errors
+
+        - If the artifact contents could not be downloaded.
+    */
+    pub async fn download_artifact_contents(&self, artifact: &Artifact) -> GiteaResult<Vec<u8>> {
+        self.download_artifact_contents_with_progress(artifact, |_, _| {})
+            .await
+    }
+
+    /**
+        Downloads the contents of the given artifact, reporting progress as it downloads.
+
+        See [`ArtifactSource::download_artifact_contents_with_progress`](super::ArtifactSource::download_artifact_contents_with_progress).
+
+        # Errors
+
+        - If the artifact contents could not be downloaded.
+    */
+    pub async fn download_artifact_contents_with_progress<F>(
+        &self,
+        artifact: &Artifact,
+        on_progress: F,
+    ) -> GiteaResult<Vec<u8>>
+    where
+        F: Fn(u64, Option<u64>),
+    {
+        let response = self
+            .request_artifact_contents(artifact, None, &DownloadMirrors::default())
+            .await?;
+        Ok(read_body_with_progress(response, on_progress).await?)
+    }
+
+    /*
+        Sends the request for the contents of the given artifact, optionally
+        for only the contents starting at the given byte offset, which is
+        used for resuming interrupted downloads.
+    */
+    #[instrument(skip(self, artifact, mirrors), level = "debug")]
+    pub(crate) async fn request_artifact_contents(
+        &self,
+        artifact: &Artifact,
+        range_start: Option<u64>,
+        mirrors: &DownloadMirrors,
+    ) -> GiteaResult<Response> {
+        assert_eq!(
+            artifact.provider,
+            ArtifactProvider::Gitea,
+            "artifact must be from Gitea"
+        );
+
+        let id = artifact.id.as_ref().expect("Gitea artifacts have ids");
+        let name = artifact.name.as_ref().expect("Gitea artifacts have names");
+        let url = artifact.url.as_ref().expect("Gitea artifacts have urls");
+        debug!(id, name, "downloading artifact contents");
+
+        let url = mirrors.rewrite(url.as_str());
+
+        self.get_contents(&url, range_start).await
+    }
+}
+
+fn is_404(err: &GiteaError) -> bool {
+    if let GiteaError::Reqwest(reqwest_err) = err {
+        if let Some(status) = reqwest_err.status() {
+            return status == StatusCode::NOT_FOUND;
+        }
+    }
+    false
+}
+
+fn is_unauthenticated(err: &GiteaError) -> bool {
+    if let GiteaError::Reqwest(reqwest_err) = err {
+        if let Some(status) = reqwest_err.status() {
+            return matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN);
+        }
+    }
+    false
+}
+
+fn artifacts_from_release(release: &Release, spec: &ToolSpec) -> Vec<Artifact> {
+    release
+        .assets
+        .iter()
+        .map(|asset| Artifact::from_gitea_release_asset(asset, spec))
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_scoped_to_hosts() {
+        let tokens = HashMap::from([
+            (String::from("Codeberg.org"), String::from("abc")),
+            (String::from("localhost:3000"), String::from("def")),
+        ]);
+        let provider = GiteaProvider::new_inner(tokens, None, &[]).unwrap();
+        assert!(provider
+            .token_for("https://codeberg.org/api/v1/user")
+            .is_some());
+        assert!(provider
+            .token_for("https://localhost:3000/api/v1/user")
+            .is_some());
+        assert!(provider
+            .token_for("https://localhost/api/v1/user")
+            .is_none());
+        assert!(provider.token_for("https://example.com/file.zip").is_none());
+    }
+}
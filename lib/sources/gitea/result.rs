@@ -0,0 +1,50 @@
+use reqwest::{header::InvalidHeaderValue, Error as ReqwestError};
+use thiserror::Error;
+
+use crate::{
+    sources::format_release_not_found,
+    tool::{ToolId, ToolSpec},
+};
+
+#[derive(Debug, Error)]
+pub enum GiteaError {
+    #[error("tool '{0}' is missing the host of the Gitea instance to use")]
+    MissingHost(Box<ToolId>),
+    #[error("no latest release was found for tool '{0}'")]
+    LatestReleaseNotFound(Box<ToolId>),
+    #[error("{}", format_release_not_found(tool_spec, tags))]
+    ReleaseNotFound {
+        tool_spec: Box<ToolSpec>,
+        tags: Vec<String>,
+    },
+    #[error("failed to build client - invalid header value: {0}")]
+    ReqwestHeader(Box<InvalidHeaderValue>),
+    #[error("reqwest middleware error: {0}")]
+    ReqwestMiddleware(Box<reqwest_middleware::Error>),
+    #[error("reqwest error: {0}")]
+    Reqwest(Box<reqwest::Error>),
+    #[error("other error: {0}")]
+    Other(String),
+}
+
+pub type GiteaResult<T> = Result<T, GiteaError>;
+
+// FUTURE: Figure out some way to reduce this boxing boilerplate
+
+impl From<InvalidHeaderValue> for GiteaError {
+    fn from(err: InvalidHeaderValue) -> Self {
+        GiteaError::ReqwestHeader(err.into())
+    }
+}
+
+impl From<reqwest_middleware::Error> for GiteaError {
+    fn from(err: reqwest_middleware::Error) -> Self {
+        GiteaError::ReqwestMiddleware(err.into())
+    }
+}
+
+impl From<ReqwestError> for GiteaError {
+    fn from(err: ReqwestError) -> Self {
+        GiteaError::Reqwest(err.into())
+    }
+}
@@ -1,74 +1,269 @@
-use reqwest_middleware::ClientWithMiddleware;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest_middleware::{ClientWithMiddleware, RequestBuilder};
 use semver::Version;
 use serde::de::DeserializeOwned;
 use tracing::{debug, instrument};
+use url::{form_urlencoded::byte_serialize, Url};
 
 use reqwest::{
-    header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION},
-    StatusCode,
+    header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, RANGE},
+    Certificate, Response, StatusCode,
 };
 
-use crate::tool::{ToolId, ToolSpec};
+use crate::{
+    tool::{ToolId, ToolSpec},
+    util::str::normalize_host,
+};
 
-use super::{client::create_client, Artifact, ArtifactProvider};
+use super::{
+    client::{create_client, read_body_with_progress},
+    mirrors::DownloadMirrors,
+    release_cache::{CachedRelease, ReleaseCache},
+    release_tags,
+    retry::MaxRetries,
+    Artifact, ArtifactProvider, ReleaseInfo,
+};
 
+const BASE_HOST: &str = "api.github.com";
 const BASE_URL: &str = "https://api.github.com";
+const RATE_LIMIT_REMAINING_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+const RATE_LIMIT_RESET_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+const RATE_LIMIT_UNKNOWN: u64 = u64::MAX;
+const RELEASES_PER_PAGE: usize = 100;
+const MAX_RELEASE_PAGES: usize = 10;
+const MAX_SEARCH_RESULTS: usize = 100;
 
 pub mod models;
 mod result;
 
-use self::models::Release;
+use self::models::{Release, Repository, RepositorySearch};
 
 pub use self::result::{GithubError, GithubResult};
 
+/**
+    Rate limit information for the GitHub API, as last seen in a response.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GithubRateLimit {
+    /// The number of requests remaining in the current rate limit window.
+    pub remaining: u64,
+    /// The time at which the current rate limit window resets.
+    pub reset: Option<SystemTime>,
+}
+
+/**
+    A provider for releases on GitHub, as well as GitHub Enterprise Server instances.
+
+    Tool ids for GitHub Enterprise must include the host of the instance,
+    such as `ghe:github.mycorp.com/author/name`, and tokens for those
+    are stored per host, same as for [`GiteaProvider`](super::gitea::GiteaProvider).
+*/
 #[derive(Debug, Clone)]
 pub struct GithubProvider {
     client: ClientWithMiddleware,
-    has_auth: bool,
+    token: Option<HeaderValue>,
+    enterprise_tokens: HashMap<String, HeaderValue>,
+    rate_limit_remaining: Arc<AtomicU64>,
+    rate_limit_reset: Arc<AtomicU64>,
+    release_cache: Option<ReleaseCache>,
+    max_retries: Option<u32>,
+    enterprise_max_retries: Option<u32>,
 }
 
 impl GithubProvider {
-    fn new_inner(pat: Option<String>) -> GithubResult<Self> {
-        let has_auth = pat.is_some();
-        let headers = {
-            let mut headers = HeaderMap::new();
-            headers.insert(
-                HeaderName::from_static("x-github-api-version"),
-                HeaderValue::from_static("2022-11-28"),
-            );
-            if let Some(pat) = pat {
-                let token = format!("Bearer {pat}");
-                headers.insert(AUTHORIZATION, HeaderValue::from_str(&token)?);
-            }
-            headers
+    pub(crate) fn new_inner(
+        pat: Option<String>,
+        enterprise_tokens: HashMap<String, String>,
+        proxy: Option<&Url>,
+        root_certificates: &[Certificate],
+    ) -> GithubResult<Self> {
+        let token_header = |pat: &str| -> GithubResult<HeaderValue> {
+            let mut value = HeaderValue::from_str(&format!("Bearer {}", pat.trim()))?;
+            value.set_sensitive(true);
+            Ok(value)
+        };
+        let token = pat.map(|pat| token_header(&pat)).transpose()?;
+        let enterprise_tokens = enterprise_tokens
+            .into_iter()
+            .map(|(host, pat)| Ok((normalize_host(&host), token_header(&pat)?)))
+            .collect::<GithubResult<HashMap<_, _>>>()?;
+
+        // NOTE: Tokens are intentionally not added as default headers here,
+        // since each token must only ever be sent to the host it belongs to.
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-github-api-version"),
+            HeaderValue::from_static("2022-11-28"),
+        );
+        let client = create_client(headers, proxy, root_certificates)?;
+
+        Ok(Self {
+            client,
+            token,
+            enterprise_tokens,
+            rate_limit_remaining: Arc::new(AtomicU64::new(RATE_LIMIT_UNKNOWN)),
+            rate_limit_reset: Arc::new(AtomicU64::new(RATE_LIMIT_UNKNOWN)),
+            release_cache: None,
+            max_retries: None,
+            enterprise_max_retries: None,
+        })
+    }
+
+    /*
+        Records rate limit information from the given response, and returns
+        a descriptive error if the rate limit has been exceeded, or any
+        other error if the response was otherwise not successful.
+    */
+    fn check_response(&self, response: Response) -> GithubResult<Response> {
+        let header_u64 = |name: &HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
         };
 
-        let client = create_client(headers)?;
+        // NOTE: GitHub Enterprise instances have their own rate limits, if any,
+        // which must not be mixed up with the rate limits for the public API
+        let is_public_api = response.url().host_str() == Some(BASE_HOST);
+        let remaining = header_u64(&RATE_LIMIT_REMAINING_HEADER);
+        let reset = header_u64(&RATE_LIMIT_RESET_HEADER);
+        if let Some(remaining) = remaining.filter(|_| is_public_api) {
+            self.rate_limit_remaining.store(remaining, Ordering::SeqCst);
+            self.rate_limit_reset
+                .store(reset.unwrap_or(RATE_LIMIT_UNKNOWN), Ordering::SeqCst);
+        }
+
+        let status = response.status();
+        let is_limited = matches!(
+            status,
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+        ) && remaining == Some(0);
+        if is_limited {
+            let reset_in = reset.and_then(|reset| {
+                let reset = UNIX_EPOCH + Duration::from_secs(reset);
+                reset.duration_since(SystemTime::now()).ok()
+            });
+            return Err(GithubError::RateLimitExceeded {
+                reset_in,
+                has_auth: self.token.is_some(),
+            });
+        }
+
+        Ok(response.error_for_status()?)
+    }
+
+    /*
+        Gets the base API URL for the given tool, which is either the
+        public GitHub API, or the API of a GitHub Enterprise Server instance.
+    */
+    fn api_base_url(tool_id: &ToolId) -> GithubResult<String> {
+        if tool_id.provider() != ArtifactProvider::GitHubEnterprise {
+            return Ok(BASE_URL.to_string());
+        }
+        let host = tool_id
+            .host()
+            .ok_or_else(|| GithubError::MissingHost(tool_id.clone().into()))?;
+        Ok(enterprise_api_url(host))
+    }
 
-        Ok(Self { client, has_auth })
+    fn token_for(&self, url: &str) -> Option<&HeaderValue> {
+        if is_public_url(url) {
+            return self.token.as_ref();
+        }
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        let host = match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+        self.enterprise_tokens.get(&normalize_host(&host))
+    }
+
+    fn get(&self, url: &str) -> RequestBuilder {
+        let mut request = self.client.get(url);
+        let max_retries = if is_public_url(url) {
+            self.max_retries
+        } else {
+            self.enterprise_max_retries
+        };
+        if let Some(max_retries) = max_retries {
+            request = request.with_extension(MaxRetries(max_retries));
+        }
+        match self.token_for(url) {
+            Some(token) => request.header(AUTHORIZATION, token.clone()),
+            None => request,
+        }
     }
 
     async fn get_json<T: DeserializeOwned>(&self, url: &str) -> GithubResult<T> {
         let response = self
-            .client
             .get(url)
             .header(ACCEPT, "application/vnd.github.v3+json")
             .send()
-            .await?
-            .error_for_status()?;
+            .await?;
+        let response = self.check_response(response)?;
         Ok(response.json().await?)
     }
 
-    async fn get_bytes(&self, url: &str) -> GithubResult<Vec<u8>> {
-        let response = self
-            .client
+    /*
+        Fetches release metadata, making a conditional request if a previously
+        cached response is given. Returns `None` if the cached response is
+        still valid, meaning the server responded with 304 Not Modified.
+    */
+    async fn get_release_json(
+        &self,
+        url: &str,
+        cached: Option<&CachedRelease>,
+    ) -> GithubResult<Option<CachedRelease>> {
+        let mut request = self
             .get(url)
-            .header(ACCEPT, HeaderValue::from_static("application/octet-stream"))
-            .send()
-            .await?
-            .error_for_status()?;
-        let bytes = response.bytes().await.map(|bytes| bytes.to_vec());
-        Ok(bytes?)
+            .header(ACCEPT, "application/vnd.github.v3+json");
+        if let Some(cached) = cached {
+            request = request.headers(cached.conditional_headers());
+        }
+
+        let response = self.check_response(request.send().await?)?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        let headers = response.headers().clone();
+        let body = response.json::<serde_json::Value>().await?;
+        Ok(Some(CachedRelease::new(url, &headers, body)))
+    }
+
+    /*
+        Creates a request for the contents of a release asset, using its API URL.
+
+        Assets in private repositories can only be downloaded through the API,
+        which requires both the token for the host and the `application/octet-stream`
+        accept header, otherwise the API responds with 404 or the asset metadata.
+        The API then redirects to the actual contents, and the token is
+        never forwarded to the redirected host, since it is a different one.
+    */
+    fn contents_request(&self, url: &str, range_start: Option<u64>) -> RequestBuilder {
+        let mut request = self
+            .get(url)
+            .header(ACCEPT, HeaderValue::from_static("application/octet-stream"));
+        if let Some(start) = range_start {
+            request = request.header(RANGE, format!("bytes={start}-"));
+        }
+        request
+    }
+
+    async fn get_contents(&self, url: &str, range_start: Option<u64>) -> GithubResult<Response> {
+        let response = self.contents_request(url, range_start).send().await?;
+        self.check_response(response)
     }
 
     /**
@@ -79,7 +274,7 @@ impl GithubProvider {
         - If the GitHub API client could not be created.
     */
     pub fn new() -> GithubResult<Self> {
-        Self::new_inner(None)
+        Self::new_inner(None, HashMap::new(), None, &[])
     }
 
     /**
@@ -94,7 +289,70 @@ impl GithubProvider {
     */
     pub fn new_authenticated(pat: impl AsRef<str>) -> GithubResult<Self> {
         let pat: String = pat.as_ref().trim().to_string();
-        Self::new_inner(Some(pat))
+        Self::new_inner(Some(pat), HashMap::new(), None, &[])
+    }
+
+    /**
+        Creates a new authenticated GitHub source instance with a
+        token for the given GitHub Enterprise Server host.
+
+        Note that this does not verify the formatting or validity of the token,
+        use the `verify_enterprise_authentication` method for checking with the API.
+
+        # Errors
+
+        - If the GitHub API client could not be created.
+    */
+    pub fn new_enterprise_authenticated(
+        host: impl AsRef<str>,
+        pat: impl AsRef<str>,
+    ) -> GithubResult<Self> {
+        let tokens = HashMap::from([(host.as_ref().to_string(), pat.as_ref().to_string())]);
+        Self::new_inner(None, tokens, None, &[])
+    }
+
+    /**
+        Enables caching of release metadata in the given directory.
+
+        See [`ArtifactSource::with_release_cache`](super::ArtifactSource::with_release_cache).
+    */
+    #[must_use]
+    pub fn with_release_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.release_cache = Some(ReleaseCache::new(dir));
+        self
+    }
+
+    /**
+        Sets the maximum number of retries for requests to either the
+        public GitHub API, or to GitHub Enterprise Server instances.
+    */
+    #[must_use]
+    pub(crate) fn with_max_retries(mut self, enterprise: bool, max_retries: u32) -> Self {
+        if enterprise {
+            self.enterprise_max_retries = Some(max_retries);
+        } else {
+            self.max_retries = Some(max_retries);
+        }
+        self
+    }
+
+    /**
+        Gets the rate limit information for the GitHub API, as last seen in a response.
+
+        Returns `None` if no requests have been made yet, or if
+        the GitHub API did not send any rate limit information.
+    */
+    #[must_use]
+    pub fn rate_limit(&self) -> Option<GithubRateLimit> {
+        let remaining = self.rate_limit_remaining.load(Ordering::SeqCst);
+        if remaining == RATE_LIMIT_UNKNOWN {
+            return None;
+        }
+        let reset = self.rate_limit_reset.load(Ordering::SeqCst);
+        Some(GithubRateLimit {
+            remaining,
+            reset: (reset != RATE_LIMIT_UNKNOWN).then(|| UNIX_EPOCH + Duration::from_secs(reset)),
+        })
     }
 
     /**
@@ -109,12 +367,37 @@ impl GithubProvider {
         - If the request to the GitHub API failed.
     */
     pub async fn verify_authentication(&self) -> GithubResult<bool> {
-        if !self.has_auth {
+        if self.token.is_none() {
             return Ok(false);
         }
+        self.verify_authentication_url(&format!("{BASE_URL}/rate_limit"))
+            .await
+    }
+
+    /**
+        Verifies that the current authentication token for the
+        given GitHub Enterprise Server host is valid.
+
+        Returns `true` if the token is valid, `false` if it is not.
 
-        let url = format!("{BASE_URL}/rate_limit");
-        let res = self.get_json::<serde_json::Value>(&url).await;
+        Always returns `false` if the source is not authenticated for the host.
+
+        # Errors
+
+        - If the request to the GitHub Enterprise API failed.
+    */
+    pub async fn verify_enterprise_authentication(&self, host: &str) -> GithubResult<bool> {
+        if !self.enterprise_tokens.contains_key(&normalize_host(host)) {
+            return Ok(false);
+        }
+        // NOTE: Rate limiting may be disabled on GitHub Enterprise, in which case
+        // the rate limit endpoint does not exist, so we check the user instead
+        self.verify_authentication_url(&format!("{}/user", enterprise_api_url(host)))
+            .await
+    }
+
+    async fn verify_authentication_url(&self, url: &str) -> GithubResult<bool> {
+        let res = self.get_json::<serde_json::Value>(url).await;
 
         match res {
             Ok(_) => Ok(true),
@@ -125,13 +408,26 @@ impl GithubProvider {
 
     /**
         Fetches the latest release for a given tool.
+
+        # Errors
+
+        - If the latest release could not be fetched.
     */
-    #[instrument(skip(self), fields(%tool_id), level = "debug")]
     pub async fn get_latest_release(&self, tool_id: &ToolId) -> GithubResult<Vec<Artifact>> {
+        Ok(self.get_latest_release_info(tool_id).await?.artifacts)
+    }
+
+    /**
+        Fetches the latest release for a given tool, including details
+        such as when it was published and its release notes.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_latest_release_info(&self, tool_id: &ToolId) -> GithubResult<ReleaseInfo> {
         debug!(id = %tool_id, "fetching latest release for tool");
 
         let url = format!(
-            "{BASE_URL}/repos/{owner}/{repo}/releases/latest",
+            "{base_url}/repos/{owner}/{repo}/releases/latest",
+            base_url = Self::api_base_url(tool_id)?,
             owner = tool_id.author(),
             repo = tool_id.name(),
         );
@@ -151,52 +447,283 @@ impl GithubProvider {
             .map_err(|e| GithubError::Other(e.to_string()))?;
 
         let tool_spec: ToolSpec = (tool_id.clone(), version).into();
-        Ok(artifacts_from_release(&release, &tool_spec))
+        let artifacts = artifacts_from_release(&release, &tool_spec);
+        Ok(ReleaseInfo::new(
+            tool_spec,
+            artifacts,
+            release.published_at,
+            release.body,
+        ))
     }
 
     /**
-        Fetches a specific release for a given tool.
+        Checks if the repository for a given tool exists, and can be accessed.
     */
-    #[instrument(skip(self), fields(%tool_spec), level = "debug")]
-    pub async fn get_specific_release(&self, tool_spec: &ToolSpec) -> GithubResult<Vec<Artifact>> {
-        debug!(spec = %tool_spec, "fetching release for tool");
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn repository_exists(&self, tool_id: &ToolId) -> GithubResult<bool> {
+        debug!(id = %tool_id, "checking if repository exists");
+        Ok(self.get_canonical_id(tool_id).await?.is_some())
+    }
 
-        let url_with_prefix = format!(
-            "{BASE_URL}/repos/{owner}/{repo}/releases/tags/v{tag}",
-            owner = tool_spec.author(),
-            repo = tool_spec.name(),
-            tag = tool_spec.version(),
-        );
-        let url_without_prefix = format!(
-            "{BASE_URL}/repos/{owner}/{repo}/releases/tags/{tag}",
-            owner = tool_spec.author(),
-            repo = tool_spec.name(),
-            tag = tool_spec.version(),
+    /**
+        Fetches the id of a given tool with the casing of its author
+        and name as on GitHub, or `None` if the repository does not exist.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_canonical_id(&self, tool_id: &ToolId) -> GithubResult<Option<ToolId>> {
+        let base_url = Self::api_base_url(tool_id)?;
+        let url = format!(
+            "{base_url}/repos/{owner}/{repo}",
+            owner = tool_id.author(),
+            repo = tool_id.name(),
         );
 
-        let release: Release = match self.get_json(&url_with_prefix).await {
-            Err(e) if is_404(&e) => match self.get_json(&url_without_prefix).await {
+        match self.get_json::<Repository>(&url).await {
+            Ok(repository) => Ok(Some(tool_id.with_casing_of(&repository.full_name))),
+            Err(e) if is_404(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+        Fetches all available release versions for a given tool, sorted in ascending order.
+
+        Releases with tags that are not valid versions are ignored, as are drafts, and
+        prereleases unless `include_prereleases` is `true`.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_available_versions(
+        &self,
+        tool_id: &ToolId,
+        include_prereleases: bool,
+    ) -> GithubResult<Vec<Version>> {
+        debug!(id = %tool_id, "fetching available versions for tool");
+
+        let base_url = Self::api_base_url(tool_id)?;
+        let mut versions = Vec::new();
+        for page in 1..=MAX_RELEASE_PAGES {
+            let url = format!(
+                "{base_url}/repos/{owner}/{repo}/releases?per_page={RELEASES_PER_PAGE}&page={page}",
+                owner = tool_id.author(),
+                repo = tool_id.name(),
+            );
+
+            let releases: Vec<Release> = match self.get_json(&url).await {
                 Err(e) if is_404(&e) => {
-                    return Err(GithubError::ReleaseNotFound(tool_spec.clone().into()));
+                    return Err(GithubError::LatestReleaseNotFound(tool_id.clone().into()));
                 }
                 Err(e) => return Err(e),
                 Ok(r) => r,
-            },
-            Err(e) => return Err(e),
-            Ok(r) => r,
+            };
+
+            let page_len = releases.len();
+            versions.extend(releases.into_iter().filter_map(|release| {
+                let version = release.tag_name.trim_start_matches('v').parse::<Version>();
+                let version = version.ok()?;
+                let is_prerelease = release.prerelease || !version.pre.is_empty();
+                if release.draft || (is_prerelease && !include_prereleases) {
+                    None
+                } else {
+                    Some(version)
+                }
+            }));
+
+            if page_len < RELEASES_PER_PAGE {
+                break;
+            }
+        }
+
+        versions.sort();
+        versions.dedup();
+        Ok(versions)
+    }
+
+    /**
+        Searches for repositories matching the given query, sorted by stars.
+
+        At most `limit` repositories are returned, up to a maximum of 100.
+    */
+    #[instrument(skip(self), level = "debug")]
+    pub async fn search_repositories(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> GithubResult<Vec<Repository>> {
+        debug!(query, "searching for repositories");
+
+        let url = format!(
+            "{BASE_URL}/search/repositories?q={query}&sort=stars&order=desc&per_page={limit}",
+            query = byte_serialize(query.as_bytes()).collect::<String>(),
+            limit = limit.clamp(1, MAX_SEARCH_RESULTS),
+        );
+
+        let search: RepositorySearch = self.get_json(&url).await?;
+        Ok(search.items)
+    }
+
+    /**
+        Gets a specific release for a given tool from the release cache,
+        without making any requests, even if the cached release is stale.
+
+        Returns `None` if the release is not cached, or caching is disabled.
+    */
+    pub async fn get_cached_release(&self, tool_spec: &ToolSpec) -> Option<Vec<Artifact>> {
+        let cache = self.release_cache.as_ref()?;
+        let release = cache.get(tool_spec).await?.release().ok()?;
+        Some(artifacts_from_release(&release, tool_spec))
+    }
+
+    /**
+        Fetches a specific release for a given tool.
+
+        # Errors
+
+        - If the release could not be fetched, or does not exist.
+    */
+    pub async fn get_specific_release(&self, tool_spec: &ToolSpec) -> GithubResult<Vec<Artifact>> {
+        self.get_specific_release_with_tag_prefix(tool_spec, None)
+            .await
+    }
+
+    /**
+        Fetches a specific release for a given tool, also trying
+        release tags with the given prefix, such as `cli-1.2.3`.
+
+        # Errors
+
+        - If the release could not be fetched, or does not exist.
+    */
+    #[instrument(skip(self), fields(%tool_spec), level = "debug")]
+    pub async fn get_specific_release_with_tag_prefix(
+        &self,
+        tool_spec: &ToolSpec,
+        tag_prefix: Option<&str>,
+    ) -> GithubResult<Vec<Artifact>> {
+        debug!(spec = %tool_spec, "fetching release for tool");
+
+        let base_url = Self::api_base_url(tool_spec.id())?;
+        let tags = release_tags(tool_spec.version(), tag_prefix);
+        let tag_url = |tag: &str| {
+            format!(
+                "{base_url}/repos/{owner}/{repo}/releases/tags/{tag}",
+                owner = tool_spec.author(),
+                repo = tool_spec.name(),
+                tag = byte_serialize(tag.as_bytes()).collect::<String>(),
+            )
         };
 
+        // Try to use cached release metadata first, refreshing it if it is stale
+        if let Some(cache) = &self.release_cache {
+            if let Some(mut cached) = cache.get(tool_spec).await {
+                if cached.is_fresh(cache.ttl()) {
+                    debug!(spec = %tool_spec, "using cached release");
+                    return Ok(artifacts_from_release(&cached.release()?, tool_spec));
+                }
+                match self
+                    .get_release_json(&cached.url.clone(), Some(&cached))
+                    .await
+                {
+                    Ok(None) => {
+                        debug!(spec = %tool_spec, "cached release was not modified");
+                        cached.touch();
+                        cache.put(tool_spec, &cached).await;
+                        return Ok(artifacts_from_release(&cached.release()?, tool_spec));
+                    }
+                    Ok(Some(fetched)) => {
+                        cache.put(tool_spec, &fetched).await;
+                        return Ok(artifacts_from_release(&fetched.release()?, tool_spec));
+                    }
+                    // The release may have been deleted, don't let it stick around
+                    Err(e) if is_404(&e) => cache.remove(tool_spec).await,
+                    // Stale metadata is better than nothing when offline - releases
+                    // are rarely modified, and artifacts are verified when downloaded
+                    Err(e) if is_connection_error(&e) => {
+                        debug!(spec = %tool_spec, "using stale cached release, network unavailable");
+                        return Ok(artifacts_from_release(&cached.release()?, tool_spec));
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        let mut fetched = None;
+        for tag in &tags {
+            match self.get_release_json(&tag_url(tag), None).await {
+                Err(e) if is_404(&e) => debug!(spec = %tool_spec, tag, "release tag not found"),
+                Err(e) => return Err(e),
+                Ok(r) => {
+                    fetched = Some(r.expect("unconditional requests are never 304 Not Modified"));
+                    break;
+                }
+            }
+        }
+        let Some(fetched) = fetched else {
+            return Err(GithubError::ReleaseNotFound {
+                tool_spec: tool_spec.clone().into(),
+                tags,
+            });
+        };
+
+        if let Some(cache) = &self.release_cache {
+            cache.put(tool_spec, &fetched).await;
+        }
+
+        let release = fetched.release()?;
         Ok(artifacts_from_release(&release, tool_spec))
     }
 
     /**
         Downloads the contents of the given artifact.
+
+        # Errors
+
+        - If the artifact contents could not be downloaded.
     */
-    #[instrument(skip(self, artifact), level = "debug")]
     pub async fn download_artifact_contents(&self, artifact: &Artifact) -> GithubResult<Vec<u8>> {
-        assert_eq!(
-            artifact.provider,
-            ArtifactProvider::GitHub,
+        self.download_artifact_contents_with_progress(artifact, |_, _| {})
+            .await
+    }
+
+    /**
+        Downloads the contents of the given artifact, reporting progress as it downloads.
+
+        See [`ArtifactSource::download_artifact_contents_with_progress`](super::ArtifactSource::download_artifact_contents_with_progress).
+
+        # Errors
+
+        - If the artifact contents could not be downloaded.
+    */
+    pub async fn download_artifact_contents_with_progress<F>(
+        &self,
+        artifact: &Artifact,
+        on_progress: F,
+    ) -> GithubResult<Vec<u8>>
+    where
+        F: Fn(u64, Option<u64>),
+    {
+        let response = self
+            .request_artifact_contents(artifact, None, &DownloadMirrors::default())
+            .await?;
+        Ok(read_body_with_progress(response, on_progress).await?)
+    }
+
+    /*
+        Sends the request for the contents of the given artifact, optionally
+        for only the contents starting at the given byte offset, which is
+        used for resuming interrupted downloads.
+    */
+    #[instrument(skip(self, artifact, mirrors), level = "debug")]
+    pub(crate) async fn request_artifact_contents(
+        &self,
+        artifact: &Artifact,
+        range_start: Option<u64>,
+        mirrors: &DownloadMirrors,
+    ) -> GithubResult<Response> {
+        assert!(
+            matches!(
+                artifact.provider,
+                ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise
+            ),
             "artifact must be from GitHub"
         );
 
@@ -205,15 +732,53 @@ impl GithubProvider {
         debug!(id, name, "downloading artifact contents");
 
         let url = format!(
-            "{BASE_URL}/repos/{owner}/{repo}/releases/assets/{id}",
+            "{base_url}/repos/{owner}/{repo}/releases/assets/{id}",
+            base_url = Self::api_base_url(artifact.tool_spec.id())?,
             owner = artifact.tool_spec.author(),
             repo = artifact.tool_spec.name(),
         );
-
-        self.get_bytes(&url).await
+        let url = mirrors.rewrite(&url);
+
+        self.get_contents(&url, range_start).await.map_err(|e| {
+            if is_404(&e) {
+                GithubError::AssetNotFound {
+                    name: name.clone(),
+                    tool_spec: artifact.tool_spec.clone().into(),
+                    has_auth: self.token_for(&url).is_some(),
+                }
+            } else {
+                e
+            }
+        })
     }
 }
 
+fn enterprise_api_url(host: &str) -> String {
+    format!("https://{}/api/v3", host.trim().trim_end_matches('/'))
+}
+
+// Checks if the given url points to github.com, and not to
+// a GitHub Enterprise Server instance or some other host
+fn is_public_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| {
+        url.host_str()
+            .is_some_and(|host| host.eq_ignore_ascii_case(BASE_HOST))
+            && url.port().is_none()
+    })
+}
+
+fn is_connection_error(err: &GithubError) -> bool {
+    let reqwest_err = match err {
+        GithubError::Reqwest(e) => e.as_ref(),
+        GithubError::ReqwestMiddleware(e) => match e.as_ref() {
+            reqwest_middleware::Error::Reqwest(e) => e,
+            reqwest_middleware::Error::Middleware(_) => return false,
+        },
+        _ => return false,
+    };
+    reqwest_err.is_connect()
+}
+
 fn is_404(err: &GithubError) -> bool {
     if let GithubError::Reqwest(reqwest_err) = err {
         if let Some(status) = reqwest_err.status() {
@@ -232,6 +797,12 @@ fn is_unauthenticated(err: &GithubError) -> bool {
     false
 }
 
+impl CachedRelease {
+    fn release(&self) -> GithubResult<Release> {
+        serde_json::from_value(self.body.clone()).map_err(|e| GithubError::Other(e.to_string()))
+    }
+}
+
 fn artifacts_from_release(release: &Release, spec: &ToolSpec) -> Vec<Artifact> {
     release
         .assets
@@ -239,3 +810,65 @@ fn artifacts_from_release(release: &Release, spec: &ToolSpec) -> Vec<Artifact> {
         .map(|asset| Artifact::from_github_release_asset(asset, spec))
         .collect::<Vec<_>>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_scoped_to_hosts() {
+        let tokens = HashMap::from([(String::from("GitHub.MyCorp.com"), String::from("def"))]);
+        let provider =
+            GithubProvider::new_inner(Some(String::from("abc")), tokens, None, &[]).unwrap();
+        let token = |url| provider.token_for(url).cloned();
+        assert_eq!(
+            token("https://api.github.com/rate_limit"),
+            provider.token.clone()
+        );
+        assert_eq!(
+            token("https://github.mycorp.com/api/v3/user"),
+            provider.enterprise_tokens.get("github.mycorp.com").cloned()
+        );
+        assert_ne!(
+            token("https://api.github.com/rate_limit"),
+            token("https://github.mycorp.com/api/v3/user")
+        );
+        assert!(token("https://api.github.com.example.com/file.zip").is_none());
+        assert!(token("https://example.com/file.zip").is_none());
+    }
+
+    #[test]
+    fn contents_requests_are_authenticated() {
+        let url = "https://api.github.com/repos/a/b/releases/assets/1";
+        let header = |provider: &GithubProvider, name| {
+            let request = provider.contents_request(url, Some(5)).build().unwrap();
+            request.headers().get(name).cloned()
+        };
+
+        let provider = GithubProvider::new_authenticated("abc").unwrap();
+        assert_eq!(header(&provider, AUTHORIZATION), provider.token.clone());
+        assert_eq!(
+            header(&provider, ACCEPT).unwrap(),
+            "application/octet-stream"
+        );
+        assert_eq!(header(&provider, RANGE).unwrap(), "bytes=5-");
+
+        let provider = GithubProvider::new().unwrap();
+        assert!(header(&provider, AUTHORIZATION).is_none());
+        assert_eq!(
+            header(&provider, ACCEPT).unwrap(),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn enterprise_api_urls() {
+        let id = "ghe:github.mycorp.com/a/b".parse::<ToolId>().unwrap();
+        assert_eq!(
+            GithubProvider::api_base_url(&id).unwrap(),
+            "https://github.mycorp.com/api/v3"
+        );
+        let id = "a/b".parse::<ToolId>().unwrap();
+        assert_eq!(GithubProvider::api_base_url(&id).unwrap(), BASE_URL);
+    }
+}
@@ -6,6 +6,12 @@ pub struct Release {
     pub assets: Vec<Asset>,
     pub tag_name: String,
     pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -13,4 +19,21 @@ pub struct Asset {
     pub id: u64,
     pub url: Url,
     pub name: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Repository {
+    pub full_name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub stargazers_count: u64,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepositorySearch {
+    pub items: Vec<Repository>,
 }
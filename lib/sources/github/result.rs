@@ -1,16 +1,37 @@
+use std::time::Duration;
+
 use reqwest::{header::InvalidHeaderValue, Error as ReqwestError};
 use thiserror::Error;
 
-use crate::tool::{ToolId, ToolSpec};
+use crate::{
+    sources::format_release_not_found,
+    tool::{ToolId, ToolSpec},
+};
 
 #[derive(Debug, Error)]
 pub enum GithubError {
     #[error("unrecognized access token format - must begin with `ghp_` or `gho_`.")]
     UnrecognizedAccessToken,
+    #[error("{}", format_rate_limit_exceeded(*.reset_in, *.has_auth))]
+    RateLimitExceeded {
+        reset_in: Option<Duration>,
+        has_auth: bool,
+    },
+    #[error("tool '{0}' is missing the host of the GitHub Enterprise instance to use")]
+    MissingHost(Box<ToolId>),
     #[error("no latest release was found for tool '{0}'")]
     LatestReleaseNotFound(Box<ToolId>),
-    #[error("no release was found for tool '{0}'")]
-    ReleaseNotFound(Box<ToolSpec>),
+    #[error("{}", format_release_not_found(tool_spec, tags))]
+    ReleaseNotFound {
+        tool_spec: Box<ToolSpec>,
+        tags: Vec<String>,
+    },
+    #[error("{}", format_asset_not_found(name, tool_spec, *.has_auth))]
+    AssetNotFound {
+        name: String,
+        tool_spec: Box<ToolSpec>,
+        has_auth: bool,
+    },
     #[error("failed to build client - invalid header value: {0}")]
     ReqwestHeader(Box<InvalidHeaderValue>),
     #[error("reqwest middleware error: {0}")]
@@ -23,6 +44,43 @@ pub enum GithubError {
 
 pub type GithubResult<T> = Result<T, GithubError>;
 
+fn format_rate_limit_exceeded(reset_in: Option<Duration>, has_auth: bool) -> String {
+    let mut lines = vec![String::from("GitHub API rate limit exceeded.")];
+    if let Some(reset_in) = reset_in {
+        let minutes = reset_in.as_secs().div_ceil(60);
+        let s = if minutes == 1 { "" } else { "s" };
+        lines.push(format!("The rate limit resets in {minutes} minute{s}."));
+    }
+    if !has_auth {
+        lines.push(String::from(
+            "Authenticating with GitHub will raise the rate limit, to do so, run:\
+            \n`rokit authenticate github --token YOUR_TOKEN_HERE`\
+            \nor set the `GITHUB_TOKEN` environment variable.",
+        ));
+    }
+    lines.join("\n")
+}
+
+fn format_asset_not_found(name: &str, tool_spec: &ToolSpec, has_auth: bool) -> String {
+    let mut lines = vec![format!(
+        "artifact '{name}' for tool '{tool_spec}' was not found."
+    )];
+    if has_auth {
+        lines.push(String::from(
+            "If the repository is private, make sure that the token \
+            used to authenticate with GitHub has access to it.",
+        ));
+    } else {
+        lines.push(String::from(
+            "Artifacts in private repositories can only be downloaded when \
+            authenticated with GitHub, to do so, run:\
+            \n`rokit authenticate github --token YOUR_TOKEN_HERE`\
+            \nor set the `GITHUB_TOKEN` environment variable.",
+        ));
+    }
+    lines.join("\n")
+}
+
 // FUTURE: Figure out some way to reduce this boxing boilerplate
 
 impl From<InvalidHeaderValue> for GithubError {
@@ -0,0 +1,619 @@
+use std::env::var;
+
+use reqwest_middleware::{ClientWithMiddleware, RequestBuilder};
+use semver::Version;
+use serde::de::DeserializeOwned;
+use tracing::{debug, instrument};
+use url::{form_urlencoded::byte_serialize, Url};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, RANGE},
+    Certificate, Response, StatusCode,
+};
+
+use crate::tool::{ToolId, ToolSpec};
+
+use super::{
+    client::{create_client, read_body_with_progress},
+    mirrors::DownloadMirrors,
+    release_tags,
+    retry::MaxRetries,
+    Artifact, ArtifactProvider, ReleaseInfo,
+};
+
+const DEFAULT_BASE_URL: &str = "https://gitlab.com";
+const BASE_URL_ENV_VAR: &str = "ROKIT_GITLAB_URL";
+const NEXT_PAGE_HEADER: HeaderName = HeaderName::from_static("x-next-page");
+const LINKS_PER_PAGE: usize = 100;
+const RELEASES_PER_PAGE: usize = 100;
+const MAX_RELEASE_PAGES: usize = 10;
+
+pub mod models;
+mod result;
+
+use self::models::{AssetLink, Release};
+
+pub use self::result::{GitlabError, GitlabResult};
+
+#[derive(Debug, Clone)]
+pub struct GitlabProvider {
+    client: ClientWithMiddleware,
+    base_url: Url,
+    token: Option<HeaderValue>,
+    max_retries: Option<u32>,
+}
+
+impl GitlabProvider {
+    pub(crate) fn new_inner(
+        pat: Option<String>,
+        proxy: Option<&Url>,
+        root_certificates: &[Certificate],
+    ) -> GitlabResult<Self> {
+        let base_url = base_url_from_env()?;
+
+        let token = match pat {
+            Some(pat) => {
+                // NOTE: GitLab also accepts tokens in a custom `PRIVATE-TOKEN` header,
+                // but only the standard `Authorization` header is dropped when a
+                // download redirects to external storage on a different host
+                let mut value = HeaderValue::from_str(&format!("Bearer {pat}"))?;
+                value.set_sensitive(true);
+                Some(value)
+            }
+            None => None,
+        };
+
+        // NOTE: The token is intentionally not added as a default header here,
+        // asset links may point to any host and we must only ever send the
+        // token to the GitLab instance that it was created for.
+        let client = create_client(HeaderMap::new(), proxy, root_certificates)?;
+
+        Ok(Self {
+            client,
+            base_url,
+            token,
+            max_retries: None,
+        })
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!(
+            "{}/api/v4/{}",
+            self.base_url.as_str().trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
+    fn project_url(&self, tool_id: &ToolId, path: &str) -> String {
+        let project = format!("{}/{}", tool_id.author(), tool_id.name());
+        let project = byte_serialize(project.as_bytes()).collect::<String>();
+        self.api_url(&format!(
+            "projects/{project}/{}",
+            path.trim_start_matches('/')
+        ))
+    }
+
+    fn is_same_host(&self, url: &str) -> bool {
+        Url::parse(url).is_ok_and(|url| {
+            url.scheme() == self.base_url.scheme()
+                && url.host_str() == self.base_url.host_str()
+                && url.port_or_known_default() == self.base_url.port_or_known_default()
+        })
+    }
+
+    /**
+        Sets the maximum number of retries for requests to this provider.
+    */
+    #[must_use]
+    pub(crate) fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    fn request(&self, url: &str, accept: &'static str) -> RequestBuilder {
+        let mut request = self
+            .client
+            .get(url)
+            .header(ACCEPT, HeaderValue::from_static(accept));
+        if let Some(max_retries) = self.max_retries {
+            request = request.with_extension(MaxRetries(max_retries));
+        }
+        if let Some(token) = &self.token {
+            if self.is_same_host(url) {
+                request = request.header(AUTHORIZATION, token.clone());
+            }
+        }
+        request
+    }
+
+    async fn get(&self, url: &str, accept: &'static str) -> GitlabResult<Response> {
+        Ok(self.request(url, accept).send().await?.error_for_status()?)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> GitlabResult<T> {
+        let response = self.get(url, "application/json").await?;
+        Ok(response.json().await?)
+    }
+
+    async fn get_contents(&self, url: &str, range_start: Option<u64>) -> GitlabResult<Response> {
+        let mut request = self.request(url, "application/octet-stream");
+        if let Some(start) = range_start {
+            request = request.header(RANGE, format!("bytes={start}-"));
+        }
+        Ok(request.send().await?.error_for_status()?)
+    }
+
+    /**
+        Fetches all asset links for a release, following pagination until exhausted.
+    */
+    async fn get_asset_links(&self, tool_id: &ToolId, tag: &str) -> GitlabResult<Vec<AssetLink>> {
+        let tag = byte_serialize(tag.as_bytes()).collect::<String>();
+
+        let mut links = Vec::new();
+        let mut page = String::from("1");
+        loop {
+            let url = self.project_url(
+                tool_id,
+                &format!("releases/{tag}/assets/links?per_page={LINKS_PER_PAGE}&page={page}"),
+            );
+
+            let response = self.get(&url, "application/json").await?;
+            let headers = response.headers().clone();
+
+            let page_links: Vec<AssetLink> = response.json().await?;
+            let page_len = page_links.len();
+            links.extend(page_links);
+
+            match next_page(&headers, &page, page_len) {
+                Some(next) => page = next,
+                None => break,
+            }
+        }
+
+        Ok(links)
+    }
+
+    /**
+        Creates a new GitLab source instance.
+
+        Uses `https://gitlab.com` as the GitLab instance, unless
+        the `ROKIT_GITLAB_URL` environment variable is set.
+
+        # Errors
+
+        - If the GitLab API client could not be created.
+        - If the GitLab instance url is invalid.
+    */
+    pub fn new() -> GitlabResult<Self> {
+        Self::new_inner(None, None, &[])
+    }
+
+    /**
+        Creates a new authenticated GitLab source instance with a token.
+
+        Note that this does not verify the formatting or validity of the token,
+        use the `verify_authentication` method for checking with the GitLab API.
+
+        # Errors
+
+        - If the GitLab API client could not be created.
+        - If the GitLab instance url is invalid.
+    */
+    pub fn new_authenticated(pat: impl AsRef<str>) -> GitlabResult<Self> {
+        let pat: String = pat.as_ref().trim().to_string();
+        Self::new_inner(Some(pat), None, &[])
+    }
+
+    /**
+        Verifies that the current authentication token is valid.
+
+        Returns `true` if the token is valid, `false` if it is not.
+
+        Always returns `false` if the source is not authenticated.
+
+        # Errors
+
+        - If the request to the GitLab API failed.
+    */
+    pub async fn verify_authentication(&self) -> GitlabResult<bool> {
+        if self.token.is_none() {
+            return Ok(false);
+        }
+
+        let url = self.api_url("user");
+        let res = self.get_json::<serde_json::Value>(&url).await;
+
+        match res {
+            Ok(_) => Ok(true),
+            Err(e) if is_unauthenticated(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+        Fetches the latest release for a given tool.
+
+        # Errors
+
+        - If the latest release could not be fetched.
+    */
+    pub async fn get_latest_release(&self, tool_id: &ToolId) -> GitlabResult<Vec<Artifact>> {
+        Ok(self.get_latest_release_info(tool_id).await?.artifacts)
+    }
+
+    /**
+        Fetches the latest release for a given tool, including details
+        such as when it was published and its release notes.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_latest_release_info(&self, tool_id: &ToolId) -> GitlabResult<ReleaseInfo> {
+        debug!(id = %tool_id, "fetching latest release for tool");
+
+        let url = self.project_url(tool_id, "releases/permalink/latest");
+
+        let release: Release = match self.get_json(&url).await {
+            Err(e) if is_404(&e) => {
+                return Err(GitlabError::LatestReleaseNotFound(tool_id.clone().into()));
+            }
+            Err(e) => return Err(e),
+            Ok(r) => r,
+        };
+
+        let version = release
+            .tag_name
+            .trim_start_matches('v')
+            .parse::<Version>()
+            .map_err(|e| GitlabError::Other(e.to_string()))?;
+
+        let tool_spec: ToolSpec = (tool_id.clone(), version).into();
+        let links = self.get_asset_links(tool_id, &release.tag_name).await?;
+        let artifacts = artifacts_from_links(&links, &tool_spec);
+        Ok(ReleaseInfo::new(
+            tool_spec,
+            artifacts,
+            release.released_at,
+            release.description,
+        ))
+    }
+
+    /**
+        Fetches all available release versions for a given tool, sorted in ascending order.
+
+        Releases with tags that are not valid versions are ignored, as are
+        upcoming releases and prereleases, unless `include_prereleases` is `true`.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_available_versions(
+        &self,
+        tool_id: &ToolId,
+        include_prereleases: bool,
+    ) -> GitlabResult<Vec<Version>> {
+        debug!(id = %tool_id, "fetching available versions for tool");
+
+        let mut versions = Vec::new();
+        for page in 1..=MAX_RELEASE_PAGES {
+            let url = self.project_url(
+                tool_id,
+                &format!("releases?per_page={RELEASES_PER_PAGE}&page={page}"),
+            );
+
+            let releases: Vec<Release> = match self.get_json(&url).await {
+                Err(e) if is_404(&e) => {
+                    return Err(GitlabError::LatestReleaseNotFound(tool_id.clone().into()));
+                }
+                Err(e) => return Err(e),
+                Ok(r) => r,
+            };
+
+            let page_len = releases.len();
+            versions.extend(releases.into_iter().filter_map(|release| {
+                let version = release.tag_name.trim_start_matches('v').parse::<Version>();
+                let version = version.ok()?;
+                let is_prerelease = release.upcoming_release || !version.pre.is_empty();
+                if is_prerelease && !include_prereleases {
+                    None
+                } else {
+                    Some(version)
+                }
+            }));
+
+            if page_len < RELEASES_PER_PAGE {
+                break;
+            }
+        }
+
+        versions.sort();
+        versions.dedup();
+        Ok(versions)
+    }
+
+    /**
+        Checks if the project for a given tool exists, and can be accessed.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn repository_exists(&self, tool_id: &ToolId) -> GitlabResult<bool> {
+        debug!(id = %tool_id, "checking if project exists");
+        Ok(self.get_canonical_id(tool_id).await?.is_some())
+    }
+
+    /**
+        Fetches the id of a given tool with the casing of its author
+        and name as on GitLab, or `None` if the project does not exist.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_canonical_id(&self, tool_id: &ToolId) -> GitlabResult<Option<ToolId>> {
+        let project = format!("{}/{}", tool_id.author(), tool_id.name());
+        let project = byte_serialize(project.as_bytes()).collect::<String>();
+        let url = self.api_url(&format!("projects/{project}"));
+
+        match self.get_json::<serde_json::Value>(&url).await {
+            Ok(project) => Ok(Some(
+                match project.get("path_with_namespace").and_then(|p| p.as_str()) {
+                    Some(full_name) => tool_id.with_casing_of(full_name),
+                    None => tool_id.clone(),
+                },
+            )),
+            Err(e) if is_404(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+        Fetches a specific release for a given tool.
+
+        # Errors
+
+        - If the release could not be fetched, or does not exist.
+    */
+    pub async fn get_specific_release(&self, tool_spec: &ToolSpec) -> GitlabResult<Vec<Artifact>> {
+        self.get_specific_release_with_tag_prefix(tool_spec, None)
+            .await
+    }
+
+    /**
+        Fetches a specific release for a given tool, also trying
+        release tags with the given prefix, such as `cli-1.2.3`.
+
+        # Errors
+
+        - If the release could not be fetched, or does not exist.
+    */
+    #[instrument(skip(self), fields(%tool_spec), level = "debug")]
+    pub async fn get_specific_release_with_tag_prefix(
+        &self,
+        tool_spec: &ToolSpec,
+        tag_prefix: Option<&str>,
+    ) -> GitlabResult<Vec<Artifact>> {
+        debug!(spec = %tool_spec, "fetching release for tool");
+
+        let tool_id = tool_spec.id();
+        let tags = release_tags(tool_spec.version(), tag_prefix);
+        let tag_url = |tag: &str| {
+            let tag = byte_serialize(tag.as_bytes()).collect::<String>();
+            self.project_url(tool_id, &format!("releases/{tag}"))
+        };
+
+        let mut found = None;
+        for tag in &tags {
+            match self.get_json::<Release>(&tag_url(tag)).await {
+                Err(e) if is_404(&e) => debug!(spec = %tool_spec, tag, "release tag not found"),
+                Err(e) => return Err(e),
+                Ok(r) => {
+                    found = Some(r);
+                    break;
+                }
+            }
+        }
+        let Some(release) = found else {
+            return Err(GitlabError::ReleaseNotFound {
+                tool_spec: tool_spec.clone().into(),
+                tags,
+            });
+        };
+
+        let links = self.get_asset_links(tool_id, &release.tag_name).await?;
+        Ok(artifacts_from_links(&links, tool_spec))
+    }
+
+    /**
+        Downloads the contents of the given artifact.
+
+        # Errors
+
+        - If the artifact contents could not be downloaded.
+    */
+    pub async fn download_artifact_contents(&self, artifact: &Artifact) -> GitlabResult<Vec<u8>> {
+        self.download_artifact_contents_with_progress(artifact, |_, _| {})
+            .await
+    }
+
+    /**
+        Downloads the contents of the given artifact, reporting progress as it downloads.
+
+        See [`ArtifactSource::download_artifact_contents_with_progress`](super::ArtifactSource::download_artifact_contents_with_progress).
+
+        # Errors
+
+        - If the artifact contents could not be downloaded.
+    */
+    pub async fn download_artifact_contents_with_progress<F>(
+        &self,
+        artifact: &Artifact,
+        on_progress: F,
+    ) -> GitlabResult<Vec<u8>>
+    where
+        F: Fn(u64, Option<u64>),
+    {
+        let response = self
+            .request_artifact_contents(artifact, None, &DownloadMirrors::default())
+            .await?;
+        Ok(read_body_with_progress(response, on_progress).await?)
+    }
+
+    /*
+        Sends the request for the contents of the given artifact, optionally
+        for only the contents starting at the given byte offset, which is
+        used for resuming interrupted downloads.
+    */
+    #[instrument(skip(self, artifact, mirrors), level = "debug")]
+    pub(crate) async fn request_artifact_contents(
+        &self,
+        artifact: &Artifact,
+        range_start: Option<u64>,
+        mirrors: &DownloadMirrors,
+    ) -> GitlabResult<Response> {
+        assert_eq!(
+            artifact.provider,
+            ArtifactProvider::GitLab,
+            "artifact must be from GitLab"
+        );
+
+        let id = artifact.id.as_ref().expect("GitLab artifacts have ids");
+        let name = artifact.name.as_ref().expect("GitLab artifacts have names");
+        let url = artifact.url.as_ref().expect("GitLab artifacts have urls");
+        debug!(id, name, "downloading artifact contents");
+
+        let url = mirrors.rewrite(url.as_str());
+
+        self.get_contents(&url, range_start).await
+    }
+}
+
+fn base_url_from_env() -> GitlabResult<Url> {
+    let base = var(BASE_URL_ENV_VAR)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+    Url::parse(&base).map_err(|e| GitlabError::InvalidBaseUrl(base, e))
+}
+
+fn is_404(err: &GitlabError) -> bool {
+    if let GitlabError::Reqwest(reqwest_err) = err {
+        if let Some(status) = reqwest_err.status() {
+            return status == StatusCode::NOT_FOUND;
+        }
+    }
+    false
+}
+
+fn is_unauthenticated(err: &GitlabError) -> bool {
+    if let GitlabError::Reqwest(reqwest_err) = err {
+        if let Some(status) = reqwest_err.status() {
+            return matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN);
+        }
+    }
+    false
+}
+
+/*
+    Gets the next page of a paginated response to fetch, from its headers,
+    stopping once a page is empty, or the next page would be the same page.
+*/
+fn next_page(headers: &HeaderMap, page: &str, page_len: usize) -> Option<String> {
+    let next = headers
+        .get(NEXT_PAGE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())?;
+    if page_len > 0 && next != page {
+        Some(next.to_string())
+    } else {
+        None
+    }
+}
+
+fn artifacts_from_links(links: &[AssetLink], spec: &ToolSpec) -> Vec<Artifact> {
+    links
+        .iter()
+        .map(|link| Artifact::from_gitlab_release_link(link, spec))
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_scoped_to_host() {
+        let provider = GitlabProvider::new_authenticated("abc").unwrap();
+        let header = |url| {
+            let request = provider.request(url, "application/json").build().unwrap();
+            request.headers().get(AUTHORIZATION).cloned()
+        };
+        let url = provider.api_url("user");
+        assert_eq!(header(&url), provider.token.clone());
+        assert_eq!(provider.token.clone().unwrap(), "Bearer abc");
+        assert!(header("https://example.com/file.zip").is_none());
+    }
+
+    #[test]
+    fn tokens_require_same_scheme_and_port() {
+        let provider = GitlabProvider::new_authenticated("abc").unwrap();
+        assert!(provider.is_same_host("https://gitlab.com/api/v4/user"));
+        assert!(provider.is_same_host("https://GitLab.com:443/api/v4/user"));
+        assert!(!provider.is_same_host("http://gitlab.com/api/v4/user"));
+        assert!(!provider.is_same_host("https://gitlab.com:8443/api/v4/user"));
+        assert!(!provider.is_same_host("https://gitlab.com.example.com/file.zip"));
+        assert!(!provider.is_same_host("not a url"));
+    }
+
+    #[test]
+    fn pagination_follows_next_page_header() {
+        let headers = |next: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(NEXT_PAGE_HEADER, HeaderValue::from_static(next));
+            headers
+        };
+        assert_eq!(next_page(&headers("2"), "1", 100), Some(String::from("2")));
+        assert_eq!(next_page(&headers(" 3 "), "2", 1), Some(String::from("3")));
+        // The last page has an empty header, or no header at all
+        assert_eq!(next_page(&headers(""), "3", 100), None);
+        assert_eq!(next_page(&HeaderMap::new(), "1", 100), None);
+        // Empty or repeated pages must never loop forever
+        assert_eq!(next_page(&headers("2"), "1", 0), None);
+        assert_eq!(next_page(&headers("2"), "2", 100), None);
+    }
+
+    #[test]
+    fn asset_links_map_to_artifacts() {
+        let links: Vec<AssetLink> = serde_json::from_str(
+            r#"[
+                {
+                    "id": 1,
+                    "name": "tool-linux-x86_64.tar.gz",
+                    "url": "https://example.com/tool-linux-x86_64.tar.gz",
+                    "direct_asset_url": "https://gitlab.com/a/b/-/releases/v1.0.0/downloads/tool.tar.gz"
+                },
+                {
+                    "id": 2,
+                    "name": "tool-windows-x86_64.zip",
+                    "url": "https://example.com/tool-windows-x86_64.zip"
+                }
+            ]"#,
+        )
+        .unwrap();
+        let spec = "gitlab:a/b@1.0.0".parse::<ToolSpec>().unwrap();
+        let artifacts = artifacts_from_links(&links, &spec);
+        assert_eq!(artifacts.len(), 2);
+
+        let first = &artifacts[0];
+        assert_eq!(first.provider, ArtifactProvider::GitLab);
+        assert_eq!(first.id.as_deref(), Some("1"));
+        assert_eq!(first.name.as_deref(), Some("tool-linux-x86_64"));
+        assert_eq!(
+            first.url.as_ref().unwrap().as_str(),
+            "https://gitlab.com/a/b/-/releases/v1.0.0/downloads/tool.tar.gz",
+            "direct asset urls are preferred"
+        );
+        assert_eq!(first.tool_spec, spec);
+
+        let second = &artifacts[1];
+        assert_eq!(second.id.as_deref(), Some("2"));
+        assert_eq!(second.name.as_deref(), Some("tool-windows-x86_64"));
+        assert_eq!(
+            second.url.as_ref().unwrap().as_str(),
+            "https://example.com/tool-windows-x86_64.zip"
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use serde::Deserialize;
+use url::Url;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub upcoming_release: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub released_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetLink {
+    pub id: u64,
+    pub name: String,
+    pub url: Url,
+    pub direct_asset_url: Option<Url>,
+}
+
+impl AssetLink {
+    /**
+        Gets the URL that should be used to download the contents of this asset link.
+
+        Prefers the direct asset URL when one is available, since it
+        always points at the GitLab instance that the release lives on.
+    */
+    #[must_use]
+    pub fn download_url(&self) -> &Url {
+        self.direct_asset_url.as_ref().unwrap_or(&self.url)
+    }
+}
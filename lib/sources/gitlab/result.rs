@@ -0,0 +1,51 @@
+use reqwest::{header::InvalidHeaderValue, Error as ReqwestError};
+use thiserror::Error;
+use url::ParseError as UrlParseError;
+
+use crate::{
+    sources::format_release_not_found,
+    tool::{ToolId, ToolSpec},
+};
+
+#[derive(Debug, Error)]
+pub enum GitlabError {
+    #[error("invalid GitLab base url '{0}': {1}")]
+    InvalidBaseUrl(String, UrlParseError),
+    #[error("no latest release was found for tool '{0}'")]
+    LatestReleaseNotFound(Box<ToolId>),
+    #[error("{}", format_release_not_found(tool_spec, tags))]
+    ReleaseNotFound {
+        tool_spec: Box<ToolSpec>,
+        tags: Vec<String>,
+    },
+    #[error("failed to build client - invalid header value: {0}")]
+    ReqwestHeader(Box<InvalidHeaderValue>),
+    #[error("reqwest middleware error: {0}")]
+    ReqwestMiddleware(Box<reqwest_middleware::Error>),
+    #[error("reqwest error: {0}")]
+    Reqwest(Box<reqwest::Error>),
+    #[error("other error: {0}")]
+    Other(String),
+}
+
+pub type GitlabResult<T> = Result<T, GitlabError>;
+
+// FUTURE: Figure out some way to reduce this boxing boilerplate
+
+impl From<InvalidHeaderValue> for GitlabError {
+    fn from(err: InvalidHeaderValue) -> Self {
+        GitlabError::ReqwestHeader(err.into())
+    }
+}
+
+impl From<reqwest_middleware::Error> for GitlabError {
+    fn from(err: reqwest_middleware::Error) -> Self {
+        GitlabError::ReqwestMiddleware(err.into())
+    }
+}
+
+impl From<ReqwestError> for GitlabError {
+    fn from(err: ReqwestError) -> Self {
+        GitlabError::Reqwest(err.into())
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use tracing::debug;
+use url::Url;
+
+use crate::util::str::normalize_host;
+
+/**
+    Rules for downloading artifacts from mirrors, instead of from
+    the hosts that their providers would normally download them from.
+
+    Rules are keyed by the exact host to rewrite, such as `api.github.com`,
+    and map to the base URL of the mirror, such as `https://mirror.corp.net`.
+    The path of the original URL is appended to the path of the mirror, so a
+    mirror such as `https://mirror.corp.net/github` is also supported.
+
+    Only artifact downloads are rewritten - release metadata is
+    still fetched from the providers, or from the release cache.
+*/
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DownloadMirrors {
+    rules: BTreeMap<String, Url>,
+}
+
+impl DownloadMirrors {
+    pub(crate) fn new(rules: &BTreeMap<String, Url>) -> Self {
+        let rules = rules
+            .iter()
+            .map(|(host, mirror)| (normalize_host(host), mirror.clone()))
+            .collect();
+        Self { rules }
+    }
+
+    /**
+        Rewrites the given download URL to point at its mirror, if there is
+        a rule for its host, returning the URL unchanged otherwise.
+
+        Every rewrite is logged, so that surprising redirects are visible.
+    */
+    pub(crate) fn rewrite(&self, url: &str) -> String {
+        let Ok(parsed) = Url::parse(url) else {
+            return url.to_string();
+        };
+        let Some(host) = parsed.host_str() else {
+            return url.to_string();
+        };
+        let Some(mirror) = self.rules.get(&normalize_host(host)) else {
+            return url.to_string();
+        };
+
+        let mut rewritten = mirror.clone();
+        rewritten.set_path(&format!(
+            "{}{}",
+            mirror.path().trim_end_matches('/'),
+            parsed.path()
+        ));
+        rewritten.set_query(parsed.query());
+        debug!(from = %url, to = %rewritten, "rewriting download host {host} to mirror");
+        rewritten.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirrors(rules: &[(&str, &str)]) -> DownloadMirrors {
+        let rules = rules
+            .iter()
+            .map(|(host, mirror)| (host.to_string(), Url::parse(mirror).unwrap()))
+            .collect();
+        DownloadMirrors::new(&rules)
+    }
+
+    #[test]
+    fn rewrites_matching_hosts() {
+        let mirrors = mirrors(&[("API.github.com", "https://mirror.corp.net")]);
+        assert_eq!(
+            mirrors.rewrite("https://api.github.com/repos/a/b/releases/assets/1"),
+            "https://mirror.corp.net/repos/a/b/releases/assets/1"
+        );
+        assert_eq!(
+            mirrors.rewrite("https://github.com/a/b/releases/download/v1/b.zip"),
+            "https://github.com/a/b/releases/download/v1/b.zip"
+        );
+    }
+
+    #[test]
+    fn keeps_mirror_paths_and_queries() {
+        let mirrors = mirrors(&[("gitlab.com", "https://mirror.corp.net:8443/gitlab/")]);
+        assert_eq!(
+            mirrors.rewrite("https://gitlab.com/api/v4/file?ref=main"),
+            "https://mirror.corp.net:8443/gitlab/api/v4/file?ref=main"
+        );
+    }
+}
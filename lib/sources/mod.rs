@@ -1,11 +1,30 @@
 mod artifact;
+mod artifact_cache;
+mod certificates;
 mod client;
 mod decompression;
+mod download;
 mod extraction;
+mod mirrors;
+mod release_cache;
+mod release_info;
+mod retry;
+mod search;
 mod source;
+mod tags;
 
+pub mod gitea;
 pub mod github;
+pub mod gitlab;
 
-pub use self::artifact::{Artifact, ArtifactFormat, ArtifactProvider};
+pub use self::artifact::{
+    Artifact, ArtifactFormat, ArtifactPattern, ArtifactPatternError, ArtifactProvider, KeyId,
+    MinisignPublicKey, SignatureError,
+};
+pub use self::artifact_cache::{ArtifactCache, ArtifactCacheCleanup};
+pub(crate) use self::certificates::{ca_bundle_from_env, load_ca_certificates};
 pub use self::extraction::ExtractError;
+pub use self::release_info::ReleaseInfo;
+pub use self::search::SearchResult;
 pub use self::source::ArtifactSource;
+pub(crate) use self::tags::{format_release_not_found, release_tags};
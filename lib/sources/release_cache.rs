@@ -0,0 +1,197 @@
+use std::{
+    env::var,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{create_dir_all, read, remove_file, write};
+use tracing::{debug, warn};
+
+use crate::{tool::ToolSpec, util::str::normalize_host};
+
+const TTL_ENV_VAR: &str = "ROKIT_RELEASE_CACHE_TTL";
+const DEFAULT_TTL: Duration = Duration::from_hours(1);
+
+/**
+    A cached release metadata response, along with the information
+    necessary to make a conditional request to refresh it later.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CachedRelease {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub fetched_at: u64,
+    pub body: serde_json::Value,
+}
+
+impl CachedRelease {
+    pub fn new(url: impl Into<String>, headers: &HeaderMap, body: serde_json::Value) -> Self {
+        let header_string = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(ToString::to_string)
+        };
+        Self {
+            url: url.into(),
+            etag: header_string(ETAG),
+            last_modified: header_string(LAST_MODIFIED),
+            fetched_at: unix_now(),
+            body,
+        }
+    }
+
+    /**
+        Checks if this cached release is still within the given TTL,
+        meaning that it can be used without any network requests.
+    */
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        unix_now().saturating_sub(self.fetched_at) < ttl.as_secs()
+    }
+
+    /**
+        Marks this cached release as freshly fetched, after the
+        server confirmed that it has not been modified.
+    */
+    pub fn touch(&mut self) {
+        self.fetched_at = unix_now();
+    }
+
+    /**
+        Gets the headers to send for a conditional request to refresh this cached release.
+    */
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = self.etag.as_deref().and_then(|e| e.parse().ok()) {
+            headers.insert(IF_NONE_MATCH, value);
+        }
+        if let Some(value) = self.last_modified.as_deref().and_then(|l| l.parse().ok()) {
+            headers.insert(IF_MODIFIED_SINCE, value);
+        }
+        headers
+    }
+}
+
+/**
+    An on-disk cache for release metadata, keyed by tool id and version.
+
+    Entries are used directly within a short TTL, which defaults to one hour and
+    can be changed using the `ROKIT_RELEASE_CACHE_TTL` environment variable (in seconds).
+    After that, they should be refreshed using a conditional request.
+
+    Any errors reading or writing the cache are ignored, since the
+    cache is only an optimization and must never break installs.
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct ReleaseCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl ReleaseCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let ttl = match var(TTL_ENV_VAR) {
+            Err(_) => DEFAULT_TTL,
+            Ok(value) => value.trim().parse::<u64>().map_or_else(
+                |_| {
+                    warn!(
+                        "Invalid value for {TTL_ENV_VAR} - expected a number of seconds, got '{value}'\
+                        \nUsing the default of {} seconds instead.",
+                        DEFAULT_TTL.as_secs()
+                    );
+                    DEFAULT_TTL
+                },
+                Duration::from_secs,
+            ),
+        };
+        Self {
+            dir: dir.into(),
+            ttl,
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn path(&self, spec: &ToolSpec) -> PathBuf {
+        let mut provider_dir = self.dir.join(spec.provider().as_str());
+        if let Some(host) = spec.id().host() {
+            provider_dir = provider_dir.join(normalize_host(host).replace(':', "_"));
+        }
+        provider_dir
+            .join(spec.author())
+            .join(spec.name())
+            .join(format!("{}.json", spec.version()))
+    }
+
+    pub async fn get(&self, spec: &ToolSpec) -> Option<CachedRelease> {
+        let bytes = read(self.path(spec)).await.ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    pub async fn put(&self, spec: &ToolSpec, release: &CachedRelease) {
+        let path = self.path(spec);
+        if let Err(e) = write_json(&path, release).await {
+            debug!(%spec, path = %path.display(), "failed to write release cache: {e}");
+        }
+    }
+
+    pub async fn remove(&self, spec: &ToolSpec) {
+        debug!(%spec, "removing release from cache");
+        let _ = remove_file(self.path(spec)).await;
+    }
+}
+
+async fn write_json(path: &Path, release: &CachedRelease) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).await?;
+    }
+    write(path, serde_json::to_vec(release)?).await
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(fetched_at: u64) -> CachedRelease {
+        CachedRelease {
+            url: String::from("https://example.com"),
+            etag: Some(String::from("\"abc\"")),
+            last_modified: None,
+            fetched_at,
+            body: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn freshness() {
+        let ttl = Duration::from_mins(1);
+        assert!(cached(unix_now()).is_fresh(ttl));
+        assert!(!cached(unix_now() - 120).is_fresh(ttl));
+        assert!(!cached(unix_now()).is_fresh(Duration::ZERO));
+
+        let mut stale = cached(0);
+        stale.touch();
+        assert!(stale.is_fresh(ttl));
+    }
+
+    #[test]
+    fn conditional_headers() {
+        let headers = cached(0).conditional_headers();
+        assert_eq!(headers.get(IF_NONE_MATCH).unwrap(), "\"abc\"");
+        assert!(headers.get(IF_MODIFIED_SINCE).is_none());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::tool::ToolSpec;
+
+use super::Artifact;
+
+/**
+    Details about a release of a tool, see [`ArtifactSource::get_latest_release_info`].
+
+    [`ArtifactSource::get_latest_release_info`]: super::ArtifactSource::get_latest_release_info
+*/
+#[derive(Debug, Clone)]
+pub struct ReleaseInfo {
+    /// The tool and version of the release.
+    pub spec: ToolSpec,
+    /// All artifacts in the release.
+    pub artifacts: Vec<Artifact>,
+    /// When the release was published, as an RFC 3339 timestamp, if known.
+    pub published_at: Option<String>,
+    /// The release notes, usually markdown, if there are any.
+    pub changelog: Option<String>,
+}
+
+impl ReleaseInfo {
+    pub(crate) fn new(
+        spec: ToolSpec,
+        artifacts: Vec<Artifact>,
+        published_at: Option<String>,
+        changelog: Option<String>,
+    ) -> Self {
+        let non_empty =
+            |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        Self {
+            spec,
+            artifacts,
+            published_at: non_empty(published_at),
+            changelog: non_empty(changelog),
+        }
+    }
+
+    /**
+        Gets the date that the release was published, such as `2024-05-01`, if known.
+    */
+    #[must_use]
+    pub fn published_date(&self) -> Option<&str> {
+        let published_at = self.published_at.as_deref()?;
+        Some(
+            published_at
+                .split_once('T')
+                .map_or(published_at, |(date, _)| date),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn published_date() {
+        let info = |published_at: Option<&str>| {
+            ReleaseInfo::new(
+                "author/tool@1.0.0".parse().unwrap(),
+                Vec::new(),
+                published_at.map(ToString::to_string),
+                Some(String::from("  \n")),
+            )
+        };
+        assert_eq!(
+            info(Some("2024-05-01T12:34:56Z")).published_date(),
+            Some("2024-05-01")
+        );
+        assert_eq!(
+            info(Some("2024-05-01")).published_date(),
+            Some("2024-05-01")
+        );
+        assert_eq!(info(Some(" ")).published_date(), None);
+        assert_eq!(info(None).changelog, None);
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    env::var,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use http::Extensions;
+use reqwest::{header::RETRY_AFTER, Method, Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next, Result};
+use reqwest_retry::{
+    default_on_request_failure, policies::ExponentialBackoff, RetryDecision, RetryPolicy, Retryable,
+};
+use tracing::{debug, warn};
+
+const MAX_RETRIES_ENV_VAR: &str = "ROKIT_MAX_RETRIES";
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const MAX_RETRY_AFTER: Duration = Duration::from_mins(1);
+
+/**
+    Middleware for retrying idempotent requests with exponential backoff and jitter.
+
+    Requests are only retried on network errors, server errors (5xx), and
+    when being rate limited (429), in which case any `Retry-After` header
+    sent by the server is respected, unless it asks for a very long wait.
+
+    The maximum number of retries defaults to 3 and can be changed using
+    the `ROKIT_MAX_RETRIES` environment variable, or for individual requests
+    using the [`MaxRetries`] extension, which the environment variable
+    takes precedence over.
+*/
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RetryMiddleware {
+    max_retries: Option<u32>,
+}
+
+impl RetryMiddleware {
+    pub(super) fn from_env() -> Self {
+        Self {
+            max_retries: max_retries_from_env(),
+        }
+    }
+}
+
+/**
+    Request extension for the maximum number of retries to use for
+    a request, such as one configured for a specific provider.
+*/
+#[derive(Debug, Clone, Copy)]
+pub(super) struct MaxRetries(pub(super) u32);
+
+/**
+    Gets the maximum number of retries to use, which is read from the
+    `ROKIT_MAX_RETRIES` environment variable if set, then the given
+    configured number of retries, and otherwise falls back to the default of 3.
+*/
+pub(super) fn max_retries_or(configured: Option<u32>) -> u32 {
+    max_retries_from_env()
+        .or(configured)
+        .unwrap_or(DEFAULT_MAX_RETRIES)
+}
+
+fn max_retries_from_env() -> Option<u32> {
+    let value = var(MAX_RETRIES_ENV_VAR).ok()?;
+    if let Ok(max_retries) = value.trim().parse::<u32>() {
+        Some(max_retries)
+    } else {
+        warn!(
+            "Invalid value for {MAX_RETRIES_ENV_VAR} - expected a number, got '{value}'\
+            \nUsing the default number of retries instead."
+        );
+        None
+    }
+}
+
+#[async_trait]
+impl Middleware for RetryMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let max_retries = self
+            .max_retries
+            .or_else(|| extensions.get::<MaxRetries>().map(|m| m.0))
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        if max_retries == 0 || !is_idempotent(req.method()) {
+            return next.run(req, extensions).await;
+        }
+
+        let policy = ExponentialBackoff::builder().build_with_max_retries(max_retries);
+
+        let start_time = SystemTime::now();
+        let mut n_past_retries = 0;
+        loop {
+            // NOTE: Requests with streaming bodies can't be cloned and retried,
+            // but we never send those, so just send them through as they are
+            let Some(duplicate_request) = req.try_clone() else {
+                return next.run(req, extensions).await;
+            };
+
+            let result = next.clone().run(duplicate_request, extensions).await;
+            let retry_after = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    parse_retry_after(response)
+                }
+                Err(err) if default_on_request_failure(err) == Some(Retryable::Transient) => None,
+                _ => return result,
+            };
+
+            let RetryDecision::Retry { execute_after } =
+                policy.should_retry(start_time, n_past_retries)
+            else {
+                return result;
+            };
+
+            let backoff = execute_after
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            let delay = match retry_after {
+                Some(retry_after) if retry_after > MAX_RETRY_AFTER => return result,
+                Some(retry_after) => retry_after.max(backoff),
+                None => backoff,
+            };
+
+            n_past_retries += 1;
+            debug!(
+                attempt = n_past_retries,
+                max_retries,
+                ?delay,
+                "retrying request after transient error"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+// NOTE: The Retry-After header may also be an HTTP date, but
+// all the providers we use send a number of seconds instead
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    let seconds = value.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(StatusCode::OK));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::FORBIDDEN));
+        assert!(!is_retryable_status(StatusCode::REQUEST_TIMEOUT));
+    }
+
+    #[test]
+    fn idempotent_methods() {
+        assert!(is_idempotent(&Method::GET));
+        assert!(is_idempotent(&Method::HEAD));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(!is_idempotent(&Method::PATCH));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::tool::ToolId;
+
+use super::github::models::Repository;
+
+/**
+    A tool found when searching an artifact provider, see [`ArtifactSource::search`].
+
+    [`ArtifactSource::search`]: super::ArtifactSource::search
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// The id of the tool, which can be used to add it.
+    pub id: ToolId,
+    /// The description of the tool, if any.
+    pub description: Option<String>,
+    /// The number of stars the tool has.
+    pub stars: u64,
+}
+
+impl SearchResult {
+    pub(crate) fn from_github_repository(repository: &Repository) -> Option<Self> {
+        let id = repository.full_name.parse::<ToolId>().ok()?;
+        let description = repository
+            .description
+            .as_deref()
+            .map(str::trim)
+            .filter(|description| !description.is_empty())
+            .map(ToString::to_string);
+        Some(Self {
+            id,
+            description,
+            stars: repository.stargazers_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(full_name: &str, description: Option<&str>) -> Repository {
+        Repository {
+            full_name: full_name.to_string(),
+            description: description.map(ToString::to_string),
+            stargazers_count: 42,
+            archived: false,
+        }
+    }
+
+    #[test]
+    fn from_github_repository() {
+        let result =
+            SearchResult::from_github_repository(&repository("rojo-rbx/rojo", Some(" Rojo ")))
+                .unwrap();
+        assert_eq!(result.id.to_string(), "rojo-rbx/rojo");
+        assert_eq!(result.description.as_deref(), Some("Rojo"));
+        assert_eq!(result.stars, 42);
+
+        let result =
+            SearchResult::from_github_repository(&repository("rojo-rbx/rojo", Some("  "))).unwrap();
+        assert_eq!(result.description, None);
+
+        assert!(SearchResult::from_github_repository(&repository("invalid", None)).is_none());
+    }
+}
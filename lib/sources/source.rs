@@ -1,20 +1,39 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use reqwest::Certificate;
+use semver::Version;
+use tracing::{debug, instrument};
+use url::Url;
 
 use crate::{
-    result::RokitResult,
-    tool::{ToolId, ToolSpec},
+    result::{RokitError, RokitResult},
+    tool::{ToolId, ToolSpec, ToolSpecReq},
+    util::str::edit_distance,
 };
 
-use super::{github::GithubProvider, Artifact, ArtifactProvider};
+use super::{
+    download::PartialDownload, gitea::GiteaProvider, github::GithubProvider,
+    gitlab::GitlabProvider, mirrors::DownloadMirrors, retry::max_retries_or, Artifact,
+    ArtifactCache, ArtifactProvider, ReleaseInfo, SearchResult,
+};
 
 /**
     A source for artifacts.
 
-    Provides high-level access abstracting over individual providers such as GitHub, ...
+    Provides high-level access abstracting over individual providers such as GitHub, GitLab, ...
 */
 #[derive(Debug, Clone)]
 pub struct ArtifactSource {
     github: GithubProvider,
+    gitlab: GitlabProvider,
+    gitea: GiteaProvider,
+    artifact_cache: Option<ArtifactCache>,
+    refresh_artifacts: bool,
+    max_retries: HashMap<ArtifactProvider, u32>,
+    mirrors: DownloadMirrors,
 }
 
 impl ArtifactSource {
@@ -30,24 +49,188 @@ impl ArtifactSource {
     */
     pub fn new() -> RokitResult<Self> {
         let github = GithubProvider::new()?;
-        Ok(Self { github })
+        let gitlab = GitlabProvider::new()?;
+        let gitea = GiteaProvider::new()?;
+        Ok(Self {
+            github,
+            gitlab,
+            gitea,
+            artifact_cache: None,
+            refresh_artifacts: false,
+            max_retries: HashMap::new(),
+            mirrors: DownloadMirrors::default(),
+        })
     }
 
     /**
         Creates a new authenticated artifact source.
 
         This source is authenticated and can access private resources.
+        Tokens are only ever given to the provider they belong to.
 
         # Errors
 
         - If the artifact source could not be created.
     */
     pub fn new_authenticated(auth: &HashMap<ArtifactProvider, String>) -> RokitResult<Self> {
-        let github = match auth.get(&ArtifactProvider::GitHub) {
-            Some(token) => GithubProvider::new_authenticated(token)?,
-            None => GithubProvider::new()?,
+        Self::new_authenticated_with_proxy(auth, &HashMap::new(), None)
+    }
+
+    /**
+        Creates a new authenticated artifact source, using the given proxy for all requests.
+
+        Tokens for providers that require a host, such as GitHub Enterprise and Gitea, are given separately
+        in `host_auth`, keyed by provider and then by host, and are only ever
+        given to the host they belong to.
+
+        If no proxy is given, proxies are read from the standard `HTTP_PROXY`,
+        `HTTPS_PROXY` and `NO_PROXY` environment variables, same as for
+        [`ArtifactSource::new_authenticated`].
+
+        # Errors
+
+        - If the artifact source could not be created.
+    */
+    pub fn new_authenticated_with_proxy(
+        auth: &HashMap<ArtifactProvider, String>,
+        host_auth: &HashMap<ArtifactProvider, HashMap<String, String>>,
+        proxy: Option<&Url>,
+    ) -> RokitResult<Self> {
+        Self::new_authenticated_with_certificates(auth, host_auth, proxy, &[])
+    }
+
+    /**
+        Creates a new authenticated artifact source, using the given proxy for
+        all requests, and trusting the given root certificates in addition to
+        the built-in ones, such as for a TLS-intercepting corporate proxy.
+
+        See [`ArtifactSource::new_authenticated_with_proxy`] for more information.
+
+        # Errors
+
+        - If the artifact source could not be created.
+    */
+    pub fn new_authenticated_with_certificates(
+        auth: &HashMap<ArtifactProvider, String>,
+        host_auth: &HashMap<ArtifactProvider, HashMap<String, String>>,
+        proxy: Option<&Url>,
+        root_certificates: &[Certificate],
+    ) -> RokitResult<Self> {
+        let token = |provider| {
+            auth.get(&provider)
+                .map(|token: &String| token.trim().to_string())
         };
-        Ok(Self { github })
+        let host_tokens = |provider| host_auth.get(&provider).cloned().unwrap_or_default();
+        let github = GithubProvider::new_inner(
+            token(ArtifactProvider::GitHub),
+            host_tokens(ArtifactProvider::GitHubEnterprise),
+            proxy,
+            root_certificates,
+        )?;
+        let gitlab =
+            GitlabProvider::new_inner(token(ArtifactProvider::GitLab), proxy, root_certificates)?;
+        let gitea = GiteaProvider::new_inner(
+            host_tokens(ArtifactProvider::Gitea),
+            proxy,
+            root_certificates,
+        )?;
+        Ok(Self {
+            github,
+            gitlab,
+            gitea,
+            artifact_cache: None,
+            refresh_artifacts: false,
+            max_retries: HashMap::new(),
+            mirrors: DownloadMirrors::default(),
+        })
+    }
+
+    /**
+        Enables caching of release metadata in the given directory, so that
+        repeated installs of the same tools don't need to make new API requests.
+
+        Cached releases are used directly for a short time, and refreshed
+        afterwards using conditional requests, which are much cheaper.
+        Releases that are no longer found are removed from the cache.
+
+        Currently only used for GitHub releases.
+    */
+    #[must_use]
+    pub fn with_release_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.github = self.github.with_release_cache(dir);
+        self
+    }
+
+    /**
+        Enables caching of downloaded artifact contents in the given directory, so
+        that installing the same artifact again does not need to download it again.
+
+        See [`ArtifactCache`] for more information.
+    */
+    #[must_use]
+    pub fn with_artifact_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.artifact_cache = Some(ArtifactCache::new(dir));
+        self
+    }
+
+    /**
+        Makes downloads ignore any artifact contents that are already in the
+        artifact cache, and always download them again - the cache is then
+        updated with the newly downloaded contents, as usual.
+    */
+    #[must_use]
+    pub fn with_artifact_cache_refresh(mut self) -> Self {
+        self.refresh_artifacts = true;
+        self
+    }
+
+    /**
+        Sets the maximum number of retries for requests to the given provider,
+        including when resuming interrupted artifact downloads from it.
+
+        The `ROKIT_MAX_RETRIES` environment variable, if set, takes precedence.
+    */
+    #[must_use]
+    pub fn with_max_retries(mut self, provider: ArtifactProvider, max_retries: u32) -> Self {
+        match provider {
+            ArtifactProvider::GitHub => {
+                self.github = self.github.with_max_retries(false, max_retries);
+            }
+            ArtifactProvider::GitHubEnterprise => {
+                self.github = self.github.with_max_retries(true, max_retries);
+            }
+            ArtifactProvider::GitLab => self.gitlab = self.gitlab.with_max_retries(max_retries),
+            ArtifactProvider::Gitea => self.gitea = self.gitea.with_max_retries(max_retries),
+        }
+        self.max_retries.insert(provider, max_retries);
+        self
+    }
+
+    /**
+        Sets mirrors to download artifacts from, keyed by the host to rewrite,
+        such as `api.github.com`, and mapping to the base URL of the mirror.
+
+        Only artifact downloads use mirrors, and release metadata is still fetched
+        from providers. Authentication tokens are never sent to mirrors.
+    */
+    #[must_use]
+    pub fn with_download_mirrors(mut self, mirrors: &BTreeMap<String, Url>) -> Self {
+        self.mirrors = DownloadMirrors::new(mirrors);
+        self
+    }
+
+    /**
+        Gets the number of API requests that can still be made to the
+        given provider before being rate limited, if currently known.
+    */
+    #[must_use]
+    pub fn remaining_requests(&self, provider: ArtifactProvider) -> Option<u64> {
+        match provider {
+            ArtifactProvider::GitHub => self.github.rate_limit().map(|r| r.remaining),
+            ArtifactProvider::GitHubEnterprise
+            | ArtifactProvider::GitLab
+            | ArtifactProvider::Gitea => None,
+        }
     }
 
     /**
@@ -58,22 +241,348 @@ impl ArtifactSource {
         - If the latest release could not be fetched.
     */
     pub async fn get_latest_release(&self, id: &ToolId) -> RokitResult<Vec<Artifact>> {
-        Ok(match id.provider() {
-            ArtifactProvider::GitHub => self.github.get_latest_release(id).await?,
-        })
+        let result = match id.provider() {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => {
+                self.github.get_latest_release(id).await.map_err(Into::into)
+            }
+            ArtifactProvider::GitLab => {
+                self.gitlab.get_latest_release(id).await.map_err(Into::into)
+            }
+            ArtifactProvider::Gitea => self.gitea.get_latest_release(id).await.map_err(Into::into),
+        };
+        check_network(result)
+    }
+
+    /**
+        Gets the latest release for a tool, including details such
+        as when it was published and its release notes.
+
+        # Errors
+
+        - If the latest release could not be fetched.
+    */
+    pub async fn get_latest_release_info(&self, id: &ToolId) -> RokitResult<ReleaseInfo> {
+        let result = match id.provider() {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => self
+                .github
+                .get_latest_release_info(id)
+                .await
+                .map_err(Into::into),
+            ArtifactProvider::GitLab => self
+                .gitlab
+                .get_latest_release_info(id)
+                .await
+                .map_err(Into::into),
+            ArtifactProvider::Gitea => self
+                .gitea
+                .get_latest_release_info(id)
+                .await
+                .map_err(Into::into),
+        };
+        check_network(result)
+    }
+
+    /**
+        Gets all available release versions for a tool, sorted in ascending order.
+
+        Prereleases are only included if `include_prereleases` is `true`.
+
+        # Errors
+
+        - If the available versions could not be fetched.
+    */
+    pub async fn get_available_versions(
+        &self,
+        id: &ToolId,
+        include_prereleases: bool,
+    ) -> RokitResult<Vec<Version>> {
+        let result = match id.provider() {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => self
+                .github
+                .get_available_versions(id, include_prereleases)
+                .await
+                .map_err(Into::into),
+            ArtifactProvider::GitLab => self
+                .gitlab
+                .get_available_versions(id, include_prereleases)
+                .await
+                .map_err(Into::into),
+            ArtifactProvider::Gitea => self
+                .gitea
+                .get_available_versions(id, include_prereleases)
+                .await
+                .map_err(Into::into),
+        };
+        check_network(result)
+    }
+
+    /**
+        Resolves a tool version requirement, such as `^1.2` or `latest`, to the
+        highest available version matching it. Exact versions are returned as-is.
+
+        # Errors
+
+        - If the available versions could not be fetched.
+        - If no available version matches the requirement.
+    */
+    #[instrument(skip(self), fields(%requirement), level = "debug")]
+    pub async fn resolve_requirement(&self, requirement: &ToolSpecReq) -> RokitResult<ToolSpec> {
+        if let Some(spec) = requirement.exact() {
+            return Ok(spec);
+        }
+
+        let version_req = requirement.version_req();
+        let versions = self
+            .get_available_versions(requirement.id(), version_req.allows_prereleases())
+            .await?;
+
+        match version_req.resolve(&versions) {
+            Some(version) => {
+                debug!(%requirement, %version, "resolved version requirement");
+                Ok((requirement.id().clone(), version).into())
+            }
+            None => Err(RokitError::NoMatchingVersion {
+                requirement: Box::new(requirement.clone()),
+                closest: version_req.closest(&versions),
+            }),
+        }
+    }
+
+    /**
+        Checks if the repository for a tool exists on its provider, and can be accessed.
+
+        This is a single, cheap request, which can be used to catch typos
+        in tool ids before trying to resolve versions or fetch releases.
+
+        # Errors
+
+        - If the request failed for any other reason than the repository not existing.
+    */
+    pub async fn repository_exists(&self, id: &ToolId) -> RokitResult<bool> {
+        let result = match id.provider() {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => {
+                self.github.repository_exists(id).await.map_err(Into::into)
+            }
+            ArtifactProvider::GitLab => self.gitlab.repository_exists(id).await.map_err(Into::into),
+            ArtifactProvider::Gitea => self.gitea.repository_exists(id).await.map_err(Into::into),
+        };
+        check_network(result)
+    }
+
+    /**
+        Gets the id of a tool with the casing of its author and name as on its
+        provider, such as `Author/Name` for `author/name`, which is the same
+        request as [`ArtifactSource::repository_exists`].
+
+        Returns `None` if the repository does not exist. Repositories that
+        were renamed keep their original id, since only the casing may change.
+
+        # Errors
+
+        - If the request failed for any other reason than the repository not existing.
+    */
+    pub async fn get_canonical_id(&self, id: &ToolId) -> RokitResult<Option<ToolId>> {
+        let result = match id.provider() {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => {
+                self.github.get_canonical_id(id).await.map_err(Into::into)
+            }
+            ArtifactProvider::GitLab => self.gitlab.get_canonical_id(id).await.map_err(Into::into),
+            ArtifactProvider::Gitea => self.gitea.get_canonical_id(id).await.map_err(Into::into),
+        };
+        check_network(result)
+    }
+
+    /**
+        Finds tools with ids similar to the given one, such as the correct
+        id for a tool with a typo in its author or name, most similar first.
+
+        Currently only finds tools on GitHub, and always returns
+        an empty list for tools from any other provider.
+
+        # Errors
+
+        - If the search request failed.
+    */
+    pub async fn find_similar_tools(&self, id: &ToolId) -> RokitResult<Vec<ToolId>> {
+        if id.provider() != ArtifactProvider::GitHub {
+            return Ok(Vec::new());
+        }
+        let results = self.search(id.name(), 20).await?;
+        Ok(similar_tool_ids(id, results.into_iter().map(|r| r.id)))
+    }
+
+    /**
+        Searches for tools matching the given query, most popular first.
+
+        Currently only searches GitHub, and archived repositories are skipped.
+        Note that results are not guaranteed to have any releases.
+
+        # Errors
+
+        - If the search request failed.
+    */
+    pub async fn search(&self, query: &str, limit: usize) -> RokitResult<Vec<SearchResult>> {
+        let repositories = check_network(
+            self.github
+                .search_repositories(query, limit)
+                .await
+                .map_err(Into::into),
+        )?;
+        Ok(repositories
+            .iter()
+            .filter(|repository| !repository.archived)
+            .filter_map(SearchResult::from_github_repository)
+            .collect())
+    }
+
+    /**
+        Gets a specific release for a tool from the release cache, without making
+        any requests, no matter how long ago the release was cached.
+
+        Returns `None` if the release has not been cached. Releases are currently
+        only cached for GitHub, see [`ArtifactSource::with_release_cache`].
+    */
+    pub async fn get_cached_release(&self, spec: &ToolSpec) -> Option<Vec<Artifact>> {
+        match spec.provider() {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => {
+                self.github.get_cached_release(spec).await
+            }
+            ArtifactProvider::GitLab | ArtifactProvider::Gitea => None,
+        }
     }
 
     /**
         Gets a specific release for a tool.
 
+        The release is looked for using the tags `v1.2.3` and `1.2.3`,
+        see [`ArtifactSource::get_specific_release_with_tag_prefix`]
+        for tools that tag their releases using some other prefix.
+
         # Errors
 
         - If the specific release could not be fetched.
     */
     pub async fn get_specific_release(&self, spec: &ToolSpec) -> RokitResult<Vec<Artifact>> {
-        Ok(match spec.provider() {
-            ArtifactProvider::GitHub => self.github.get_specific_release(spec).await?,
-        })
+        self.get_specific_release_with_tag_prefix(spec, None).await
+    }
+
+    /**
+        Gets a specific release for a tool, looking for a release tag
+        with the given prefix first, such as `cli-1.2.3` for `cli-`.
+
+        See [`ArtifactSource::get_specific_release`] for more information.
+
+        # Errors
+
+        - If the specific release could not be fetched.
+    */
+    pub async fn get_specific_release_with_tag_prefix(
+        &self,
+        spec: &ToolSpec,
+        tag_prefix: Option<&str>,
+    ) -> RokitResult<Vec<Artifact>> {
+        let result = match spec.provider() {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => self
+                .github
+                .get_specific_release_with_tag_prefix(spec, tag_prefix)
+                .await
+                .map_err(Into::into),
+            ArtifactProvider::GitLab => self
+                .gitlab
+                .get_specific_release_with_tag_prefix(spec, tag_prefix)
+                .await
+                .map_err(Into::into),
+            ArtifactProvider::Gitea => self
+                .gitea
+                .get_specific_release_with_tag_prefix(spec, tag_prefix)
+                .await
+                .map_err(Into::into),
+        };
+        check_network(result)
+    }
+
+    /**
+        Finds and attaches a checksum to the given artifact, if the release publishes one.
+
+        Checksums are looked for in a checksum file specific to the artifact,
+        such as `tool.zip.sha256`, or a list of checksums such as `checksums.txt`.
+
+        Returns the artifact unchanged if no checksum was found.
+
+        # Errors
+
+        - If a checksum file was found but could not be downloaded.
+    */
+    pub async fn attach_checksum(
+        &self,
+        artifact: &Artifact,
+        release_artifacts: &[Artifact],
+    ) -> RokitResult<Artifact> {
+        let mut artifact = artifact.clone();
+        for checksum_artifact in artifact.find_checksum_artifacts(release_artifacts) {
+            let contents = self.download_artifact_contents(checksum_artifact).await?;
+            if let Some(checksum) = artifact.parse_checksum(&contents) {
+                debug!(
+                    spec = %artifact.tool_spec,
+                    source = ?checksum_artifact.file_name,
+                    "found checksum for artifact"
+                );
+                artifact.checksum = Some(checksum);
+                break;
+            }
+        }
+        Ok(artifact)
+    }
+
+    /**
+        Finds and attaches a minisign signature to the given artifact, if the release publishes one.
+
+        Signatures are looked for in a signature file specific to the artifact, such as `tool.zip.minisig`.
+
+        Returns the artifact unchanged if no signature was found.
+
+        # Errors
+
+        - If a signature file was found but could not be downloaded.
+    */
+    pub async fn attach_signature(
+        &self,
+        artifact: &Artifact,
+        release_artifacts: &[Artifact],
+    ) -> RokitResult<Artifact> {
+        let mut artifact = artifact.clone();
+        if let Some(signature_artifact) = artifact.find_signature_artifact(release_artifacts) {
+            let contents = self.download_artifact_contents(signature_artifact).await?;
+            debug!(
+                spec = %artifact.tool_spec,
+                source = ?signature_artifact.file_name,
+                "found signature for artifact"
+            );
+            artifact.signature = Some(String::from_utf8_lossy(&contents).to_string());
+        }
+        Ok(artifact)
+    }
+
+    /**
+        Checks if the contents of an artifact are in the artifact cache, and match
+        its checksum if one is known, meaning that downloading it makes no requests.
+
+        Always returns `false` if no artifact cache is enabled,
+        or if the artifact cache is being refreshed.
+    */
+    pub async fn is_cached(&self, artifact: &Artifact) -> bool {
+        match self.cached_artifacts() {
+            Some(cache) => cache.get(artifact).await.is_some(),
+            None => false,
+        }
+    }
+
+    // Gets the artifact cache to read cached contents from,
+    // which is skipped entirely when refreshing the cache
+    fn cached_artifacts(&self) -> Option<&ArtifactCache> {
+        self.artifact_cache
+            .as_ref()
+            .filter(|_| !self.refresh_artifacts)
     }
 
     /**
@@ -84,8 +593,187 @@ impl ArtifactSource {
         - If the artifact contents could not be downloaded.
     */
     pub async fn download_artifact_contents(&self, artifact: &Artifact) -> RokitResult<Vec<u8>> {
-        Ok(match &artifact.provider {
-            ArtifactProvider::GitHub => self.github.download_artifact_contents(artifact).await?,
+        self.download_artifact_contents_with_progress(artifact, |_, _| {})
+            .await
+    }
+
+    /**
+        Downloads the contents of an artifact, reporting progress as it downloads.
+
+        The given callback is called with the number of bytes downloaded so far, as
+        well as the total number of bytes, if the provider sent a content length.
+
+        Downloads that stall and time out, or that are interrupted, are retried up
+        to the same number of times as other requests are retried, which defaults
+        to 3 and can be changed using the `ROKIT_MAX_RETRIES` environment variable.
+
+        Contents are streamed to a temporary file, and if the server supports range
+        requests, retried downloads resume from where they left off instead of
+        starting over. Resumed contents are validated against the content length
+        and checksum of the artifact, and downloaded again in full if invalid.
+
+        If an artifact cache is enabled, cached contents are used instead of
+        downloading the artifact, and downloaded contents are added to the cache.
+
+        # Errors
+
+        - If the artifact contents could not be downloaded.
+    */
+    #[instrument(
+        skip(self, artifact, on_progress),
+        fields(spec = %artifact.tool_spec, name = ?artifact.name),
+        level = "debug"
+    )]
+    pub async fn download_artifact_contents_with_progress<F>(
+        &self,
+        artifact: &Artifact,
+        on_progress: F,
+    ) -> RokitResult<Vec<u8>>
+    where
+        F: Fn(u64, Option<u64>),
+    {
+        if let Some(cache) = self.cached_artifacts() {
+            if let Some(contents) = cache.get(artifact).await {
+                debug!(spec = %artifact.tool_spec, "using cached artifact contents");
+                let len = contents.len() as u64;
+                on_progress(len, Some(len));
+                return Ok(contents);
+            }
+        }
+
+        let max_retries = max_retries_or(self.max_retries.get(&artifact.provider).copied());
+        let mut n_past_retries = 0;
+        let mut download = PartialDownload::new()?;
+        loop {
+            let resuming = download.resume_from().is_some();
+            let result = match self
+                .download_once(artifact, &mut download, &on_progress)
+                .await
+            {
+                Ok(()) => download.finish(artifact).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(contents) => {
+                    if let Some(cache) = &self.artifact_cache {
+                        cache.put(artifact, &contents).await;
+                    }
+                    return Ok(contents);
+                }
+                // The server rejected the range, or sent contents that were
+                // invalid, so resuming is disabled and the download starts over
+                Err(e) if resuming && !e.is_timeout() && !matches!(e, RokitError::Download(_)) => {
+                    debug!("failed to resume download, starting over: {e}");
+                    download.restart().await?;
+                }
+                // Downloads that time out, or that are interrupted after making
+                // some progress which can be resumed, are retried
+                Err(e)
+                    if (e.is_timeout() || download.resume_from().is_some())
+                        && n_past_retries < max_retries =>
+                {
+                    n_past_retries += 1;
+                    debug!(
+                        attempt = n_past_retries,
+                        max_retries,
+                        resume_from = download.resume_from(),
+                        "retrying interrupted download: {e}"
+                    );
+                }
+                Err(e) => return check_network(Err(e)),
+            }
+        }
+    }
+
+    async fn download_once<F>(
+        &self,
+        artifact: &Artifact,
+        download: &mut PartialDownload,
+        on_progress: F,
+    ) -> RokitResult<()>
+    where
+        F: Fn(u64, Option<u64>),
+    {
+        let range_start = download.resume_from();
+        let response = match &artifact.provider {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => {
+                self.github
+                    .request_artifact_contents(artifact, range_start, &self.mirrors)
+                    .await?
+            }
+            ArtifactProvider::GitLab => {
+                self.gitlab
+                    .request_artifact_contents(artifact, range_start, &self.mirrors)
+                    .await?
+            }
+            ArtifactProvider::Gitea => {
+                self.gitea
+                    .request_artifact_contents(artifact, range_start, &self.mirrors)
+                    .await?
+            }
+        };
+        download
+            .read_response(artifact, response, on_progress)
+            .await
+    }
+}
+
+// Keeps only the ids that are a few edits away from the given id,
+// which is enough to catch most typos without suggesting unrelated tools
+fn similar_tool_ids(id: &ToolId, candidates: impl IntoIterator<Item = ToolId>) -> Vec<ToolId> {
+    const MAX_DISTANCE: usize = 3;
+    let target = format!("{}/{}", id.author(), id.name()).to_ascii_lowercase();
+    let mut similar = candidates
+        .into_iter()
+        .filter(|candidate| candidate != id)
+        .filter_map(|candidate| {
+            let name = format!("{}/{}", candidate.author(), candidate.name()).to_ascii_lowercase();
+            let distance = edit_distance(&target, &name);
+            (distance <= MAX_DISTANCE).then_some((distance, candidate))
         })
+        .collect::<Vec<_>>();
+    similar.sort_by_key(|(distance, _)| *distance);
+    similar
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/*
+    Replaces low-level connection errors with an error that tells the user that
+    the network could not be reached, and what they can do about it.
+
+    Timeouts are not replaced, since those are retried and usually mean a slow
+    or stalled connection rather than no network at all.
+*/
+fn check_network<T>(result: RokitResult<T>) -> RokitResult<T> {
+    result.map_err(|e| {
+        if e.is_connect() && !e.is_timeout() && !matches!(e, RokitError::NetworkUnavailable(_)) {
+            debug!("network unavailable: {e}");
+            RokitError::NetworkUnavailable(e.into())
+        } else {
+            e
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similar_tool_ids_catch_typos() {
+        let id = |s: &str| s.parse::<ToolId>().unwrap();
+        let candidates = [
+            id("rojo-rbx/rojo-vscode"),
+            id("rojo-rbx/rojo"),
+            id("unrelated/tool"),
+            id("rojo-rbx/rjoo"),
+        ];
+        assert_eq!(
+            similar_tool_ids(&id("rojo-rbz/rojo"), candidates.clone()),
+            [id("rojo-rbx/rojo"), id("rojo-rbx/rjoo")]
+        );
+        assert!(similar_tool_ids(&id("rojo-rbx/rojo"), [id("rojo-rbx/rojo")]).is_empty());
     }
 }
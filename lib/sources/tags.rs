@@ -0,0 +1,70 @@
+use semver::Version;
+
+use crate::tool::ToolSpec;
+
+/**
+    Gets the release tags to look for, in order, when finding the release for a specific version.
+
+    Most tools tag their releases as `v1.2.3` or `1.2.3`, which are always tried,
+    but some use a different prefix such as `cli-1.2.3` - a custom prefix for the
+    tool is tried first, if given, since it is the most specific.
+*/
+pub(crate) fn release_tags(version: &Version, tag_prefix: Option<&str>) -> Vec<String> {
+    let custom = tag_prefix.map(|prefix| format!("{prefix}{version}"));
+    let mut tags = Vec::new();
+    for tag in custom
+        .into_iter()
+        .chain([format!("v{version}"), version.to_string()])
+    {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/**
+    Formats the error message for when no release was found
+    for a tool, using any of the given release tags.
+*/
+pub(crate) fn format_release_not_found(tool_spec: &ToolSpec, tags: &[String]) -> String {
+    let tags = tags
+        .iter()
+        .map(|tag| format!("'{tag}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "no release was found for tool '{tool_spec}', tried the tags {tags}\
+        \nif the tool uses another prefix for its tags, such as `cli-` for `cli-1.2.3`,\
+        \nset `tag_prefix` for the tool in your manifest to use it"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(prefix: Option<&str>) -> Vec<String> {
+        release_tags(&Version::new(1, 2, 3), prefix)
+    }
+
+    #[test]
+    fn common_prefixes_are_always_tried() {
+        assert_eq!(tags(None), vec!["v1.2.3", "1.2.3"]);
+    }
+
+    #[test]
+    fn custom_prefix_is_tried_first() {
+        assert_eq!(tags(Some("cli-")), vec!["cli-1.2.3", "v1.2.3", "1.2.3"]);
+        assert_eq!(tags(Some("v")), vec!["v1.2.3", "1.2.3"]);
+        assert_eq!(tags(Some("")), vec!["1.2.3", "v1.2.3"]);
+    }
+
+    #[test]
+    fn not_found_lists_tried_tags() {
+        let spec = "author/tool@1.2.3".parse::<ToolSpec>().unwrap();
+        let message = format_release_not_found(&spec, &tags(Some("cli-")));
+        assert!(message.contains("'cli-1.2.3', 'v1.2.3', '1.2.3'"));
+        assert!(message.contains("tag_prefix"));
+    }
+}
@@ -1,15 +1,21 @@
-use std::env::var;
+use std::env::var_os;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use tokio::fs::create_dir_all;
+use url::Url;
 
-use crate::manifests::AuthManifest;
+use crate::manifests::{AuthManifest, RokitConfig, TrustManifest};
 use crate::result::{RokitError, RokitResult};
-use crate::sources::ArtifactSource;
+use crate::sources::{ca_bundle_from_env, load_ca_certificates, ArtifactCache, ArtifactSource};
 
 use super::{ToolCache, ToolStorage};
 
+const ROOT_ENV_VAR: &str = "ROKIT_ROOT";
+const DEFAULT_DIR_NAME: &str = ".rokit";
+pub(super) const LINK_DIR_NAME: &str = "bin";
+
 /**
     Rokit's home directory - this is where Rokit stores its
     configuration, tools, and other data. Can be cheaply cloned
@@ -23,6 +29,9 @@ pub struct Home {
     path: Arc<Path>,
     tool_storage: ToolStorage,
     tool_cache: ToolCache,
+    proxy: Option<Arc<Url>>,
+    ca_bundle: Option<Arc<Path>>,
+    config: Arc<RokitConfig>,
 }
 
 impl Home {
@@ -39,6 +48,9 @@ impl Home {
             path,
             tool_storage,
             tool_cache,
+            proxy: None,
+            ca_bundle: None,
+            config: Arc::new(RokitConfig::default()),
         })
     }
 
@@ -48,23 +60,97 @@ impl Home {
         This will read, and if necessary, create the Rokit home directory
         and its contents - including trust storage, tools storage, etc.
 
-        If the `ROKIT_ROOT` environment variable is set, this will use
-        that as the home directory. Otherwise, it will use `$HOME/.rokit`.
+        The home directory is found using [`Home::path_from_env`], and all
+        other paths used by Rokit, such as for tool storage, links, caches,
+        and manifests, are always derived from it.
 
         # Errors
 
         - If the home directory could not be read or created.
     */
     pub async fn load_from_env() -> RokitResult<Self> {
-        if let Ok(root_str) = var("ROKIT_ROOT") {
-            Self::load_from_path(root_str).await
-        } else {
-            let path = dirs::home_dir()
-                .ok_or(RokitError::HomeNotFound)?
-                .join(".rokit");
-            create_dir_all(&path).await?;
-            Self::load_from_path(path).await
+        let path = Self::path_from_env()?;
+        create_dir_all(&path).await?;
+        Self::load_from_path(path).await
+    }
+
+    /**
+        Gets the path to the Rokit home directory, without loading it.
+
+        If the `ROKIT_ROOT` environment variable is set, this will use
+        that as the home directory. Otherwise, it will use `$HOME/.rokit`.
+
+        # Errors
+
+        - If `ROKIT_ROOT` is not set, and the user home directory was not found.
+    */
+    pub fn path_from_env() -> RokitResult<PathBuf> {
+        path_from_root(var_os(ROOT_ENV_VAR))
+    }
+
+    /**
+        Gets the default path to the Rokit home directory, `$HOME/.rokit`,
+        which is used when the `ROKIT_ROOT` environment variable is not set.
+
+        # Errors
+
+        - If the user home directory was not found.
+    */
+    pub fn default_path() -> RokitResult<PathBuf> {
+        dirs::home_dir()
+            .map(|home| home.join(DEFAULT_DIR_NAME))
+            .ok_or(RokitError::HomeNotFound)
+    }
+
+    /**
+        Sets an explicit proxy to use for all requests made by
+        artifact sources created from this `Home`, overriding
+        any proxy set using environment variables.
+    */
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: Option<Url>) -> Self {
+        self.proxy = proxy.map(Arc::new);
+        self
+    }
+
+    /**
+        Sets a bundle of PEM-encoded CA certificates to trust for all requests
+        made by artifact sources created from this `Home`, in addition to the
+        built-in root certificates, overriding any bundle set using environment
+        variables - see [`Home::artifact_source`].
+    */
+    #[must_use]
+    pub fn with_ca_bundle(mut self, ca_bundle: Option<PathBuf>) -> Self {
+        self.ca_bundle = ca_bundle.map(Into::into);
+        self
+    }
+
+    /**
+        Sets the config to use for this `Home`, such as one
+        found using [`crate::discovery::discover_config`].
+
+        Any proxy in the config is used for requests made by artifact
+        sources created from this `Home`, unless an explicit proxy
+        was set using [`Home::with_proxy`].
+    */
+    #[must_use]
+    pub fn with_config(mut self, config: RokitConfig) -> Self {
+        if self.proxy.is_none() {
+            self.proxy = config.proxy.clone().map(Arc::new);
         }
+        self.config = Arc::new(config);
+        self
+    }
+
+    /**
+        Returns a reference to the `RokitConfig` for this `Home`.
+
+        This contains default settings, which CLI flags
+        and environment variables should take precedence over.
+    */
+    #[must_use]
+    pub fn config(&self) -> &RokitConfig {
+        &self.config
     }
 
     /**
@@ -75,6 +161,32 @@ impl Home {
         &self.path
     }
 
+    /**
+        Gets the path to the directory containing links to Rokit and all tools,
+        which needs to be on PATH for tools to be runnable by their aliases.
+    */
+    #[must_use]
+    pub fn link_dir(&self) -> PathBuf {
+        self.path.join(LINK_DIR_NAME)
+    }
+
+    /**
+        Formats a path inside of this `Home` for display, such as `~/.rokit/rokit.toml`,
+        or as the full path if this `Home` is not in the default location.
+
+        Returns `None` if the path is not inside of this `Home`.
+    */
+    #[must_use]
+    pub fn display_path(&self, path: &Path) -> Option<String> {
+        let stripped = path.strip_prefix(&self.path).ok()?;
+        let is_default = Self::default_path().is_ok_and(|default| *default == *self.path);
+        if is_default {
+            Some(format!("~/{DEFAULT_DIR_NAME}/{}", stripped.display()))
+        } else {
+            Some(path.display().to_string())
+        }
+    }
+
     /**
         Returns a reference to the `ToolStorage` for this `Home`.
     */
@@ -94,17 +206,72 @@ impl Home {
     /**
         Creates a new `ArtifactSource` for this `Home`.
 
-        This will load any stored authentication from disk and use
-        it to authenticate with the artifact source and various providers.
+        This will load any stored authentication from disk, as well as any tokens
+        given using environment variables, and use them to authenticate with
+        the artifact source and various providers.
+        Release metadata will be cached in the `cache/releases` directory,
+        downloaded artifacts will be cached in the `cache/artifacts` directory,
+        and any proxy set using [`Home::with_proxy`] will be used for requests.
+        Retries configured for specific providers in the config are also used.
+
+        Certificates in any CA bundle set using [`Home::with_ca_bundle`], or
+        otherwise in the `ROKIT_CA_BUNDLE` or `SSL_CERT_FILE` environment
+        variables, are trusted in addition to the built-in root certificates.
 
         # Errors
 
         - If the auth manifest could not be loaded or created.
+        - If the CA bundle could not be loaded.
         - If the artifact source could not be created.
     */
     pub async fn artifact_source(&self) -> RokitResult<ArtifactSource> {
         let auth = AuthManifest::load_or_create(&self.path).await?;
-        ArtifactSource::new_authenticated(&auth.get_all_tokens())
+        let proxy = self.proxy.as_deref();
+        let ca_bundle = match &self.ca_bundle {
+            Some(path) => Some(path.to_path_buf()),
+            None => ca_bundle_from_env(),
+        };
+        let root_certificates = match ca_bundle {
+            Some(path) => load_ca_certificates(&path).await?,
+            None => Vec::new(),
+        };
+        let source = ArtifactSource::new_authenticated_with_certificates(
+            &auth.get_all_tokens_with_env().await,
+            &auth.get_all_host_tokens().await,
+            proxy,
+            &root_certificates,
+        )?;
+        let mut source = source
+            .with_release_cache(self.path.join("cache").join("releases"))
+            .with_artifact_cache(self.path.join("cache").join("artifacts"))
+            .with_download_mirrors(&self.config.mirrors);
+        for (provider, config) in &self.config.providers {
+            if let Some(max_retries) = config.max_retries {
+                source = source.with_max_retries(*provider, max_retries);
+            }
+        }
+        Ok(source)
+    }
+
+    /**
+        Gets the cache for downloaded artifacts, used by [`Home::artifact_source`].
+    */
+    #[must_use]
+    pub fn artifact_cache(&self) -> ArtifactCache {
+        ArtifactCache::new(self.path.join("cache").join("artifacts"))
+    }
+
+    /**
+        Loads the `TrustManifest` for this `Home`, containing trust
+        policies for tools that should be trusted automatically.
+
+        # Errors
+
+        - If the trust manifest could not be loaded or created.
+        - If the trust manifest contains invalid trust policies.
+    */
+    pub async fn trust_manifest(&self) -> RokitResult<TrustManifest> {
+        TrustManifest::load_or_create(&self.path).await
     }
 
     /**
@@ -120,6 +287,15 @@ impl Home {
     }
 }
 
+// An empty root is treated the same as an unset one, since
+// it would otherwise point at the current working directory
+fn path_from_root(root: Option<OsString>) -> RokitResult<PathBuf> {
+    match root.filter(|root| !root.is_empty()) {
+        Some(root) => Ok(PathBuf::from(root)),
+        None => Home::default_path(),
+    }
+}
+
 /*
     Implement Drop with an error message if the Home was dropped
     without being saved - this should never happen since a Home
@@ -143,3 +319,48 @@ impl Drop for Home {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tool::ToolSpec;
+
+    #[test]
+    fn root_overrides_default_path() {
+        let root = tempfile::tempdir().unwrap();
+        let path = path_from_root(Some(root.path().as_os_str().to_owned())).unwrap();
+        assert_eq!(path, root.path());
+        if let Ok(default) = Home::default_path() {
+            assert_eq!(path_from_root(Some(OsString::new())).unwrap(), default);
+            assert_eq!(path_from_root(None).unwrap(), default);
+        }
+    }
+
+    #[tokio::test]
+    async fn all_paths_derive_from_root() {
+        let root = tempfile::tempdir().unwrap();
+        let home = Home::load_from_path(root.path()).await.unwrap();
+
+        let spec = "author/tool@1.0.0".parse::<ToolSpec>().unwrap();
+        assert!(home
+            .tool_storage()
+            .tool_path(&spec)
+            .starts_with(root.path()));
+        for link in home.tool_storage().all_link_paths().await.unwrap() {
+            assert!(link.starts_with(root.path()));
+        }
+        assert_eq!(home.link_dir(), root.path().join("bin"));
+        for file in ["rokit.toml", "auth.toml", "bin", "tool-storage"] {
+            assert!(root.path().join(file).exists(), "missing {file} in root");
+        }
+
+        let manifest = root.path().join("rokit.toml");
+        let displayed = home.display_path(&manifest).unwrap();
+        assert!(!displayed.starts_with("~/.rokit"));
+        assert_eq!(displayed, manifest.display().to_string());
+
+        home.save().await.unwrap();
+        assert!(root.path().join("tool-storage").join("cache.json").exists());
+    }
+}
@@ -52,7 +52,14 @@ impl RokitLinkMetadata {
         Parses metadata from the end of a file.
     */
     pub(crate) fn parse_from(contents: impl AsRef<[u8]>) -> Option<RokitLinkMetadata> {
-        let contents = contents.as_ref();
+        Self::split_from(contents.as_ref()).map(|(_, metadata)| metadata)
+    }
+
+    /**
+        Parses metadata from the end of a file, and also returns
+        the original contents of the file, without any metadata.
+    */
+    pub(crate) fn split_from(contents: &[u8]) -> Option<(&[u8], RokitLinkMetadata)> {
         let len = contents.len();
 
         if contents.ends_with(&ROKIT_META_TRAILER) && len >= 16 {
@@ -68,7 +75,9 @@ impl RokitLinkMetadata {
             }
             // FUTURE: Handle multiple metadata versions?
             if meta_version == ROKIT_META_VERSION {
-                return postcard::from_bytes(&contents[(len - 16 - meta_len)..(len - 16)]).ok();
+                let metadata =
+                    postcard::from_bytes(&contents[(len - 16 - meta_len)..(len - 16)]).ok()?;
+                return Some((&contents[..(len - 16 - meta_len)], metadata));
             }
         }
 
@@ -95,3 +104,22 @@ impl RokitLinkMetadata {
         Ok(contents)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_from_roundtrip() {
+        let contents = b"binary contents".to_vec();
+        let with_meta = RokitLinkMetadata::current()
+            .append_to(contents.clone())
+            .unwrap();
+
+        let (stripped, metadata) = RokitLinkMetadata::split_from(&with_meta).unwrap();
+        assert_eq!(stripped, contents.as_slice());
+        assert!(metadata.is_current());
+
+        assert!(RokitLinkMetadata::split_from(&contents).is_none());
+    }
+}
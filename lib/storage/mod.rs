@@ -5,4 +5,4 @@ mod tool_storage;
 
 pub use self::home::Home;
 pub use self::tool_cache::ToolCache;
-pub use self::tool_storage::ToolStorage;
+pub use self::tool_storage::{LinkHealth, ToolStorage};
@@ -18,7 +18,7 @@ use tracing::{instrument, trace};
 
 use crate::{
     result::RokitResult,
-    tool::{ToolId, ToolSpec},
+    tool::{ToolId, ToolSpec, ToolSpecReq},
 };
 
 /**
@@ -162,6 +162,17 @@ impl ToolCache {
         sorted_set.into_iter().collect()
     }
 
+    /**
+        Find the highest cached version of a tool matching the given
+        version requirement in this `ToolCache`, if any.
+    */
+    #[must_use]
+    pub fn find_installed_matching(&self, requirement: &ToolSpecReq) -> Option<ToolSpec> {
+        let versions = self.all_installed_versions_for_id(requirement.id());
+        let version = requirement.version_req().resolve(&versions)?;
+        Some((requirement.id().clone(), version).into())
+    }
+
     fn path(home_path: impl AsRef<Path>) -> PathBuf {
         home_path.as_ref().join("tool-storage").join("cache.json")
     }
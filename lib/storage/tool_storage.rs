@@ -5,22 +5,46 @@ use std::{
 };
 
 use filepath::FilePath;
-use futures::{stream::FuturesUnordered, TryStreamExt};
+use futures::{future::ready, stream::FuturesUnordered, StreamExt};
+use semver::Version;
 use tokio::{
-    fs::{create_dir_all, read, read_dir, remove_file, rename},
+    fs::{
+        create_dir_all, metadata, read, read_dir, remove_dir_all, remove_file, rename,
+        symlink_metadata,
+    },
     sync::Mutex as AsyncMutex,
 };
-use tracing::{debug, trace};
+use tracing::{debug, instrument, trace};
 
 use crate::{
     manifests::{AuthManifest, RokitManifest},
-    result::RokitResult,
-    storage::metadata::RokitLinkMetadata,
+    result::{RokitError, RokitResult},
+    sources::ArtifactProvider,
+    storage::{home::LINK_DIR_NAME, metadata::RokitLinkMetadata},
     system::current_exe_contents,
     tool::{ToolAlias, ToolSpec},
-    util::fs::{path_exists, write_executable_file},
+    util::fs::{add_executable_permissions, path_exists, write_executable_file},
 };
 
+const STAGING_DIR_NAME: &str = ".staging";
+
+/**
+    The health of a link in the binary directory, see [`ToolStorage::link_health`].
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkHealth {
+    /// The link is up-to-date with the currently running Rokit binary.
+    Healthy,
+    /// The link was created by a different version of Rokit.
+    Outdated,
+    /// The link is missing executable permissions.
+    NotExecutable,
+    /// The link is a symbolic link pointing to a file that does not exist.
+    Dangling,
+    /// The link is not a Rokit link, or could not be read.
+    Invalid,
+}
+
 /**
     Storage for tool binaries and aliases.
 
@@ -31,6 +55,7 @@ use crate::{
 pub struct ToolStorage {
     pub(super) tools_dir: Arc<Path>,
     pub(super) aliases_dir: Arc<Path>,
+    pub(super) backup_dir: Arc<Path>,
     current_rokit_contents: Arc<AsyncMutex<Option<Vec<u8>>>>,
 }
 
@@ -38,8 +63,19 @@ impl ToolStorage {
     fn tool_paths(&self, spec: &ToolSpec) -> (PathBuf, PathBuf) {
         // NOTE: We use uncased strings for the tool author and name
         // to ensure that the tool paths are always case-insensitive
-        let tool_dir = self
-            .tools_dir
+        // Tools from non-default providers are kept in their own directory
+        // so that e.g. `gitlab:a/b` and `a/b` never overwrite each other,
+        // and tools from providers with hosts are also kept per host
+        let mut provider_dir = if spec.id.provider == ArtifactProvider::default() {
+            self.tools_dir.to_path_buf()
+        } else {
+            self.tools_dir
+                .join(format!(".{}", spec.id.provider.as_str()))
+        };
+        if let Some(host) = &spec.id.host {
+            provider_dir = provider_dir.join(host.uncased_str().replace(':', "_"));
+        }
+        let tool_dir = provider_dir
             .join(spec.id.author.uncased_str())
             .join(spec.id.name.uncased_str())
             .join(spec.version.to_string());
@@ -50,7 +86,13 @@ impl ToolStorage {
         (tool_dir, tool_file)
     }
 
-    fn alias_path(&self, alias: &ToolAlias) -> PathBuf {
+    /**
+        Returns the path to the link for the given tool alias.
+
+        Note that this does not check if the link actually exists.
+    */
+    #[must_use]
+    pub fn alias_path(&self, alias: &ToolAlias) -> PathBuf {
         let alias_file_name = format!("{}{EXE_SUFFIX}", alias.name.uncased_str());
         self.aliases_dir.join(alias_file_name)
     }
@@ -59,6 +101,10 @@ impl ToolStorage {
         self.aliases_dir.join(format!("rokit{EXE_SUFFIX}"))
     }
 
+    fn rokit_backup_path(&self) -> PathBuf {
+        self.backup_dir.join(format!("rokit{EXE_SUFFIX}"))
+    }
+
     async fn rokit_contents(&self) -> RokitResult<Vec<u8>> {
         let mut guard = self.current_rokit_contents.lock().await;
         if let Some(contents) = &*guard {
@@ -82,18 +128,55 @@ impl ToolStorage {
     /**
         Replaces the binary contents for the given tool.
 
+        The contents are first written to a staging directory, which is then
+        moved into place, so that storage never contains a partially written
+        tool - if writing fails, any existing contents are left untouched.
+
         # Errors
 
         - If the binary could not be written.
     */
+    #[instrument(skip(self, contents), fields(%spec), level = "debug")]
     pub async fn replace_tool_contents(
         &self,
         spec: &ToolSpec,
         contents: impl AsRef<[u8]>,
     ) -> RokitResult<()> {
         let (dir_path, file_path) = self.tool_paths(spec);
-        create_dir_all(dir_path).await?;
-        write_executable_file(&file_path, contents).await?;
+
+        // NOTE: The staging directory is removed when dropped, meaning it
+        // also gets cleaned up on errors, or if this future is cancelled
+        let staging_root = self.tools_dir.join(STAGING_DIR_NAME);
+        create_dir_all(&staging_root).await?;
+        let staging = tempfile::Builder::new().tempdir_in(&staging_root)?;
+        let staged_dir = staging.path().join("new");
+        create_dir_all(&staged_dir).await?;
+        let file_name = file_path.file_name().expect("tool paths have file names");
+        write_executable_file(staged_dir.join(file_name), contents).await?;
+        trace!(
+            ?staged_dir,
+            ?dir_path,
+            "moving staged tool contents into place"
+        );
+
+        // Directories can not be renamed over existing ones, so any
+        // existing contents are moved into the staging directory first
+        if let Some(parent) = dir_path.parent() {
+            create_dir_all(parent).await?;
+        }
+        let old_dir = staging.path().join("old");
+        let had_existing = path_exists(&dir_path).await;
+        if had_existing {
+            rename(&dir_path, &old_dir).await?;
+        }
+        if let Err(e) = rename(&staged_dir, &dir_path).await {
+            if had_existing {
+                rename(&old_dir, &dir_path).await.ok();
+            }
+            return Err(e.into());
+        }
+        debug!(path = ?file_path, "replaced tool contents");
+
         Ok(())
     }
 
@@ -108,15 +191,39 @@ impl ToolStorage {
         self.current_rokit_contents.lock().await.replace(contents);
     }
 
+    /**
+        Removes the binary contents for the given tool.
+
+        Returns `true` if any contents were removed, `false` if there were none.
+
+        # Errors
+
+        - If the contents could not be removed.
+    */
+    pub async fn remove_tool_contents(&self, spec: &ToolSpec) -> RokitResult<bool> {
+        let (dir_path, _) = self.tool_paths(spec);
+        if !path_exists(&dir_path).await {
+            return Ok(false);
+        }
+        remove_dir_all(&dir_path).await?;
+        Ok(true)
+    }
+
     /**
         Creates a link for the given tool alias.
 
+        Links are copies of the Rokit executable rather than symlinks, which
+        run the tool for the alias they are named after and forward all
+        arguments and the exit code. This means that creating links does not
+        require developer mode or administrator privileges on Windows.
+
         Note that if the link already exists, it will be overwritten.
 
         # Errors
 
         - If the link could not be written.
     */
+    #[instrument(skip(self, alias), fields(%alias), level = "debug")]
     pub async fn create_tool_link(&self, alias: &ToolAlias) -> RokitResult<()> {
         let path = self.alias_path(alias);
 
@@ -132,11 +239,93 @@ impl ToolStorage {
         // Create the new link
         let rokit_contents = self.rokit_contents().await?;
         let rokit_metadata = RokitLinkMetadata::current();
+        debug!(?path, "writing tool link");
         skip_or_write_link_with_meta(path, &rokit_contents, &rokit_metadata).await?;
 
         Ok(())
     }
 
+    /**
+        Removes the link for the given tool alias.
+
+        Returns `true` if a link was removed, `false` if there was no link.
+
+        # Errors
+
+        - If the link could not be removed.
+    */
+    pub async fn remove_tool_link(&self, alias: &ToolAlias) -> RokitResult<bool> {
+        let path = self.alias_path(alias);
+        let mut removed = false;
+
+        // NOTE: A previous version of Rokit was not adding exe extensions correctly,
+        // so make sure we also remove any links that do not have the extension
+        if should_check_exe_extensions() {
+            let no_extension = strip_exe_extension(&path);
+            if no_extension != path && path_exists(&no_extension).await {
+                remove_file(&no_extension).await?;
+                removed = true;
+            }
+        }
+
+        if path_exists(&path).await {
+            remove_file(&path).await?;
+            removed = true;
+        }
+
+        Ok(removed)
+    }
+
+    /**
+        Checks if a link currently exists for the given tool alias.
+    */
+    pub async fn has_tool_link(&self, alias: &ToolAlias) -> bool {
+        path_exists(self.alias_path(alias)).await
+    }
+
+    /**
+        Checks if the link / main executable for Rokit itself exists in the binary directory.
+    */
+    pub async fn has_rokit_link(&self) -> bool {
+        path_exists(self.rokit_path()).await
+    }
+
+    /**
+        Checks the health of the link at the given path, which should
+        be one of the paths returned by [`ToolStorage::all_link_paths`].
+
+        Any unhealthy link can be repaired using [`ToolStorage::recreate_all_links`].
+    */
+    pub async fn link_health(&self, path: impl AsRef<Path>) -> LinkHealth {
+        let path = path.as_ref();
+        if is_dangling_symlink(path).await {
+            return LinkHealth::Dangling;
+        }
+
+        let Ok(contents) = read(path).await else {
+            return LinkHealth::Invalid;
+        };
+        match RokitLinkMetadata::parse_from(&contents) {
+            None => return LinkHealth::Invalid,
+            Some(meta) if !meta.is_current() => return LinkHealth::Outdated,
+            Some(_) => {}
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = metadata(path)
+                .await
+                .map(|meta| meta.permissions().mode())
+                .unwrap_or_default();
+            if mode & 0o111 != 0o111 {
+                return LinkHealth::NotExecutable;
+            }
+        }
+
+        LinkHealth::Healthy
+    }
+
     /**
         Reads all currently known link paths for tool aliases in the binary directory.
 
@@ -169,6 +358,10 @@ impl ToolStorage {
         Recreates all known links for tool aliases in the binary directory.
         This includes the link / main executable for Rokit itself.
 
+        Links are recreated concurrently, and links that are recreated successfully are
+        kept even if others fail. Links that are already up-to-date are not rewritten,
+        so this can safely be called again to retry any links that failed.
+
         Returns a tuple with information about any existing Rokit link:
 
         - The first value is `true` if the existing Rokit link was found, `false` otherwise.
@@ -178,25 +371,90 @@ impl ToolStorage {
 
         # Errors
 
-        - If any link could not be written.
+        - If the Rokit binary could not be written.
+        - If any link could not be written, listing every link that failed.
     */
     pub async fn recreate_all_links(&self) -> RokitResult<(bool, bool)> {
-        let rokit_path = self.rokit_path();
         let rokit_contents = self.rokit_contents().await?;
-        let rokit_link_existed = path_exists(&rokit_path).await;
+        let rokit_metadata = RokitLinkMetadata::current();
+        self.recreate_all_links_with(&rokit_contents, &rokit_metadata, false)
+            .await
+    }
 
-        let mut link_paths = self.all_link_paths().await?;
+    /**
+        Backs up the Rokit binary that is currently in memory, so that
+        it can be restored later using [`ToolStorage::restore_rokit_backup`].
 
-        // NOTE: A previous version of Rokit was not adding exe extensions correctly,
-        // so look for and try to remove existing links that do not have the extension
-        if should_check_exe_extensions() {
-            for link_path in &mut link_paths {
-                if !has_exe_extension(link_path.as_path()) {
-                    remove_file(link_path.as_path()).await?;
-                    *link_path = append_exe_extension(&link_path);
-                }
-            }
-        }
+        This should be called before [`ToolStorage::replace_rokit_contents`],
+        since the backup is always made from the contents currently in memory.
+
+        # Errors
+
+        - If the backup could not be written.
+    */
+    pub async fn backup_rokit(&self) -> RokitResult<()> {
+        let rokit_contents = self.rokit_contents().await?;
+        let backup_contents = RokitLinkMetadata::current().append_to(rokit_contents)?;
+        create_dir_all(&self.backup_dir).await?;
+        write_executable_file(self.rokit_backup_path(), backup_contents).await?;
+        Ok(())
+    }
+
+    /**
+        Returns the version of the backed up Rokit binary, if a backup exists.
+    */
+    pub async fn rokit_backup_version(&self) -> Option<Version> {
+        let backup_contents = read(self.rokit_backup_path()).await.ok()?;
+        let (_, metadata) = RokitLinkMetadata::split_from(&backup_contents)?;
+        metadata.version.parse().ok()
+    }
+
+    /**
+        Restores the Rokit binary and all links from a backup
+        previously made using [`ToolStorage::backup_rokit`].
+
+        Unlike [`ToolStorage::recreate_all_links`], this always rewrites
+        every link, since links may have been partially overwritten.
+
+        Returns the version of the restored Rokit binary,
+        or `None` if there was no backup to restore.
+
+        # Errors
+
+        - If the backup could not be read.
+        - If any link could not be written.
+    */
+    pub async fn restore_rokit_backup(&self) -> RokitResult<Option<Version>> {
+        let Ok(backup_contents) = read(self.rokit_backup_path()).await else {
+            return Ok(None);
+        };
+        let Some((rokit_contents, rokit_metadata)) =
+            RokitLinkMetadata::split_from(&backup_contents)
+        else {
+            return Ok(None);
+        };
+        let Ok(version) = rokit_metadata.version.parse::<Version>() else {
+            return Ok(None);
+        };
+
+        let rokit_contents = rokit_contents.to_vec();
+        self.recreate_all_links_with(&rokit_contents, &rokit_metadata, true)
+            .await?;
+        self.replace_rokit_contents(rokit_contents).await;
+
+        Ok(Some(version))
+    }
+
+    async fn recreate_all_links_with(
+        &self,
+        rokit_contents: &[u8],
+        rokit_metadata: &RokitLinkMetadata,
+        force: bool,
+    ) -> RokitResult<(bool, bool)> {
+        let rokit_path = self.rokit_path();
+        let rokit_link_existed = path_exists(&rokit_path).await;
+
+        let link_paths = self.all_link_paths().await?;
 
         // Write the Rokit binary if necessary to ensure it's up-to-date
         let existing_rokit_binary = read(&rokit_path).await.unwrap_or_default();
@@ -229,20 +487,27 @@ impl ToolStorage {
                 );
                 rename(&rokit_path, temp_path).await?;
             }
-            write_executable_file(&rokit_path, &rokit_contents).await?;
+            write_executable_file(&rokit_path, rokit_contents).await?;
             true
         };
 
-        // If any link already has the correct Rokit contents, we
-        // can skip creating it, to avoid OS permission errors if the
-        // link is currently being used to run some Rokit-managed program.
-        let rokit_metadata = RokitLinkMetadata::current();
-        link_paths
+        // Links are recreated independently of each other, so that one link
+        // that can not be written, such as a file that is locked by another
+        // process, does not prevent the rest of the links from being recreated
+        let mut failures = link_paths
             .into_iter()
-            .map(|path| skip_or_write_link_with_meta(path, &rokit_contents, &rokit_metadata))
+            .map(|path| async move {
+                let result = recreate_link(&path, rokit_contents, rokit_metadata, force).await;
+                result.err().map(|e| (path, e))
+            })
             .collect::<FuturesUnordered<_>>()
-            .try_collect::<Vec<_>>()
-            .await?;
+            .filter_map(ready)
+            .collect::<Vec<_>>()
+            .await;
+        if !failures.is_empty() {
+            failures.sort_by(|(a, _), (b, _)| a.cmp(b));
+            return Err(RokitError::LinksFailed(failures));
+        }
 
         Ok((rokit_link_existed, was_rokit_updated))
     }
@@ -251,7 +516,8 @@ impl ToolStorage {
         let home_path = home_path.as_ref();
 
         let tools_dir = home_path.join("tool-storage").into();
-        let aliases_dir = home_path.join("bin").into();
+        let aliases_dir = home_path.join(LINK_DIR_NAME).into();
+        let backup_dir = home_path.join("backup").into();
 
         tokio::try_join!(
             RokitManifest::load_or_create(&home_path),
@@ -265,6 +531,7 @@ impl ToolStorage {
         Ok(Self {
             tools_dir,
             aliases_dir,
+            backup_dir,
             current_rokit_contents,
         })
     }
@@ -307,6 +574,53 @@ fn append_exe_extension(path: impl Into<PathBuf>) -> PathBuf {
     path
 }
 
+async fn is_dangling_symlink(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    let is_symlink = symlink_metadata(path)
+        .await
+        .is_ok_and(|meta| meta.file_type().is_symlink());
+    is_symlink && metadata(path).await.is_err()
+}
+
+/*
+    Recreates a single link for a tool alias in the binary directory.
+
+    If any link already has the correct Rokit contents, we can skip
+    creating it, to avoid OS permission errors if the link is currently
+    being used to run some Rokit-managed program - unless forced.
+*/
+async fn recreate_link(
+    path: &Path,
+    rokit_contents: &[u8],
+    rokit_metadata: &RokitLinkMetadata,
+    force: bool,
+) -> RokitResult<()> {
+    // NOTE: Writing to a dangling symlink would write through to its missing
+    // target, or fail if the target directory is gone, so remove those first
+    let dangling = is_dangling_symlink(path).await;
+    if dangling {
+        trace!(?path, "removing dangling link");
+        remove_file(path).await?;
+    }
+
+    // NOTE: A previous version of Rokit was not adding exe extensions correctly,
+    // so look for and try to remove existing links that do not have the extension
+    let mut path = path.to_path_buf();
+    if should_check_exe_extensions() && !has_exe_extension(&path) {
+        if !dangling {
+            remove_file(&path).await?;
+        }
+        path = append_exe_extension(&path);
+    }
+
+    if force {
+        let link_contents = rokit_metadata.append_to(rokit_contents)?;
+        write_executable_file(path, link_contents).await
+    } else {
+        skip_or_write_link_with_meta(path, rokit_contents, rokit_metadata).await
+    }
+}
+
 // Utility functions for checking and writing metadata at the _end_ of link executables
 
 async fn skip_or_write_link_with_meta(
@@ -321,6 +635,8 @@ async fn skip_or_write_link_with_meta(
     if let Some(meta) = existing_metadata {
         if meta.is_current() {
             trace!(?link_path, ?meta, "link is up-to-date");
+            // NOTE: Links may still have lost their executable permissions
+            add_executable_permissions(link_path).await?;
             return Ok(());
         }
         trace!(?link_path, ?meta, "link is outdated");
@@ -331,3 +647,63 @@ async fn skip_or_write_link_with_meta(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn replace_tool_contents_is_staged() {
+        let home = tempfile::tempdir().unwrap();
+        let storage = ToolStorage::load(home.path()).await.unwrap();
+        let spec = "author/tool@1.0.0".parse::<ToolSpec>().unwrap();
+
+        storage
+            .replace_tool_contents(&spec, b"first")
+            .await
+            .unwrap();
+        storage
+            .replace_tool_contents(&spec, b"second")
+            .await
+            .unwrap();
+        assert_eq!(read(storage.tool_path(&spec)).await.unwrap(), b"second");
+
+        let staging_root = storage.tools_dir.join(STAGING_DIR_NAME);
+        let staged = std::fs::read_dir(staging_root).unwrap().count();
+        assert_eq!(staged, 0, "staging directories should be removed");
+    }
+
+    #[tokio::test]
+    async fn recreate_links_aggregates_failures() {
+        let home = tempfile::tempdir().unwrap();
+        let storage = ToolStorage::load(home.path()).await.unwrap();
+        let link_path = |name: &str| storage.aliases_dir.join(format!("{name}{EXE_SUFFIX}"));
+
+        // Directories can never be replaced with links, so they always fail
+        for name in ["broken-a", "broken-b"] {
+            create_dir_all(link_path(name)).await.unwrap();
+        }
+        write_executable_file(link_path("working"), b"outdated")
+            .await
+            .unwrap();
+
+        let metadata = RokitLinkMetadata::current();
+        let err = storage
+            .recreate_all_links_with(b"rokit", &metadata, false)
+            .await
+            .unwrap_err();
+        let RokitError::LinksFailed(failures) = &err else {
+            panic!("expected link failures, got {err}");
+        };
+        let failed = failures
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec![link_path("broken-a"), link_path("broken-b")]);
+        assert!(err.to_string().contains("broken-a"));
+        assert!(err.to_string().contains("broken-b"));
+
+        let contents = read(link_path("working")).await.unwrap();
+        assert_eq!(RokitLinkMetadata::parse_from(&contents), Some(metadata));
+    }
+}
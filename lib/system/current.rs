@@ -1,6 +1,6 @@
 use std::{
     env::{self, consts::EXE_SUFFIX},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use async_once_cell::OnceCell as AsyncOnceCell;
@@ -47,31 +47,33 @@ pub fn current_exe_name() -> String {
     CURRENT_EXE_NAME
         .get_or_init(|| {
             let arg0 = env::args().next().expect("Missing arg0");
+            exe_name_from_path(arg0).expect("Invalid or non-UTF8 file name passed as arg0")
+        })
+        .clone()
+}
 
-            let exe_path = PathBuf::from(arg0);
-            let exe_name = exe_path
-                .file_name()
-                .expect("Invalid file name passed as arg0")
-                .to_str()
-                .expect("Non-UTF8 file name passed as arg0");
+/**
+    Gets the name of the executable at the given path, without any executable
+    extension, which is what Rokit uses to determine the tool that a link runs.
+*/
+pub fn exe_name_from_path(path: impl AsRef<Path>) -> Option<String> {
+    let exe_name = path.as_ref().file_name()?.to_str()?;
 
-            // NOTE: Shells on Windows can be weird sometimes and pass arg0
-            // using either a lowercase or uppercase extension, so we fix that
-            let exe_name = if EXE_SUFFIX.is_empty() {
-                exe_name
-            } else {
-                let suffix_lower = EXE_SUFFIX.to_ascii_lowercase();
-                let suffix_upper = EXE_SUFFIX.to_ascii_uppercase();
-                if let Some(stripped) = exe_name.strip_suffix(&suffix_lower) {
-                    stripped
-                } else if let Some(stripped) = exe_name.strip_suffix(&suffix_upper) {
-                    stripped
-                } else {
-                    exe_name
-                }
-            };
+    // NOTE: Shells on Windows can be weird sometimes and pass arg0
+    // using either a lowercase or uppercase extension, so we fix that
+    let exe_name = if EXE_SUFFIX.is_empty() {
+        exe_name
+    } else {
+        let suffix_lower = EXE_SUFFIX.to_ascii_lowercase();
+        let suffix_upper = EXE_SUFFIX.to_ascii_uppercase();
+        if let Some(stripped) = exe_name.strip_suffix(&suffix_lower) {
+            stripped
+        } else if let Some(stripped) = exe_name.strip_suffix(&suffix_upper) {
+            stripped
+        } else {
+            exe_name
+        }
+    };
 
-            exe_name.to_string()
-        })
-        .clone()
+    Some(exe_name.to_string())
 }
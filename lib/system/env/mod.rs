@@ -1,10 +1,9 @@
-use std::{
-    env::{split_paths, var_os},
-    path::MAIN_SEPARATOR_STR,
-};
+use std::env::{split_paths, var_os};
 
 use crate::{result::RokitResult, storage::Home};
 
+mod profile;
+
 #[cfg(unix)]
 mod shell;
 
@@ -14,6 +13,8 @@ mod unix;
 #[cfg(windows)]
 mod windows;
 
+pub use self::profile::ShellKind;
+
 /**
     Tries to add the Rokit binaries directory to the system PATH.
 
@@ -40,9 +41,15 @@ pub async fn add_to_path(home: &Home) -> RokitResult<bool> {
     Returns `true` if the directory is in the PATH, `false` otherwise.
 */
 #[must_use]
-pub fn exists_in_path(_home: &Home) -> bool {
-    let pattern = format!("rokit{MAIN_SEPARATOR_STR}bin");
-    var_os("PATH").map_or(false, |path| {
-        split_paths(&path).any(|item| item.ends_with(&pattern))
+pub fn exists_in_path(home: &Home) -> bool {
+    let link_dir = home.link_dir();
+    let canonical_link_dir = link_dir.canonicalize().ok();
+    var_os("PATH").is_some_and(|path| {
+        split_paths(&path).any(|item| {
+            item == link_dir
+                || canonical_link_dir
+                    .as_ref()
+                    .is_some_and(|dir| item.canonicalize().is_ok_and(|item| item == *dir))
+        })
     })
 }
@@ -0,0 +1,251 @@
+use std::{
+    env::var_os,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use tokio::{
+    fs::{create_dir_all, read_to_string, write},
+    io::ErrorKind,
+};
+
+use crate::{
+    result::{RokitError, RokitResult},
+    storage::Home,
+};
+
+/**
+    A shell with a profile that can add the Rokit link directory to PATH.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl ShellKind {
+    pub const ALL: [Self; 4] = [Self::Bash, Self::Zsh, Self::Fish, Self::PowerShell];
+
+    /**
+        Detects the shell that the user is currently using.
+
+        On Unix, this uses the `SHELL` environment variable. On Windows,
+        PowerShell is assumed, since it is the default shell.
+    */
+    #[must_use]
+    pub fn detect() -> Option<Self> {
+        if cfg!(windows) {
+            return Some(Self::PowerShell);
+        }
+        let shell = PathBuf::from(var_os("SHELL")?);
+        let name = shell.file_stem()?.to_str()?;
+        match name {
+            "pwsh" | "powershell" => Some(Self::PowerShell),
+            _ => name.parse().ok(),
+        }
+    }
+
+    /**
+        Gets the name of this shell, such as `bash`.
+    */
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Fish => "fish",
+            Self::PowerShell => "powershell",
+        }
+    }
+
+    /**
+        Gets the path to the profile for this shell, which is
+        run every time that a new interactive shell is started.
+
+        Returns `None` if the user home directory was not found.
+    */
+    #[must_use]
+    pub fn profile_path(self) -> Option<PathBuf> {
+        let home_dir = dirs::home_dir()?;
+        let config_dir = var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map_or_else(|| home_dir.join(".config"), PathBuf::from);
+        Some(match self {
+            Self::Bash => home_dir.join(".bashrc"),
+            Self::Zsh => home_dir.join(".zshenv"),
+            Self::Fish => config_dir.join("fish").join("config.fish"),
+            Self::PowerShell if cfg!(windows) => dirs::document_dir()?
+                .join("PowerShell")
+                .join("Microsoft.PowerShell_profile.ps1"),
+            Self::PowerShell => config_dir
+                .join("powershell")
+                .join("Microsoft.PowerShell_profile.ps1"),
+        })
+    }
+
+    /**
+        Creates the line to add to the profile for this shell, which
+        adds the link directory of the given `Home` to PATH.
+
+        # Errors
+
+        - If the link directory is not valid UTF-8.
+    */
+    pub fn path_line(self, home: &Home) -> RokitResult<String> {
+        let link_dir = home.link_dir();
+        let link_dir = link_dir.to_str().ok_or(RokitError::InvalidUtf8)?;
+        let link_dir = replace_home_path_with_var(link_dir);
+        Ok(match self {
+            Self::Bash | Self::Zsh => format!("export PATH=\"{link_dir}:$PATH\""),
+            Self::Fish => format!("fish_add_path \"{link_dir}\""),
+            Self::PowerShell => {
+                format!("$env:PATH = \"{link_dir}\" + [IO.Path]::PathSeparator + $env:PATH")
+            }
+        })
+    }
+
+    /**
+        Checks if the profile for this shell already contains
+        the line created by [`ShellKind::path_line`].
+    */
+    pub async fn has_path_line(self, home: &Home) -> bool {
+        let (Some(profile_path), Ok(line)) = (self.profile_path(), self.path_line(home)) else {
+            return false;
+        };
+        read_to_string(profile_path)
+            .await
+            .is_ok_and(|contents| contents.contains(&line))
+    }
+
+    /**
+        Appends the line created by [`ShellKind::path_line`] to the
+        profile for this shell, creating the profile if necessary.
+
+        Returns `true` if the line was added, `false` if it already existed.
+
+        # Errors
+
+        - If the user home directory was not found.
+        - If the profile could not be read or written.
+    */
+    pub async fn add_path_line(self, home: &Home) -> RokitResult<bool> {
+        let profile_path = self.profile_path().ok_or(RokitError::HomeNotFound)?;
+        if let Some(parent) = profile_path.parent() {
+            create_dir_all(parent).await?;
+        }
+        append_to_shell_file(profile_path, self.path_line(home)?, true).await
+    }
+}
+
+impl FromStr for ShellKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let l = s.trim().to_lowercase();
+        match l.as_str() {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            "powershell" | "pwsh" => Ok(Self::PowerShell),
+            _ => Err(format!(
+                "unknown shell '{l}' - expected one of: bash, zsh, fish, powershell"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ShellKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+/**
+    Appends a line to a shell file, unless the file already contains it.
+
+    Returns `true` if the line was added, `false` otherwise.
+*/
+pub(super) async fn append_to_shell_file(
+    file_path: impl AsRef<Path>,
+    line_to_append: String,
+    create_if_nonexistent: bool,
+) -> RokitResult<bool> {
+    let file_path = file_path.as_ref();
+    let mut file_contents = match read_to_string(file_path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound && create_if_nonexistent => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    if file_contents.contains(&line_to_append) {
+        return Ok(false);
+    }
+
+    // NOTE: Make sure we put the new contents on their own
+    // line and not conflicting with any existing command(s)
+    if !file_contents.is_empty() && !file_contents.ends_with('\n') {
+        file_contents.push('\n');
+    }
+
+    file_contents.push_str(&line_to_append);
+    file_contents.push('\n');
+
+    write(file_path, file_contents).await?;
+
+    Ok(true)
+}
+
+/**
+    Replaces the user home directory in a path with `$HOME`, so that
+    shell profiles keep working if the home directory is moved.
+*/
+pub(super) fn replace_home_path_with_var(path: &str) -> String {
+    let Some(home_dir) = dirs::home_dir() else {
+        return path.to_string();
+    };
+    let Some(home_dir_str) = home_dir.to_str() else {
+        return path.to_string();
+    };
+    path.replace(home_dir_str, "$HOME")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_shell_names() {
+        for shell in ShellKind::ALL {
+            assert_eq!(shell.name().parse::<ShellKind>(), Ok(shell));
+        }
+        assert_eq!("pwsh".parse::<ShellKind>(), Ok(ShellKind::PowerShell));
+        assert!("nushell".parse::<ShellKind>().is_err());
+    }
+
+    #[tokio::test]
+    async fn appends_lines_only_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("profile");
+        std::fs::write(&file, "echo hello").unwrap();
+
+        let line = String::from("export PATH=\"/rokit/bin:$PATH\"");
+        assert!(append_to_shell_file(&file, line.clone(), false)
+            .await
+            .unwrap());
+        assert!(!append_to_shell_file(&file, line.clone(), false)
+            .await
+            .unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            format!("echo hello\n{line}\n")
+        );
+
+        let missing = dir.path().join("missing");
+        assert!(append_to_shell_file(&missing, line.clone(), false)
+            .await
+            .is_err());
+        assert!(append_to_shell_file(&missing, line, true).await.unwrap());
+    }
+}
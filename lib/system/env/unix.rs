@@ -1,24 +1,22 @@
-use std::path::PathBuf;
-
 use futures::{stream::FuturesUnordered, StreamExt};
-use tokio::{
-    fs::{read_to_string, write},
-    io::ErrorKind,
-};
+use tokio::fs::write;
 
 use crate::{
     result::{RokitError, RokitResult},
     storage::Home,
 };
 
-use super::shell::Shell;
+use super::{
+    profile::{append_to_shell_file, replace_home_path_with_var},
+    shell::Shell,
+};
 
 const ENV_SHELL_FILE_PATH: &str = "env";
 const ENV_SHELL_SCRIPT: &str = include_str!("./env.sh");
 
 pub async fn add_to_path(home: &Home) -> RokitResult<bool> {
     // Find our binaries dir and try to format it as "$HOME/.rokit/bin"
-    let bin_dir = home.path().join("bin");
+    let bin_dir = home.link_dir();
     let bin_dir_str = bin_dir.to_str().ok_or(RokitError::InvalidUtf8)?;
     let bin_dir_in_home = replace_home_path_with_var(bin_dir_str);
 
@@ -58,42 +56,3 @@ pub async fn add_to_path(home: &Home) -> RokitResult<bool> {
 
     Ok(added_any)
 }
-
-async fn append_to_shell_file(
-    file_path: PathBuf,
-    line_to_append: String,
-    create_if_nonexistent: bool,
-) -> RokitResult<bool> {
-    let mut file_contents = match read_to_string(&file_path).await {
-        Ok(contents) => contents,
-        Err(e) if e.kind() == ErrorKind::NotFound && create_if_nonexistent => String::new(),
-        Err(e) => return Err(e.into()),
-    };
-
-    if file_contents.contains(&line_to_append) {
-        return Ok(false);
-    }
-
-    // NOTE: Make sure we put the new contents on their own
-    // line and not conflicting with any existing command(s)
-    if !file_contents.ends_with('\n') {
-        file_contents.push('\n');
-    }
-
-    file_contents.push_str(&line_to_append);
-    file_contents.push('\n');
-
-    write(file_path, file_contents).await?;
-
-    Ok(true)
-}
-
-fn replace_home_path_with_var(path: &str) -> String {
-    let Some(home_dir) = dirs::home_dir() else {
-        return path.to_string();
-    };
-    let Some(home_dir_str) = home_dir.to_str() else {
-        return path.to_string();
-    };
-    path.replace(home_dir_str, "$HOME")
-}
@@ -12,7 +12,7 @@ use crate::{
 pub async fn add_to_path(home: &Home) -> RokitResult<bool> {
     // NOTE: Calls to canonicalize may use blocking filesystem
     // operations, so we spawn a task where that's acceptable.
-    let dir = home.path().join("bin");
+    let dir = home.link_dir();
     let task = spawn_blocking(move || {
         let dir = dir.canonicalize()?;
 
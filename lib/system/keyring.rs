@@ -0,0 +1,131 @@
+use keyring::{Entry, Error as KeyringError};
+use tokio::task::spawn_blocking;
+
+use crate::result::{RokitError, RokitResult};
+
+const SERVICE_NAME: &str = "rokit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    MacOSKeychain,
+    WindowsCredentialManager,
+    SecretService,
+}
+
+/**
+    The system keyring, used to store authentication tokens
+    outside of the plaintext auth manifest.
+
+    Supports the macOS Keychain, the Windows Credential Manager,
+    and the Secret Service on Linux and other Unix systems.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keyring {
+    backend: Backend,
+}
+
+impl Keyring {
+    /**
+        Detects the keyring for the current system.
+
+        Returns `None` if the system has no supported keyring.
+    */
+    #[must_use]
+    pub fn detect() -> Option<Self> {
+        let backend = if cfg!(target_os = "macos") {
+            Backend::MacOSKeychain
+        } else if cfg!(windows) {
+            Backend::WindowsCredentialManager
+        } else if cfg!(any(
+            target_os = "linux",
+            target_os = "freebsd",
+            target_os = "openbsd"
+        )) {
+            Backend::SecretService
+        } else {
+            return None;
+        };
+        Some(Self { backend })
+    }
+
+    /**
+        Gets the name of this keyring, such as `macOS Keychain`.
+    */
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self.backend {
+            Backend::MacOSKeychain => "macOS Keychain",
+            Backend::WindowsCredentialManager => "Windows Credential Manager",
+            Backend::SecretService => "Secret Service",
+        }
+    }
+
+    /**
+        Gets the token stored for the given account.
+
+        Returns `None` if no token is stored for the account.
+
+        # Errors
+
+        - If the keyring could not be accessed.
+    */
+    pub async fn get(self, account: &str) -> RokitResult<Option<String>> {
+        with_entry(account, |entry| match entry.get_password() {
+            Ok(token) => Ok(Some(token)),
+            Err(KeyringError::NoEntry) => Ok(None),
+            Err(e) => Err(e),
+        })
+        .await
+    }
+
+    /**
+        Stores the given token for the given account,
+        replacing any token that is already stored for it.
+
+        # Errors
+
+        - If the keyring could not be accessed.
+    */
+    pub async fn set(self, account: &str, token: &str) -> RokitResult<()> {
+        let token = token.to_string();
+        with_entry(account, move |entry| entry.set_password(&token)).await
+    }
+
+    /**
+        Removes the token stored for the given account.
+
+        Returns `true` if a token was removed, `false` if no token was stored.
+
+        # Errors
+
+        - If the keyring could not be accessed.
+    */
+    pub async fn remove(self, account: &str) -> RokitResult<bool> {
+        with_entry(account, |entry| match entry.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(KeyringError::NoEntry) => Ok(false),
+            Err(e) => Err(e),
+        })
+        .await
+    }
+}
+
+/*
+    Runs the given operation on the keyring entry for the given account.
+
+    Keyrings are accessed using blocking calls, some of which
+    may wait for the user to unlock the keyring, so these run
+    on a separate thread to not block the async runtime.
+*/
+async fn with_entry<T, F>(account: &str, f: F) -> RokitResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Entry) -> Result<T, KeyringError> + Send + 'static,
+{
+    let account = account.to_string();
+    spawn_blocking(move || f(&Entry::new(SERVICE_NAME, &account)?))
+        .await?
+        .map_err(|e| RokitError::Keyring {
+            reason: e.to_string(),
+        })
+}
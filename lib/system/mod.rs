@@ -1,9 +1,13 @@
 mod current;
 mod env;
+mod keyring;
 mod process;
 mod runner;
 
-pub use self::current::{current_dir, current_exe, current_exe_contents, current_exe_name};
-pub use self::env::{add_to_path, exists_in_path};
+pub use self::current::{
+    current_dir, current_exe, current_exe_contents, current_exe_name, exe_name_from_path,
+};
+pub use self::env::{add_to_path, exists_in_path, ShellKind};
+pub use self::keyring::Keyring;
 pub use self::process::{Launcher as ProcessLauncher, Parent as ProcessParent};
-pub use self::runner::run_interruptible;
+pub use self::runner::{run_interruptible, run_interruptible_with_env};
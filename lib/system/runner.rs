@@ -1,61 +1,138 @@
 use std::ffi::OsStr;
+use std::future::pending;
 use std::io::Result as IoResult;
+use std::process::ExitStatus;
 
 #[cfg(windows)]
 use command_group::AsyncCommandGroup;
 
+#[cfg(unix)]
 use async_signal::{Signal, Signals};
+#[cfg(unix)]
 use futures::StreamExt;
-use tokio::{
-    process::Command,
-    task::{spawn, JoinHandle},
-};
+use tokio::process::Command;
+#[cfg(windows)]
+use tokio::signal::windows::{ctrl_break, ctrl_c, CtrlBreak, CtrlC};
 
 /*
-    If we got a signal, we'll return 128 + signal number as our exit code.
+    If the process was terminated by a signal, we'll return 128 + signal
+    number as our exit code, the same convention that shells use.
 
     When debugging, and for experienced users, this may
     make it slightly more obvious that the program they were
     running didn't error - it was interrupted by a signal.
 */
+#[cfg(unix)]
 const EXIT_CODE_GOT_SIGNAL: i32 = 128;
 
-fn spawn_signal_listener_task() -> IoResult<JoinHandle<i32>> {
-    let mut signals = if cfg!(target_os = "windows") {
-        Signals::new([Signal::Int])?
-    } else {
-        Signals::new([
+/*
+    Listens for signals sent to Rokit while running a process, so that they
+    can be forwarded to it, instead of terminating Rokit and orphaning it.
+*/
+#[cfg(unix)]
+struct SignalForwarder {
+    signals: Signals,
+}
+
+#[cfg(unix)]
+impl SignalForwarder {
+    fn new() -> IoResult<Self> {
+        let signals = Signals::new([
             Signal::Int,  // Interrupt
             Signal::Term, // Terminate
             Signal::Quit, // Quit
-        ])?
-    };
-
-    let task = spawn(async move {
-        while let Some(result) = signals.next().await {
-            match result {
-                Ok(sig) => return EXIT_CODE_GOT_SIGNAL + (sig as i32),
-                Err(err) => tracing::error!("Failed to listen for signal: {err}"),
+            Signal::Hup,  // Hangup
+        ])?;
+        Ok(Self { signals })
+    }
+
+    async fn forward_next(&mut self, pid: Option<u32>) {
+        let signal = match self.signals.next().await {
+            Some(Ok(signal)) => signal,
+            Some(Err(err)) => {
+                tracing::error!("Failed to listen for signal: {err}");
+                return;
             }
+            None => pending().await,
+        };
+
+        // NOTE: Interrupts and quits from the terminal are sent to the whole
+        // foreground process group, which the process is also a part of, so
+        // forwarding those would make the process receive them twice
+        if matches!(signal, Signal::Int | Signal::Quit) {
+            return;
+        }
+        let Some(pid) = pid.and_then(|pid| libc::pid_t::try_from(pid).ok()) else {
+            return;
+        };
+        tracing::debug!(?signal, pid, "forwarding signal to process");
+        // SAFETY: Sending a signal has no memory safety requirements, and
+        // the pid belongs to our own child process, which was not yet reaped
+        unsafe {
+            libc::kill(pid, signal as i32);
         }
+    }
+}
+
+/*
+    Listens for Ctrl+C and Ctrl+Break while running a process, so that Rokit
+    is not terminated by them - the process also receives them since it is
+    attached to the same console, and decides for itself when to exit.
+*/
+#[cfg(windows)]
+struct SignalForwarder {
+    ctrl_c: CtrlC,
+    ctrl_break: CtrlBreak,
+}
 
-        EXIT_CODE_GOT_SIGNAL
-    });
+#[cfg(windows)]
+impl SignalForwarder {
+    fn new() -> IoResult<Self> {
+        Ok(Self {
+            ctrl_c: ctrl_c()?,
+            ctrl_break: ctrl_break()?,
+        })
+    }
+
+    async fn forward_next(&mut self, _pid: Option<u32>) {
+        let received = tokio::select! {
+            received = self.ctrl_c.recv() => received,
+            received = self.ctrl_break.recv() => received,
+        };
+        if received.is_none() {
+            pending::<()>().await;
+        }
+    }
+}
 
-    Ok(task)
+// Gets the exit code for a process, which on Unix is 128 + signal
+// number if it was terminated by a signal, or 1 if no code exists
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return EXIT_CODE_GOT_SIGNAL + signal;
+        }
+    }
+    status.code().unwrap_or(1)
 }
 
 /**
     Runs the given command with the given arguments and returns its exit code.
 
-    This command is interruptible by passing one of the following signals to Rokit:
+    The exit code of the command is passed through as-is, and if the command
+    was terminated by a signal, the exit code is 128 + the signal number.
 
-    - SIGINT (Ctrl+C)
-    - SIGTERM
-    - SIGQUIT
+    Signals sent to Rokit are forwarded to the command, and Rokit waits for
+    the command to exit, instead of exiting and orphaning it:
 
-    Note that on Windows, only SIGINT (Ctrl+C) is supported, but
-    the process may also be reaped as part of the current job group.
+    - SIGTERM and SIGHUP are forwarded to the command.
+    - SIGINT (Ctrl+C) and SIGQUIT are not forwarded, since the terminal already
+      sends them to the command, which is in the same process group as Rokit.
+
+    On Windows, Ctrl+C and Ctrl+Break are also sent to the command by the console,
+    and the command is reaped as part of the current job group if Rokit exits.
 
     # Errors
 
@@ -68,8 +145,37 @@ where
     A: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    let signal_handle = spawn_signal_listener_task()?;
-    let signal_aborter = signal_handle.abort_handle();
+    run_interruptible_with_env(command, args, Vec::<(String, String)>::new()).await
+}
+
+/**
+    Runs the given command with the given arguments and additional
+    environment variables, and returns its exit code.
+
+    The child process inherits the environment of the current process,
+    with the given variables added on top, replacing any existing values.
+
+    See [`run_interruptible`] for more information.
+
+    # Errors
+
+    - If signal listeners could not be created
+    - If the given command could not be spawned
+*/
+pub async fn run_interruptible_with_env<C, A, S, E, K, V>(
+    command: C,
+    args: A,
+    envs: E,
+) -> IoResult<i32>
+where
+    C: AsRef<OsStr>,
+    A: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    E: IntoIterator<Item = (K, V)>,
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    let mut signals = SignalForwarder::new()?;
 
     /*
         Important - we do not want to leave any zombie
@@ -83,6 +189,7 @@ where
         for inheriting process group but it doesn't seem to work as expected.
     */
     let mut command = Command::new(command);
+    command.envs(envs);
     let mut child = {
         #[cfg(unix)]
         {
@@ -94,21 +201,36 @@ where
         }
     };
 
-    let code = tokio::select! {
-        // If the spawned process exits cleanly, we'll return its exit code,
-        // which may or may not exist. Interpret a non-existent code as 1.
-        command_result = child.wait() => {
-            let code = command_result.ok().and_then(|s| s.code()).unwrap_or(1);
-            signal_aborter.abort();
-            code
-        }
-        // If the command was manually interrupted by a signal, we will
-        // return a special exit code for the signal. More details above.
-        task_result = signal_handle => {
-            child.kill().await.ok();
-            task_result.unwrap_or(EXIT_CODE_GOT_SIGNAL)
+    // NOTE: The process decides for itself how to handle any signals, and
+    // may keep running after them, so we always wait for it to exit
+    let pid = child.id();
+    loop {
+        tokio::select! {
+            // If the spawned process exits, we'll return its exit code,
+            // which may or may not exist. Interpret a non-existent code as 1.
+            command_result = child.wait() => {
+                return Ok(command_result.map_or(1, exit_code));
+            }
+            () = signals.forward_next(pid) => {}
         }
-    };
+    }
+}
 
-    Ok(code)
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn exit_codes_are_passed_through() {
+        let code = run_interruptible("sh", ["-c", "exit 3"]).await.unwrap();
+        assert_eq!(code, 3);
+    }
+
+    #[tokio::test]
+    async fn signals_map_to_exit_codes() {
+        let code = run_interruptible("sh", ["-c", "kill -TERM $$"])
+            .await
+            .unwrap();
+        assert_eq!(code, EXIT_CODE_GOT_SIGNAL + libc::SIGTERM);
+    }
 }
@@ -19,6 +19,8 @@ pub enum ToolIdParseError {
     MissingSeparator,
     #[error("artifact provider '{0}' is invalid")]
     InvalidProvider(String),
+    #[error("host '{0}' is empty or invalid")]
+    InvalidHost(String),
     #[error("author '{0}' is empty or invalid")]
     InvalidAuthor(String),
     #[error("name '{0}' is empty or invalid")]
@@ -32,13 +34,15 @@ pub enum ToolIdParseError {
     their original casing for display and serialization purposes.
     See [`CaseInsensitiveString`] for more information.
 
-    Also includes the provider of the artifact, which by default is `GitHub`.
+    Also includes the provider of the artifact, which by default is `GitHub`,
+    and the host of the provider instance, for providers that require one.
 
     Used to uniquely identify a tool, but not its version.
 */
 #[derive(Debug, Clone, PartialEq, Eq, Hash, DeserializeFromStr, SerializeDisplay)]
 pub struct ToolId {
     pub(crate) provider: ArtifactProvider,
+    pub(crate) host: Option<CaseInsensitiveString>,
     pub(crate) author: CaseInsensitiveString,
     pub(crate) name: CaseInsensitiveString,
 }
@@ -49,6 +53,17 @@ impl ToolId {
         self.provider
     }
 
+    /**
+        Returns the host of the provider instance for this tool, such as `codeberg.org`.
+
+        This is only present for providers that require a host, see
+        [`ArtifactProvider::requires_host`] for more information.
+    */
+    #[must_use]
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_ref().map(CaseInsensitiveString::original_str)
+    }
+
     #[must_use]
     pub fn author(&self) -> &str {
         self.author.original_str()
@@ -68,6 +83,31 @@ impl ToolId {
     pub fn into_alias(self) -> ToolAlias {
         ToolAlias::from(self)
     }
+
+    /**
+        Creates a copy of this id using the casing of the given full name, such as
+        `Author/Name`, which is usually the full name as returned by the provider.
+
+        The full name is ignored if it is not the same id apart from its casing,
+        such as for repositories that were renamed, which are never followed.
+    */
+    #[must_use]
+    pub(crate) fn with_casing_of(&self, full_name: &str) -> Self {
+        let Some((author, name)) = full_name.split_once('/') else {
+            return self.clone();
+        };
+        let cased = Self {
+            provider: self.provider,
+            host: self.host.clone(),
+            author: CaseInsensitiveString::new(author),
+            name: CaseInsensitiveString::new(name),
+        };
+        if cased == *self {
+            cased
+        } else {
+            self.clone()
+        }
+    }
 }
 
 impl Ord for ToolId {
@@ -75,6 +115,8 @@ impl Ord for ToolId {
         self.author
             .cmp(&other.author)
             .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.provider.cmp(&other.provider))
+            .then_with(|| self.host.cmp(&other.host))
     }
 }
 
@@ -95,12 +137,25 @@ impl FromStr for ToolId {
             None => (ArtifactProvider::default(), s),
             Some((left, right)) => {
                 let provider = ArtifactProvider::from_str(left)
-                    .map_err(|e| ToolIdParseError::InvalidProvider(e.to_string()))?;
+                    .map_err(|e| ToolIdParseError::InvalidProvider(e.clone()))?;
                 (provider, right)
             }
         };
 
-        let Some((before, after)) = after_provider.split_once('/') else {
+        let (host, after_host) = if provider.requires_host() {
+            let Some((host, rest)) = after_provider.split_once('/') else {
+                return Err(ToolIdParseError::MissingSeparator);
+            };
+            let host = host.trim();
+            if is_invalid_host(host) {
+                return Err(ToolIdParseError::InvalidHost(host.to_string()));
+            }
+            (Some(CaseInsensitiveString::new(host)), rest)
+        } else {
+            (None, after_provider)
+        };
+
+        let Some((before, after)) = after_host.split_once('/') else {
             return Err(ToolIdParseError::MissingSeparator);
         };
 
@@ -116,6 +171,7 @@ impl FromStr for ToolId {
 
         Ok(Self {
             provider,
+            host,
             author: CaseInsensitiveString::new(before),
             name: CaseInsensitiveString::new(after),
         })
@@ -124,6 +180,15 @@ impl FromStr for ToolId {
 
 impl fmt::Display for ToolId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // NOTE: The default provider is omitted to keep ids short and
+        // backwards compatible, other providers must be written out
+        // for the id to round-trip through manifests and caches
+        if self.provider != ArtifactProvider::default() {
+            write!(f, "{}:", self.provider)?;
+        }
+        if let Some(host) = &self.host {
+            write!(f, "{}/", host.original_str())?;
+        }
         write!(
             f,
             "{}/{}",
@@ -133,6 +198,13 @@ impl fmt::Display for ToolId {
     }
 }
 
+// Hosts may contain a port, but no other separators or whitespace
+fn is_invalid_host(s: &str) -> bool {
+    s.is_empty()
+        || s.starts_with(':')
+        || s.chars().any(|c| c.is_whitespace() || c == '/' || c == '@')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,6 +212,7 @@ mod tests {
     fn new_id_with_provider(provider: ArtifactProvider, author: &str, name: &str) -> ToolId {
         ToolId {
             provider,
+            host: None,
             author: CaseInsensitiveString::new(author),
             name: CaseInsensitiveString::new(name),
         }
@@ -188,11 +261,77 @@ mod tests {
     fn parse_valid_provider() {
         // Known provider strings should parse ok
         assert!("github:a/b".parse::<ToolId>().is_ok());
+        assert!("gitlab:a/b".parse::<ToolId>().is_ok());
         // The parsed ToolId should match the input
         assert_eq!(
             "github:a/b".parse::<ToolId>().unwrap(),
             new_id_with_provider(ArtifactProvider::GitHub, "a", "b")
         );
+        assert_eq!(
+            "gitlab:a/b".parse::<ToolId>().unwrap(),
+            new_id_with_provider(ArtifactProvider::GitLab, "a", "b")
+        );
+        // Different providers should not be equal
+        assert_ne!(
+            "github:a/b".parse::<ToolId>().unwrap(),
+            "gitlab:a/b".parse::<ToolId>().unwrap()
+        );
+    }
+
+    #[test]
+    fn display_provider_roundtrip() {
+        // The default provider should be omitted
+        assert_eq!(new_id("a", "b").to_string(), "a/b");
+        assert_eq!("github:a/b".parse::<ToolId>().unwrap().to_string(), "a/b");
+        // Other providers should be kept so that ids round-trip
+        let id = new_id_with_provider(ArtifactProvider::GitLab, "a", "b");
+        assert_eq!(id.to_string(), "gitlab:a/b");
+        assert_eq!(id.to_string().parse::<ToolId>().unwrap(), id);
+    }
+
+    #[test]
+    fn parse_valid_host() {
+        // Providers that require a host should parse it before the author
+        let id = "gitea:codeberg.org/a/b".parse::<ToolId>().unwrap();
+        assert_eq!(id.provider(), ArtifactProvider::Gitea);
+        assert_eq!(id.host(), Some("codeberg.org"));
+        assert_eq!(id.author(), "a");
+        assert_eq!(id.name(), "b");
+        assert_eq!(id.to_string(), "gitea:codeberg.org/a/b");
+        assert_eq!(id.to_string().parse::<ToolId>().unwrap(), id);
+        // Hosts may include a port, and are not case sensitive
+        let id = "gitea:localhost:3000/a/b".parse::<ToolId>().unwrap();
+        assert_eq!(id.host(), Some("localhost:3000"));
+        assert_eq!(
+            "gitea:Codeberg.org/a/b".parse::<ToolId>().unwrap(),
+            "gitea:codeberg.org/a/b".parse::<ToolId>().unwrap()
+        );
+        // Different hosts should not be equal
+        assert_ne!(
+            "gitea:codeberg.org/a/b".parse::<ToolId>().unwrap(),
+            "gitea:example.com/a/b".parse::<ToolId>().unwrap()
+        );
+        // GitHub Enterprise also requires a host
+        let id = "ghe:github.mycorp.com/a/b".parse::<ToolId>().unwrap();
+        assert_eq!(id.provider(), ArtifactProvider::GitHubEnterprise);
+        assert_eq!(id.host(), Some("github.mycorp.com"));
+        assert_eq!(id.to_string(), "ghe:github.mycorp.com/a/b");
+        assert_eq!(
+            "github-enterprise:github.mycorp.com/a/b"
+                .parse::<ToolId>()
+                .unwrap(),
+            id
+        );
+        // Other providers should not have a host
+        assert_eq!("gitlab:a/b".parse::<ToolId>().unwrap().host(), None);
+    }
+
+    #[test]
+    fn parse_invalid_host() {
+        // Providers that require a host must have one
+        assert!("gitea:a/b".parse::<ToolId>().is_err());
+        assert!("gitea:/a/b".parse::<ToolId>().is_err());
+        assert!("gitea: /a/b".parse::<ToolId>().is_err());
     }
 
     #[test]
@@ -270,4 +409,17 @@ mod tests {
         assert_eq!(map.get(&new_id("Author", "Name")), Some(&2));
         assert_eq!(map.get(&new_id("123ABC456", "78DE90")), Some(&3));
     }
+
+    #[test]
+    fn casing_of_full_names() {
+        let id = new_id("author", "name");
+        let cased = id.with_casing_of("Author/Name");
+        assert_eq!(cased.to_string(), "Author/Name");
+        assert_eq!(id.with_casing_of("Other/Name").to_string(), "author/name");
+        assert_eq!(
+            id.with_casing_of("group/author/name").to_string(),
+            "author/name"
+        );
+        assert_eq!(id.with_casing_of("invalid").to_string(), "author/name");
+    }
 }
@@ -1,8 +1,10 @@
 mod alias;
 mod id;
 mod spec;
+mod spec_req;
 mod util;
 
 pub use self::alias::{ToolAlias, ToolAliasParseError};
 pub use self::id::{ToolId, ToolIdParseError};
 pub use self::spec::{ToolSpec, ToolSpecParseError};
+pub use self::spec_req::{ToolSpecReq, ToolVersionReq};
@@ -25,8 +25,8 @@ pub enum ToolSpecParseError {
     VersionParseError(#[from] semver::Error),
     #[error(
         "{0}\nNote: It seems like you may be trying to use a version \
-        requirement, which is not supported in Rokit. To use this tool, \
-        specify an exact version instead."
+        requirement, which is only supported for tools in manifests. \
+        Specify an exact version here instead."
     )]
     VersionParseErrorSuspectedVersionReq(String),
 }
@@ -0,0 +1,281 @@
+use std::{fmt, str::FromStr};
+
+use semver::{Version, VersionReq};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+
+use super::{util::is_invalid_identifier, ToolId, ToolSpec, ToolSpecParseError};
+
+const LATEST_KEYWORD: &str = "latest";
+const CLOSEST_VERSIONS_BELOW: usize = 2;
+const CLOSEST_VERSIONS_ABOVE: usize = 3;
+
+/**
+    A version requirement for a tool.
+
+    May be an exact version, a semver version range, or the latest version.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ToolVersionReq {
+    /// An exact version, such as `1.2.3`.
+    Exact(Version),
+    /// A semver version range, such as `^1.2` or `~1.2.0`.
+    Range(VersionReq),
+    /// The latest version, excluding any prereleases.
+    Latest,
+}
+
+impl ToolVersionReq {
+    /**
+        Checks if the given version satisfies this requirement.
+    */
+    #[must_use]
+    pub fn matches(&self, version: &Version) -> bool {
+        match self {
+            Self::Exact(exact) => exact == version,
+            Self::Range(req) => req.matches(version),
+            Self::Latest => version.pre.is_empty(),
+        }
+    }
+
+    /**
+        Checks if this requirement may be satisfied by prerelease versions.
+    */
+    #[must_use]
+    pub fn allows_prereleases(&self) -> bool {
+        match self {
+            Self::Exact(exact) => !exact.pre.is_empty(),
+            Self::Range(req) => req.comparators.iter().any(|c| !c.pre.is_empty()),
+            Self::Latest => false,
+        }
+    }
+
+    /**
+        Finds the highest version satisfying this requirement, if any.
+    */
+    #[must_use]
+    pub fn resolve<'a>(&self, versions: impl IntoIterator<Item = &'a Version>) -> Option<Version> {
+        versions
+            .into_iter()
+            .filter(|version| self.matches(version))
+            .max()
+            .cloned()
+    }
+
+    /**
+        Finds the versions closest to this requirement, out of the given sorted
+        versions. Used to give helpful suggestions when nothing matches.
+    */
+    #[must_use]
+    pub fn closest(&self, sorted_versions: &[Version]) -> Vec<Version> {
+        let target = match self {
+            Self::Exact(exact) => exact.clone(),
+            Self::Range(req) => match req.comparators.first() {
+                Some(c) => Version::new(c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0)),
+                None => return Vec::new(),
+            },
+            Self::Latest => Version::new(u64::MAX, 0, 0),
+        };
+
+        let index = sorted_versions.partition_point(|version| *version < target);
+        let start = index.saturating_sub(CLOSEST_VERSIONS_BELOW);
+        let end = (index + CLOSEST_VERSIONS_ABOVE).min(sorted_versions.len());
+        sorted_versions[start..end].to_vec()
+    }
+}
+
+impl FromStr for ToolVersionReq {
+    type Err = ToolSpecParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case(LATEST_KEYWORD) {
+            return Ok(Self::Latest);
+        }
+        if let Ok(version) = s.parse::<Version>() {
+            return Ok(Self::Exact(version));
+        }
+        Ok(Self::Range(s.parse::<VersionReq>()?))
+    }
+}
+
+impl fmt::Display for ToolVersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(version) => version.fmt(f),
+            Self::Range(req) => req.fmt(f),
+            Self::Latest => LATEST_KEYWORD.fmt(f),
+        }
+    }
+}
+
+/**
+    A tool specification with a version requirement, as found in manifests.
+
+    This is similar to a [`ToolSpec`], but may not point to a specific
+    version, and must be resolved to a [`ToolSpec`] before being used.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, DeserializeFromStr, SerializeDisplay)]
+pub struct ToolSpecReq {
+    pub(crate) id: ToolId,
+    pub(crate) version: ToolVersionReq,
+}
+
+impl ToolSpecReq {
+    #[must_use]
+    pub fn id(&self) -> &ToolId {
+        &self.id
+    }
+
+    #[must_use]
+    pub fn version_req(&self) -> &ToolVersionReq {
+        &self.version
+    }
+
+    /**
+        Returns the exact tool specification, if this requirement is for an exact version.
+    */
+    #[must_use]
+    pub fn exact(&self) -> Option<ToolSpec> {
+        match &self.version {
+            ToolVersionReq::Exact(version) => {
+                Some(ToolSpec::from((self.id.clone(), version.clone())))
+            }
+            _ => None,
+        }
+    }
+
+    /**
+        Checks if the given tool specification satisfies this requirement.
+    */
+    #[must_use]
+    pub fn matches(&self, spec: &ToolSpec) -> bool {
+        self.id == spec.id && self.version.matches(&spec.version)
+    }
+}
+
+impl FromStr for ToolSpecReq {
+    type Err = ToolSpecParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ToolSpecParseError::Empty);
+        }
+
+        let Some((before, after)) = s.split_once('@') else {
+            return Err(ToolSpecParseError::MissingVersionSeparator);
+        };
+
+        let id = before.trim().parse::<ToolId>()?;
+
+        let after = after.trim();
+        if is_invalid_identifier(after) {
+            return Err(ToolSpecParseError::InvalidVersion(after.to_string()));
+        }
+
+        let version = after.parse::<ToolVersionReq>()?;
+        Ok(Self { id, version })
+    }
+}
+
+impl fmt::Display for ToolSpecReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.id, self.version)
+    }
+}
+
+impl From<ToolSpec> for ToolSpecReq {
+    fn from(spec: ToolSpec) -> Self {
+        Self {
+            id: spec.id,
+            version: ToolVersionReq::Exact(spec.version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> ToolVersionReq {
+        s.parse().unwrap()
+    }
+
+    fn versions(vs: &[&str]) -> Vec<Version> {
+        vs.iter().map(|v| v.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn parse_version_reqs() {
+        assert_eq!(req("1.2.3"), ToolVersionReq::Exact(Version::new(1, 2, 3)));
+        assert_eq!(req("latest"), ToolVersionReq::Latest);
+        assert_eq!(req("LATEST"), ToolVersionReq::Latest);
+        assert!(matches!(req("^1.2"), ToolVersionReq::Range(_)));
+        assert!(matches!(req("~1.2.0"), ToolVersionReq::Range(_)));
+        assert!(matches!(req(">=1.0.0, <2.0.0"), ToolVersionReq::Range(_)));
+        assert!("not a version".parse::<ToolVersionReq>().is_err());
+    }
+
+    #[test]
+    fn parse_spec_reqs() {
+        let spec_req = "author/name@^1.2".parse::<ToolSpecReq>().unwrap();
+        assert_eq!(spec_req.id().name(), "name");
+        assert!(spec_req.exact().is_none());
+        assert_eq!(spec_req.to_string(), "author/name@^1.2");
+
+        let spec_req = "author/name@1.2.3".parse::<ToolSpecReq>().unwrap();
+        assert_eq!(
+            spec_req.exact(),
+            Some("author/name@1.2.3".parse::<ToolSpec>().unwrap())
+        );
+
+        assert!("author/name".parse::<ToolSpecReq>().is_err());
+        assert!("author/name@".parse::<ToolSpecReq>().is_err());
+    }
+
+    #[test]
+    fn spec_reqs_round_trip() {
+        for s in [
+            "author/name@1.2.3",
+            "Author/Name@^1.2",
+            "author/name@1.2",
+            "author/name@LATEST",
+            " author/name @ >=1.0.0, <2.0.0 ",
+            "github:author/name@1.2.3",
+            "gitlab:author/name@~1.2.0",
+            "gitea:codeberg.org/author/name@1.0.0-rc.1",
+        ] {
+            let parsed = s.parse::<ToolSpecReq>().unwrap();
+            let formatted = parsed.to_string();
+            let reparsed = formatted.parse::<ToolSpecReq>().unwrap();
+            assert_eq!(parsed, reparsed);
+            assert_eq!(reparsed.to_string(), formatted);
+        }
+    }
+
+    #[test]
+    fn resolve_highest_matching() {
+        let available = versions(&["1.1.0", "1.2.0", "1.2.5", "1.3.0", "2.0.0", "2.1.0-rc.1"]);
+        assert_eq!(req("^1.2").resolve(&available), Some(Version::new(1, 3, 0)));
+        assert_eq!(
+            req("~1.2.0").resolve(&available),
+            Some(Version::new(1, 2, 5))
+        );
+        assert_eq!(
+            req("latest").resolve(&available),
+            Some(Version::new(2, 0, 0))
+        );
+        assert_eq!(
+            req("1.1.0").resolve(&available),
+            Some(Version::new(1, 1, 0))
+        );
+        assert_eq!(req("^3").resolve(&available), None);
+    }
+
+    #[test]
+    fn closest_versions() {
+        let available = versions(&["1.0.0", "1.1.0", "1.2.0", "2.0.0", "3.0.0", "4.0.0"]);
+        assert_eq!(
+            req("^1.5").closest(&available),
+            versions(&["1.1.0", "1.2.0", "2.0.0", "3.0.0", "4.0.0"])
+        );
+        assert_eq!(req("^5").closest(&available), versions(&["3.0.0", "4.0.0"]));
+    }
+}
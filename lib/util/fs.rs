@@ -1,7 +1,7 @@
 use std::{env::consts::EXE_EXTENSION, path::Path, str::FromStr};
 
-use tokio::fs::{metadata, read_to_string, write};
-use tracing::{error, warn};
+use tokio::fs::{metadata, read_to_string, remove_file, rename, write};
+use tracing::error;
 
 use crate::result::{RokitError, RokitResult};
 
@@ -53,8 +53,14 @@ pub async fn path_exists(path: impl AsRef<Path>) -> bool {
 }
 
 /**
-    Writes the given contents to the file at the
-    given path, and adds executable permissions to it.
+    Writes the given contents to the file at the given path, and adds
+    executable permissions to it, verifying that the file is executable.
+
+    On Windows, the path must have an executable (`.exe`) extension.
+
+    The contents are first written to a temporary file next to the given path,
+    which then replaces it, so that an interrupted write never leaves a
+    partially written executable at the given path.
 */
 pub async fn write_executable_file(
     path: impl AsRef<Path>,
@@ -65,40 +71,97 @@ pub async fn write_executable_file(
     if !EXE_EXTENSION.is_empty() {
         match path.extension() {
             Some(extension) if extension == EXE_EXTENSION => {}
-            _ => warn!(
-                "An executable file was written without an executable extension!\
-                \nThe file at '{path:?}' may not be usable.\
-                \nThis is most likely a bug in Rokit, please report it at {}",
-                env!("CARGO_PKG_REPOSITORY").trim_end_matches(".git")
-            ),
+            _ => {
+                error!(
+                    "An executable file was about to be written without an executable extension!\
+                    \nThis is most likely a bug in Rokit, please report it at {}",
+                    env!("CARGO_PKG_REPOSITORY").trim_end_matches(".git")
+                );
+                return Err(RokitError::NotExecutable {
+                    path: path.into(),
+                    reason: format!("missing .{EXE_EXTENSION} extension"),
+                });
+            }
         }
     }
-    if let Err(e) = write(path, contents).await {
+
+    let mut partial_name = path.file_name().unwrap_or_default().to_os_string();
+    partial_name.push(".partial");
+    let partial_path = path.with_file_name(partial_name);
+
+    let result = async {
+        write(&partial_path, contents).await?;
+        add_executable_permissions(&partial_path).await?;
+        rename(&partial_path, path).await?;
+        RokitResult::Ok(())
+    }
+    .await;
+    if let Err(e) = result {
         error!("Failed to write executable to {path:?}:\n{e}");
-        return Err(e.into());
+        remove_file(&partial_path).await.ok();
+        return Err(e);
     }
 
-    add_executable_permissions(path).await?;
-
     Ok(())
 }
 
+/**
+    Makes sure that the file at the given path has executable permissions,
+    adding them if necessary, and verifying that they were actually set.
+
+    This is a no-op on platforms without executable permissions, such as Windows.
+*/
 #[cfg(unix)]
-async fn add_executable_permissions(path: impl AsRef<Path>) -> RokitResult<()> {
+pub async fn add_executable_permissions(path: impl AsRef<Path>) -> RokitResult<()> {
     use std::fs::Permissions;
     use std::os::unix::fs::PermissionsExt;
     use tokio::fs::set_permissions;
 
     let path = path.as_ref();
+    let not_executable = |reason: String| RokitError::NotExecutable {
+        path: path.into(),
+        reason,
+    };
+
     if let Err(e) = set_permissions(path, Permissions::from_mode(0o755)).await {
         error!("Failed to set executable permissions on {path:?}:\n{e}");
-        return Err(e.into());
+        return Err(not_executable(e.to_string()));
+    }
+
+    // NOTE: Some file systems, such as certain network mounts, may silently
+    // ignore permission changes, so we verify that they actually got set
+    let mode = metadata(path)
+        .await
+        .map_err(|e| not_executable(e.to_string()))?
+        .permissions()
+        .mode();
+    if mode & 0o111 != 0o111 {
+        return Err(not_executable(format!(
+            "permissions are {:o} after setting them to 755",
+            mode & 0o777
+        )));
     }
 
     Ok(())
 }
 
 #[cfg(not(unix))]
-async fn add_executable_permissions(_path: impl AsRef<Path>) -> RokitResult<()> {
+pub async fn add_executable_permissions(_path: impl AsRef<Path>) -> RokitResult<()> {
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn write_executable_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool").with_extension(EXE_EXTENSION);
+        write_executable_file(&path, b"first").await.unwrap();
+        write_executable_file(&path, b"second").await.unwrap();
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"second");
+        let entries = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(entries, 1, "no partial files should be left behind");
+    }
+}
@@ -85,3 +85,49 @@ impl fmt::Display for CaseInsensitiveString {
         self.original_str().fmt(f)
     }
 }
+
+/**
+    Normalizes a host name, such as `GitHub.com`, for comparisons
+    and for use as a key - host names are case-insensitive.
+
+    For internal use only.
+*/
+pub(crate) fn normalize_host(host: &str) -> String {
+    host.trim().to_ascii_lowercase()
+}
+
+/**
+    Computes the edit distance between two strings, which is the number of single
+    character insertions, deletions, and substitutions needed to turn one into the other.
+
+    For internal use only.
+*/
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("rojo", "rojo"), 0);
+        assert_eq!(edit_distance("", "rojo"), 4);
+        assert_eq!(edit_distance("ownr/repo", "owner/repo"), 1);
+        assert_eq!(edit_distance("rjoo", "rojo"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
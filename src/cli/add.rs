@@ -1,16 +1,20 @@
+use std::path::Path;
+
 use anyhow::{bail, Context, Result};
 use clap::Parser;
 use console::style;
 
 use rokit::{
     discovery::discover_all_manifests,
-    manifests::RokitManifest,
+    install::{install_spec_with_options, InstallOptions},
+    manifests::{RokitManifest, ToolVerify},
     storage::Home,
     tool::{ToolAlias, ToolId},
 };
 
 use crate::util::{
-    find_most_compatible_artifact, prompt_for_trust, CliProgressTracker, ToolIdOrSpec,
+    find_most_compatible_artifact, parse_artifact_pattern, parse_minisign_key, prompt_for_trust,
+    CliProgressTracker, ToolIdOrSpec, INSTALL_SUBTASKS,
 };
 
 /// Adds a new tool to Rokit and installs it.
@@ -21,12 +25,17 @@ pub struct AddSubcommand {
     pub tool: ToolIdOrSpec,
     /// The name that will be used to run the tool.
     pub alias: Option<ToolAlias>,
+    /// The name of the binary to use, for tools that ship several binaries,
+    /// or a binary named differently than the tool. May also be a path inside
+    /// of the archive, such as `bin/name`. The alias defaults to this name.
+    #[clap(long)]
+    pub bin: Option<String>,
     /// Add this tool globally instead of adding
     /// it to the nearest manifest file.
     #[clap(long)]
     pub global: bool,
-    /// Force add and install the tool, even
-    /// if it is already added or installed.
+    /// Force add and install the tool, even if it is already installed,
+    /// or if its alias is already used by a different tool.
     #[clap(long)]
     pub force: bool,
 }
@@ -34,9 +43,10 @@ pub struct AddSubcommand {
 impl AddSubcommand {
     pub async fn run(self, home: &Home) -> Result<()> {
         let id: ToolId = self.tool.clone().into();
-        let alias: ToolAlias = match self.alias.as_ref() {
-            Some(alias) => alias.clone(),
-            None => self.tool.clone().into(),
+        let alias: ToolAlias = match (self.alias.as_ref(), self.bin.as_deref()) {
+            (Some(alias), _) => alias.clone(),
+            (None, Some(bin)) => alias_for_binary(bin).unwrap_or_else(|| self.tool.clone().into()),
+            (None, None) => self.tool.clone().into(),
         };
 
         let tool_cache = home.tool_cache();
@@ -44,15 +54,16 @@ impl AddSubcommand {
         let source = home.artifact_source().await?;
 
         // 1. Check for trust, or prompt the user to trust the tool
-        if !tool_cache.is_trusted(&id) {
+        let trust_manifest = home.trust_manifest().await?;
+        if !tool_cache.is_trusted(&id) && !trust_manifest.is_trusted(&id) {
             if !self.force && !prompt_for_trust(id.clone()).await? {
                 bail!("Tool is not trusted - operation was aborted");
             }
             let _ = tool_cache.add_trust(id.clone());
         }
 
-        // 2. Load manifest and do a preflight check to
-        // ensure we don't overwrite any existing tool(s)
+        // 2. Load manifest and do a preflight check to ensure we
+        // don't overwrite any existing tool(s) with a different id
         let manifest_path = if self.global {
             home.path().to_path_buf()
         } else {
@@ -71,54 +82,97 @@ impl AddSubcommand {
         } else {
             RokitManifest::load(&manifest_path).await?
         };
-        if manifest.has_tool(&alias) && !self.force {
+        let existing = manifest.get_tool_requirement(&alias);
+        if existing.as_ref().is_some_and(|e| e.id() != &id) && !self.force {
             let global_flag = if self.global { "--global " } else { "" };
             bail!(
-                "Tool already exists and can't be added: {id}\n\
-                \n  - To update the tool, run `rokit update {global_flag}{id}`\
-                \n  - To remove the tool, run `rokit remove {global_flag}{id}`"
+                "Alias '{alias}' is already used by a different tool: {}\n\
+                \n  - To replace the tool, run `rokit add --force {global_flag}{id} {alias}`\
+                \n  - To remove the tool, run `rokit uninstall {global_flag}{alias}`",
+                existing.unwrap().id()
             );
         }
 
+        // NOTE: Existing tools keep their other settings when updated
+        // in place, so any minisign key must also be used to verify
+        let minisign_key =
+            parse_minisign_key(&alias, manifest.get_tool_minisign_key(&alias).as_deref())?;
+        let artifact_pattern = parse_artifact_pattern(
+            &alias,
+            manifest.get_tool_artifact_pattern(&alias).as_deref(),
+        )?;
+        let tag_prefix = manifest.get_tool_tag_prefix(&alias);
+
         // 3. If we only got an id without a specified version, we
         // will fetch the latest non-prerelease release and use that
-        let pt = CliProgressTracker::new_with_message("Fetching", 3);
-        let (spec, artifact) = match self.tool.clone() {
+        let pt =
+            CliProgressTracker::new_with_message_and_subtasks("Fetching", 1, 1 + INSTALL_SUBTASKS);
+        let (spec, artifact, artifacts) = match self.tool.clone() {
             ToolIdOrSpec::Spec(spec) => {
-                let artifacts = source.get_specific_release(&spec).await?;
-                let artifact = find_most_compatible_artifact(&artifacts, &id)?;
-                (spec, artifact)
+                let artifacts = source
+                    .get_specific_release_with_tag_prefix(&spec, tag_prefix.as_deref())
+                    .await?;
+                let artifact =
+                    find_most_compatible_artifact(&artifacts, &id, artifact_pattern.as_ref())?;
+                (spec, artifact, artifacts)
             }
             ToolIdOrSpec::Id(id) => {
                 let artifacts = source.get_latest_release(&id).await?;
-                let artifact = find_most_compatible_artifact(&artifacts, &id)?;
-                (artifact.tool_spec.clone(), artifact)
+                let artifact =
+                    find_most_compatible_artifact(&artifacts, &id, artifact_pattern.as_ref())?;
+                (artifact.tool_spec.clone(), artifact, artifacts)
             }
         };
-        pt.task_completed();
+        pt.subtask_completed();
 
         // 4. Add the tool spec to the desired manifest file and save it
-        manifest.add_tool(&alias, &spec);
+        // NOTE: Existing tools are updated in place, to keep any other settings
+        if let Some(existing) = &existing {
+            manifest.update_tool(&alias, &spec);
+            pt.print_message(format!(
+                "{} Tool {} was already added as {existing}, and has been changed to {spec}",
+                style("Warning:").bold().yellow(),
+                style(alias.to_string()).bold().cyan(),
+            ));
+        } else {
+            manifest.add_tool(&alias, &spec);
+        }
+        if let Some(bin) = &self.bin {
+            manifest.set_tool_binary(&alias, bin);
+        }
         manifest.save(manifest_path).await?;
 
         // 5. Download and install the tool
         if !tool_cache.is_installed(&spec) || self.force {
-            let contents = source
-                .download_artifact_contents(&artifact)
-                .await
-                .with_context(|| format!("Failed to download contents for {spec}"))?;
-            pt.task_completed();
             pt.update_message("Installing");
-            let extracted = artifact
-                .extract_contents(contents)
-                .await
-                .with_context(|| format!("Failed to extract contents for {spec}"))?;
-            tool_storage.replace_tool_contents(&spec, extracted).await?;
-            pt.task_completed();
-            let _ = tool_cache.add_installed(spec.clone());
+            let binary = manifest.get_tool_binary(&alias);
+            let verify = manifest.get_tool_verify(&alias);
+            let post_install = manifest.get_tool_post_install(&alias);
+            let options = InstallOptions {
+                binary: binary.as_deref(),
+                alias: Some(&alias),
+                artifact_pattern: artifact_pattern.as_ref(),
+                minisign_key: minisign_key.as_ref(),
+                tag_prefix: tag_prefix.as_deref(),
+                verify_args: verify.as_ref().and_then(ToolVerify::args),
+                post_install: post_install.as_ref(),
+                release: Some(&artifacts),
+                artifact: Some(&artifact),
+            };
+            let bar = pt.new_tool_bar(&spec, "installing");
+            let installed = install_spec_with_options(home, &source, &spec, options, |p| {
+                bar.report_install(p);
+            })
+            .await
+            .with_context(|| format!("Failed to install {spec}"))?;
+            drop(bar);
+            if let Some(e) = installed.post_install_error {
+                pt.print_message(format!("{} {e}", style("Warning:").bold().yellow()));
+            }
         } else {
-            pt.task_completed();
-            pt.task_completed();
+            for _ in 0..INSTALL_SUBTASKS {
+                pt.subtask_completed();
+            }
         }
 
         // 6. Create the tool alias link
@@ -141,3 +195,11 @@ impl AddSubcommand {
         Ok(())
     }
 }
+
+// Tools with a binary named differently than the tool are run using the name
+// of the binary, so that a repository `foo-tools` shipping `bar` is run as `bar`
+fn alias_for_binary(bin: &str) -> Option<ToolAlias> {
+    let name = Path::new(bin).file_name()?.to_str()?;
+    let name = name.strip_suffix(".exe").unwrap_or(name);
+    name.parse().ok()
+}
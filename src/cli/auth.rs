@@ -0,0 +1,385 @@
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use console::style;
+use futures::future::join_all;
+
+use rokit::{manifests::AuthManifest, sources::ArtifactProvider, storage::Home, system::Keyring};
+
+use crate::util::{print_message, prompt_for_token, CliProgressTracker};
+
+use super::authenticate::{is_token_valid, verify_token};
+
+/// Manages authentication tokens for artifact providers, such as GitHub.
+#[derive(Debug, Parser)]
+pub struct AuthSubcommand {
+    #[clap(subcommand)]
+    pub command: AuthCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthCommand {
+    Login(AuthLoginSubcommand),
+    Logout(AuthLogoutSubcommand),
+    Status(AuthStatusSubcommand),
+}
+
+impl AuthSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        match self.command {
+            AuthCommand::Login(cmd) => cmd.run(home).await,
+            AuthCommand::Logout(cmd) => cmd.run(home).await,
+            AuthCommand::Status(cmd) => cmd.run(home).await,
+        }
+    }
+}
+
+/// Stores a token for an artifact provider, after verifying it.
+///
+/// The token is prompted for if not given using `--token`.
+/// Any existing token for the provider is replaced.
+#[derive(Debug, Parser)]
+pub struct AuthLoginSubcommand {
+    /// The artifact / tool provider to log in to.
+    pub provider: ArtifactProvider,
+    /// The host to log in to, such as `codeberg.org`.
+    /// Required for providers with several hosts, such as Gitea.
+    #[clap(long)]
+    pub host: Option<String>,
+    /// The token to use for authentication.
+    #[clap(long)]
+    pub token: Option<String>,
+    /// If parsing validation should be skipped for the token.
+    #[clap(long)]
+    pub skip_parse: bool,
+    /// If live API verification should be skipped for the token.
+    #[clap(long)]
+    pub skip_verify: bool,
+    /// Store the token in the system keyring, such as the macOS Keychain, the Windows
+    /// Credential Manager, or the Secret Service on Linux, and only a reference to it
+    /// in the auth manifest.
+    /// Falls back to storing the token in the auth manifest if no keyring is available.
+    #[clap(long)]
+    pub keyring: bool,
+}
+
+impl AuthLoginSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let host = parse_host(self.provider, self.host, "login")?;
+        let styled_provider = styled_provider(self.provider, host.as_deref());
+
+        let token = match self.token {
+            Some(token) => token.trim().to_string(),
+            None => prompt_for_token(provider_name(self.provider, host.as_deref())).await?,
+        };
+        if token.is_empty() {
+            bail!("A token must be given to log in to {styled_provider}.");
+        }
+
+        let pt = CliProgressTracker::new_with_message("Verifying", 2);
+        verify_token(
+            self.provider,
+            host.as_deref(),
+            &token,
+            self.skip_parse,
+            self.skip_verify,
+        )
+        .await?;
+        pt.task_completed();
+
+        pt.update_message("Saving");
+        let mut auth = AuthManifest::load_or_create(home.path())
+            .await
+            .context("Failed to load or create auth manifest")?;
+        let account = AuthManifest::keyring_account(self.provider, host.as_deref());
+        let previous_keyring_account = auth.get_keyring_account(self.provider, host.as_deref());
+        let keyring = if self.keyring {
+            store_in_keyring(&account, &token).await
+        } else {
+            None
+        };
+        let replaced = match (keyring, &host) {
+            (Some(_), _) => auth.set_keyring_token(self.provider, host.as_deref()),
+            (None, Some(host)) => auth.set_host_token(self.provider, host, token),
+            (None, None) => auth.set_token(self.provider, token),
+        };
+        auth.save(home.path()).await?;
+        // Tokens that are now stored in the auth manifest should not linger in the keyring
+        if keyring.is_none() {
+            if let Some(previous) = previous_keyring_account {
+                remove_from_keyring(&previous).await;
+            }
+        }
+        pt.task_completed();
+
+        pt.finish_with_emoji_and_message(
+            "✓",
+            format!(
+                "Logged in to {styled_provider}{}{}{}. {}",
+                if self.skip_verify {
+                    ""
+                } else {
+                    " with a verified token"
+                },
+                match keyring {
+                    Some(keyring) => format!(", stored in the {}", keyring.name()),
+                    None => String::new(),
+                },
+                if replaced {
+                    ", replacing the previous token"
+                } else {
+                    ""
+                },
+                pt.formatted_elapsed()
+            ),
+        );
+
+        Ok(())
+    }
+}
+
+/// Removes the stored token for an artifact provider.
+#[derive(Debug, Parser)]
+pub struct AuthLogoutSubcommand {
+    /// The artifact / tool provider to log out of.
+    pub provider: ArtifactProvider,
+    /// The host to log out of, such as `codeberg.org`.
+    /// Required for providers with several hosts, such as Gitea.
+    #[clap(long)]
+    pub host: Option<String>,
+}
+
+impl AuthLogoutSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let host = parse_host(self.provider, self.host, "logout")?;
+        let styled_provider = styled_provider(self.provider, host.as_deref());
+
+        let mut auth = AuthManifest::load_or_create(home.path())
+            .await
+            .context("Failed to load or create auth manifest")?;
+        let keyring_account = auth.get_keyring_account(self.provider, host.as_deref());
+        let removed = match &host {
+            Some(host) => auth.unset_host_token(self.provider, host),
+            None => auth.unset_token(self.provider),
+        };
+        if !removed {
+            bail!("No stored token for {styled_provider} exists.");
+        }
+        auth.save(home.path()).await?;
+        if let Some(account) = keyring_account {
+            remove_from_keyring(&account).await;
+        }
+
+        let still_authenticated = host.is_none()
+            && auth
+                .get_all_tokens_with_env()
+                .await
+                .contains_key(&self.provider);
+        let env_note = if still_authenticated {
+            format!(
+                "\n\n{} A token for {styled_provider} is still set using an environment variable.",
+                style("Note:").bold().yellow()
+            )
+        } else {
+            String::new()
+        };
+
+        print_message(format!(
+            "{} Logged out of {styled_provider}.{env_note}",
+            style("✓").bold().green()
+        ));
+
+        Ok(())
+    }
+}
+
+/// Shows which providers have tokens, and if those tokens are valid.
+///
+/// Tokens themselves are never displayed.
+#[derive(Debug, Parser)]
+pub struct AuthStatusSubcommand {
+    /// If live API verification of tokens should be skipped.
+    #[clap(long)]
+    pub skip_verify: bool,
+}
+
+impl AuthStatusSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let auth = AuthManifest::load_or_create(home.path())
+            .await
+            .context("Failed to load or create auth manifest")?;
+
+        // 1. Gather all tokens, and where each of them came from
+        let file_tokens = auth.get_all_tokens().await;
+        let all_tokens = auth.get_all_tokens_with_env().await;
+        let mut entries = [ArtifactProvider::GitHub, ArtifactProvider::GitLab]
+            .into_iter()
+            .map(|provider| {
+                let token = all_tokens.get(&provider).cloned();
+                let from_env = token.is_some() && token.as_ref() != file_tokens.get(&provider);
+                let from_keyring = !from_env && auth.get_keyring_account(provider, None).is_some();
+                StatusEntry {
+                    provider,
+                    host: None,
+                    token,
+                    from_env,
+                    from_keyring,
+                }
+            })
+            .collect::<Vec<_>>();
+        for (provider, hosts) in auth.get_all_host_tokens().await {
+            let mut hosts = hosts.into_iter().collect::<Vec<_>>();
+            hosts.sort();
+            entries.extend(hosts.into_iter().map(|(host, token)| StatusEntry {
+                provider,
+                from_keyring: auth.get_keyring_account(provider, Some(&host)).is_some(),
+                host: Some(host),
+                token: Some(token),
+                from_env: false,
+            }));
+        }
+
+        // 2. Verify all tokens at the same time, unless skipped
+        let pt = CliProgressTracker::new_with_message("Verifying", entries.len());
+        let validity = join_all(entries.iter().map(|entry| async {
+            let res = match &entry.token {
+                Some(token) if !self.skip_verify => {
+                    Some(is_token_valid(entry.provider, entry.host.as_deref(), token).await)
+                }
+                _ => None,
+            };
+            pt.task_completed();
+            res
+        }))
+        .await;
+        pt.finish_and_clear();
+
+        // 3. Display the results, never including the tokens themselves
+        let bullet = style("•").dim();
+        let lines = entries
+            .iter()
+            .zip(validity)
+            .map(|(entry, valid)| {
+                let provider = styled_provider(entry.provider, entry.host.as_deref());
+                let source = if entry.from_env {
+                    style("(environment variable)").dim()
+                } else if entry.from_keyring {
+                    style("(system keyring)").dim()
+                } else {
+                    style("(auth.toml)").dim()
+                };
+                let status = match (&entry.token, valid) {
+                    (None, _) => style("not logged in").dim().to_string(),
+                    (Some(_), None) => format!("{} {source}", style("token set").bold()),
+                    (Some(_), Some(Ok(true))) => {
+                        format!("{} {source}", style("✓ valid").bold().green())
+                    }
+                    (Some(_), Some(Ok(false))) => {
+                        format!("{} {source}", style("✗ invalid").bold().red())
+                    }
+                    (Some(_), Some(Err(e))) => format!(
+                        "{} {source}\n    {} {e}",
+                        style("? could not verify").bold().yellow(),
+                        style("→").dim()
+                    ),
+                };
+                format!("{bullet} {provider}: {status}")
+            })
+            .collect::<Vec<_>>();
+
+        print_message(format!("Authentication status:\n{}", lines.join("\n")));
+
+        Ok(())
+    }
+}
+
+struct StatusEntry {
+    provider: ArtifactProvider,
+    host: Option<String>,
+    token: Option<String>,
+    from_env: bool,
+    from_keyring: bool,
+}
+
+/*
+    Stores a token in the system keyring, returning the keyring if it was stored.
+
+    Warns and returns `None` if no keyring is available or storing failed,
+    in which case the token should be stored in the auth manifest instead.
+*/
+async fn store_in_keyring(account: &str, token: &str) -> Option<Keyring> {
+    let Some(keyring) = Keyring::detect() else {
+        tracing::warn!(
+            "No supported system keyring is available, \
+            the token will be stored in the auth manifest instead."
+        );
+        return None;
+    };
+    match keyring.set(account, token).await {
+        Ok(()) => Some(keyring),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to store the token in the {}, \
+                it will be stored in the auth manifest instead.\
+                \nError: {e}",
+                keyring.name()
+            );
+            None
+        }
+    }
+}
+
+/*
+    Removes a token from the system keyring, warning if that was not possible.
+*/
+async fn remove_from_keyring(account: &str) {
+    let Some(keyring) = Keyring::detect() else {
+        tracing::warn!(
+            "No supported system keyring is available, \
+            the token for '{account}' could not be removed from it."
+        );
+        return;
+    };
+    if let Err(e) = keyring.remove(account).await {
+        tracing::warn!(
+            "Failed to remove the token for '{account}' from the {}!\
+            \nError: {e}",
+            keyring.name()
+        );
+    }
+}
+
+fn parse_host(
+    provider: ArtifactProvider,
+    host: Option<String>,
+    command: &str,
+) -> Result<Option<String>> {
+    match (provider.requires_host(), host) {
+        (true, Some(host)) => Ok(Some(host.trim().to_string())),
+        (true, None) => bail!(
+            "A host must be given for {}.\
+            \nExample usage: `{}`",
+            provider.display_name(),
+            style(format!(
+                "rokit auth {command} {provider} --host codeberg.org"
+            ))
+            .bold()
+            .green(),
+        ),
+        (false, Some(_)) => bail!("{} does not support custom hosts.", provider.display_name()),
+        (false, None) => Ok(None),
+    }
+}
+
+fn provider_name(provider: ArtifactProvider, host: Option<&str>) -> String {
+    match host {
+        Some(host) => format!("{} ({host})", provider.display_name()),
+        None => provider.display_name().to_string(),
+    }
+}
+
+fn styled_provider(provider: ArtifactProvider, host: Option<&str>) -> String {
+    style(provider_name(provider, host))
+        .bold()
+        .white()
+        .to_string()
+}
@@ -4,8 +4,11 @@ use clap::Parser;
 use console::style;
 use rokit::{
     manifests::AuthManifest,
-    sources::{github::GithubProvider, ArtifactProvider},
+    sources::{
+        gitea::GiteaProvider, github::GithubProvider, gitlab::GitlabProvider, ArtifactProvider,
+    },
     storage::Home,
+    system::Keyring,
 };
 
 use crate::util::CliProgressTracker;
@@ -15,6 +18,10 @@ use crate::util::CliProgressTracker;
 pub struct AuthenticateSubcommand {
     /// The artifact / tool provider to authenticate with.
     pub provider: ArtifactProvider,
+    /// The host to authenticate with, such as `codeberg.org`.
+    /// Required for providers with several hosts, such as GitHub Enterprise or Gitea.
+    #[clap(long)]
+    pub host: Option<String>,
     /// The token to use for authentication.
     /// Can be omitted when removing a token.
     #[clap(long)]
@@ -37,28 +44,60 @@ impl AuthenticateSubcommand {
             if self.token.is_some() { 4 } else { 3 },
         );
 
+        let host = match (self.provider.requires_host(), self.host) {
+            (true, Some(host)) => Some(host.trim().to_string()),
+            (true, None) => bail!(
+                "A host must be given to authenticate with {}.\
+                \nExample usage: `{}`",
+                self.provider.display_name(),
+                style(format!(
+                    "rokit authenticate {} --host {} --token YOUR_TOKEN_HERE",
+                    self.provider,
+                    if self.provider == ArtifactProvider::GitHubEnterprise {
+                        "github.mycorp.com"
+                    } else {
+                        "codeberg.org"
+                    },
+                ))
+                .bold()
+                .green(),
+            ),
+            (false, Some(_)) => bail!(
+                "{} does not support custom hosts.",
+                self.provider.display_name()
+            ),
+            (false, None) => None,
+        };
+
         let mut auth = AuthManifest::load_or_create(home.path())
             .await
             .context("Failed to load or create auth manifest")?;
         pt.task_completed();
 
-        let styled_provider = style(self.provider.display_name())
-            .bold()
-            .white()
-            .to_string();
+        let provider_args = match &host {
+            Some(host) => format!("{} --host {host}", self.provider),
+            None => self.provider.to_string(),
+        };
+        let styled_provider = match &host {
+            Some(host) => format!("{} ({host})", self.provider.display_name()),
+            None => self.provider.display_name().to_string(),
+        };
+        let styled_provider = style(styled_provider).bold().white().to_string();
         let styled_add_command = style(format!(
-            "rokit authenticate {} --token YOUR_TOKEN_HERE",
-            self.provider
+            "rokit authenticate {provider_args} --token YOUR_TOKEN_HERE"
         ))
         .bold()
         .green()
         .to_string();
-        let styled_remove_command = style(format!("rokit authenticate {} --remove", self.provider))
+        let styled_remove_command = style(format!("rokit authenticate {provider_args} --remove"))
             .bold()
             .green()
             .to_string();
 
-        let exists = auth.has_token(self.provider);
+        let exists = match &host {
+            Some(host) => auth.has_host_token(self.provider, host),
+            None => auth.has_token(self.provider),
+        };
         if self.remove {
             if !exists {
                 bail!(
@@ -73,17 +112,31 @@ impl AuthenticateSubcommand {
             );
         }
 
+        let keyring_account = auth.get_keyring_account(self.provider, host.as_deref());
         if self.remove {
-            let was_removed = auth.unset_token(self.provider);
+            let was_removed = match &host {
+                Some(host) => auth.unset_host_token(self.provider, host),
+                None => auth.unset_token(self.provider),
+            };
             assert!(was_removed, "token was not removed");
         } else if let Some(token) = self.token {
             let token = token.trim().to_string();
 
             pt.update_message("Verifying");
-            verify_token(self.provider, &token, self.skip_parse, self.skip_verify).await?;
+            verify_token(
+                self.provider,
+                host.as_deref(),
+                &token,
+                self.skip_parse,
+                self.skip_verify,
+            )
+            .await?;
             pt.task_completed();
 
-            let had_token = auth.set_token(self.provider, token);
+            let had_token = match &host {
+                Some(host) => auth.set_host_token(self.provider, host, token),
+                None => auth.set_token(self.provider, token),
+            };
             assert!(!had_token, "token was overwritten");
         } else {
             bail!(
@@ -94,6 +147,17 @@ impl AuthenticateSubcommand {
 
         pt.update_message("Saving");
         auth.save(home.path()).await?;
+        if let (true, Some(account), Some(keyring)) =
+            (self.remove, keyring_account, Keyring::detect())
+        {
+            if let Err(e) = keyring.remove(&account).await {
+                tracing::warn!(
+                    "Failed to remove the token for '{account}' from the {}!\
+                    \nError: {e}",
+                    keyring.name()
+                );
+            }
+        }
 
         pt.finish_with_emoji_and_message(
             "✓",
@@ -113,32 +177,46 @@ impl AuthenticateSubcommand {
     }
 }
 
-async fn verify_token(
+pub(super) async fn verify_token(
     provider: ArtifactProvider,
+    host: Option<&str>,
     token: &str,
     skip_parse: bool,
     skip_verify: bool,
 ) -> Result<()> {
+    let provider_args = match host {
+        Some(host) => format!("{provider} --host {host}"),
+        None => provider.to_string(),
+    };
+
     // Verify the formatting of the token, if desired.
     if !skip_parse {
         let validated = match provider {
-            ArtifactProvider::GitHub => {
+            ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => {
                 is_gh_classic_token(token) || is_gh_fine_grained_token(token)
             }
+            ArtifactProvider::GitLab => is_gl_personal_token(token),
+            ArtifactProvider::Gitea => is_gitea_token(token),
         };
 
         if !validated {
             let bullet = style("•").dim();
             let valid_formats = match provider {
-                ArtifactProvider::GitHub => vec![
+                ArtifactProvider::GitHub | ArtifactProvider::GitHubEnterprise => vec![
                     format!("{bullet} Starting with 'gh' followed by a lowercase letter and an underscore"),
                     format!("{bullet} Starting with 'github_pat_'"),
                 ],
+                ArtifactProvider::GitLab => vec![
+                    format!("{bullet} Starting with 'glpat-'"),
+                ],
+                ArtifactProvider::Gitea => vec![
+                    format!("{bullet} 40 hexadecimal characters"),
+                ],
             };
 
             let styled_flag = style("--skip-parse").bold().green();
             let styled_command = style(format!(
-                "rokit authenticate {provider} --token YOUR_TOKEN_HERE --skip-parse"
+                "rokit authenticate {provider_args} --token YOUR_TOKEN_HERE --skip-parse"
             ))
             .bold()
             .green();
@@ -161,18 +239,11 @@ async fn verify_token(
 
     // Verify the actual validity of the token, if desired.
     if !skip_verify {
-        let verified = match provider {
-            ArtifactProvider::GitHub => {
-                let client = GithubProvider::new_authenticated(token)?;
-                let verify_res = client.verify_authentication().await;
-                verify_res.context("GitHub API returned an error during token verification")?
-            }
-        };
-
+        let verified = is_token_valid(provider, host, token).await?;
         if !verified {
             let styled_flag = style("--skip-verify").bold().green();
             let styled_command = style(format!(
-                "rokit authenticate {provider} --token YOUR_TOKEN_HERE --skip-verify"
+                "rokit authenticate {provider_args} --token YOUR_TOKEN_HERE --skip-verify"
             ))
             .bold()
             .green();
@@ -191,6 +262,44 @@ async fn verify_token(
     Ok(())
 }
 
+/**
+    Checks if the given token is valid, using the API of the provider.
+
+    Returns `false` if the token was rejected, and an error
+    if the API could not be reached or returned another error.
+*/
+pub(super) async fn is_token_valid(
+    provider: ArtifactProvider,
+    host: Option<&str>,
+    token: &str,
+) -> Result<bool> {
+    Ok(match provider {
+        ArtifactProvider::GitHub => {
+            let client = GithubProvider::new_authenticated(token)?;
+            let verify_res = client.verify_authentication().await;
+            verify_res.context("GitHub API returned an error during token verification")?
+        }
+        ArtifactProvider::GitHubEnterprise => {
+            let host = host.expect("GitHub Enterprise tokens have hosts");
+            let client = GithubProvider::new_enterprise_authenticated(host, token)?;
+            let verify_res = client.verify_enterprise_authentication(host).await;
+            verify_res
+                .context("GitHub Enterprise API returned an error during token verification")?
+        }
+        ArtifactProvider::GitLab => {
+            let client = GitlabProvider::new_authenticated(token)?;
+            let verify_res = client.verify_authentication().await;
+            verify_res.context("GitLab API returned an error during token verification")?
+        }
+        ArtifactProvider::Gitea => {
+            let host = host.expect("Gitea tokens have hosts");
+            let client = GiteaProvider::new_authenticated(host, token)?;
+            let verify_res = client.verify_authentication(host).await;
+            verify_res.context("Gitea API returned an error during token verification")?
+        }
+    })
+}
+
 fn is_gh_classic_token(token: &str) -> bool {
     match token.chars().take(4).collect::<Vec<_>>().as_slice() {
         ['g', 'h', c, '_'] => c.is_ascii_alphabetic() && c.is_ascii_lowercase(),
@@ -202,3 +311,11 @@ fn is_gh_classic_token(token: &str) -> bool {
 fn is_gh_fine_grained_token(token: &str) -> bool {
     token.starts_with("github_pat_")
 }
+
+fn is_gl_personal_token(token: &str) -> bool {
+    token.starts_with("glpat-")
+}
+
+fn is_gitea_token(token: &str) -> bool {
+    token.len() == 40 && token.chars().all(|c| c.is_ascii_hexdigit())
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use console::style;
+use indicatif::HumanBytes;
+use tokio::fs::metadata;
+
+use rokit::{discovery::discover_all_manifests, storage::Home, tool::ToolSpec};
+
+use crate::util::{print_message, prompt_for_removal, CliProgressTracker};
+
+/// Inspects and cleans up installed tool versions and cached downloads.
+#[derive(Debug, Parser)]
+pub struct CacheSubcommand {
+    #[clap(subcommand)]
+    pub command: CacheCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CacheCommand {
+    List(CacheListSubcommand),
+    Clean(CacheCleanSubcommand),
+}
+
+impl CacheSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        match self.command {
+            CacheCommand::List(cmd) => cmd.run(home).await,
+            CacheCommand::Clean(cmd) => cmd.run(home).await,
+        }
+    }
+}
+
+/// Lists installed tool versions and cached downloads, and their disk usage.
+///
+/// Versions that are not used by any manifest found from
+/// the current directory can be removed using `rokit cache clean`.
+#[derive(Debug, Parser)]
+pub struct CacheListSubcommand {}
+
+impl CacheListSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let versions = collect_installed_versions(home).await;
+        let artifacts_size = home
+            .artifact_cache()
+            .size()
+            .await
+            .context("Failed to read artifact cache")?;
+
+        let bullet = style("•").dim();
+        let mut lines = Vec::new();
+        if versions.is_empty() {
+            lines.push(String::from("🗄️  No tools are installed."));
+        } else {
+            lines.push(String::from("🗄️  Installed tool versions:\n"));
+            let longest_spec_len = versions
+                .iter()
+                .map(|version| version.spec.to_string().len())
+                .max()
+                .unwrap_or(0);
+            for version in &versions {
+                let spec = version.spec.to_string();
+                let usage = if version.used {
+                    style("in use").green()
+                } else {
+                    style("unused").yellow()
+                };
+                lines.push(format!(
+                    "{bullet} {}{} {:>10} {}",
+                    style(&spec).bold().magenta(),
+                    " ".repeat(longest_spec_len - spec.len()),
+                    HumanBytes(version.size).to_string(),
+                    style(format!("({usage})")).dim(),
+                ));
+            }
+        }
+
+        let versions_size = versions.iter().map(|v| v.size).sum::<u64>();
+        let reclaimable = versions
+            .iter()
+            .filter(|v| !v.used)
+            .map(|v| v.size)
+            .sum::<u64>();
+        lines.push(String::new());
+        lines.push(format!(
+            "Installed tools use {}, and cached downloads use {}.",
+            style(HumanBytes(versions_size)).bold(),
+            style(HumanBytes(artifacts_size)).bold(),
+        ));
+        if reclaimable > 0 {
+            lines.push(format!(
+                "Run `{}` to reclaim {} from unused versions.",
+                style("rokit cache clean").bold().green(),
+                style(HumanBytes(reclaimable)).bold(),
+            ));
+        }
+
+        print_message(lines.join("\n"));
+
+        Ok(())
+    }
+}
+
+/// Removes installed tool versions that are not used by any manifest.
+///
+/// Manifests are found from the current directory, so versions used only by
+/// other projects may be removed - these are installed again when needed.
+#[derive(Debug, Parser)]
+pub struct CacheCleanSubcommand {
+    /// Remove all installed tool versions and all cached downloads,
+    /// even if they are used by a manifest.
+    #[clap(long)]
+    pub all: bool,
+    /// Also remove cached downloads until the cache is at most this size,
+    /// such as `500MB` or `2GB`. The least recently used downloads are removed first.
+    #[clap(long, value_parser = parse_size, conflicts_with = "all")]
+    pub max_size: Option<u64>,
+    /// Remove files without asking for confirmation first.
+    #[clap(long, short)]
+    pub yes: bool,
+}
+
+impl CacheCleanSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let artifact_cache = home.artifact_cache();
+        let tool_cache = home.tool_cache();
+        let tool_storage = home.tool_storage();
+
+        // 1. Find what would be removed, and how much space that would reclaim
+        let removable = collect_installed_versions(home)
+            .await
+            .into_iter()
+            .filter(|version| self.all || !version.used)
+            .collect::<Vec<_>>();
+        let artifacts_size = artifact_cache
+            .size()
+            .await
+            .context("Failed to read artifact cache")?;
+        let artifacts_max_size = if self.all { Some(0) } else { self.max_size };
+        let artifacts_reclaimable =
+            artifacts_max_size.map_or(0, |max| artifacts_size.saturating_sub(max));
+        let versions_reclaimable = removable.iter().map(|v| v.size).sum::<u64>();
+
+        if removable.is_empty() && artifacts_reclaimable == 0 {
+            print_message("🗄️  Nothing to clean up.");
+            return Ok(());
+        }
+
+        // 2. Show the user what would be removed, and ask for confirmation
+        let bullet = style("•").dim();
+        let mut lines = Vec::new();
+        if !removable.is_empty() {
+            lines.push(String::from("These tool versions will be removed:\n"));
+            for version in &removable {
+                lines.push(format!(
+                    "{bullet} {} {}",
+                    style(&version.spec).bold().magenta(),
+                    style(format!("({})", HumanBytes(version.size))).dim(),
+                ));
+            }
+            lines.push(String::new());
+        }
+        if artifacts_reclaimable > 0 {
+            lines.push(format!(
+                "Cached downloads will be reduced by {}.",
+                style(HumanBytes(artifacts_reclaimable)).bold(),
+            ));
+        }
+        lines.push(format!(
+            "This will reclaim {} in total.",
+            style(HumanBytes(versions_reclaimable + artifacts_reclaimable)).bold(),
+        ));
+        print_message(format!("{}\n", lines.join("\n")));
+
+        if !self.yes && !prompt_for_removal(String::from("Remove these files?")).await? {
+            bail!(
+                "Nothing was removed.\
+                \nRun this command again with `{}` to remove these files without confirmation.",
+                style("--yes").bold().green(),
+            );
+        }
+
+        // 3. Remove everything, updating the tool cache as we go
+        let pt = CliProgressTracker::new_with_message("Cleaning", removable.len() + 1);
+        for version in &removable {
+            tool_storage.remove_tool_contents(&version.spec).await?;
+            let _ = tool_cache.remove_installed(&version.spec);
+            pt.task_completed();
+        }
+        let mut reclaimed = versions_reclaimable;
+        if let Some(max_size) = artifacts_max_size {
+            let cleanup = artifact_cache
+                .clean(Some(max_size))
+                .await
+                .context("Failed to clean artifact cache")?;
+            reclaimed += cleanup.removed_bytes;
+        }
+        pt.task_completed();
+
+        let s = if removable.len() == 1 { "" } else { "s" };
+        pt.finish_with_message(format!(
+            "Removed {} tool version{s}, reclaiming {} {}",
+            style(removable.len()).bold().magenta(),
+            style(HumanBytes(reclaimed)).bold(),
+            pt.formatted_elapsed(),
+        ));
+
+        Ok(())
+    }
+}
+
+// An installed tool version, its size on disk, and if it is used by any manifest
+struct InstalledVersion {
+    spec: ToolSpec,
+    size: u64,
+    used: bool,
+}
+
+/*
+    Collects all installed tool versions, and checks if they are used by any
+    manifest, either as an exact version or by matching a version requirement.
+
+    This is the same definition of "used" as for `rokit uninstall --prune`.
+*/
+async fn collect_installed_versions(home: &Home) -> Vec<InstalledVersion> {
+    let manifests = discover_all_manifests(false, false).await;
+    let used_specs = manifests
+        .iter()
+        .flat_map(|m| m.tools().map(|(_, spec)| spec.clone()))
+        .collect::<BTreeSet<_>>();
+    let used_requirements = manifests
+        .iter()
+        .flat_map(|m| m.requirements().map(|(_, requirement)| requirement))
+        .collect::<Vec<_>>();
+
+    let mut versions = Vec::new();
+    for spec in home.tool_cache().all_installed() {
+        let size = metadata(home.tool_storage().tool_path(&spec))
+            .await
+            .map(|meta| meta.len())
+            .unwrap_or_default();
+        let used = used_specs.contains(&spec) || used_requirements.iter().any(|r| r.matches(&spec));
+        versions.push(InstalledVersion { spec, size, used });
+    }
+    versions.sort_by(|a, b| a.spec.cmp(&b.spec));
+    versions
+}
+
+// Parses a size in bytes, with an optional unit such as `KB`, `MB`, or `GB`
+fn parse_size(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number = number
+        .parse::<u64>()
+        .with_context(|| format!("Invalid size '{s}', expected a number such as `500MB`"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        _ => bail!("Invalid size unit '{unit}', expected one of `B`, `KB`, `MB`, or `GB`"),
+    };
+    number
+        .checked_mul(multiplier)
+        .with_context(|| format!("Size '{s}' is too large"))
+}
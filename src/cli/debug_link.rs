@@ -0,0 +1,135 @@
+use std::{fmt::Display, path::Path};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use console::style;
+use tokio::fs::{read_link, symlink_metadata};
+
+use rokit::{
+    storage::{Home, LinkHealth},
+    system::exe_name_from_path,
+    tool::ToolAlias,
+};
+
+use crate::{
+    runner::{resolve_tool, ResolvedTool, Runner},
+    util::print_message,
+};
+
+/// Prints how running the link for a tool would be resolved, without running it.
+///
+/// Shows the link, the executable name that Rokit sees when the link is run,
+/// the tool spec discovered from manifests, and the binary that would be run.
+#[derive(Debug, Parser)]
+pub struct DebugLinkSubcommand {
+    /// The alias of the tool link to debug.
+    pub alias: ToolAlias,
+}
+
+impl DebugLinkSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let alias = self.alias;
+        let storage = home.tool_storage();
+        let link_path = storage.alias_path(&alias);
+
+        // 1. The link itself, which should be a file with the Rokit binary contents
+        print_message("Link:");
+        print_field("Path", display_path(home, &link_path));
+        let target = if let Ok(target) = read_link(&link_path).await {
+            format!("symlink to {}", display_path(home, &target))
+        } else if symlink_metadata(&link_path).await.is_ok() {
+            String::from("file, not a symlink")
+        } else {
+            style("missing, run `rokit install` to create it")
+                .yellow()
+                .to_string()
+        };
+        print_field("Target", target);
+        if symlink_metadata(&link_path).await.is_ok() {
+            let health = match storage.link_health(&link_path).await {
+                LinkHealth::Healthy => style("healthy").green(),
+                LinkHealth::Outdated => {
+                    style("outdated, created by another Rokit version").yellow()
+                }
+                LinkHealth::NotExecutable => style("not executable").red(),
+                LinkHealth::Dangling => style("points to a missing file").red(),
+                LinkHealth::Invalid => style("not a valid Rokit link").red(),
+            };
+            print_field("Health", health);
+        }
+
+        // 2. What the runner sees when the link is run, which is only
+        // the name of the executable, and never the path to the link
+        let exe_name = exe_name_from_path(&link_path).context("Invalid link file name")?;
+        let runner = Runner::with_exe_name(exe_name);
+        print_message("\nTrampoline:");
+        print_field("Exe name", runner.exe_name());
+        let mode = if runner.should_run() {
+            format!("runs tool '{}'", runner.exe_name())
+        } else {
+            style("runs Rokit itself").yellow().to_string()
+        };
+        print_field("Mode", mode);
+
+        // 3. The tool that the runner would find in manifests, and run
+        print_message("\nResolution:");
+        let resolved = resolve_tool(home, &alias)
+            .await
+            .with_context(|| format!("Failed to resolve tool '{alias}'"))?;
+        let (source, binary) = match resolved {
+            ResolvedTool::Managed {
+                requirement,
+                locked,
+                installed: Some(spec),
+                ..
+            } => {
+                print_field("Requirement", &requirement);
+                if let Some(locked) = locked {
+                    print_field("Locked", locked);
+                }
+                (spec.to_string(), Some(storage.tool_path(&spec)))
+            }
+            ResolvedTool::Managed {
+                requirement,
+                locked,
+                installed: None,
+                ..
+            } => {
+                print_field("Requirement", &requirement);
+                if let Some(locked) = locked {
+                    print_field("Locked", locked);
+                }
+                let note = "no matching version installed, it would be installed when run";
+                (style(note).yellow().to_string(), None)
+            }
+            ResolvedTool::Local(path) => (String::from("locally built"), Some(path)),
+            ResolvedTool::NonRokit(path) => (
+                String::from("system PATH, not managed by Rokit"),
+                Some(path),
+            ),
+        };
+        print_field("Spec", source);
+        if let Some(binary) = binary {
+            let mut binary_display = display_path(home, &binary);
+            if !binary.exists() {
+                binary_display.push_str(&style(" (missing)").red().to_string());
+            }
+            print_field("Binary", binary_display);
+        }
+
+        Ok(())
+    }
+}
+
+fn print_field(name: &str, value: impl Display) {
+    print_message(format!(
+        "  {} {name:<11} {} {value}",
+        style("•").dim(),
+        style("→").dim()
+    ));
+}
+
+fn display_path(home: &Home, path: &Path) -> String {
+    home.display_path(path)
+        .unwrap_or_else(|| path.display().to_string())
+}
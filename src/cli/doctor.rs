@@ -0,0 +1,336 @@
+use std::{collections::HashSet, env::consts::EXE_SUFFIX, path::Path};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use console::style;
+
+use rokit::{
+    discovery::{discover_all_manifests, ToolSource},
+    storage::{Home, LinkHealth},
+    system::exists_in_path,
+    tool::{ToolAlias, ToolSpec},
+};
+
+use crate::util::{print_message, prompt_for_repair, CliProgressTracker};
+
+/// Checks for problems with Rokit, its tool links, and installed tools.
+///
+/// Problems that can be fixed automatically are repaired after confirmation,
+/// and instructions are given for any problems that need your action.
+#[derive(Debug, Parser)]
+pub struct DoctorSubcommand {
+    /// Repair any problems that can be fixed automatically, without prompting.
+    #[clap(long)]
+    pub fix: bool,
+}
+
+impl DoctorSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let pt = CliProgressTracker::new_with_message("Checking", 3);
+
+        let mut problems = check_links(home).await?;
+        pt.task_completed();
+        problems.extend(check_tools(home).await);
+        pt.task_completed();
+        if !exists_in_path(home) {
+            problems.push(Problem::NeedsAction {
+                description: String::from("Rokit is not in your PATH"),
+                hint: format!(
+                    "Run `{}` and restart your terminal",
+                    style("rokit setup").bold().green()
+                ),
+            });
+        }
+        pt.task_completed();
+
+        if problems.is_empty() {
+            pt.finish_with_message(format!(
+                "No problems found, Rokit is healthy! {}",
+                pt.formatted_elapsed()
+            ));
+            return Ok(());
+        }
+
+        let (fixable, needs_action): (Vec<_>, Vec<_>) =
+            problems.into_iter().partition(Problem::is_fixable);
+
+        let bullet = style("•").dim();
+        let total = fixable.len() + needs_action.len();
+        let mut lines = vec![format!(
+            "Found {total} problem{} {}",
+            if total == 1 { "" } else { "s" },
+            pt.formatted_elapsed()
+        )];
+        if !fixable.is_empty() {
+            lines.push(format!("\n{}", style("Fixable automatically:").bold()));
+            for problem in &fixable {
+                lines.push(format!("  {bullet} {}", problem.description()));
+            }
+        }
+        if !needs_action.is_empty() {
+            lines.push(format!("\n{}", style("Needs your action:").bold()));
+            for problem in &needs_action {
+                lines.push(format!("  {bullet} {}", problem.description()));
+                if let Problem::NeedsAction { hint, .. } = problem {
+                    lines.push(format!("    {} {hint}", style("→").dim()));
+                }
+            }
+        }
+        // NOTE: The report is printed directly instead of as the final progress
+        // message, so that it is also visible when stderr is not a terminal
+        pt.finish_and_clear();
+        print_message(lines.join("\n"));
+
+        if fixable.is_empty() {
+            return Ok(());
+        }
+
+        let should_repair = self.fix || prompt_for_repair(fixable.len()).await?;
+        if !should_repair {
+            eprintln!(
+                "Run `{}` to repair these problems.",
+                style("rokit doctor --fix").bold().green()
+            );
+            return Ok(());
+        }
+
+        repair(home, &fixable).await
+    }
+}
+
+async fn repair(home: &Home, fixable: &[Problem]) -> Result<()> {
+    let storage = home.tool_storage();
+    let cache = home.tool_cache();
+
+    let pt = CliProgressTracker::new_with_message("Repairing", fixable.len());
+
+    // Forget about missing tools first, so that
+    // links are recreated based on the actual state
+    for problem in fixable {
+        if let Problem::Fixable {
+            fix: Fix::ForgetInstalled(spec),
+            ..
+        } = problem
+        {
+            let _ = cache.remove_installed(spec);
+            pt.task_completed();
+        }
+    }
+
+    for problem in fixable {
+        if let Problem::Fixable {
+            fix: Fix::CreateLink(alias),
+            ..
+        } = problem
+        {
+            storage
+                .create_tool_link(alias)
+                .await
+                .with_context(|| format!("Failed to create link for tool '{alias}'"))?;
+            pt.task_completed();
+        }
+    }
+
+    let relinks = fixable
+        .iter()
+        .filter(|problem| {
+            matches!(
+                problem,
+                Problem::Fixable {
+                    fix: Fix::RecreateLinks,
+                    ..
+                }
+            )
+        })
+        .count();
+    if relinks > 0 {
+        storage.recreate_all_links().await.context(
+            "Failed to recreate tool links!\
+            \nYour installation may be corrupted.",
+        )?;
+        for _ in 0..relinks {
+            pt.task_completed();
+        }
+    }
+
+    pt.finish_with_message(format!(
+        "Repaired {} problem{}! {}",
+        fixable.len(),
+        if fixable.len() == 1 { "" } else { "s" },
+        pt.formatted_elapsed()
+    ));
+
+    Ok(())
+}
+
+// Checks the Rokit link and all tool links in the binary directory
+async fn check_links(home: &Home) -> Result<Vec<Problem>> {
+    let storage = home.tool_storage();
+    let mut problems = Vec::new();
+
+    if !storage.has_rokit_link().await {
+        problems.push(Problem::Fixable {
+            description: String::from("Rokit is missing from the binary directory"),
+            fix: Fix::RecreateLinks,
+        });
+    }
+
+    let manifest_aliases = discover_all_manifests(false, false)
+        .await
+        .into_iter()
+        .flat_map(|manifest| manifest.entries.into_keys())
+        .collect::<HashSet<_>>();
+
+    let link_paths = storage
+        .all_link_paths()
+        .await
+        .context("Failed to read tool links")?;
+    for path in link_paths {
+        let name = link_display_name(&path);
+        let description = match storage.link_health(&path).await {
+            LinkHealth::Healthy => None,
+            LinkHealth::Outdated => Some(format!("Link for '{name}' is outdated")),
+            LinkHealth::NotExecutable => Some(format!("Link for '{name}' is not executable")),
+            LinkHealth::Dangling => Some(format!("Link for '{name}' points to a missing file")),
+            LinkHealth::Invalid => Some(format!("Link for '{name}' is not a valid Rokit link")),
+        };
+        if let Some(description) = description {
+            problems.push(Problem::Fixable {
+                description,
+                fix: Fix::RecreateLinks,
+            });
+        }
+
+        let alias = name.parse::<ToolAlias>().ok();
+        if alias.is_none_or(|alias| !manifest_aliases.contains(&alias)) {
+            problems.push(Problem::NeedsAction {
+                description: format!("Link for '{name}' does not match any tool in a manifest"),
+                hint: format!(
+                    "If it is not used by a project in another directory, run `{}`",
+                    style(format!("rokit uninstall {name}")).bold().green()
+                ),
+            });
+        }
+    }
+
+    Ok(problems)
+}
+
+// Checks that tools in manifests are installed, linked, and
+// that the tool cache matches what is actually on disk
+async fn check_tools(home: &Home) -> Vec<Problem> {
+    let storage = home.tool_storage();
+    let cache = home.tool_cache();
+    let mut problems = Vec::new();
+
+    let mut missing_specs = HashSet::new();
+    for spec in cache.all_installed() {
+        if !storage.tool_path(&spec).exists() {
+            problems.push(Problem::Fixable {
+                description: format!("Tool '{spec}' is marked as installed, but is missing"),
+                fix: Fix::ForgetInstalled(spec.clone()),
+            });
+            missing_specs.insert(spec);
+        }
+    }
+
+    let is_installed = |spec: &ToolSpec| cache.is_installed(spec) && !missing_specs.contains(spec);
+    let install_hint = || format!("Run `{}`", style("rokit install").bold().green());
+
+    let mut seen = HashSet::new();
+    for manifest in discover_all_manifests(false, false).await {
+        let mut entries = manifest.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(alias, _)| *alias);
+
+        for (alias, entry) in entries {
+            // Manifests are in order of precedence, so
+            // only the first occurrence of each alias is used
+            if !seen.insert(alias.clone()) {
+                continue;
+            }
+
+            match &entry.source {
+                ToolSource::Spec(spec) => {
+                    if !is_installed(spec) {
+                        problems.push(Problem::NeedsAction {
+                            description: format!("Tool '{alias}' ({spec}) is not installed"),
+                            hint: install_hint(),
+                        });
+                    }
+                }
+                ToolSource::Requirement(requirement) => {
+                    let matching = cache
+                        .find_installed_matching(requirement)
+                        .filter(|spec| is_installed(spec));
+                    if matching.is_none() {
+                        problems.push(Problem::NeedsAction {
+                            description: format!(
+                                "Tool '{alias}' has no installed version matching '{}'",
+                                requirement.version_req()
+                            ),
+                            hint: install_hint(),
+                        });
+                    }
+                }
+                ToolSource::Local(path) => {
+                    if !path.exists() {
+                        problems.push(Problem::NeedsAction {
+                            description: format!(
+                                "Tool '{alias}' points to '{}', which does not exist",
+                                path.display()
+                            ),
+                            hint: String::from("Build the tool, or fix its path in the manifest"),
+                        });
+                    }
+                }
+            }
+
+            if !storage.has_tool_link(alias).await {
+                problems.push(Problem::Fixable {
+                    description: format!("Tool '{alias}' has no link"),
+                    fix: Fix::CreateLink(alias.clone()),
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+fn link_display_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match name.strip_suffix(EXE_SUFFIX) {
+        Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+        _ => name,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Fix {
+    RecreateLinks,
+    CreateLink(ToolAlias),
+    ForgetInstalled(ToolSpec),
+}
+
+#[derive(Debug, Clone)]
+enum Problem {
+    Fixable { description: String, fix: Fix },
+    NeedsAction { description: String, hint: String },
+}
+
+impl Problem {
+    fn is_fixable(&self) -> bool {
+        matches!(self, Self::Fixable { .. })
+    }
+
+    fn description(&self) -> &str {
+        match self {
+            Self::Fixable { description, .. } | Self::NeedsAction { description, .. } => {
+                description
+            }
+        }
+    }
+}
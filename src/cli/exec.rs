@@ -0,0 +1,48 @@
+use std::process::exit;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+
+use rokit::{
+    storage::Home,
+    system::run_interruptible,
+    tool::{ToolSpec, ToolSpecReq},
+};
+
+use crate::util::install_missing_tool;
+
+/// Runs a specific version of a tool, without adding it to any manifest.
+///
+/// The tool will be installed if necessary, but no links will be created.
+#[derive(Debug, Parser)]
+pub struct ExecSubcommand {
+    /// The tool specification to run, such as `author/name@1.2.3`.
+    pub tool: ToolSpec,
+    /// Arguments to pass to the tool - use `--` to
+    /// separate them from arguments passed to Rokit.
+    #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+impl ExecSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let spec = self.tool;
+        let alias = spec.id().clone().into_alias();
+
+        let tool_path = home.tool_storage().tool_path(&spec);
+        if !home.tool_cache().is_installed(&spec) || !tool_path.exists() {
+            let requirement = ToolSpecReq::from(spec.clone());
+            install_missing_tool(home, &alias, &requirement, None).await?;
+        }
+
+        // NOTE: We exit directly with the exit code of the tool below,
+        // so we need to make sure any newly installed tool is saved first
+        home.save().await?;
+
+        let code = run_interruptible(&tool_path, &self.args)
+            .await
+            .with_context(|| format!("Failed to run tool {spec}"))?;
+
+        exit(code);
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use console::style;
+use futures::{stream::FuturesUnordered, TryStreamExt};
+use tokio::fs::{read_to_string, write};
+
+use rokit::{
+    discovery::discover_all_manifests,
+    manifests::RokitManifest,
+    storage::Home,
+    system::current_dir,
+    tool::{ToolAlias, ToolId},
+};
+
+use crate::util::{print_message, CliProgressTracker};
+
+use super::tree::display_path;
+
+/// Formats the tool specifications in Rokit manifests consistently.
+///
+/// Specs are rewritten in their canonical form, such as `"author/name@1.2.3"`,
+/// using the casing of the author and name of each tool as on its provider.
+/// Comments, the order of tools, and any other settings for tools are kept.
+#[derive(Debug, Parser)]
+pub struct FmtSubcommand {
+    /// Check if manifests are formatted, without changing them.
+    /// Fails if any manifest needs formatting, for use on CI.
+    #[clap(long)]
+    pub check: bool,
+    /// Only normalize the formatting of specs, without looking
+    /// up the casing of tool ids from their providers.
+    #[clap(long)]
+    pub offline: bool,
+    /// Format the manifest at this path instead of discovering manifests
+    /// from the current directory. Can be given several times.
+    #[clap(long = "manifest", value_name = "PATH")]
+    pub manifests: Vec<PathBuf>,
+}
+
+impl FmtSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        // 1. Find and parse all of the manifests to format
+        let paths = if self.manifests.is_empty() {
+            discover_all_manifests(true, false)
+                .await
+                .into_iter()
+                .map(|manifest| manifest.path)
+                .collect()
+        } else {
+            self.manifests
+        };
+        let mut manifests = Vec::new();
+        for path in paths {
+            let contents = read_to_string(&path)
+                .await
+                .with_context(|| format!("Failed to read manifest at {}", path.display()))?;
+            let manifest = contents
+                .parse::<RokitManifest>()
+                .with_context(|| format!("Failed to parse manifest at {}", path.display()))?;
+            manifests.push((path, manifest));
+        }
+        if manifests.is_empty() {
+            print_message("No manifests were found to format.");
+            return Ok(());
+        }
+
+        // 2. Look up the canonical casing of all tool ids, unless offline
+        let canonical_ids = if self.offline {
+            HashMap::new()
+        } else {
+            let ids = manifests
+                .iter()
+                .flat_map(|(_, manifest)| manifest.tool_requirements())
+                .map(|(_, requirement)| requirement.id().clone())
+                .collect::<BTreeSet<_>>();
+            fetch_canonical_ids(home, ids).await?
+        };
+
+        // 3. Normalize all specs, and write any changed manifests unless checking
+        let cwd = current_dir().await;
+        let mut changed = Vec::new();
+        for (path, mut manifest) in manifests {
+            let aliases = manifest.normalize_tool_specs(|id| canonical_ids.get(id).cloned());
+            if aliases.is_empty() {
+                continue;
+            }
+            if !self.check {
+                write(&path, manifest.to_string())
+                    .await
+                    .with_context(|| format!("Failed to write manifest at {}", path.display()))?;
+            }
+            changed.push((display_path(&path, home, &cwd), aliases));
+        }
+
+        if changed.is_empty() {
+            print_message("All manifests are formatted.");
+            return Ok(());
+        }
+        let list = format_changed(&changed);
+        if self.check {
+            bail!(
+                "Some manifests are not formatted:\n{list}\nRun `{}` to format them.",
+                style("rokit fmt").bold().green(),
+            );
+        }
+        print_message(format!("Formatted tool specs in manifests:\n{list}"));
+
+        Ok(())
+    }
+}
+
+/*
+    Fetches the ids of all given tools with the casing of their author and
+    name as on their providers - tools that do not exist keep their ids.
+*/
+async fn fetch_canonical_ids(
+    home: &Home,
+    ids: BTreeSet<ToolId>,
+) -> Result<HashMap<ToolId, ToolId>> {
+    let source = home.artifact_source().await?;
+    let pt = CliProgressTracker::new_with_message("Fetching", ids.len());
+    let canonical_ids = ids
+        .into_iter()
+        .map(|id| {
+            let (source, pt) = (&source, &pt);
+            async move {
+                let canonical = source.get_canonical_id(&id).await.with_context(|| {
+                    format!(
+                        "Failed to look up tool '{id}'\
+                        \nRun this command again with `{}` to skip looking up tools.",
+                        style("--offline").bold().green(),
+                    )
+                })?;
+                pt.task_completed();
+                anyhow::Ok(canonical.map(|canonical| (id, canonical)))
+            }
+        })
+        .collect::<FuturesUnordered<_>>()
+        .try_collect::<Vec<_>>()
+        .await;
+    pt.finish_and_clear();
+    Ok(canonical_ids?.into_iter().flatten().collect())
+}
+
+fn format_changed(changed: &[(String, Vec<ToolAlias>)]) -> String {
+    changed
+        .iter()
+        .map(|(path, aliases)| {
+            let aliases = aliases
+                .iter()
+                .map(|alias| style(alias.name()).bold().cyan().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("  {} {} ({aliases})", style("•").dim(), style(path).bold())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
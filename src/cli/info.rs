@@ -0,0 +1,185 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use console::style;
+
+use rokit::{
+    descriptor::Descriptor,
+    sources::{Artifact, ReleaseInfo},
+    storage::Home,
+    tool::ToolId,
+};
+
+use crate::util::{print_message, CliProgressTracker};
+
+const CHANGELOG_MAX_LINES: usize = 20;
+
+/// Shows details about the latest release of a tool, without installing it.
+///
+/// This includes the artifacts in the release, which one would be
+/// installed on the current system and why, and the release notes.
+#[derive(Debug, Parser)]
+pub struct InfoSubcommand {
+    /// The tool to show details for, such as `rojo-rbx/rojo`.
+    pub tool: ToolId,
+    /// Show the full release notes instead of only the first lines.
+    #[clap(long)]
+    pub full: bool,
+}
+
+impl InfoSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let source = home.artifact_source().await?;
+
+        let pt = CliProgressTracker::new_with_message("Fetching", 1);
+        let release = source
+            .get_latest_release_info(&self.tool)
+            .await
+            .with_context(|| format!("Failed to fetch the latest release of {}", self.tool))?;
+        pt.finish_and_clear();
+
+        let bullet = style("•").dim();
+        let mut lines = vec![format!(
+            "ℹ️  {} {}{}",
+            style(&self.tool).bold().magenta(),
+            style(release.spec.version()).bold(),
+            match release.published_date() {
+                Some(date) => style(format!(" (published {date})")).dim().to_string(),
+                None => String::new(),
+            }
+        )];
+
+        // 1. List artifacts, and which one would be selected for this system
+        lines.push(format!("\n{}", style("Artifacts:").bold()));
+        let selected = select_artifact(&release);
+        let artifacts = release
+            .artifacts
+            .iter()
+            .filter(|artifact| !artifact.is_checksum_file() && !artifact.is_signature_file())
+            .collect::<Vec<_>>();
+        if artifacts.is_empty() {
+            lines.push(format!("{bullet} {}", style("(no artifacts)").dim()));
+        }
+        let longest_name_len = artifacts
+            .iter()
+            .map(|artifact| artifact_name(artifact).len())
+            .max()
+            .unwrap_or(0);
+        for artifact in artifacts {
+            let name = artifact_name(artifact);
+            let padding = " ".repeat(longest_name_len - name.len());
+            let is_selected = selected
+                .as_ref()
+                .is_some_and(|(selected, _)| selected.name == artifact.name);
+            if is_selected {
+                lines.push(format!(
+                    "{bullet} {} {padding}{}",
+                    style(name).bold().green(),
+                    style("← selected").green(),
+                ));
+            } else {
+                lines.push(format!(
+                    "{bullet} {name} {padding}{}",
+                    style(describe_artifact(artifact)).dim(),
+                ));
+            }
+        }
+
+        match &selected {
+            Some((artifact, reason)) => lines.push(format!(
+                "\nRokit would install {} on this system, since {reason}.",
+                style(artifact_name(artifact)).bold(),
+            )),
+            None => lines.push(format!(
+                "\n{}",
+                style("No artifact is compatible with this system.")
+                    .bold()
+                    .red()
+            )),
+        }
+
+        // 2. Show the release notes, truncated unless requested otherwise
+        if let Some(changelog) = release.changelog.as_deref() {
+            lines.push(format!("\n{}", style("Release notes:").bold()));
+            let changelog_lines = changelog.lines().collect::<Vec<_>>();
+            let max_lines = if self.full {
+                changelog_lines.len()
+            } else {
+                CHANGELOG_MAX_LINES
+            };
+            for line in changelog_lines.iter().take(max_lines) {
+                lines.push(format!("  {}", line.trim_end()));
+            }
+            if changelog_lines.len() > max_lines {
+                lines.push(format!(
+                    "  {}",
+                    style(format!(
+                        "... {} more lines, use `--full` to show all",
+                        changelog_lines.len() - max_lines
+                    ))
+                    .dim()
+                ));
+            }
+        }
+
+        print_message(lines.join("\n"));
+
+        Ok(())
+    }
+}
+
+// Selects the artifact that would be installed, the same way as when adding
+// or installing tools, along with a short reason for why it was selected
+fn select_artifact(release: &ReleaseInfo) -> Option<(Artifact, String)> {
+    if let Some(artifact) = Artifact::sort_by_system_compatibility(&release.artifacts)
+        .into_iter()
+        .next()
+    {
+        let desc = artifact_descriptor(&artifact)?;
+        let reason = if artifact.is_emulated_on_current_system() {
+            format!(
+                "it is for {} and runs through emulation",
+                describe_descriptor(desc)
+            )
+        } else {
+            format!(
+                "it is the best match for this system ({})",
+                describe_descriptor(Descriptor::current_system())
+            )
+        };
+        return Some((artifact, reason));
+    }
+    let artifact = Artifact::find_partially_compatible_fallback(&release.artifacts)?;
+    let reason = String::from("it is the only artifact for this operating system");
+    Some((artifact, reason))
+}
+
+fn artifact_name(artifact: &Artifact) -> &str {
+    artifact.name.as_deref().unwrap_or("N/A")
+}
+
+fn artifact_descriptor(artifact: &Artifact) -> Option<Descriptor> {
+    artifact.name.as_deref().and_then(Descriptor::detect)
+}
+
+fn describe_artifact(artifact: &Artifact) -> String {
+    let Some(desc) = artifact_descriptor(artifact) else {
+        return String::from("(unknown system)");
+    };
+    let compatibility = if Descriptor::current_system().is_compatible_with(&desc) {
+        "compatible"
+    } else {
+        "incompatible"
+    };
+    format!("({}, {compatibility})", describe_descriptor(desc))
+}
+
+fn describe_descriptor(desc: Descriptor) -> String {
+    let mut parts = vec![format!("{:?}", desc.os())];
+    if let Some(arch) = desc.arch() {
+        parts.push(format!("{arch:?}"));
+    }
+    if let Some(toolchain) = desc.toolchain() {
+        parts.push(toolchain.as_str().to_string());
+    }
+    parts.join(" ")
+}
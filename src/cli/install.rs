@@ -1,136 +1,1350 @@
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    env::var,
+    ffi::OsStr,
+    num::{NonZeroU64, NonZeroUsize},
+    path::{Path, PathBuf},
+    process::exit,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
 
 use console::style;
-use futures::{stream::FuturesUnordered, TryStreamExt};
-use rokit::{discovery::discover_all_manifests, storage::Home};
+use futures::{stream::FuturesUnordered, StreamExt, TryStreamExt};
+use indicatif::HumanBytes;
+use rokit::{
+    descriptor::Descriptor,
+    discovery::{
+        find_alias_collisions, find_shadowed_tools, group_aliases_by_spec, DiscoveredManifest,
+        ToolEntry, ToolSource,
+    },
+    install::{
+        install_spec_with_options, InstallOptions, InstallSummary, InstalledTool, SkippedTool,
+    },
+    manifests::{
+        LockedArtifact, RokitLockfile, ToolPostInstall, ToolVerify, ROKIT_MANIFEST_FILE_NAME,
+    },
+    result::RokitError,
+    sources::{Artifact, ArtifactPattern, ArtifactSource, MinisignPublicKey},
+    storage::Home,
+    tool::{ToolAlias, ToolId, ToolSpec, ToolSpecReq},
+};
+use tokio::{signal::ctrl_c, sync::Semaphore, time::timeout};
+use url::Url;
 
-use crate::util::{find_most_compatible_artifact, prompt_for_trust_specs, CliProgressTracker};
+use crate::util::{
+    find_artifact_for_target, find_most_compatible_artifact, load_or_discover_manifests,
+    parse_artifact_pattern, parse_minisign_key, parse_target, prompt_for_trust_specs,
+    CliProgressTracker, INSTALL_SUBTASKS,
+};
+
+const JOBS_ENV_VAR: &str = "ROKIT_JOBS";
+const DEFAULT_JOBS: usize = 8;
+const SKIPPED_EXIT_CODE: i32 = 3;
+
+// Downloads usually take most of the time spent installing a tool, so they
+// are weighted as much as this many of the other steps of installing a tool
+const DOWNLOAD_WEIGHT: usize = 20;
 
 /// Adds a new tool using Rokit and installs it.
 #[derive(Debug, Parser)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct InstallSubcommand {
+    /// The aliases of the tools to install, such as `rojo`.
+    /// All tools in all manifests are installed if not given.
+    #[clap(value_name = "ALIAS")]
+    pub aliases: Vec<ToolAlias>,
     /// Skip checking if tools have been trusted before.
     /// It is recommended to only use this on CI machines.
+    /// Can also be set using `no_trust_check = true` in a Rokit config file.
     #[clap(long)]
     pub no_trust_check: bool,
     /// Force install all tools, even if they are already installed.
-    #[clap(long)]
+    /// This downloads all tools again, ignoring the download cache.
+    #[clap(long, conflicts_with_all = ["locked", "frozen"])]
     pub force: bool,
+    /// Reinstall all tools, even if they are already installed, by extracting them
+    /// again from the download cache and recreating their links, which repairs broken
+    /// tools and links. Unlike `--force`, tools are only downloaded again if they are
+    /// not in the download cache, and cached releases are used without refreshing them.
+    #[clap(long, conflicts_with_all = ["force", "locked", "frozen", "target"])]
+    pub reinstall: bool,
+    /// Only use tools that are already installed, without any network access.
+    /// Fails if any tool is missing, which is useful for reproducible CI builds.
+    #[clap(long)]
+    pub locked: bool,
+    /// Like `--locked`, but also fails if any tool is not in the lockfile, even if
+    /// it is already installed, or if there is no lockfile at all. This is useful
+    /// for checking that manifests and lockfiles are kept in sync on CI.
+    #[clap(long)]
+    pub frozen: bool,
+    /// Resolve all tools again and update the lockfile,
+    /// instead of using the versions in the lockfile.
+    #[clap(long, conflicts_with_all = ["locked", "frozen"])]
+    pub update: bool,
+    /// Resolve all tools and find their artifacts, and report what would be
+    /// downloaded, without downloading, installing, or linking anything.
+    #[clap(long, conflicts_with_all = ["locked", "frozen"])]
+    pub dry_run: bool,
+    /// Verify that each installed tool runs, by running it with `--version`.
+    /// Tools may use different arguments, or opt out, using `verify` in their
+    /// manifest entry, which also enables verification without this flag.
+    #[clap(long, conflicts_with_all = ["locked", "frozen"])]
+    pub verify: bool,
+    /// Install tools to the tool storage without creating links for them, such as
+    /// when baking tools into a container image and managing `PATH` separately.
+    /// The path to each installed tool can be printed using `rokit which`.
+    #[clap(long)]
+    pub no_links: bool,
+    /// Download artifacts for another system instead of the current one, such as
+    /// `x86_64-pc-windows-msvc` or `windows-x64`, to prime the download cache for
+    /// that system. Tools are only downloaded and verified, not installed or linked.
+    #[clap(long, value_parser = parse_target, conflicts_with_all = ["locked", "frozen", "dry_run", "verify"])]
+    pub target: Option<Descriptor>,
+    /// Skip tools that have no compatible artifact for the current system, such as
+    /// tools that are only available on other platforms, and install all other tools.
+    /// Exits with code 3 instead of failing if any tools were skipped.
+    #[clap(long, alias = "ignore-platform-errors", conflicts_with = "target")]
+    pub skip_incompatible: bool,
+    /// The maximum number of tools to download and install at the same time.
+    /// Defaults to the `ROKIT_JOBS` environment variable,
+    /// then `jobs` in a Rokit config file, or 8 if neither is set.
+    #[clap(long, short)]
+    pub jobs: Option<NonZeroUsize>,
+    /// The maximum number of seconds that downloading and installing tools may take,
+    /// in total. Individual requests also time out if they stall for too long.
+    /// Defaults to `timeout` in a Rokit config file, if set.
+    #[clap(long)]
+    pub timeout: Option<NonZeroU64>,
+    /// Use the manifest at this path instead of discovering manifests from the
+    /// current directory. Can be given several times to merge the tools from
+    /// several manifests, with earlier manifests taking priority.
+    #[clap(long = "manifest", value_name = "PATH")]
+    pub manifests: Vec<PathBuf>,
 }
 
 impl InstallSubcommand {
     pub async fn run(self, home: &Home) -> Result<()> {
         let force = self.force;
+        let reinstall = self.reinstall;
+        let install_all = force || reinstall;
+        let locked = self.locked || self.frozen;
+        let config = home.config();
+        let no_trust_check = self.no_trust_check || config.no_trust_check.unwrap_or_default();
+        let install_timeout = self.timeout.or(config.timeout);
 
         let source = home.artifact_source().await?;
-        let manifests = discover_all_manifests(false, false).await;
+        let source = if force {
+            source.with_artifact_cache_refresh()
+        } else {
+            source
+        };
+        let manifests = load_or_discover_manifests(&self.manifests).await?;
+
+        // NOTE: The lockfile is stored next to the closest Rokit manifest,
+        // which is the manifest for the current project, if there is one
+        let lockfile_dir = manifests
+            .iter()
+            .find(|manifest| {
+                manifest.path.file_name() == Some(OsStr::new(ROKIT_MANIFEST_FILE_NAME))
+            })
+            .and_then(|manifest| manifest.path.parent())
+            .map(Path::to_path_buf);
+        let existing_lockfile = match &lockfile_dir {
+            Some(dir) => RokitLockfile::load(dir)
+                .await
+                .context("Failed to load lockfile")?,
+            None => None,
+        };
+        let lockfile = existing_lockfile.as_ref().filter(|_| !self.update);
 
         let tool_cache = home.tool_cache();
         let tool_storage = home.tool_storage();
 
         // 1. Gather tool specifications from all known manifests
 
-        let tools = manifests
+        // NOTE: Manifests are ordered by priority, closest to the current directory
+        // first, so project manifests override the global manifest, and we only keep
+        // the first tool found for each alias - never the highest version, which
+        // would make the result depend on what other manifests happen to contain.
+        // Users are warned about any shadowed tools, since this may be surprising.
+        // Locally built tools have nothing to install, but must still be linked.
+        // When only some aliases were given, all other tools are skipped, but
+        // are still kept track of so that their lockfile entries are not removed.
+        let mut seen_aliases = HashSet::new();
+        let mut tool_aliases = BTreeSet::new();
+        let mut tool_specs = BTreeSet::new();
+        let mut requirements = HashSet::new();
+        let mut manifest_requirements = HashSet::new();
+        for manifest in &manifests {
+            for (alias, entry) in &manifest.entries {
+                if !seen_aliases.insert(alias.clone()) {
+                    continue;
+                }
+                let is_selected = self.aliases.is_empty() || self.aliases.contains(alias);
+                match &entry.source {
+                    ToolSource::Spec(spec) => {
+                        manifest_requirements.insert(ToolSpecReq::from(spec.clone()));
+                        if is_selected {
+                            tool_specs.insert(spec.clone());
+                        }
+                    }
+                    ToolSource::Requirement(requirement) => {
+                        manifest_requirements.insert(requirement.clone());
+                        if is_selected {
+                            requirements.insert(requirement.clone());
+                        }
+                    }
+                    ToolSource::Local(_) => {}
+                }
+                if is_selected {
+                    tool_aliases.insert(alias.clone());
+                }
+            }
+        }
+        check_unknown_aliases(&self.aliases, &seen_aliases)?;
+        // NOTE: Links are named after aliases, so aliases that differ only by case
+        // would replace each other on case-insensitive file systems, which must be
+        // caught before downloading anything, even if it works on this system
+        let collisions = find_alias_collisions(&manifests)
+            .into_iter()
+            .filter(|collision| {
+                self.aliases.is_empty()
+                    || collision
+                        .aliases
+                        .iter()
+                        .any(|(alias, _)| self.aliases.contains(alias))
+            })
+            .collect::<Vec<_>>();
+        if !collisions.is_empty() {
+            bail!(
+                "Some tool aliases differ only by case, and would replace each other \
+                on case-insensitive file systems, such as on macOS and Windows:\
+                \n{}\
+                \nRename or remove one of the aliases in each group.",
+                collisions
+                    .iter()
+                    .map(|collision| {
+                        collision
+                            .aliases
+                            .iter()
+                            .map(|(alias, path)| {
+                                format!(
+                                    "  {} {} in {}",
+                                    style("•").dim(),
+                                    style(alias.name()).bold().cyan(),
+                                    path.display()
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            );
+        }
+        for shadowed in find_shadowed_tools(&manifests) {
+            if self.aliases.is_empty() || self.aliases.contains(&shadowed.alias) {
+                tracing::warn!("{shadowed}");
+            }
+        }
+
+        let exact_requirements = tool_specs
             .iter()
-            .flat_map(|manifest| manifest.tools.clone().into_iter())
+            .map(|spec| (ToolSpecReq::from(spec.clone()), spec.clone()))
             .collect::<Vec<_>>();
 
         // 2. Check for trust
 
-        // NOTE: Deduplicate tool aliases and specs since they may appear in several manifests
-        let tool_aliases = tools
-            .iter()
-            .map(|(alias, _)| alias.clone())
-            .collect::<BTreeSet<_>>();
-        let tool_specs = tools
-            .into_iter()
-            .map(|(_, spec)| spec)
-            .collect::<BTreeSet<_>>();
-
-        let tool_specs = if self.no_trust_check {
-            tool_specs
+        // NOTE: Tools using version requirements must be resolved to
+        // exact versions first, which are what then get installed
+        let mut resolved = HashMap::new();
+        if locked {
+            // NOTE: Frozen mode is stricter, and never falls back to installed
+            // tools, so that any drift between manifests and lockfile is caught
+            if self.frozen {
+                check_lockfile_in_sync(lockfile, &tool_specs, &requirements)?;
+            }
+            // NOTE: In locked mode, requirements are resolved using the lockfile, or
+            // against installed tools only if there is no lockfile, and we must never
+            // hit the network to install anything
+            let mut missing = Vec::new();
+            for requirement in requirements.iter().cloned() {
+                let spec = match lockfile {
+                    Some(lockfile) => lockfile.get_resolved(&requirement).cloned(),
+                    None => tool_cache.find_installed_matching(&requirement),
+                };
+                if let Some(spec) = spec {
+                    tool_specs.insert(spec.clone());
+                    resolved.insert(requirement, spec);
+                } else if lockfile.is_some() {
+                    missing.push(format!("{requirement} (not in lockfile)"));
+                } else {
+                    missing.push(requirement.to_string());
+                }
+            }
+            for spec in &tool_specs {
+                if !tool_cache.is_installed(spec) || !tool_storage.tool_path(spec).exists() {
+                    missing.push(spec.to_string());
+                }
+            }
+            if !missing.is_empty() {
+                missing.sort();
+                bail!(
+                    "Some tools are not installed, and can not be installed in locked mode:\
+                    \n{}\
+                    \nInstall these tools without the `--locked` flag first.",
+                    missing
+                        .iter()
+                        .map(|spec| format!("  {} {spec}", style("•").dim()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+            }
         } else {
-            let (trusted_specs, untrusted_specs) = tool_specs
+            // NOTE: Only tools that need network access anyway are checked,
+            // so that installed and locked tools never make extra requests
+            let ids_to_check = tool_specs
+                .iter()
+                .filter(|spec| force || !tool_cache.is_installed(spec))
+                .map(|spec| spec.id().clone())
+                .chain(
+                    requirements
+                        .iter()
+                        .filter(|r| lockfile.and_then(|l| l.get_resolved(r)).is_none())
+                        .map(|r| r.id().clone()),
+                )
+                .collect::<BTreeSet<_>>();
+            check_tools_exist(&source, ids_to_check).await?;
+
+            let resolved_specs = requirements
+                .iter()
+                .cloned()
+                .map(|requirement| {
+                    let source = &source;
+                    async move {
+                        if let Some(spec) = lockfile.and_then(|l| l.get_resolved(&requirement)) {
+                            return anyhow::Ok((requirement, spec.clone()));
+                        }
+                        // NOTE: Reinstalling prefers the installed versions,
+                        // to avoid making requests to resolve requirements
+                        if let Some(spec) = reinstall
+                            .then(|| tool_cache.find_installed_matching(&requirement))
+                            .flatten()
+                        {
+                            return anyhow::Ok((requirement, spec));
+                        }
+                        let spec = source
+                            .resolve_requirement(&requirement)
+                            .await
+                            .with_context(|| format!("Failed to resolve tool '{requirement}'"))?;
+                        anyhow::Ok((requirement, spec))
+                    }
+                })
+                .collect::<FuturesUnordered<_>>()
+                .try_collect::<Vec<_>>()
+                .await?;
+            tool_specs.extend(resolved_specs.iter().map(|(_, spec)| spec.clone()));
+            resolved.extend(resolved_specs);
+        }
+
+        // NOTE: A dry run only reports which tools would need to be
+        // trusted, since trusting them would not be a dry run at all
+        if self.dry_run {
+            let untrusted_specs = if no_trust_check {
+                BTreeSet::new()
+            } else {
+                let trust_manifest = home.trust_manifest().await?;
+                tool_specs
+                    .iter()
+                    .filter(|spec| {
+                        !tool_cache.is_trusted(spec.id()) && !trust_manifest.is_trusted(spec.id())
+                    })
+                    .cloned()
+                    .collect()
+            };
+            let (installed_specs, missing_specs) = tool_specs
                 .into_iter()
-                .partition(|spec| tool_cache.is_trusted(spec.id()));
+                .partition(|spec| !install_all && tool_cache.is_installed(spec));
+            return report_dry_run(
+                &source,
+                &manifests,
+                &resolved,
+                lockfile,
+                missing_specs,
+                &installed_specs,
+                &untrusted_specs,
+            )
+            .await;
+        }
+
+        let (tool_specs, newly_trusted) = if no_trust_check {
+            (tool_specs, Vec::new())
+        } else {
+            // NOTE: Tools trusted by a trust policy are not added to the tool cache,
+            // so that removing them from the policy also removes their trust
+            let trust_manifest = home.trust_manifest().await?;
+            let (trusted_specs, untrusted_specs) = tool_specs.into_iter().partition(|spec| {
+                tool_cache.is_trusted(spec.id()) || trust_manifest.is_trusted(spec.id())
+            });
             let newly_trusted_specs = prompt_for_trust_specs(untrusted_specs).await?;
             for spec in &newly_trusted_specs {
                 let _ = tool_cache.add_trust(spec.id().clone());
             }
-            trusted_specs
+            let tool_specs = trusted_specs
                 .iter()
                 .chain(newly_trusted_specs.iter())
                 .cloned()
-                .collect::<BTreeSet<_>>()
+                .collect::<BTreeSet<_>>();
+            let newly_trusted = newly_trusted_specs
+                .into_iter()
+                .map(|spec| spec.id().clone())
+                .collect();
+            (tool_specs, newly_trusted)
         };
 
+        // NOTE: Artifacts for other systems can not be installed or
+        // linked, and must not be added to the lockfile either
+        if let Some(target) = self.target {
+            return download_for_target(&source, &manifests, &resolved, tool_specs, target).await;
+        }
+
         // 3. Find artifacts, download and install them
 
-        let pt =
-            CliProgressTracker::new_with_message_and_subtasks("Installing", tool_specs.len(), 5);
-        let installed_specs = tool_specs
+        // NOTE: Several aliases may use the same tool spec, which is only
+        // downloaded and installed once, but every alias must still be linked
+        let spec_aliases = group_aliases_by_spec(&manifests, &resolved);
+        for (spec, aliases) in spec_aliases.iter().filter(|(_, a)| a.len() > 1) {
+            tracing::debug!(%spec, ?aliases, "installing tool once for multiple aliases");
+        }
+
+        let pt = CliProgressTracker::new_with_message_and_subtasks(
+            "Installing",
+            tool_specs.len(),
+            INSTALL_SUBTASKS,
+        )
+        .with_download_weight(DOWNLOAD_WEIGHT);
+        let tools_left = AtomicUsize::new(
+            tool_specs
+                .iter()
+                .filter(|spec| install_all || !tool_cache.is_installed(spec))
+                .count(),
+        );
+        let warned_rate_limit = AtomicBool::new(false);
+        let fallback_jobs = config.jobs.map_or(DEFAULT_JOBS, NonZeroUsize::get);
+        let jobs = Semaphore::new(
+            self.jobs
+                .map_or_else(|| jobs_from_env(fallback_jobs), NonZeroUsize::get),
+        );
+        let provider_jobs = config
+            .providers
+            .iter()
+            .filter_map(|(provider, config)| Some((*provider, Semaphore::new(config.jobs?.get()))))
+            .collect::<HashMap<_, _>>();
+        let installing = tool_specs
             .into_iter()
             .map(|tool_spec| async {
-                if tool_cache.is_installed(&tool_spec) && !force {
+                if tool_cache.is_installed(&tool_spec) && !install_all {
                     pt.task_completed();
                     // HACK: Force the async closure to take ownership
                     // of tool_spec by returning it from the closure
-                    return anyhow::Ok(tool_spec);
+                    return anyhow::Ok((tool_spec, ToolOutcome::AlreadyInstalled));
                 }
 
-                let artifacts = source.get_specific_release(&tool_spec).await?;
-                pt.subtask_completed();
+                // NOTE: Only a limited number of tools are downloaded and installed at
+                // once, to avoid opening too many connections and tripping abuse detection,
+                // and providers may be configured with their own, lower limit - the permit
+                // for the provider is acquired first to not hold up tools from other providers
+                let _provider_permit = match provider_jobs.get(&tool_spec.provider()) {
+                    Some(provider_jobs) => Some(provider_jobs.acquire().await?),
+                    None => None,
+                };
+                let _permit = jobs.acquire().await?;
+                let started = Instant::now();
 
-                let artifact = find_most_compatible_artifact(&artifacts, tool_spec.id())?;
-                pt.subtask_completed();
+                let (alias, binary) = binary_selection(&manifests, &resolved, &tool_spec);
+                let bar = pt.new_tool_bar(&tool_spec, "fetching release");
+                // NOTE: Reinstalling uses cached releases even if they are stale,
+                // since the tool was already installed using the same release
+                let cached = if reinstall {
+                    source.get_cached_release(&tool_spec).await
+                } else {
+                    None
+                };
+                let artifacts = match cached {
+                    Some(artifacts) => artifacts,
+                    None => {
+                        source
+                            .get_specific_release_with_tag_prefix(
+                                &tool_spec,
+                                tag_prefix(&manifests, alias.as_ref()),
+                            )
+                            .await?
+                    }
+                };
 
-                let contents = source
-                    .download_artifact_contents(&artifact)
-                    .await
-                    .with_context(|| format!("Failed to download contents for {tool_spec}"))?;
-                pt.subtask_completed();
+                // Warn (once) if we are about to run into rate limits
+                let left = tools_left.fetch_sub(1, Ordering::SeqCst) - 1;
+                let remaining = source.remaining_requests(tool_spec.provider());
+                if remaining.is_some_and(|r| r < left as u64)
+                    && !warned_rate_limit.swap(true, Ordering::SeqCst)
+                {
+                    pt.print_message(format!(
+                        "{} Only {} {} API requests remain before being rate limited, \
+                        but {left} tools are left to install.\
+                        \nRun `{}` to raise the rate limit.",
+                        style("Warning:").bold().yellow(),
+                        remaining.unwrap_or_default(),
+                        tool_spec.provider().display_name(),
+                        style(format!(
+                            "rokit authenticate {} --token YOUR_TOKEN_HERE",
+                            tool_spec.provider()
+                        ))
+                        .bold()
+                        .green(),
+                    ));
+                }
 
-                let extracted = artifact
-                    .extract_contents(contents)
-                    .await
-                    .with_context(|| format!("Failed to extract contents for {tool_spec}"))?;
-                pt.subtask_completed();
+                let (minisign_key, artifact_pattern) =
+                    artifact_settings(&manifests, alias.as_ref())?;
 
-                tool_storage
-                    .replace_tool_contents(&tool_spec, extracted)
-                    .await?;
-                pt.subtask_completed();
+                // NOTE: Locked artifacts must be used exactly as they
+                // were, including the checksum of their contents
+                let locked = match lockfile.and_then(|l| l.get_artifact(&tool_spec)) {
+                    Some(locked) => find_locked_artifact(&artifacts, locked, &tool_spec)?,
+                    None => None,
+                };
+                let artifact = if let Some(locked) = locked {
+                    locked
+                } else {
+                    match find_most_compatible_artifact(
+                        &artifacts,
+                        tool_spec.id(),
+                        artifact_pattern.as_ref(),
+                    ) {
+                        Ok(artifact) => artifact,
+                        // NOTE: Tools with an artifact pattern that matches nothing are
+                        // never skipped, since that is a mistake in the manifest instead
+                        Err(e) if self.skip_incompatible && artifact_pattern.is_none() => {
+                            pt.print_message(format!(
+                                "{} Skipping {} - {e}",
+                                style("Warning:").bold().yellow(),
+                                style(&tool_spec).bold().magenta(),
+                            ));
+                            for _ in 0..INSTALL_SUBTASKS {
+                                pt.subtask_completed();
+                            }
+                            pt.download_skipped();
+                            let reason = format!(
+                                "no compatible artifact for {}",
+                                Descriptor::current_system()
+                            );
+                            return Ok((tool_spec, ToolOutcome::Skipped { reason }));
+                        }
+                        Err(e) => return Err(e),
+                    }
+                };
 
-                let _ = tool_cache.add_installed(tool_spec.clone());
-                Ok(tool_spec)
+                // NOTE: Post-install commands only run for tools that are actually
+                // downloaded and installed, never for tools that were already installed
+                let verify_args = verify_args(&manifests, alias.as_ref(), self.verify);
+                let post_install = spec_aliases.get(&tool_spec).and_then(|aliases| {
+                    post_install_command(&manifests, aliases.intersection(&tool_aliases))
+                });
+                let options = InstallOptions {
+                    binary: binary.as_deref(),
+                    alias: alias.as_ref(),
+                    artifact_pattern: artifact_pattern.as_ref(),
+                    minisign_key: minisign_key.as_ref(),
+                    verify_args: verify_args.as_deref(),
+                    post_install: post_install.map(|(_, post_install)| post_install),
+                    release: Some(&artifacts),
+                    artifact: Some(&artifact),
+                    ..InstallOptions::default()
+                };
+                let installed =
+                    install_spec_with_options(home, &source, &tool_spec, options, |p| {
+                        bar.report_install(p);
+                    })
+                    .await
+                    .map_err(|e| install_error(e, &tool_spec, post_install))?;
+                if let Some(e) = installed.post_install_error {
+                    pt.print_message(format!(
+                        "{} {e}\nRun `{}` with `{}` to see its output.",
+                        style("Warning:").bold().yellow(),
+                        style("rokit install").bold().green(),
+                        style("--verbose").bold().green(),
+                    ));
+                }
+
+                Ok((
+                    tool_spec,
+                    ToolOutcome::Downloaded(DownloadedTool {
+                        locked: installed.url.map(|url| (url, installed.checksum)),
+                        bytes: installed.bytes,
+                        elapsed: started.elapsed(),
+                    }),
+                ))
             })
             .collect::<FuturesUnordered<_>>()
-            .try_collect::<Vec<_>>()
-            .await?;
+            .try_collect::<Vec<_>>();
+        let installing = async {
+            match install_timeout {
+                None => installing.await,
+                Some(secs) => timeout(Duration::from_secs(secs.get()), installing)
+                    .await
+                    .map_err(|_| {
+                        anyhow!(
+                            "Installation timed out after {secs} second{}!\
+                            \nTools that finished installing have been kept, \
+                            run `{}` again to install the rest.",
+                            if secs.get() == 1 { "" } else { "s" },
+                            style("rokit install").bold().green()
+                        )
+                    })?,
+            }
+        };
+
+        // NOTE: Dropping the installation future cancels all downloads, and tool
+        // binaries are written atomically, so cancelling never leaves partial files
+        let results = tokio::select! {
+            result = installing => result?,
+            _ = ctrl_c() => {
+                pt.finish_and_clear();
+                bail!(
+                    "Installation was cancelled!\
+                    \nTools that finished installing have been kept, \
+                    run `{}` again to install the rest.",
+                    style("rokit install").bold().green()
+                );
+            }
+        };
 
-        // 4. Link all of the (possibly new) aliases, we do this even if the
+        // NOTE: Skipped tools are neither locked nor linked, so
+        // that running them does not silently use some other version
+        let mut installed_specs = Vec::new();
+        let mut skipped = Vec::new();
+        for (spec, outcome) in results {
+            match outcome {
+                ToolOutcome::AlreadyInstalled => installed_specs.push((spec, None)),
+                ToolOutcome::Downloaded(downloaded) => {
+                    installed_specs.push((spec, Some(downloaded)));
+                }
+                ToolOutcome::Skipped { reason } => {
+                    for alias in spec_aliases.get(&spec).into_iter().flatten() {
+                        tool_aliases.remove(alias);
+                    }
+                    skipped.push(SkippedTool { spec, reason });
+                }
+            }
+        }
+
+        // 4. Update the lockfile with any newly resolved and installed tools,
+        // removing tools that are no longer in any manifest, unless locked
+        if let Some(dir) = lockfile_dir.as_ref().filter(|_| !locked) {
+            let installed = installed_specs
+                .iter()
+                .map(|(spec, _)| spec)
+                .collect::<HashSet<_>>();
+            let all_requirements = resolved.into_iter().chain(exact_requirements);
+            let mut new_lockfile = existing_lockfile.clone().unwrap_or_default();
+            for (requirement, spec) in all_requirements {
+                if installed.contains(&spec) {
+                    new_lockfile.lock_resolved(requirement, spec);
+                }
+            }
+            for (spec, downloaded) in &installed_specs {
+                if let Some((url, checksum)) = downloaded.as_ref().and_then(|d| d.locked.as_ref()) {
+                    new_lockfile.lock_artifact(spec, url, checksum);
+                }
+            }
+            new_lockfile.retain_requirements(&manifest_requirements);
+            let is_unchanged = match &existing_lockfile {
+                Some(existing) => existing == &new_lockfile,
+                None => new_lockfile == RokitLockfile::default(),
+            };
+            if !is_unchanged {
+                new_lockfile
+                    .save(dir)
+                    .await
+                    .context("Failed to save lockfile")?;
+            }
+        }
+
+        // 5. Link all of the (possibly new) aliases, we do this even if the
         // tool is already installed in case the link(s) have been corrupted
-        // and the user tries to re-install tools to fix it.
+        // and the user tries to re-install tools to fix it - unless links
+        // were not wanted at all, in which case tools are only installed.
 
-        pt.update_message("Linking");
-        tool_aliases
-            .iter()
-            .map(|alias| tool_storage.create_tool_link(alias))
-            .collect::<FuturesUnordered<_>>()
-            .try_collect::<Vec<_>>()
-            .await?;
+        if !self.no_links {
+            pt.update_message("Linking");
+            tool_aliases
+                .iter()
+                .map(|alias| tool_storage.create_tool_link(alias))
+                .collect::<FuturesUnordered<_>>()
+                .try_collect::<Vec<_>>()
+                .await?;
+        }
 
-        // 5. Finally, display a nice message to the user
-        let s = if installed_specs.len() == 1 { "" } else { "s" };
+        // 6. Finally, summarize what was installed and display a nice message to the user
+        let mut summary = InstallSummary {
+            skipped,
+            newly_trusted,
+            linked: if self.no_links {
+                Vec::new()
+            } else {
+                tool_aliases.into_iter().collect()
+            },
+            ..InstallSummary::default()
+        };
+        for (spec, downloaded) in installed_specs {
+            match downloaded {
+                Some(downloaded) => summary.installed.push(InstalledTool {
+                    spec,
+                    bytes: downloaded.bytes,
+                    elapsed: downloaded.elapsed,
+                }),
+                None => summary.already_installed.push(spec),
+            }
+        }
+        let s = if summary.len() == 1 { "" } else { "s" };
+        let links = if self.no_links {
+            String::from(", links were skipped")
+        } else {
+            let links_s = if summary.linked.len() == 1 { "" } else { "s" };
+            format!(
+                " and created {} link{links_s}",
+                style(summary.linked.len()).bold().magenta()
+            )
+        };
         pt.finish_with_message(format!(
-            "Installed and created link{s} for {} tool{s} {}",
-            style(installed_specs.len()).bold().magenta(),
+            "Installed {} tool{s}{links} {}{}{}",
+            style(summary.len()).bold().magenta(),
             pt.formatted_elapsed(),
+            format_install_summary(&summary),
+            format_skipped_tools(&summary),
         ));
 
+        // NOTE: We exit directly with a distinct exit code below, so that
+        // scripts can tell skipped tools apart from failures, which means
+        // that any newly installed tools and trust must be saved first
+        if !summary.skipped.is_empty() {
+            home.save().await?;
+            exit(SKIPPED_EXIT_CODE);
+        }
+
         Ok(())
     }
 }
+
+// Makes sure that all of the given aliases exist in at least one manifest
+fn check_unknown_aliases(aliases: &[ToolAlias], known: &HashSet<ToolAlias>) -> Result<()> {
+    let unknown = aliases
+        .iter()
+        .filter(|alias| !known.contains(*alias))
+        .map(|alias| format!("'{alias}'"))
+        .collect::<Vec<_>>();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    let mut known = known.iter().map(ToString::to_string).collect::<Vec<_>>();
+    known.sort();
+    let s = if unknown.len() == 1 { "" } else { "es" };
+    bail!(
+        "No tool{} found for the alias{s} {}\
+        \nKnown aliases: {}",
+        if unknown.len() == 1 { " was" } else { "s were" },
+        unknown.join(", "),
+        if known.is_empty() {
+            String::from("(none)")
+        } else {
+            known.join(", ")
+        },
+    );
+}
+
+/*
+    Checks that every tool spec and requirement from the manifests has been
+    resolved in the lockfile, without looking at which tools are installed.
+*/
+fn check_lockfile_in_sync(
+    lockfile: Option<&RokitLockfile>,
+    tool_specs: &BTreeSet<ToolSpec>,
+    requirements: &HashSet<ToolSpecReq>,
+) -> Result<()> {
+    let Some(lockfile) = lockfile else {
+        bail!(
+            "No lockfile was found, which is required in frozen mode.\
+            \nRun `{}` to create the lockfile.",
+            style("rokit install").bold().green(),
+        );
+    };
+    let mut drifted = tool_specs
+        .iter()
+        .cloned()
+        .map(ToolSpecReq::from)
+        .chain(requirements.iter().cloned())
+        .filter(|requirement| lockfile.get_resolved(requirement).is_none())
+        .map(|requirement| requirement.to_string())
+        .collect::<Vec<_>>();
+    if drifted.is_empty() {
+        return Ok(());
+    }
+    drifted.sort();
+    bail!(
+        "Some tools in manifests are not in the lockfile, which is required in frozen mode:\
+        \n{}\
+        \nRun `{}` to update the lockfile.",
+        drifted
+            .iter()
+            .map(|requirement| format!("  {} {requirement}", style("•").dim()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        style("rokit install").bold().green(),
+    )
+}
+
+/*
+    Checks that the repository for each tool exists on its provider, so that typos
+    in tool ids fail early with a clear error, suggesting similar tools if possible.
+
+    Any other errors are ignored here, since they will surface with
+    more context once releases for the tools are actually fetched.
+*/
+async fn check_tools_exist(source: &ArtifactSource, ids: BTreeSet<ToolId>) -> Result<()> {
+    let missing = ids
+        .into_iter()
+        .map(|id| async move {
+            match source.repository_exists(&id).await {
+                Ok(false) => Some(id),
+                Ok(true) => None,
+                Err(e) => {
+                    tracing::debug!(%id, error = %e, "failed to check if repository exists");
+                    None
+                }
+            }
+        })
+        .collect::<FuturesUnordered<_>>()
+        .filter_map(|id| async move { id })
+        .collect::<BTreeSet<_>>()
+        .await;
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let mut lines = Vec::new();
+    for id in &missing {
+        let similar = source.find_similar_tools(id).await.unwrap_or_default();
+        let suggestion = match similar.first() {
+            Some(similar) => format!(" - did you mean {}?", style(similar).bold().magenta()),
+            None => String::new(),
+        };
+        lines.push(format!(
+            "  {} {}{suggestion}",
+            style("•").dim(),
+            style(id).bold().magenta()
+        ));
+    }
+    bail!(
+        "Some tools were not found, check their ids in your manifests for typos:\
+        \n{}\
+        \nRepositories that are private also require authentication to be found.",
+        lines.join("\n"),
+    )
+}
+
+/*
+    Downloads and verifies the artifacts for the given tools for another
+    system, which adds them to the download cache, without installing them.
+*/
+async fn download_for_target(
+    source: &ArtifactSource,
+    manifests: &[DiscoveredManifest],
+    resolved: &HashMap<ToolSpecReq, ToolSpec>,
+    tool_specs: BTreeSet<ToolSpec>,
+    target: Descriptor,
+) -> Result<()> {
+    let pt = CliProgressTracker::new_with_message("Downloading", tool_specs.len());
+    let downloaded = tool_specs
+        .into_iter()
+        .map(|spec| async {
+            let (alias, _) = binary_selection(manifests, resolved, &spec);
+            let artifacts = source
+                .get_specific_release_with_tag_prefix(&spec, tag_prefix(manifests, alias.as_ref()))
+                .await?;
+            let (minisign_key, artifact_pattern) = artifact_settings(manifests, alias.as_ref())?;
+            let artifact =
+                find_artifact_for_target(&artifacts, spec.id(), artifact_pattern.as_ref(), target)?;
+            let artifact = source.attach_checksum(&artifact, &artifacts).await?;
+            let artifact = if minisign_key.is_some() {
+                source.attach_signature(&artifact, &artifacts).await?
+            } else {
+                artifact
+            };
+
+            // NOTE: Cached artifacts were already verified when they were downloaded
+            if source.is_cached(&artifact).await {
+                pt.task_completed();
+                return anyhow::Ok((spec, artifact, None));
+            }
+
+            let contents = pt
+                .download_artifact(source, &artifact)
+                .await
+                .with_context(|| format!("Failed to download contents for {spec}"))?;
+            artifact
+                .verify_checksum(&contents)
+                .with_context(|| format!("Failed to verify contents for {spec}"))?;
+            if let Some(key) = &minisign_key {
+                artifact
+                    .verify_signature(&contents, key)
+                    .with_context(|| format!("Failed to verify signature for {spec}"))?;
+            }
+            pt.task_completed();
+
+            anyhow::Ok((spec, artifact, Some(contents.len() as u64)))
+        })
+        .collect::<FuturesUnordered<_>>()
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut downloaded = downloaded;
+    downloaded.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    let bullet = style("•").dim();
+    let arrow = style("→").dim();
+    let lines = downloaded
+        .iter()
+        .map(|(spec, artifact, bytes)| {
+            format!(
+                "{bullet} {} {arrow} {} {}",
+                style(spec).bold().magenta(),
+                artifact.file_name.as_deref().unwrap_or("N/A"),
+                style(match bytes {
+                    Some(bytes) => format!("({})", HumanBytes(*bytes)),
+                    None => String::from("(already cached)"),
+                })
+                .dim(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let num_cached = downloaded.iter().filter(|(_, _, b)| b.is_none()).count();
+    let total_bytes = downloaded.iter().filter_map(|(_, _, b)| *b).sum::<u64>();
+    let num_downloaded = downloaded.len() - num_cached;
+    let s = if num_downloaded == 1 { "" } else { "s" };
+    let cached_note = if num_cached == 0 {
+        String::new()
+    } else {
+        format!(", {} already cached", style(num_cached).bold().magenta())
+    };
+    pt.finish_with_message(format!(
+        "Downloaded {} tool{s} ({}) for {} into the cache{cached_note} {}\n\n{}",
+        style(num_downloaded).bold().magenta(),
+        style(HumanBytes(total_bytes)).bold(),
+        style(target).bold(),
+        pt.formatted_elapsed(),
+        lines.join("\n"),
+    ));
+
+    Ok(())
+}
+
+/*
+    Finds the artifacts that would be downloaded for the given tools,
+    without downloading them, and reports them to the user along with
+    any tools that would need to be trusted, or can not be installed.
+*/
+async fn report_dry_run(
+    source: &ArtifactSource,
+    manifests: &[DiscoveredManifest],
+    resolved: &HashMap<ToolSpecReq, ToolSpec>,
+    lockfile: Option<&RokitLockfile>,
+    missing_specs: BTreeSet<ToolSpec>,
+    installed_specs: &BTreeSet<ToolSpec>,
+    untrusted_specs: &BTreeSet<ToolSpec>,
+) -> Result<()> {
+    let pt = CliProgressTracker::new_with_message("Resolving", missing_specs.len());
+
+    // NOTE: Failures are collected instead of returned right away,
+    // so that all tools that can not be installed are reported at once
+    let results = missing_specs
+        .into_iter()
+        .map(|spec| async {
+            let result = async {
+                let (alias, _) = binary_selection(manifests, resolved, &spec);
+                let artifacts = source
+                    .get_specific_release_with_tag_prefix(
+                        &spec,
+                        tag_prefix(manifests, alias.as_ref()),
+                    )
+                    .await?;
+                let (_, artifact_pattern) = artifact_settings(manifests, alias.as_ref())?;
+                let locked = match lockfile.and_then(|l| l.get_artifact(&spec)) {
+                    Some(locked) => find_locked_artifact(&artifacts, locked, &spec)?,
+                    None => None,
+                };
+                match locked {
+                    Some(locked) => Ok(locked),
+                    None => find_most_compatible_artifact(
+                        &artifacts,
+                        spec.id(),
+                        artifact_pattern.as_ref(),
+                    ),
+                }
+            }
+            .await;
+            pt.task_completed();
+            anyhow::Ok((spec, result))
+        })
+        .collect::<FuturesUnordered<_>>()
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut artifacts = Vec::new();
+    let mut failures = Vec::new();
+    for (spec, result) in results {
+        match result {
+            Ok(artifact) => artifacts.push((spec, artifact)),
+            Err(e) => failures.push((spec, e)),
+        }
+    }
+    artifacts.sort_by(|(a, _), (b, _)| a.cmp(b));
+    failures.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let bullet = style("•").dim();
+    let arrow = style("→").dim();
+    let mut sections = Vec::new();
+    if !artifacts.is_empty() {
+        let total_size = artifacts
+            .iter()
+            .map(|(_, artifact)| artifact.size.unwrap_or_default())
+            .sum::<u64>();
+        let all_sizes_known = artifacts
+            .iter()
+            .all(|(_, artifact)| artifact.size.is_some());
+        let lines = artifacts
+            .iter()
+            .map(|(spec, artifact)| {
+                let size = artifact
+                    .size
+                    .map(|size| format!(" ({})", HumanBytes(size)))
+                    .unwrap_or_default();
+                format!(
+                    "{bullet} {} {arrow} {}{}",
+                    style(spec).bold().magenta(),
+                    artifact.file_name.as_deref().unwrap_or("N/A"),
+                    style(size).dim(),
+                )
+            })
+            .collect::<Vec<_>>();
+        sections.push(format!(
+            "Would download {} tool{} ({}{} total):\n{}",
+            style(artifacts.len()).bold().magenta(),
+            if artifacts.len() == 1 { "" } else { "s" },
+            if all_sizes_known { "" } else { "at least " },
+            HumanBytes(total_size),
+            lines.join("\n"),
+        ));
+    }
+    if !installed_specs.is_empty() {
+        let lines = installed_specs
+            .iter()
+            .map(|spec| format!("{bullet} {spec}"))
+            .collect::<Vec<_>>();
+        sections.push(format!("Already installed:\n{}", lines.join("\n")));
+    }
+    if !untrusted_specs.is_empty() {
+        let lines = untrusted_specs
+            .iter()
+            .map(ToolSpec::id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|id| format!("{bullet} {id}"))
+            .collect::<Vec<_>>();
+        sections.push(format!("Would ask to trust:\n{}", lines.join("\n")));
+    }
+
+    if sections.is_empty() {
+        sections.push(String::from("No tools would be installed."));
+    }
+    pt.finish_with_message(format!(
+        "Dry run finished, nothing was installed {}\n\n{}",
+        pt.formatted_elapsed(),
+        sections.join("\n\n"),
+    ));
+
+    if !failures.is_empty() {
+        bail!(
+            "Some tools can not be installed:\n{}",
+            failures
+                .iter()
+                .map(|(spec, e)| format!("  {bullet} {spec}: {e:#}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    Ok(())
+}
+
+/*
+    A tool that was downloaded and installed, as opposed to a tool that was
+    already installed, along with details for the lockfile and the summary.
+*/
+struct DownloadedTool {
+    locked: Option<(Url, String)>,
+    bytes: u64,
+    elapsed: Duration,
+}
+
+/*
+    What happened to a single tool when installing it.
+*/
+enum ToolOutcome {
+    AlreadyInstalled,
+    Downloaded(DownloadedTool),
+    Skipped { reason: String },
+}
+
+/*
+    Formats a summary of downloaded and already installed tools, which
+    is displayed after the final message when tools were downloaded.
+*/
+fn format_install_summary(summary: &InstallSummary) -> String {
+    let Some(slowest) = summary.slowest() else {
+        return String::new();
+    };
+
+    let downloaded = summary.installed.len();
+    let already_installed = summary.already_installed.len();
+    format!(
+        "\n\nDownloaded {} for {} tool{}, {} tool{} already installed\
+        \nSlowest tool was {} {}",
+        style(HumanBytes(summary.downloaded_bytes())).bold(),
+        downloaded,
+        if downloaded == 1 { "" } else { "s" },
+        already_installed,
+        if already_installed == 1 {
+            " was"
+        } else {
+            "s were"
+        },
+        style(&slowest.spec).bold().magenta(),
+        style(format!("(took {:.2?})", slowest.elapsed)).dim(),
+    )
+}
+
+// Formats the list of skipped tools, and why they were skipped, if any
+fn format_skipped_tools(summary: &InstallSummary) -> String {
+    if summary.skipped.is_empty() {
+        return String::new();
+    }
+    let s = if summary.skipped.len() == 1 { "" } else { "s" };
+    let lines = summary
+        .skipped
+        .iter()
+        .map(|tool| {
+            format!(
+                "  {} {} - {}",
+                style("•").dim(),
+                style(&tool.spec).bold().magenta(),
+                tool.reason
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "\n\n{} {} tool{s} that could not be installed on this system:\n{}",
+        style("Skipped").bold().yellow(),
+        summary.skipped.len(),
+        lines.join("\n"),
+    )
+}
+
+// Reads the maximum number of concurrent jobs from the environment, if set and valid
+fn jobs_from_env(fallback: usize) -> usize {
+    let Ok(value) = var(JOBS_ENV_VAR) else {
+        return fallback;
+    };
+    if let Ok(jobs) = value.trim().parse::<NonZeroUsize>() {
+        jobs.get()
+    } else {
+        tracing::warn!(
+            "Invalid value for {JOBS_ENV_VAR} - expected a positive number, got '{value}'\
+            \nUsing {fallback} jobs instead."
+        );
+        fallback
+    }
+}
+
+/*
+    Finds the artifact that was locked for a tool on the current system
+    in the artifacts for its release, with the checksum from the lockfile.
+
+    Locked artifacts that can not run on the current system, such as artifacts
+    for another libc, are ignored, so that the most compatible one is used instead.
+*/
+fn find_locked_artifact(
+    artifacts: &[Artifact],
+    locked: &LockedArtifact,
+    spec: &ToolSpec,
+) -> Result<Option<Artifact>> {
+    let artifact = artifacts
+        .iter()
+        .find(|artifact| artifact.url.as_ref() == Some(&locked.url))
+        .with_context(|| {
+            format!(
+                "The artifact locked for {spec} no longer exists in its release: {}\
+                \nRun `{}` to update the lockfile.",
+                locked.url,
+                style("rokit install --update").bold().green()
+            )
+        })?;
+    if !artifact.is_compatible_with(Descriptor::current_system()) {
+        tracing::debug!(
+            %spec,
+            name = ?artifact.name,
+            "ignoring locked artifact that is not compatible with the current system"
+        );
+        return Ok(None);
+    }
+    Ok(Some(Artifact {
+        checksum: Some(locked.checksum.clone()),
+        ..artifact.clone()
+    }))
+}
+
+/*
+    Finds the alias and binary name that a tool spec should be extracted
+    with, using the first manifest that contains the tool spec, and
+    preferring aliases that explicitly specify a binary to extract.
+*/
+fn binary_selection(
+    manifests: &[DiscoveredManifest],
+    resolved: &HashMap<ToolSpecReq, ToolSpec>,
+    spec: &ToolSpec,
+) -> (Option<ToolAlias>, Option<String>) {
+    for manifest in manifests {
+        let best = manifest
+            .entries
+            .iter()
+            .filter(|(_, entry)| match &entry.source {
+                ToolSource::Spec(s) => s == spec,
+                ToolSource::Requirement(r) => resolved.get(r) == Some(spec),
+                ToolSource::Local(_) => false,
+            })
+            .min_by_key(|(alias, entry)| (entry.binary.is_none(), *alias));
+        if let Some((alias, entry)) = best {
+            return (Some(alias.clone()), entry.binary.clone());
+        }
+    }
+    (None, None)
+}
+
+/*
+    Parses the minisign public key and artifact pattern
+    for a tool alias, if the tool has an alias at all.
+*/
+fn artifact_settings(
+    manifests: &[DiscoveredManifest],
+    alias: Option<&ToolAlias>,
+) -> Result<(Option<MinisignPublicKey>, Option<ArtifactPattern>)> {
+    let Some(alias) = alias else {
+        return Ok((None, None));
+    };
+    let entry = manifest_entry(manifests, alias);
+    Ok((
+        parse_minisign_key(alias, entry.and_then(|entry| entry.minisign_key.as_deref()))?,
+        parse_artifact_pattern(
+            alias,
+            entry.and_then(|entry| entry.artifact_pattern.as_deref()),
+        )?,
+    ))
+}
+
+/*
+    Finds the prefix for the release tags of a tool alias,
+    if the tool has an alias, and the prefix was specified.
+*/
+fn tag_prefix<'a>(
+    manifests: &'a [DiscoveredManifest],
+    alias: Option<&ToolAlias>,
+) -> Option<&'a str> {
+    manifest_entry(manifests, alias?)?.tag_prefix.as_deref()
+}
+
+/*
+    Finds the entry for a tool alias, along with its settings such as the
+    minisign public key, using the first manifest that contains the alias.
+*/
+fn manifest_entry<'a>(
+    manifests: &'a [DiscoveredManifest],
+    alias: &ToolAlias,
+) -> Option<&'a ToolEntry> {
+    manifests
+        .iter()
+        .find_map(|manifest| manifest.entries.get(alias))
+}
+
+/*
+    Gets the post-install command to run for a tool that is installed for the given
+    aliases, if any of them declares one - since each tool is only installed once,
+    the command declared for the first alias, in alphabetical order, is used.
+*/
+fn post_install_command<'a, 'b>(
+    manifests: &'a [DiscoveredManifest],
+    aliases: impl IntoIterator<Item = &'b ToolAlias>,
+) -> Option<(&'b ToolAlias, &'a ToolPostInstall)> {
+    aliases.into_iter().find_map(|alias| {
+        let post_install = manifest_entry(manifests, alias)?.post_install.as_ref()?;
+        Some((alias, post_install))
+    })
+}
+
+/*
+    Adds context to errors from installing a tool, for errors
+    that users can do something about, such as verification.
+*/
+fn install_error(
+    error: RokitError,
+    spec: &ToolSpec,
+    post_install: Option<(&ToolAlias, &ToolPostInstall)>,
+) -> anyhow::Error {
+    match (&error, post_install) {
+        (RokitError::ToolVerificationFailed { .. }, _) => {
+            anyhow::Error::from(error).context(format!(
+                "Failed to verify {spec} - the wrong artifact may have been \
+            installed for this system, or the tool may not support it"
+            ))
+        }
+        (RokitError::PostInstallFailed { .. }, Some((alias, _))) => anyhow::Error::from(error)
+            .context(format!(
+                "Failed to run the post-install command for '{alias}'\
+                \nRun `{}` with `{}` to see its output.",
+                style("rokit install").bold().green(),
+                style("--verbose").bold().green(),
+            )),
+        _ => anyhow::Error::from(error).context(format!("Failed to install {spec}")),
+    }
+}
+
+/*
+    Gets the arguments to verify that a tool runs with after installing it, if any.
+
+    Tools are verified if the manifest asks for it, or if `--verify` was
+    given, unless the manifest explicitly disables verification for the tool.
+*/
+fn verify_args(
+    manifests: &[DiscoveredManifest],
+    alias: Option<&ToolAlias>,
+    verify_all: bool,
+) -> Option<Vec<String>> {
+    let default = ToolVerify::default();
+    let setting = alias
+        .and_then(|alias| manifest_entry(manifests, alias))
+        .and_then(|entry| entry.verify.as_ref());
+    let verify = setting.or(verify_all.then_some(&default))?;
+    verify.args().map(<[String]>::to_vec)
+}
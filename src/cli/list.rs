@@ -1,18 +1,64 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use anyhow::Result;
 use clap::Parser;
 use console::style;
+use semver::Version;
+use serde_json::json;
+
+use rokit::{
+    discovery::{discover_all_manifests, DiscoveredManifest},
+    storage::Home,
+    system::current_dir,
+    tool::{ToolAlias, ToolId, ToolSpecReq},
+};
 
-use rokit::{discovery::discover_all_manifests, storage::Home, system::current_dir, tool::ToolId};
+use crate::util::{print_json, OutputFormat};
 
 /// Lists all existing tools managed by Rokit.
 #[derive(Debug, Parser)]
 pub struct ListSubcommand {
     /// A specific tool identifier to list installed versions for.
     pub id: Option<ToolId>,
+    /// List all installed tools, their versions, and links,
+    /// instead of the tools found in manifests.
+    #[clap(long, conflicts_with = "id")]
+    pub installed: bool,
+    /// Output the list as JSON instead of human-readable text.
+    #[clap(long)]
+    pub json: bool,
 }
 
 impl ListSubcommand {
     pub async fn run(self, home: &Home) -> Result<()> {
+        if self.json || OutputFormat::is_json() {
+            let value = if self.installed {
+                installed_tools_to_json(&collect_installed_tools(home).await)
+            } else if let Some(id) = self.id {
+                let cache = home.tool_cache();
+                json!({
+                    "id": id.to_string(),
+                    "versions": cache
+                        .all_installed_versions_for_id(&id)
+                        .iter()
+                        .rev()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>(),
+                })
+            } else {
+                manifest_tools_to_json().await
+            };
+            print_json(value)?;
+            return Ok(());
+        }
+
+        if self.installed {
+            let tools = collect_installed_tools(home).await;
+            let (header, lines) = list_installed_tools(&tools);
+            println!("{header}\n{}", lines.join("\n"));
+            return Ok(());
+        }
+
         let (header, lines) = if let Some(id) = self.id {
             list_versions_for_id(home, &id)
         } else {
@@ -25,6 +71,168 @@ impl ListSubcommand {
     }
 }
 
+// An installed tool, grouped by id, with all of its known aliases and versions
+struct InstalledTool {
+    id: ToolId,
+    aliases: Vec<ToolAlias>,
+    versions: Vec<Version>,
+    linked: bool,
+}
+
+impl InstalledTool {
+    fn sort_key(&self) -> String {
+        self.aliases
+            .first()
+            .map_or(self.id.name(), ToolAlias::name)
+            .to_ascii_lowercase()
+    }
+}
+
+// Collects all installed tools from the tool cache, and pairs them up with
+// aliases from any discovered manifests - falling back to the tool name
+async fn collect_installed_tools(home: &Home) -> Vec<InstalledTool> {
+    let cache = home.tool_cache();
+    let storage = home.tool_storage();
+
+    let mut aliases_by_id = BTreeMap::<ToolId, BTreeSet<ToolAlias>>::new();
+    for manifest in discover_all_manifests(false, false).await {
+        for (alias, spec) in manifest_tool_requirements(&manifest) {
+            aliases_by_id
+                .entry(spec.id().clone())
+                .or_default()
+                .insert(alias);
+        }
+    }
+
+    let mut tools = Vec::new();
+    for id in cache.all_installed_ids() {
+        let aliases = aliases_by_id.get(&id).map_or_else(
+            || vec![id.clone().into_alias()],
+            |set| set.iter().cloned().collect(),
+        );
+
+        let mut linked = false;
+        for alias in &aliases {
+            linked |= storage.has_tool_link(alias).await;
+        }
+
+        let mut versions = cache.all_installed_versions_for_id(&id);
+        versions.reverse(); // List newest versions first
+
+        tools.push(InstalledTool {
+            id,
+            aliases,
+            versions,
+            linked,
+        });
+    }
+
+    tools.sort_by_cached_key(InstalledTool::sort_key);
+    tools
+}
+
+// Lists all installed tools, grouped by id, as a table
+fn list_installed_tools(tools: &[InstalledTool]) -> (String, Vec<String>) {
+    if tools.is_empty() {
+        let header = String::from("🛠️  No tools are installed.");
+        return (header, Vec::new());
+    }
+
+    let bullet = style("•").dim();
+    let arrow = style("→").dim();
+
+    let alias_strings = tools
+        .iter()
+        .map(|tool| {
+            tool.aliases
+                .iter()
+                .map(ToolAlias::name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect::<Vec<_>>();
+    let id_strings = tools
+        .iter()
+        .map(|tool| tool.id.to_string())
+        .collect::<Vec<_>>();
+
+    let longest_alias_len = alias_strings.iter().map(String::len).max().unwrap_or(0);
+    let longest_id_len = id_strings.iter().map(String::len).max().unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for ((tool, alias), id) in tools.iter().zip(&alias_strings).zip(&id_strings) {
+        let versions = tool
+            .versions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let link = if tool.linked {
+            style("linked").green()
+        } else {
+            style("not linked").yellow()
+        };
+        lines.push(format!(
+            "{bullet} {}{} {arrow} {id}{} {} {}",
+            style(alias).bold().cyan(),
+            " ".repeat(longest_alias_len - alias.len()),
+            " ".repeat(longest_id_len - id.len()),
+            versions,
+            style(format!("({link})")).dim(),
+        ));
+    }
+
+    let header = String::from("🛠️  Installed tools:\n");
+    (header, lines)
+}
+
+fn installed_tools_to_json(tools: &[InstalledTool]) -> serde_json::Value {
+    let value = tools
+        .iter()
+        .map(|tool| {
+            json!({
+                "aliases": tool.aliases.iter().map(ToolAlias::name).collect::<Vec<_>>(),
+                "id": tool.id.to_string(),
+                "versions": tool.versions.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "linked": tool.linked,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::Array(value)
+}
+
+async fn manifest_tools_to_json() -> serde_json::Value {
+    let manifests = discover_all_manifests(true, false).await;
+    let value = manifests
+        .into_iter()
+        .map(|manifest| {
+            let mut tools = manifest_tool_requirements(&manifest);
+            tools.sort_by(|(alias_a, _), (alias_b, _)| alias_a.name().cmp(alias_b.name()));
+            json!({
+                "path": manifest.path.display().to_string(),
+                "tools": tools
+                    .into_iter()
+                    .map(|(alias, spec)| json!({
+                        "alias": alias.name(),
+                        "id": spec.id().to_string(),
+                        "version": spec.version_req().to_string(),
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::Array(value)
+}
+
+// Gets both exact tool specs and version requirements from a manifest as a single list
+fn manifest_tool_requirements(manifest: &DiscoveredManifest) -> Vec<(ToolAlias, ToolSpecReq)> {
+    manifest
+        .entries
+        .iter()
+        .filter_map(|(alias, entry)| Some((alias.clone(), entry.requirement()?)))
+        .collect()
+}
+
 // Lists all versions for a specific tool - if it is installed
 fn list_versions_for_id(home: &Home, id: &ToolId) -> (String, Vec<String>) {
     let cache = home.tool_cache();
@@ -57,7 +265,7 @@ async fn list_versions(home: &Home) -> (String, Vec<String>) {
 
     let mut manifest_lines = Vec::new();
     for manifest in manifests {
-        let mut sorted_tools = manifest.tools.into_iter().collect::<Vec<_>>();
+        let mut sorted_tools = manifest_tool_requirements(&manifest);
         sorted_tools.sort_by(|(alias_a, _), (alias_b, _)| alias_a.name().cmp(alias_b.name()));
         if sorted_tools.is_empty() {
             continue;
@@ -82,7 +290,7 @@ async fn list_versions(home: &Home) -> (String, Vec<String>) {
                 " ".repeat(longest_alias_len - alias.name().len()),
                 spec.id(),
                 " ".repeat(longest_id_len - spec.id().to_string().len()),
-                spec.version(),
+                spec.version_req(),
             ));
         }
 
@@ -96,8 +304,8 @@ async fn list_versions(home: &Home) -> (String, Vec<String>) {
 
     let mut lines = vec![];
     for (index, (path, mlines)) in manifest_lines.iter().enumerate() {
-        if let Ok(stripped) = path.strip_prefix(home.path()) {
-            lines.push(format!("~/.rokit/{}", stripped.display()));
+        if let Some(displayed) = home.display_path(path) {
+            lines.push(displayed);
         } else if let Ok(stripped) = path.strip_prefix(&cwd) {
             lines.push(format!("./{}", stripped.display()));
         } else {
@@ -117,3 +325,28 @@ async fn list_versions(home: &Home) -> (String, Vec<String>) {
         (header, lines)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installed_tools_json_shape() {
+        let tools = [InstalledTool {
+            id: "rojo-rbx/rojo".parse().unwrap(),
+            aliases: vec!["rojo".parse().unwrap()],
+            versions: vec![Version::new(7, 4, 1), Version::new(7, 3, 0)],
+            linked: true,
+        }];
+        assert_eq!(
+            installed_tools_to_json(&tools),
+            json!([{
+                "aliases": ["rojo"],
+                "id": "rojo-rbx/rojo",
+                "versions": ["7.4.1", "7.3.0"],
+                "linked": true,
+            }])
+        );
+        assert_eq!(installed_tools_to_json(&[]), json!([]));
+    }
+}
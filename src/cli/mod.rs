@@ -1,34 +1,72 @@
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
 use clap::{ArgAction, CommandFactory, Parser};
 use tokio::time::Instant;
 use tracing::level_filters::LevelFilter;
+use url::Url;
 
+use rokit::discovery::discover_config;
 use rokit::storage::Home;
 use rokit::system::ProcessParent;
 
-use crate::util::init_tracing;
+use crate::util::{init_tracing, CliProgressTracker, OutputFormat};
 
 mod add;
+mod auth;
 mod authenticate;
+mod cache;
+mod debug_link;
+mod doctor;
+mod exec;
+mod fmt;
+mod info;
 mod init;
 mod install;
 mod list;
+mod outdated;
+mod pin;
+mod prefetch;
+mod run;
+mod search;
 mod self_install;
 mod self_update;
+mod setup;
 mod system_info;
+mod tree;
 mod trust;
+mod uninstall;
+mod untrust;
 mod update;
+mod which;
 
 use self::add::AddSubcommand;
+use self::auth::AuthSubcommand;
 use self::authenticate::AuthenticateSubcommand;
+use self::cache::CacheSubcommand;
+use self::debug_link::DebugLinkSubcommand;
+use self::doctor::DoctorSubcommand;
+use self::exec::ExecSubcommand;
+use self::fmt::FmtSubcommand;
+use self::info::InfoSubcommand;
 use self::init::InitSubcommand;
 use self::install::InstallSubcommand;
 use self::list::ListSubcommand;
+use self::outdated::OutdatedSubcommand;
+use self::pin::PinSubcommand;
+use self::prefetch::PrefetchSubcommand;
+use self::run::RunSubcommand;
+use self::search::SearchSubcommand;
 use self::self_install::SelfInstallSubcommand;
 use self::self_update::SelfUpdateSubcommand;
+use self::setup::SetupSubcommand;
 use self::system_info::SystemInfoSubcommand;
+use self::tree::TreeSubcommand;
 use self::trust::TrustSubcommand;
+use self::uninstall::UninstallSubcommand;
+use self::untrust::UntrustSubcommand;
 use self::update::UpdateSubcommand;
+use self::which::WhichSubcommand;
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
@@ -41,8 +79,13 @@ pub struct Cli {
 
 impl Cli {
     pub async fn run(self) -> Result<()> {
-        // Enable the appropriate level of tracing / logging
-        init_tracing(self.options.tracing_level_filter());
+        // Enable the appropriate level of tracing / logging, and output format
+        init_tracing(
+            self.options.tracing_level_filter(),
+            self.options.log_filter.as_deref(),
+        );
+        self.options.output.set();
+        CliProgressTracker::set_quiet(self.options.quiet);
 
         // If we didn't get a subcommand, we should either print the help,
         // or automatically run self-install if launched from the explorer
@@ -61,10 +104,18 @@ impl Cli {
 
         // Load Rokit data structures
         let start_home = Instant::now();
-        let home = Home::load_from_env().await.context(
-            "Failed to load Rokit home!\
-            \nYour installation or environment may be corrupted.",
-        )?;
+        let home = Home::load_from_env()
+            .await
+            .context(
+                "Failed to load Rokit home!\
+                \nYour installation or environment may be corrupted.",
+            )?
+            .with_proxy(self.options.proxy)
+            .with_ca_bundle(self.options.cacert);
+        let config = discover_config(&home)
+            .await
+            .context("Failed to load Rokit config!")?;
+        let home = home.with_config(config);
         tracing::trace!(
             elapsed = ?start_home.elapsed(),
             "Rokit loaded"
@@ -114,42 +165,103 @@ impl Cli {
 #[derive(Debug, Parser)]
 pub enum Subcommand {
     Add(AddSubcommand),
+    Auth(AuthSubcommand),
     Authenticate(AuthenticateSubcommand),
+    Cache(CacheSubcommand),
+    #[clap(hide = true)]
+    DebugLink(DebugLinkSubcommand),
+    Doctor(DoctorSubcommand),
+    Exec(ExecSubcommand),
+    Fmt(FmtSubcommand),
+    Info(InfoSubcommand),
     Init(InitSubcommand),
     Install(InstallSubcommand),
     List(ListSubcommand),
+    Outdated(OutdatedSubcommand),
+    Pin(PinSubcommand),
+    Prefetch(PrefetchSubcommand),
+    Run(RunSubcommand),
+    Search(SearchSubcommand),
     SelfInstall(SelfInstallSubcommand),
     SelfUpdate(SelfUpdateSubcommand),
+    Setup(SetupSubcommand),
     SystemInfo(SystemInfoSubcommand),
+    Tree(TreeSubcommand),
     Trust(TrustSubcommand),
+    Uninstall(UninstallSubcommand),
+    Untrust(UntrustSubcommand),
     Update(UpdateSubcommand),
+    Which(WhichSubcommand),
 }
 
 impl Subcommand {
     pub async fn run(self, home: &Home) -> Result<()> {
         match self {
             Self::Add(cmd) => cmd.run(home).await,
+            Self::Auth(cmd) => cmd.run(home).await,
             Self::Authenticate(cmd) => cmd.run(home).await,
+            Self::Cache(cmd) => cmd.run(home).await,
+            Self::DebugLink(cmd) => cmd.run(home).await,
+            Self::Doctor(cmd) => cmd.run(home).await,
+            Self::Exec(cmd) => cmd.run(home).await,
+            Self::Fmt(cmd) => cmd.run(home).await,
+            Self::Info(cmd) => cmd.run(home).await,
             Self::Init(cmd) => cmd.run(home).await,
             Self::Install(cmd) => cmd.run(home).await,
             Self::List(cmd) => cmd.run(home).await,
+            Self::Outdated(cmd) => cmd.run(home).await,
+            Self::Pin(cmd) => cmd.run(home).await,
+            Self::Prefetch(cmd) => cmd.run(home).await,
+            Self::Run(cmd) => cmd.run(home).await,
+            Self::Search(cmd) => cmd.run(home).await,
             Self::SelfInstall(cmd) => cmd.run(home).await,
             Self::SelfUpdate(cmd) => cmd.run(home).await,
+            Self::Setup(cmd) => cmd.run(home).await,
             Self::SystemInfo(cmd) => cmd.run(home).await,
+            Self::Tree(cmd) => cmd.run(home).await,
             Self::Trust(cmd) => cmd.run(home).await,
+            Self::Uninstall(cmd) => cmd.run(home).await,
+            Self::Untrust(cmd) => cmd.run(home).await,
             Self::Update(cmd) => cmd.run(home).await,
+            Self::Which(cmd) => cmd.run(home).await,
         }
     }
 }
 
 #[derive(Debug, Parser)]
 pub struct GlobalOptions {
-    #[clap(short, long, action = ArgAction::Count)]
+    /// Show more detailed logs - once for debug logs, twice for trace logs.
+    #[clap(short, long, global = true, action = ArgAction::Count)]
     pub verbose: u8,
+    /// Hide progress bars and informational logs,
+    /// only showing final results, warnings, and errors.
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// Log filter directives, using the same syntax as `RUST_LOG`, such as
+    /// `rokit::sources=debug` for debug logs from only artifact sources.
+    /// Can also be set using the `ROKIT_LOG` environment variable.
+    #[clap(long, global = true, value_name = "FILTER")]
+    pub log_filter: Option<String>,
+    /// A proxy to use for all network requests, such as `http://proxy:8080`.
+    /// By default, the `HTTP_PROXY`, `HTTPS_PROXY`, and `NO_PROXY` environment variables are used.
+    #[clap(long, global = true)]
+    pub proxy: Option<Url>,
+    /// A bundle of PEM-encoded CA certificates to trust for all network requests, in
+    /// addition to the built-in root certificates, such as for a TLS-intercepting proxy.
+    /// Defaults to the `ROKIT_CA_BUNDLE` or `SSL_CERT_FILE` environment variables.
+    #[clap(long, global = true, value_name = "PATH")]
+    pub cacert: Option<PathBuf>,
+    /// The output format to use - `json` emits newline-delimited JSON
+    /// events to stdout instead of progress bars, for use in scripts.
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub output: OutputFormat,
 }
 
 impl GlobalOptions {
     pub fn tracing_level_filter(&self) -> LevelFilter {
+        if self.quiet {
+            return LevelFilter::WARN;
+        }
         match self.verbose {
             0 => LevelFilter::INFO,
             1 => LevelFilter::DEBUG,
@@ -0,0 +1,266 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use console::style;
+use futures::{stream::FuturesUnordered, TryStreamExt};
+use semver::Version;
+use serde_json::json;
+
+use rokit::{
+    discovery::ToolSource,
+    manifests::{RokitLockfile, ROKIT_MANIFEST_FILE_NAME},
+    storage::Home,
+    tool::{ToolAlias, ToolId},
+};
+
+use crate::util::{load_or_discover_manifests, print_json, CliProgressTracker};
+
+/// Checks all tools in manifests for newer versions.
+///
+/// Exits with a non-zero exit code if any tool is outdated.
+#[derive(Debug, Parser)]
+pub struct OutdatedSubcommand {
+    /// Also consider prereleases when looking for newer versions.
+    #[clap(long)]
+    pub pre: bool,
+    /// Output the list as JSON instead of human-readable text.
+    #[clap(long)]
+    pub json: bool,
+    /// Use the manifest at this path instead of discovering manifests from the
+    /// current directory. Can be given several times to merge the tools from
+    /// several manifests, with earlier manifests taking priority.
+    #[clap(long = "manifest", value_name = "PATH")]
+    pub manifests: Vec<PathBuf>,
+}
+
+impl OutdatedSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let source = home.artifact_source().await?;
+        let manifests = load_or_discover_manifests(&self.manifests).await?;
+
+        // NOTE: The lockfile is stored next to the closest Rokit manifest, same as for `rokit install`
+        let lockfile_dir = manifests
+            .iter()
+            .find(|manifest| {
+                manifest.path.file_name() == Some(OsStr::new(ROKIT_MANIFEST_FILE_NAME))
+            })
+            .and_then(|manifest| manifest.path.parent())
+            .map(Path::to_path_buf);
+        let lockfile = match &lockfile_dir {
+            Some(dir) => RokitLockfile::load(dir)
+                .await
+                .context("Failed to load lockfile")?,
+            None => None,
+        };
+        let tool_cache = home.tool_cache();
+
+        // 1. Gather all tools from manifests, only keeping the first tool found
+        // for each alias, same as when running tools. Tools with version requirements
+        // are compared using their locked or installed versions, and locally built
+        // tools have no releases to compare against. The ids are then deduplicated
+        // to avoid making redundant requests for the same tool.
+        let mut seen_aliases = HashSet::new();
+        let mut tools = Vec::new();
+        for manifest in &manifests {
+            for (alias, entry) in &manifest.entries {
+                if !seen_aliases.insert(alias.clone()) {
+                    continue;
+                }
+                let (id, current) = match &entry.source {
+                    ToolSource::Spec(spec) => (spec.id().clone(), Some(spec.clone())),
+                    ToolSource::Requirement(requirement) => {
+                        let current =
+                            match lockfile.as_ref().and_then(|l| l.get_resolved(requirement)) {
+                                Some(locked) => Some(locked.clone()),
+                                None => tool_cache.find_installed_matching(requirement),
+                            };
+                        (requirement.id().clone(), current)
+                    }
+                    ToolSource::Local(_) => continue,
+                };
+                let current = current.map(|spec| spec.version().clone());
+                tools.push((alias.clone(), id, current));
+            }
+        }
+        let ids = tools
+            .iter()
+            .map(|(_, id, _)| id.clone())
+            .collect::<BTreeSet<_>>();
+
+        // 2. Fetch the latest versions for all tools
+        let pt = CliProgressTracker::new_with_message("Checking", ids.len());
+        let latest_versions = ids
+            .into_iter()
+            .map(|id| async {
+                let versions = source
+                    .get_available_versions(&id, self.pre)
+                    .await
+                    .with_context(|| format!("Failed to fetch versions for '{id}'"))?;
+                pt.task_completed();
+                Ok::<_, anyhow::Error>((id, versions.last().cloned()))
+            })
+            .collect::<FuturesUnordered<_>>()
+            .try_collect::<BTreeMap<ToolId, Option<Version>>>()
+            .await?;
+
+        // 3. Compare the current versions against the latest ones
+        let mut rows = tools
+            .into_iter()
+            .map(|(alias, id, current)| {
+                let latest = latest_versions.get(&id).cloned().flatten();
+                OutdatedRow {
+                    alias,
+                    id,
+                    current,
+                    latest,
+                }
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| a.alias.name().cmp(b.alias.name()));
+
+        let outdated = rows.iter().filter(|row| row.jump().is_some()).count();
+
+        // 4. Display the results, either as JSON or a nice table
+        if self.json {
+            pt.finish_and_clear();
+            let value = rows.iter().map(OutdatedRow::to_json).collect::<Vec<_>>();
+            print_json(value.into())?;
+        } else if outdated == 0 {
+            pt.finish_with_message(format!(
+                "All tools are up-to-date! {}",
+                pt.formatted_elapsed()
+            ));
+        } else {
+            pt.finish_with_message(format!(
+                "Found {} outdated tool{} {}\n\n{}",
+                style(outdated).bold().magenta(),
+                if outdated == 1 { "" } else { "s" },
+                pt.formatted_elapsed(),
+                format_outdated_rows(&rows),
+            ));
+        }
+
+        if outdated > 0 {
+            bail!(
+                "{outdated} tool{} {} outdated",
+                if outdated == 1 { "" } else { "s" },
+                if outdated == 1 { "is" } else { "are" },
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VersionJump {
+    Major,
+    Minor,
+    Patch,
+    Prerelease,
+}
+
+impl VersionJump {
+    fn between(current: &Version, latest: &Version) -> Option<Self> {
+        if latest <= current {
+            None
+        } else if latest.major != current.major {
+            Some(Self::Major)
+        } else if latest.minor != current.minor {
+            Some(Self::Minor)
+        } else if latest.patch != current.patch {
+            Some(Self::Patch)
+        } else {
+            Some(Self::Prerelease)
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Patch => "patch",
+            Self::Prerelease => "prerelease",
+        }
+    }
+}
+
+/*
+    The current version is `None` for tools with version requirements
+    that have not been locked or installed yet, which are never outdated.
+*/
+struct OutdatedRow {
+    alias: ToolAlias,
+    id: ToolId,
+    current: Option<Version>,
+    latest: Option<Version>,
+}
+
+impl OutdatedRow {
+    fn jump(&self) -> Option<VersionJump> {
+        let current = self.current.as_ref()?;
+        let latest = self.latest.as_ref()?;
+        VersionJump::between(current, latest)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "alias": self.alias.name(),
+            "id": self.id.to_string(),
+            "current": self.current.as_ref().map(ToString::to_string),
+            "latest": self.latest.as_ref().map(ToString::to_string),
+            "outdated": self.jump().is_some(),
+            "jump": self.jump().map(VersionJump::as_str),
+        })
+    }
+}
+
+fn format_outdated_rows(rows: &[OutdatedRow]) -> String {
+    let bullet = style("•").dim();
+    let arrow = style("→").dim();
+
+    let rows = rows
+        .iter()
+        .filter_map(|row| {
+            let current = row.current.as_ref()?;
+            Some((row, current, row.jump()?, row.latest.as_ref()?))
+        })
+        .collect::<Vec<_>>();
+
+    let longest_alias_len = rows
+        .iter()
+        .map(|(row, _, _, _)| row.alias.name().len())
+        .max()
+        .unwrap_or(0);
+    let longest_version_len = rows
+        .iter()
+        .map(|(_, current, _, _)| current.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    rows.into_iter()
+        .map(|(row, current, jump, latest)| {
+            let current = current.to_string();
+            let jump = match jump {
+                VersionJump::Major => style(jump.as_str()).red(),
+                VersionJump::Minor => style(jump.as_str()).yellow(),
+                VersionJump::Patch | VersionJump::Prerelease => style(jump.as_str()).green(),
+            };
+            format!(
+                "{bullet} {}{} {}{} {arrow} {} {}",
+                style(row.alias.name()).bold().cyan(),
+                " ".repeat(longest_alias_len - row.alias.name().len()),
+                style(&current).yellow(),
+                " ".repeat(longest_version_len - current.len()),
+                style(latest).bold().yellow(),
+                style(format!("({jump})")).dim(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,123 @@
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use console::style;
+use semver::Version;
+
+use rokit::{
+    discovery::discover_all_manifests,
+    manifests::{RokitLockfile, RokitManifest},
+    storage::Home,
+    tool::{ToolAlias, ToolSpec},
+};
+
+use crate::util::{print_message, CliProgressTracker};
+
+/// Pins a tool to an exact version in the manifest.
+///
+/// Tools using version requirements are pinned to the version they
+/// currently resolve to, unless a specific version is given.
+#[derive(Debug, Parser)]
+pub struct PinSubcommand {
+    /// The alias of the tool to pin, such as `rojo`.
+    pub alias: ToolAlias,
+    /// The exact version to pin the tool to, such as `7.4.0`.
+    /// Defaults to the version that the tool currently resolves to.
+    pub version: Option<Version>,
+    /// Pin the tool in the global manifest instead of the nearest manifest file.
+    #[clap(long)]
+    pub global: bool,
+}
+
+impl PinSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let manifest_path = if self.global {
+            home.path().to_path_buf()
+        } else {
+            let non_global_manifests = discover_all_manifests(true, true).await;
+            non_global_manifests
+                .first()
+                .map(|m| m.path.parent().unwrap().to_path_buf())
+                .context(
+                    "No manifest was found for the current directory.\
+                    \nRun `rokit init` in your project root to create one.",
+                )?
+        };
+        let mut manifest = RokitManifest::load(&manifest_path).await?;
+
+        let Some(requirement) = manifest.get_tool_requirement(&self.alias) else {
+            bail!(
+                "No tool with the alias '{}' has been added to this manifest.\
+                \nYou can add the tool to the project using `{}`.",
+                self.alias,
+                style("rokit add").bold().green(),
+            );
+        };
+
+        // 1. Find the version to pin to, preferring the version that
+        // the lockfile resolved to over any installed version
+        let is_given_version = self.version.is_some();
+        let spec = if let Some(version) = self.version {
+            ToolSpec::from((requirement.id().clone(), version))
+        } else {
+            if let Some(spec) = requirement.exact() {
+                print_message(format!(
+                    "Tool {} is already pinned to {}.",
+                    style(&self.alias).bold().cyan(),
+                    style(spec.version()).bold().yellow(),
+                ));
+                return Ok(());
+            }
+            let lockfile = RokitLockfile::load(&manifest_path)
+                .await
+                .context("Failed to load lockfile")?;
+            lockfile
+                .and_then(|lockfile| lockfile.get_resolved(&requirement).cloned())
+                .or_else(|| home.tool_cache().find_installed_matching(&requirement))
+                .with_context(|| {
+                    format!(
+                        "Tool '{requirement}' has not been installed yet, \
+                        so there is no version to pin it to.\
+                        \nRun `{}` first, or give the version to pin to.",
+                        style("rokit install").bold().green(),
+                    )
+                })?
+        };
+
+        // 2. Check that the version exists, which is only needed for versions
+        // given by the user, since resolved versions always came from a release
+        let pt = CliProgressTracker::new_with_message("Pinning", 1);
+        if is_given_version {
+            let source = home.artifact_source().await?;
+            let tag_prefix = manifest.get_tool_tag_prefix(&self.alias);
+            let release = source
+                .get_specific_release_with_tag_prefix(&spec, tag_prefix.as_deref())
+                .await;
+            if let Err(e) = release {
+                tracing::debug!(%spec, error = %e, "failed to fetch release");
+                pt.print_message(format!(
+                    "{} No release was found for {}, make sure that the version exists.",
+                    style("Warning:").bold().yellow(),
+                    style(&spec).bold().magenta(),
+                ));
+            }
+        }
+
+        // 3. Update the manifest entry, which also keeps any other keys
+        // for the tool, as well as its formatting and comments
+        manifest.update_tool(&self.alias, &spec);
+        manifest.save(&manifest_path).await?;
+        pt.task_completed();
+
+        pt.finish_with_message(format!(
+            "Pinned {} from {} to {} {}\
+            \nRun `{}` to install the pinned version.",
+            style(&self.alias).bold().cyan(),
+            style(requirement.version_req()).yellow(),
+            style(spec.version()).bold().yellow(),
+            pt.formatted_elapsed(),
+            style("rokit install").bold().green(),
+        ));
+
+        Ok(())
+    }
+}
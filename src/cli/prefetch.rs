@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+
+use rokit::{descriptor::Descriptor, storage::Home};
+
+use crate::util::parse_target;
+
+use super::install::InstallSubcommand;
+
+/// Downloads all tools in all manifests into the download cache, without installing them.
+///
+/// Tools are resolved the same way as when installing, and their artifacts and checksums
+/// are downloaded and verified, skipping any artifacts that are already cached.
+/// Running `rokit install` afterwards then works without network access.
+#[derive(Debug, Parser)]
+pub struct PrefetchSubcommand {
+    /// Skip checking if tools have been trusted before.
+    /// Can also be set using `no_trust_check = true` in a Rokit config file.
+    #[clap(long)]
+    pub no_trust_check: bool,
+    /// Download artifacts for another system instead of the current one,
+    /// such as `x86_64-pc-windows-msvc` or `windows-x64`.
+    #[clap(long, value_parser = parse_target)]
+    pub target: Option<Descriptor>,
+    /// Use the manifest at this path instead of discovering manifests from the
+    /// current directory. Can be given several times to merge the tools from
+    /// several manifests, with earlier manifests taking priority.
+    #[clap(long = "manifest", value_name = "PATH")]
+    pub manifests: Vec<PathBuf>,
+}
+
+impl PrefetchSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        // NOTE: Downloading for a target only populates the download cache,
+        // and never installs, links, or adds anything to the lockfile
+        let target = self.target.unwrap_or_else(Descriptor::current_system);
+        let install = InstallSubcommand {
+            aliases: Vec::new(),
+            no_trust_check: self.no_trust_check,
+            force: false,
+            reinstall: false,
+            locked: false,
+            frozen: false,
+            update: false,
+            dry_run: false,
+            verify: false,
+            no_links: false,
+            target: Some(target),
+            skip_incompatible: false,
+            jobs: None,
+            timeout: None,
+            manifests: self.manifests,
+        };
+        install.run(home).await
+    }
+}
@@ -0,0 +1,34 @@
+use std::{ffi::OsString, process::exit};
+
+use anyhow::Result;
+use clap::Parser;
+
+use rokit::{storage::Home, tool::ToolAlias};
+
+use crate::runner::run_tool;
+
+/// Runs a tool from the project manifests, the same way as running its link would.
+///
+/// This does not require Rokit or tool links to be on the system PATH,
+/// and the tool will be installed first if necessary.
+#[derive(Debug, Parser)]
+pub struct RunSubcommand {
+    /// The alias of the tool to run, such as `rojo`.
+    pub alias: ToolAlias,
+    /// Arguments to pass to the tool - use `--` to
+    /// separate them from arguments passed to Rokit.
+    #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<OsString>,
+}
+
+impl RunSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let code = run_tool(home, &self.alias, &self.args).await?;
+
+        // NOTE: We exit directly with the exit code of the tool below,
+        // so we need to make sure any newly installed tool is saved first
+        home.save().await?;
+
+        exit(code);
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use console::style;
+use futures::{stream::FuturesOrdered, TryStreamExt};
+use semver::Version;
+use serde_json::json;
+
+use rokit::{
+    result::RokitError,
+    sources::{github::GithubError, Artifact, SearchResult},
+    storage::Home,
+};
+
+use crate::util::{print_json, CliProgressTracker};
+
+/// Searches GitHub for tools that can be installed using Rokit.
+///
+/// Only tools with a release compatible with the current system are listed.
+#[derive(Debug, Parser)]
+pub struct SearchSubcommand {
+    /// The search query, such as a tool name or keywords.
+    #[clap(required = true)]
+    pub query: Vec<String>,
+    /// The maximum number of tools to list.
+    #[clap(long, default_value_t = 10)]
+    pub limit: usize,
+    /// Output the list as JSON instead of human-readable text.
+    #[clap(long)]
+    pub json: bool,
+}
+
+impl SearchSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let query = self.query.join(" ");
+        let source = home.artifact_source().await?;
+
+        // 1. Search for candidates - many repositories do not have
+        // any releases, so fetch extra candidates to filter through
+        let pt = CliProgressTracker::new_with_message("Searching", 1);
+        let candidates = source
+            .search(&query, self.limit.saturating_mul(2))
+            .await
+            .with_context(|| format!("Failed to search for '{query}'"))?;
+        pt.finish_and_clear();
+
+        // 2. Check the latest release of each candidate for compatible artifacts
+        let pt = CliProgressTracker::new_with_message("Checking", candidates.len());
+        let checked = candidates
+            .into_iter()
+            .map(|candidate| async {
+                let latest = match source.get_latest_release(&candidate.id).await {
+                    Ok(artifacts) => latest_compatible_version(&artifacts),
+                    Err(RokitError::GitHub(e))
+                        if matches!(*e, GithubError::RateLimitExceeded { .. }) =>
+                    {
+                        return Err(RokitError::GitHub(e))
+                            .context("Failed to check releases for search results");
+                    }
+                    Err(_) => None,
+                };
+                pt.task_completed();
+                Ok::<_, anyhow::Error>(latest.map(|version| (candidate, version)))
+            })
+            .collect::<FuturesOrdered<_>>()
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let results = checked
+            .into_iter()
+            .flatten()
+            .take(self.limit)
+            .collect::<Vec<_>>();
+
+        // 3. Display the results, either as JSON or a nice list
+        if self.json {
+            pt.finish_and_clear();
+            let value = results
+                .iter()
+                .map(|(result, version)| {
+                    json!({
+                        "id": result.id.to_string(),
+                        "description": result.description,
+                        "stars": result.stars,
+                        "version": version.to_string(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            print_json(value.into())?;
+        } else if results.is_empty() {
+            pt.finish_with_message(format!(
+                "No installable tools found for '{query}'. {}",
+                pt.formatted_elapsed()
+            ));
+        } else {
+            pt.finish_with_message(format!(
+                "Found {} tool{} {}\n\n{}\n\nRun `{}` to add a tool.",
+                style(results.len()).bold().magenta(),
+                if results.len() == 1 { "" } else { "s" },
+                pt.formatted_elapsed(),
+                format_results(&results),
+                style("rokit add <id>").bold().green(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// Gets the version of a release, if it has an artifact that can run on the current system
+fn latest_compatible_version(artifacts: &[Artifact]) -> Option<Version> {
+    Artifact::sort_by_system_compatibility(artifacts)
+        .first()
+        .cloned()
+        .or_else(|| Artifact::find_partially_compatible_fallback(artifacts))
+        .map(|artifact| artifact.tool_spec.version().clone())
+}
+
+fn format_results(results: &[(SearchResult, Version)]) -> String {
+    let bullet = style("•").dim();
+    results
+        .iter()
+        .map(|(result, version)| {
+            let line = format!(
+                "{bullet} {} {} {}",
+                style(&result.id).bold(),
+                style(format!("v{version}")).green(),
+                style(format!("★ {}", result.stars)).yellow(),
+            );
+            match &result.description {
+                Some(description) => format!("{line}\n  {}", style(description).dim()),
+                None => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
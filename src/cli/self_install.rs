@@ -28,13 +28,17 @@ impl SelfInstallSubcommand {
         pt.update_message("Pathifying");
 
         let mut path_errored = false;
+        let bin_dir = home.link_dir();
+        let bin_dir = home
+            .display_path(&bin_dir)
+            .unwrap_or_else(|| bin_dir.display().to_string());
         let path_was_changed = add_to_path(home)
             .await
             .inspect_err(|e| {
                 path_errored = true;
                 warn!(
                     "Failed to automatically add Rokit to your PATH!\
-                    \nPlease add `~/.rokit/bin` to be able to run tools.
+                    \nPlease add `{bin_dir}` to be able to run tools.
                     \nError: {e:?}",
                 );
             })
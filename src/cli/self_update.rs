@@ -1,22 +1,66 @@
+use std::{cmp::Ordering, process::exit};
+
 use anyhow::{bail, Context, Result};
 use clap::Parser;
 use console::style;
 use semver::Version;
 
-use rokit::{storage::Home, tool::ToolId};
+use rokit::{
+    storage::Home,
+    tool::{ToolId, ToolSpec},
+};
+
+use crate::util::{
+    find_most_compatible_artifact, print_message, prompt_for_changelog, CliProgressTracker,
+};
 
-use crate::util::{find_most_compatible_artifact, CliProgressTracker};
+const UPDATE_AVAILABLE_EXIT_CODE: i32 = 3;
 
 /// Updates Rokit to the latest version.
+///
+/// A specific version may also be given, which can
+/// be used to downgrade Rokit to an older version.
+///
+/// The previous version is backed up before updating, and is
+/// restored automatically if the update fails, or using `--rollback`.
 #[derive(Debug, Parser)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct SelfUpdateSubcommand {
     /// Update even if the latest version is already installed.
     #[clap(long, hide = true)]
     pub force: bool,
+    /// A specific version of Rokit to update or downgrade to.
+    #[clap(long, conflicts_with = "prerelease")]
+    pub version: Option<Version>,
+    /// Also consider prereleases when looking for the latest version.
+    #[clap(long)]
+    pub prerelease: bool,
+    /// Restore the version of Rokit that was used before the last update.
+    #[clap(long, conflicts_with_all = ["version", "prerelease", "force"])]
+    pub rollback: bool,
+    /// Always print the release notes for the new version after updating.
+    #[clap(long, conflicts_with_all = ["no_changelog", "rollback"])]
+    pub changelog: bool,
+    /// Never ask to show the release notes for the new version after updating.
+    ///
+    /// Rokit never asks when the terminal is not interactive, or when running in CI.
+    #[clap(long)]
+    pub no_changelog: bool,
+    /// Skip all optional prompts, such as asking to show the release notes.
+    #[clap(long, short)]
+    pub yes: bool,
+    /// Only check if a newer version of Rokit is available, without updating.
+    /// Exits with code 3 if an update is available, which is useful in scripts.
+    #[clap(long, conflicts_with_all = ["version", "rollback", "force", "changelog"])]
+    pub check: bool,
 }
 
 impl SelfUpdateSubcommand {
     pub async fn run(self, home: &Home) -> Result<()> {
+        if self.rollback {
+            return rollback(home).await;
+        }
+
         let repo = env!("CARGO_PKG_REPOSITORY")
             .trim_start_matches("https://github.com/")
             .trim_end_matches(".git");
@@ -34,18 +78,71 @@ impl SelfUpdateSubcommand {
         pt.task_completed();
         pt.update_message("Fetching");
 
-        let artifacts = source.get_latest_release(&tool_id).await?;
+        let mut changelog = None;
+        let artifacts = if let Some(version) = &self.version {
+            let spec = ToolSpec::from((tool_id.clone(), version.clone()));
+            source
+                .get_specific_release(&spec)
+                .await
+                .with_context(|| format!("Failed to find Rokit version {version}"))?
+        } else if self.prerelease {
+            let versions = source.get_available_versions(&tool_id, true).await?;
+            let Some(version) = versions.last() else {
+                bail!("No releases were found for Rokit");
+            };
+            let spec = ToolSpec::from((tool_id.clone(), version.clone()));
+            source.get_specific_release(&spec).await?
+        } else {
+            let release = source.get_latest_release_info(&tool_id).await?;
+            changelog = release.changelog;
+            release.artifacts
+        };
 
-        // Skip updating if we are already on the latest version
+        // Skip updating if we are already on the latest (or desired) version -
+        // downgrading is only allowed when a specific version was requested
         let version_current = env!("CARGO_PKG_VERSION").parse::<Version>().unwrap();
-        let version_latest = artifacts.first().unwrap().tool_spec.version().clone();
-        if version_current >= version_latest && !self.force {
-            let msg = format!(
-                "Rokit is already up-to-date! {}\n\n\
-                The latest version is {}.",
-                pt.formatted_elapsed(),
-                style(&version_latest).bold().magenta(),
-            );
+        let version_target = artifacts.first().unwrap().tool_spec.version().clone();
+        let is_up_to_date = if self.version.is_some() {
+            version_current == version_target
+        } else {
+            version_current >= version_target
+        };
+        // NOTE: Checking never downloads or links anything, and prints
+        // plainly so that the result is also visible when not in a terminal
+        if self.check {
+            pt.finish_and_clear();
+            if is_up_to_date {
+                print_message(format!(
+                    "Rokit is already up-to-date! The latest version is {}.",
+                    style(&version_target).bold().magenta(),
+                ));
+                return Ok(());
+            }
+            print_message(format!(
+                "A new version of Rokit is available: {} → {}\
+                \nRun `{}` to update.",
+                style(&version_current).bold().magenta(),
+                style(&version_target).bold().magenta(),
+                style("rokit self-update").bold().green(),
+            ));
+            exit(UPDATE_AVAILABLE_EXIT_CODE);
+        }
+
+        if is_up_to_date && !self.force {
+            let msg = if self.version.is_some() {
+                format!(
+                    "Rokit is already running version {}! {}",
+                    style(&version_target).bold().magenta(),
+                    pt.formatted_elapsed(),
+                )
+            } else {
+                format!(
+                    "Rokit is already up-to-date! {}\n\n\
+                    The latest version is {}.",
+                    pt.formatted_elapsed(),
+                    style(&version_target).bold().magenta(),
+                )
+            };
             pt.finish_with_message(msg);
             return Ok(());
         }
@@ -55,12 +152,16 @@ impl SelfUpdateSubcommand {
         pt.task_completed();
         pt.update_message("Downloading");
 
-        let artifact = find_most_compatible_artifact(&artifacts, &tool_id)
+        let artifact = find_most_compatible_artifact(&artifacts, &tool_id, None)
             .context("No compatible Rokit artifact was found (WAT???)")?;
-        let artifact_contents = source
-            .download_artifact_contents(&artifact)
+        let artifact = source.attach_checksum(&artifact, &artifacts).await?;
+        let artifact_contents = pt
+            .download_artifact(&source, &artifact)
             .await
-            .context("Failed to download latest Rokit binary")?;
+            .context("Failed to download Rokit binary")?;
+        artifact
+            .verify_checksum(&artifact_contents)
+            .context("Failed to verify Rokit binary")?;
 
         // Extract the binary contents from the artifact
         pt.task_completed();
@@ -74,23 +175,101 @@ impl SelfUpdateSubcommand {
         pt.task_completed();
         pt.update_message("Linking");
 
+        // NOTE: If linking fails midway, Rokit may be left half-updated and
+        // unable to run, so make sure that the previous version is restored
         let storage = home.tool_storage();
-        storage.replace_rokit_contents(binary_contents).await;
         storage
-            .recreate_all_links()
+            .backup_rokit()
             .await
-            .context("Failed to create new tool links")?;
+            .context("Failed to back up the current Rokit binary")?;
+        storage.replace_rokit_contents(binary_contents).await;
+        if let Err(e) = storage.recreate_all_links().await {
+            let e = anyhow::Error::from(e).context("Failed to create new tool links");
+            return match storage.restore_rokit_backup().await {
+                Ok(_) => Err(e.context(format!(
+                    "Failed to update Rokit, version {version_current} has been restored"
+                ))),
+                Err(restore_err) => Err(e.context(format!(
+                    "Failed to update Rokit, and failed to restore version {version_current}!\
+                    \nYour installation may be corrupted, run `rokit self-install` to repair it.\
+                    \nRestore error: {restore_err}"
+                ))),
+            };
+        }
 
         // Everything went well, yay!
+        let (verb_past, verb_from) = match version_target.cmp(&version_current) {
+            Ordering::Greater => ("updated", "updated from"),
+            Ordering::Less => ("downgraded", "downgraded from"),
+            Ordering::Equal => ("reinstalled", "reinstalled over"),
+        };
         let msg = format!(
-            "Rokit has been updated successfully! {}\n\
-            \nYou are now running version {}, updated from {}.",
+            "Rokit has been {verb_past} successfully! {}\n\
+            \nYou are now running version {}, {verb_from} {}.",
             pt.formatted_elapsed(),
-            style(&version_latest).bold().magenta(),
+            style(&version_target).bold().magenta(),
             style(&version_current).bold().magenta(),
         );
         pt.finish_with_message(msg);
 
+        // Offer to show what changed, without ever blocking non-interactive updates
+        let show_changelog = if self.changelog {
+            true
+        } else if self.no_changelog || self.yes {
+            false
+        } else {
+            prompt_for_changelog(version_target.clone()).await?
+        };
+        if show_changelog {
+            print_changelog(&tool_id, &version_target, changelog.as_deref());
+        }
+
         Ok(())
     }
 }
+
+// Prints release notes in plain text, so that they can also be piped
+// or logged, or a link to the release if its notes were not fetched
+fn print_changelog(tool_id: &ToolId, version: &Version, changelog: Option<&str>) {
+    let url = format!(
+        "https://github.com/{}/{}/releases/tag/v{version}",
+        tool_id.author(),
+        tool_id.name()
+    );
+    match changelog {
+        Some(changelog) => print_message(format!(
+            "\nRelease notes for version {version}:\n\n{changelog}\n"
+        )),
+        None => print_message(format!(
+            "\nRelease notes for version {version} are available at {url}"
+        )),
+    }
+}
+
+async fn rollback(home: &Home) -> Result<()> {
+    let pt = CliProgressTracker::new_with_message("Restoring", 1);
+
+    let storage = home.tool_storage();
+    let version_current = env!("CARGO_PKG_VERSION").parse::<Version>().unwrap();
+    let Some(version_restored) = storage
+        .restore_rokit_backup()
+        .await
+        .context("Failed to restore the previous Rokit binary")?
+    else {
+        bail!(
+            "No previous version of Rokit was found to roll back to.\
+            \nA backup is made each time Rokit is updated using `rokit self-update`."
+        );
+    };
+    pt.task_completed();
+
+    pt.finish_with_message(format!(
+        "Rokit has been rolled back successfully! {}\n\
+        \nYou are now running version {}, rolled back from {}.",
+        pt.formatted_elapsed(),
+        style(&version_restored).bold().magenta(),
+        style(&version_current).bold().magenta(),
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use console::style;
+
+use rokit::{
+    storage::Home,
+    system::{exists_in_path, ShellKind},
+};
+
+use crate::util::{print_message, prompt_for_profile_change};
+
+/// Adds the Rokit link directory to your PATH using your shell profile.
+///
+/// Prints the line to add to the profile for your shell, and adds it
+/// to the profile after confirmation. Supports bash, zsh, fish, and PowerShell.
+#[derive(Debug, Parser)]
+pub struct SetupSubcommand {
+    /// The shell to set up, such as `bash`, `zsh`, `fish`, or `powershell`.
+    /// Defaults to the shell that is currently being used.
+    #[clap(long)]
+    pub shell: Option<ShellKind>,
+    /// Add the line to the shell profile without asking for confirmation first.
+    #[clap(long, short)]
+    pub yes: bool,
+}
+
+impl SetupSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let link_dir = home.link_dir();
+        let link_dir = home
+            .display_path(&link_dir)
+            .unwrap_or_else(|| link_dir.display().to_string());
+
+        if exists_in_path(home) {
+            print_message(format!(
+                "Rokit is already set up, `{}` is in your {}.",
+                style(link_dir).bold(),
+                style("PATH").bold(),
+            ));
+            return Ok(());
+        }
+
+        // 1. Find the shell and its profile, which is where the line will be added
+        let shell = self.shell.or_else(ShellKind::detect).with_context(|| {
+            format!(
+                "Failed to detect your shell.\
+                \nRun this command again with `{}` to choose a shell.",
+                style("--shell <bash|zsh|fish|powershell>").bold().green(),
+            )
+        })?;
+        let profile_path = shell
+            .profile_path()
+            .with_context(|| format!("Failed to find the profile for {shell}"))?;
+        let line = shell
+            .path_line(home)
+            .context("Failed to create the line for the shell profile")?;
+
+        if shell.has_path_line(home).await {
+            print_message(format!(
+                "Your {shell} profile at `{}` already adds `{}` to your {}.\
+                \nPlease restart your terminal for the changes to take effect.",
+                style(profile_path.display()).bold(),
+                style(link_dir).bold(),
+                style("PATH").bold(),
+            ));
+            return Ok(());
+        }
+
+        // 2. Show the user what will be added, and ask for confirmation
+        print_message(format!(
+            "To add `{}` to your {}, this line needs to be added to your {shell} profile at `{}`:\
+            \n\n    {}\n",
+            style(&link_dir).bold(),
+            style("PATH").bold(),
+            style(profile_path.display()).bold(),
+            style(&line).cyan(),
+        ));
+
+        let prompt = format!("Add this line to {}?", profile_path.display());
+        if !self.yes && !prompt_for_profile_change(prompt).await? {
+            print_message(format!(
+                "Your profile was not changed, add the line above to it yourself,\
+                \nor run this command again with `{}` to add it without confirmation.",
+                style("--yes").bold().green(),
+            ));
+            return Ok(());
+        }
+
+        // 3. Add the line, which requires a new terminal to take effect
+        shell.add_path_line(home).await.with_context(|| {
+            format!(
+                "Failed to add the line to your {shell} profile at `{}`",
+                profile_path.display()
+            )
+        })?;
+        print_message(format!(
+            "Added `{}` to your {}.\
+            \nPlease restart your terminal for the changes to take effect.",
+            style(link_dir).bold(),
+            style("PATH").bold(),
+        ));
+
+        Ok(())
+    }
+}
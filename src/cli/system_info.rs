@@ -16,6 +16,8 @@ use rokit::{
     system::{current_dir, current_exe, exists_in_path},
 };
 
+use crate::util::print_message;
+
 /// Prints out information about the current system and installed tools.
 #[derive(Debug, Parser)]
 pub struct SystemInfoSubcommand {}
@@ -165,7 +167,7 @@ impl SystemInfoSubcommand {
             )?;
         }
 
-        println!("{s}");
+        print_message(s);
 
         Ok(())
     }
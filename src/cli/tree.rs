@@ -0,0 +1,184 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use clap::Parser;
+use console::style;
+use serde_json::json;
+
+use rokit::{
+    discovery::{DiscoveredManifest, ToolSource},
+    storage::Home,
+    system::current_dir,
+    tool::ToolAlias,
+};
+
+use crate::util::{load_or_discover_manifests, print_json, OutputFormat};
+
+/// Shows all tools grouped by the manifest they are found in.
+///
+/// Manifests are listed in the order that tools are resolved in,
+/// and tools that are shadowed by a closer manifest are marked.
+#[derive(Debug, Parser)]
+pub struct TreeSubcommand {
+    /// Output the tree as JSON instead of human-readable text.
+    #[clap(long)]
+    pub json: bool,
+    /// Use the manifest at this path instead of discovering manifests from the
+    /// current directory. Can be given several times to merge the tools from
+    /// several manifests, with earlier manifests taking priority.
+    #[clap(long = "manifest", value_name = "PATH")]
+    pub manifests: Vec<PathBuf>,
+}
+
+impl TreeSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let manifests = load_or_discover_manifests(&self.manifests).await?;
+        let nodes = collect_manifest_nodes(&manifests);
+
+        if self.json || OutputFormat::is_json() {
+            print_json(manifest_nodes_to_json(&nodes))?;
+            return Ok(());
+        }
+
+        if nodes.is_empty() {
+            println!("🌳 No manifests found.");
+            return Ok(());
+        }
+
+        let cwd = current_dir().await;
+        let mut lines = vec![String::from("🌳 Tools by manifest, closest first:\n")];
+        for (index, node) in nodes.iter().enumerate() {
+            lines.push(
+                style(display_path(&node.path, home, &cwd))
+                    .bold()
+                    .to_string(),
+            );
+            if node.tools.is_empty() {
+                lines.push(format!("{}", style("└─ (no tools)").dim()));
+            }
+            for (tool_index, tool) in node.tools.iter().enumerate() {
+                let branch = if tool_index == node.tools.len() - 1 {
+                    "└─"
+                } else {
+                    "├─"
+                };
+                let branch = style(branch).dim();
+                let arrow = style("→").dim();
+                lines.push(match &tool.shadowed_by {
+                    None => format!(
+                        "{branch} {} {arrow} {}",
+                        style(tool.alias.name()).bold().cyan(),
+                        tool.target,
+                    ),
+                    Some(path) => {
+                        let shadowed_by = display_path(path, home, &cwd);
+                        format!(
+                            "{branch} {} {arrow} {} {}",
+                            style(tool.alias.name()).dim(),
+                            style(&tool.target).dim(),
+                            style(format!("(shadowed by {shadowed_by})")).yellow(),
+                        )
+                    }
+                });
+            }
+            if index < nodes.len() - 1 {
+                lines.push(String::new()); // Add a newline between manifests
+            }
+        }
+
+        println!("{}", lines.join("\n"));
+
+        Ok(())
+    }
+}
+
+// A manifest and the tools it contains, in the order they were discovered
+struct ManifestNode {
+    path: PathBuf,
+    tools: Vec<ToolNode>,
+}
+
+// A tool alias in a manifest, and the manifest that shadows it, if any
+struct ToolNode {
+    alias: ToolAlias,
+    kind: &'static str,
+    target: String,
+    shadowed_by: Option<PathBuf>,
+}
+
+/*
+    Collects the tools in each manifest, marking tools as shadowed if a
+    manifest earlier in the list - meaning closer to the current
+    directory - already contains a tool with the same alias.
+*/
+fn collect_manifest_nodes(manifests: &[DiscoveredManifest]) -> Vec<ManifestNode> {
+    let mut first_seen = HashMap::<ToolAlias, &Path>::new();
+    manifests
+        .iter()
+        .map(|manifest| {
+            let mut tools = manifest
+                .entries
+                .iter()
+                .map(|(alias, entry)| {
+                    let (kind, target) = match &entry.source {
+                        ToolSource::Spec(spec) => ("spec", spec.to_string()),
+                        ToolSource::Requirement(req) => ("requirement", req.to_string()),
+                        ToolSource::Local(path) => ("path", path.display().to_string()),
+                    };
+                    ToolNode {
+                        alias: alias.clone(),
+                        kind,
+                        target,
+                        shadowed_by: None,
+                    }
+                })
+                .collect::<Vec<_>>();
+            tools.sort_by(|a, b| a.alias.name().cmp(b.alias.name()));
+            for tool in &mut tools {
+                let seen = *first_seen
+                    .entry(tool.alias.clone())
+                    .or_insert(manifest.path.as_path());
+                if seen != manifest.path {
+                    tool.shadowed_by = Some(seen.to_path_buf());
+                }
+            }
+            ManifestNode {
+                path: manifest.path.clone(),
+                tools,
+            }
+        })
+        .collect()
+}
+
+fn manifest_nodes_to_json(nodes: &[ManifestNode]) -> serde_json::Value {
+    let value = nodes
+        .iter()
+        .map(|node| {
+            json!({
+                "path": node.path.display().to_string(),
+                "tools": node.tools.iter().map(|tool| json!({
+                    "alias": tool.alias.name(),
+                    "kind": tool.kind,
+                    "target": tool.target,
+                    "shadowed": tool.shadowed_by.is_some(),
+                    "shadowed_by": tool.shadowed_by.as_ref().map(|p| p.display().to_string()),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::Array(value)
+}
+
+// Displays manifest paths relative to the Rokit home or current directory, if possible
+pub(super) fn display_path(path: &Path, home: &Home, cwd: &Path) -> String {
+    if let Some(displayed) = home.display_path(path) {
+        displayed
+    } else if let Ok(stripped) = path.strip_prefix(cwd) {
+        format!("./{}", stripped.display())
+    } else {
+        path.display().to_string()
+    }
+}
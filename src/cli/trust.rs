@@ -4,17 +4,33 @@ use console::style;
 
 use rokit::{storage::Home, tool::ToolId};
 
-use crate::util::CliProgressTracker;
+use crate::util::{print_json, CliProgressTracker, OutputFormat};
 
 /// Mark the given tool(s) as being trusted.
 #[derive(Debug, Parser)]
 pub struct TrustSubcommand {
     /// The tool(s) to mark as trusted.
     pub tools: Vec<ToolId>,
+    /// List all trusted tools instead, sorted and one per line.
+    #[clap(long, conflicts_with = "tools")]
+    pub list: bool,
 }
 
 impl TrustSubcommand {
     pub async fn run(self, home: &Home) -> Result<()> {
+        if self.list {
+            let trusted = home.tool_cache().all_trusted();
+            if OutputFormat::is_json() {
+                print_json(trusted.iter().map(ToString::to_string).collect())?;
+                return Ok(());
+            }
+            // NOTE: Plain output without any styling, so that it can be diffed or piped
+            for tool in trusted {
+                println!("{tool}");
+            }
+            return Ok(());
+        }
+
         if self.tools.is_empty() {
             bail!("Please provide at least one tool to trust.");
         }
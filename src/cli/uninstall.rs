@@ -0,0 +1,208 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use console::style;
+use tracing::warn;
+
+use rokit::{
+    discovery::{discover_all_manifests, ToolSource},
+    manifests::{RokitManifest, ROKIT_MANIFEST_FILE_NAME},
+    storage::Home,
+    system::current_dir,
+    tool::{ToolAlias, ToolSpec},
+};
+
+use crate::util::CliProgressTracker;
+
+/// Removes tools from Rokit, along with their links.
+///
+/// Links are only removed for tools that are no longer used by any manifest,
+/// so tools in manifests must also be removed from them, using `--remove-from-manifest`.
+#[derive(Debug, Parser)]
+pub struct UninstallSubcommand {
+    /// The alias(es) of the tool(s) to remove.
+    pub aliases: Vec<ToolAlias>,
+    /// Also remove the tool(s) from the nearest manifest file.
+    #[clap(long)]
+    pub remove_from_manifest: bool,
+    /// Remove the tool(s) from the global manifest
+    /// instead of the nearest manifest file.
+    #[clap(long, requires = "remove_from_manifest")]
+    pub global: bool,
+    /// Also delete installed tool binaries, as long as
+    /// no other alias or manifest still references them.
+    #[clap(long)]
+    pub prune: bool,
+}
+
+impl UninstallSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        if self.aliases.is_empty() {
+            bail!("Please provide at least one tool to uninstall.");
+        }
+
+        let tool_cache = home.tool_cache();
+        let tool_storage = home.tool_storage();
+
+        let pt = CliProgressTracker::new_with_message("Uninstalling", 3);
+
+        // 1. Find all specs that the given aliases currently point to,
+        // and remove the aliases from the desired rokit manifest, if wanted
+        let manifests = discover_all_manifests(false, false).await;
+        let target_dir = if !self.remove_from_manifest {
+            None
+        } else if self.global {
+            Some(home.path().to_path_buf())
+                .filter(|dir| dir.join(ROKIT_MANIFEST_FILE_NAME).exists())
+        } else {
+            closest_manifest_dir().await
+        };
+
+        let mut removed_specs = BTreeSet::<ToolSpec>::new();
+        for alias in &self.aliases {
+            let mut specs = manifests
+                .iter()
+                .filter_map(|m| m.entries.get(alias)?.spec().cloned())
+                .collect::<Vec<_>>();
+            // Tools using version requirements may have several matching versions installed
+            let requirements =
+                manifests
+                    .iter()
+                    .filter_map(|m| match &m.entries.get(alias)?.source {
+                        ToolSource::Requirement(requirement) => Some(requirement),
+                        _ => None,
+                    });
+            for requirement in requirements {
+                specs.extend(
+                    tool_cache
+                        .all_installed()
+                        .into_iter()
+                        .filter(|spec| requirement.matches(spec)),
+                );
+            }
+            if specs.is_empty() && !manifests.iter().any(|m| m.has_alias(alias)) {
+                warn!("Tool {alias} was not found in any manifest, only its link will be removed");
+            }
+            removed_specs.extend(specs);
+        }
+
+        if let Some(dir) = &target_dir {
+            let path = dir.join(ROKIT_MANIFEST_FILE_NAME);
+            let mut manifest = RokitManifest::load(dir)
+                .await
+                .with_context(|| format!("Failed to load manifest at {}", path.display()))?;
+            let mut changed = false;
+            for alias in &self.aliases {
+                changed |= manifest.remove_tool(alias);
+            }
+            if changed {
+                manifest
+                    .save(dir)
+                    .await
+                    .with_context(|| format!("Failed to save manifest at {}", path.display()))?;
+            }
+        }
+        pt.task_completed();
+
+        // 2. Remove links for any aliases that are no longer
+        // referenced - this also cleans up any dangling links
+        pt.update_message("Unlinking");
+        let remaining = discover_all_manifests(false, false).await;
+        let still_referenced = |alias: &ToolAlias| remaining.iter().any(|m| m.has_alias(alias));
+
+        let mut unlinked_aliases = Vec::new();
+        let mut kept_aliases = Vec::new();
+        for alias in &self.aliases {
+            if still_referenced(alias) {
+                kept_aliases.push(alias.clone());
+            } else if tool_storage.remove_tool_link(alias).await? {
+                unlinked_aliases.push(alias.clone());
+            }
+        }
+        pt.task_completed();
+
+        // 3. If desired, delete any tool binaries that are
+        // no longer referenced by any alias in any manifest
+        let mut pruned_specs = Vec::new();
+        if self.prune {
+            pt.update_message("Pruning");
+            let referenced_specs = remaining
+                .iter()
+                .flat_map(|m| m.tools().map(|(_, spec)| spec.clone()))
+                .collect::<BTreeSet<_>>();
+            let referenced_requirements = remaining
+                .iter()
+                .flat_map(|m| m.requirements().map(|(_, requirement)| requirement))
+                .collect::<Vec<_>>();
+            for spec in removed_specs {
+                if referenced_specs.contains(&spec)
+                    || referenced_requirements.iter().any(|r| r.matches(&spec))
+                {
+                    continue;
+                }
+                tool_storage.remove_tool_contents(&spec).await?;
+                let _ = tool_cache.remove_installed(&spec);
+                pruned_specs.push(spec);
+            }
+        }
+        pt.task_completed();
+
+        // 4. Finally, display a nice message to the user
+        let bullet = style("•").dim();
+        let mut lines = Vec::new();
+        if !kept_aliases.is_empty() {
+            lines.push(if self.remove_from_manifest {
+                String::from("These tools are still used by another manifest and were kept linked:")
+            } else {
+                format!(
+                    "These tools are still used by a manifest and were kept linked, use `{}` to remove them from it:",
+                    style("--remove-from-manifest").bold().green()
+                )
+            });
+            for alias in &kept_aliases {
+                lines.push(format!("  {bullet} {alias}"));
+            }
+        }
+        if !unlinked_aliases.is_empty() {
+            lines.push(String::from("These tools were unlinked:"));
+            for alias in &unlinked_aliases {
+                lines.push(format!("  {bullet} {}", style(alias).bold().cyan()));
+            }
+        }
+        if !pruned_specs.is_empty() {
+            lines.push(String::from("These tool versions were deleted:"));
+            for spec in &pruned_specs {
+                lines.push(format!("  {bullet} {spec}"));
+            }
+        }
+
+        lines.insert(
+            0,
+            format!(
+                "Uninstalled {} tool{} {}",
+                self.aliases.len(),
+                if self.aliases.len() == 1 { "" } else { "s" },
+                pt.formatted_elapsed(),
+            ),
+        );
+        pt.finish_with_message(lines.join("\n"));
+
+        Ok(())
+    }
+}
+
+/*
+    Finds the directory of the closest Rokit manifest, which is found
+    even if it can not be parsed, so that parsing errors are not ignored.
+*/
+async fn closest_manifest_dir() -> Option<PathBuf> {
+    current_dir()
+        .await
+        .ancestors()
+        .find(|dir| dir.join(ROKIT_MANIFEST_FILE_NAME).is_file())
+        .map(Path::to_path_buf)
+}
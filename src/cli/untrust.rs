@@ -0,0 +1,76 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use console::style;
+
+use rokit::{storage::Home, tool::ToolId};
+
+use crate::util::CliProgressTracker;
+
+/// Remove trust for the given tool(s).
+#[derive(Debug, Parser)]
+pub struct UntrustSubcommand {
+    /// The tool(s) to remove trust for.
+    pub tools: Vec<ToolId>,
+}
+
+impl UntrustSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        if self.tools.is_empty() {
+            bail!("Please provide at least one tool to untrust.");
+        }
+
+        // NOTE: We use a progress bar only to show the final message to the
+        // user below, to maintain consistent formatting with other commands.
+        let pt = CliProgressTracker::new_with_message("Untrusting", 1);
+
+        let cache = home.tool_cache();
+        let (removed_tools, untrusted_tools) = self
+            .tools
+            .into_iter()
+            .partition::<Vec<_>, _>(|tool| cache.remove_trust(tool));
+
+        if removed_tools.len() == 1 && untrusted_tools.is_empty() {
+            // Special case 1 with shorter output - a singular tool was removed
+            pt.finish_with_message(format!(
+                "Tool {} is no longer trusted {}",
+                removed_tools[0],
+                pt.formatted_elapsed(),
+            ));
+        } else if untrusted_tools.len() == 1 && removed_tools.is_empty() {
+            // Special case 2 with shorter output - a singular tool was not trusted
+            pt.finish_with_message(format!(
+                "Tool {} was not trusted {}",
+                untrusted_tools[0],
+                pt.formatted_elapsed(),
+            ));
+        } else {
+            // General case with multiple tools removed and/or not trusted
+            let mut lines = Vec::new();
+            let list_bullet = style("•").dim();
+
+            if !removed_tools.is_empty() {
+                lines.push(String::from("These tools are no longer trusted:"));
+                for tool in &removed_tools {
+                    lines.push(format!("  {list_bullet} {tool}"));
+                }
+            }
+
+            if !untrusted_tools.is_empty() {
+                lines.push(String::from("These tools were not trusted:"));
+                for tool in &untrusted_tools {
+                    lines.push(format!("  {list_bullet} {tool}"));
+                }
+            }
+
+            pt.finish_with_message(format!(
+                "Changed trust for {} tool{} {}\n\n{}",
+                removed_tools.len(),
+                if removed_tools.len() == 1 { "" } else { "s" },
+                pt.formatted_elapsed(),
+                lines.join("\n")
+            ));
+        }
+
+        Ok(())
+    }
+}
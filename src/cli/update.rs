@@ -3,13 +3,23 @@ use clap::Parser;
 use console::style;
 use futures::{stream::FuturesUnordered, TryStreamExt};
 
-use rokit::{discovery::discover_all_manifests, manifests::RokitManifest, storage::Home};
+use rokit::{
+    discovery::discover_all_manifests,
+    install::{install_spec_with_options, InstallOptions, InstalledArtifact},
+    manifests::{RokitLockfile, RokitManifest, ToolVerify},
+    sources::{Artifact, ArtifactSource},
+    storage::Home,
+    tool::{ToolAlias, ToolSpec, ToolSpecReq},
+};
 
 use crate::util::{
-    find_most_compatible_artifact, CliProgressTracker, ToolAliasOrIdOrSpec, ToolIdOrSpec,
+    find_most_compatible_artifact, parse_artifact_pattern, parse_minisign_key, CliProgressTracker,
+    ToolAliasOrIdOrSpec, ToolIdOrSpec, INSTALL_SUBTASKS,
 };
 
-/// Updates all tools, or specific tools, to the latest version.
+/// Updates all tools, or specific tools, to the latest version, and installs them.
+///
+/// Tools using version requirements are updated to the newest matching version.
 #[derive(Debug, Parser)]
 pub struct UpdateSubcommand {
     /// The tools to update - can be aliases, ids, or specifications.
@@ -19,7 +29,7 @@ pub struct UpdateSubcommand {
     #[clap(long)]
     pub global: bool,
     /// Check for updates without actually updating the tools.
-    #[clap(long)]
+    #[clap(long, visible_alias = "dry-run")]
     pub check: bool,
 }
 
@@ -27,6 +37,8 @@ impl UpdateSubcommand {
     pub async fn run(self, home: &Home) -> Result<()> {
         // 1. Load tool source and the desired manifest
         let source = home.artifact_source().await?;
+        let tool_cache = home.tool_cache();
+        let tool_storage = home.tool_storage();
         let manifest_path = if self.global {
             home.path().to_path_buf()
         } else {
@@ -46,18 +58,48 @@ impl UpdateSubcommand {
             RokitManifest::load(&manifest_path).await?
         };
 
+        // NOTE: The lockfile is stored next to the manifest, same as for `rokit install`
+        let lockfile = RokitLockfile::load(&manifest_path)
+            .await
+            .context("Failed to load lockfile")?;
+
         // 2. Try to convert aliases into ids using existing tools,
         // or fill with existing tools if no tools were provided
-        let tools = if self.tools.is_empty() {
-            manifest
+        // NOTE: Tools using version requirements keep their requirement in
+        // the manifest, and are instead updated to the newest matching release
+        let (range_tools, tools) = if self.tools.is_empty() {
+            let range_tools = manifest
+                .tool_requirements()
+                .into_iter()
+                .filter(|(_, requirement)| requirement.exact().is_none())
+                .collect::<Vec<_>>();
+            let tools = manifest
                 .tool_specs()
                 .iter()
                 .cloned()
                 .map(|(alias, spec)| (alias, ToolIdOrSpec::Id(spec.id().clone())))
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>();
+            (range_tools, tools)
         } else {
+            let (range_aliases, other_tools) =
+                self.tools.into_iter().partition::<Vec<_>, _>(|tool| {
+                    matches!(tool, ToolAliasOrIdOrSpec::Alias(alias)
+                        if manifest
+                            .get_tool_requirement(alias)
+                            .is_some_and(|requirement| requirement.exact().is_none()))
+                });
+            let range_tools = range_aliases
+                .into_iter()
+                .filter_map(|tool| match tool {
+                    ToolAliasOrIdOrSpec::Alias(alias) => {
+                        let requirement = manifest.get_tool_requirement(&alias)?;
+                        Some((alias, requirement))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
             // FUTURE: Refactor this logic here below, it's quite difficult to read
-            self.tools
+            let tools = other_tools
                 .into_iter()
                 .map(|tool| {
                     // NOTE: If we were given a tool id or spec, we need
@@ -113,9 +155,14 @@ impl UpdateSubcommand {
                         }
                     }
                 })
-                .collect::<Result<Vec<_>>>()?
+                .collect::<Result<Vec<_>>>()?;
+            (range_tools, tools)
         };
-        let pt = CliProgressTracker::new_with_message_and_subtasks("Fetching", tools.len(), 3);
+        let pt = CliProgressTracker::new_with_message_and_subtasks(
+            "Fetching",
+            tools.len() + range_tools.len(),
+            1 + INSTALL_SUBTASKS,
+        );
 
         // 3. Fetch the latest or desired versions of the tools
         let tool_releases = tools
@@ -123,8 +170,11 @@ impl UpdateSubcommand {
             .map(|(alias, tool)| async {
                 let (alias, id, artifacts) = match tool {
                     ToolIdOrSpec::Spec(spec) => {
-                        let artifacts =
-                            source.get_specific_release(&spec).await.with_context(|| {
+                        let tag_prefix = manifest.get_tool_tag_prefix(&alias);
+                        let artifacts = source
+                            .get_specific_release_with_tag_prefix(&spec, tag_prefix.as_deref())
+                            .await
+                            .with_context(|| {
                                 format!(
                                     "Failed to fetch release for '{spec}'!\
                                     \nMake sure the given tool version exists."
@@ -144,45 +194,105 @@ impl UpdateSubcommand {
                     }
                 };
 
-                let artifact = find_most_compatible_artifact(&artifacts, &id)?;
+                let pattern = parse_artifact_pattern(
+                    &alias,
+                    manifest.get_tool_artifact_pattern(&alias).as_deref(),
+                )?;
+                let artifact = find_most_compatible_artifact(&artifacts, &id, pattern.as_ref())?;
                 pt.subtask_completed();
 
-                Ok::<_, anyhow::Error>((alias, id, artifact))
+                Ok::<_, anyhow::Error>((alias, artifacts, artifact))
             })
             .collect::<FuturesUnordered<_>>()
             .try_collect::<Vec<_>>()
             .await?;
 
-        // 4. Check if the --check flag was used, and if so, check for updates
-        let tools_changed = tool_releases
-            .iter()
-            .filter_map(|(alias, _, artifact)| {
-                let spec_old = manifest.get_tool(alias).unwrap();
-                let spec_new = artifact.tool_spec.clone();
-                if spec_old == spec_new {
+        // ... and the newest releases matching any version requirements, which only
+        // count as changed if they are not the locked or installed version already
+        let range_releases = range_tools
+            .into_iter()
+            .map(|(alias, requirement)| {
+                let (source, pt, manifest) = (&source, &pt, &manifest);
+                let lockfile = lockfile.as_ref();
+                async move {
+                    let spec = source
+                        .resolve_requirement(&requirement)
+                        .await
+                        .with_context(|| format!("Failed to resolve tool '{requirement}'"))?;
+                    let current = match lockfile.and_then(|l| l.get_resolved(&requirement)) {
+                        Some(locked) => Some(locked.clone()),
+                        None => tool_cache.find_installed_matching(&requirement),
+                    };
+                    if current.as_ref() == Some(&spec) {
+                        pt.subtask_completed();
+                        return Ok(None);
+                    }
+                    let tag_prefix = manifest.get_tool_tag_prefix(&alias);
+                    let artifacts = source
+                        .get_specific_release_with_tag_prefix(&spec, tag_prefix.as_deref())
+                        .await?;
+                    let pattern = parse_artifact_pattern(
+                        &alias,
+                        manifest.get_tool_artifact_pattern(&alias).as_deref(),
+                    )?;
+                    let artifact =
+                        find_most_compatible_artifact(&artifacts, spec.id(), pattern.as_ref())?;
+                    pt.subtask_completed();
+                    Ok::<_, anyhow::Error>(Some(ToolUpdate {
+                        alias,
+                        requirement,
+                        spec_old: current,
+                        release: artifacts,
+                        artifact,
+                        pinned: false,
+                    }))
+                }
+            })
+            .collect::<FuturesUnordered<_>>()
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut tools_changed = tool_releases
+            .into_iter()
+            .filter_map(|(alias, release, artifact)| {
+                let spec_old = manifest.get_tool(&alias).unwrap();
+                if spec_old == artifact.tool_spec {
                     None
                 } else {
-                    Some((alias.clone(), spec_old, spec_new))
+                    Some(ToolUpdate {
+                        alias,
+                        requirement: artifact.tool_spec.clone().into(),
+                        spec_old: Some(spec_old),
+                        release,
+                        artifact,
+                        pinned: true,
+                    })
                 }
             })
+            .chain(range_releases.into_iter().flatten())
             .collect::<Vec<_>>();
-        if self.check {
-            let bullet = style("•").dim();
-            let arrow = style("→").dim();
+        tools_changed.sort_by(|a, b| a.alias.cmp(&b.alias));
 
-            let updated_tool_lines = tools_changed
-                .iter()
-                .map(|(alias, spec_old, spec_new)| {
-                    format!(
-                        "{bullet} {} {} {arrow} {}",
-                        style(alias.to_string()).bold().cyan(),
-                        style(spec_old.version()).yellow(),
-                        style(spec_new.version()).bold().yellow()
-                    )
-                })
-                .collect::<Vec<_>>()
-                .join("\n");
+        let bullet = style("•").dim();
+        let arrow = style("→").dim();
+        let updated_tool_lines = tools_changed
+            .iter()
+            .map(|update| {
+                let version_old = match &update.spec_old {
+                    Some(spec) => style(spec.version().to_string()).yellow(),
+                    None => style(String::from("none")).dim(),
+                };
+                format!(
+                    "{bullet} {} {version_old} {arrow} {}",
+                    style(update.alias.to_string()).bold().cyan(),
+                    style(update.artifact.tool_spec.version()).bold().yellow()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
 
+        // 4. Check if the --check flag was used, and if so, only show the updates
+        if self.check {
             pt.update_message("Checking for updates");
 
             if tools_changed.is_empty() {
@@ -201,67 +311,164 @@ impl UpdateSubcommand {
                     style("rokit update").bold().green(),
                 ));
             }
-            pt.subtask_completed();
+            return Ok(());
+        }
+
+        if tools_changed.is_empty() {
+            pt.finish_with_message(format!(
+                "All tools are already up-to-date! {}",
+                pt.formatted_elapsed(),
+            ));
             return Ok(());
         }
 
         // 5. Modify the manifest with the desired new tools, save
         pt.update_message("Modifying");
 
-        for (alias, _, spec_new) in &tools_changed {
-            manifest.update_tool(alias, spec_new);
-            pt.subtask_completed();
+        for update in tools_changed.iter().filter(|update| update.pinned) {
+            manifest.update_tool(&update.alias, &update.artifact.tool_spec);
         }
         manifest.save(&manifest_path).await?;
 
-        // 6. Finally, display a nice message to the user
-        let tools_changed = tool_releases
-            .iter()
-            .filter_map(|(alias, _, artifact)| {
-                let spec_old = manifest.get_tool(alias).unwrap();
-                let spec_new = artifact.tool_spec.clone();
-                if spec_old == spec_new {
-                    None
-                } else {
-                    Some((alias.clone(), spec_old, spec_new))
+        // 6. Download, install, and link the new versions of trusted tools
+        pt.update_message("Installing");
+
+        let trust_manifest = home.trust_manifest().await?;
+        let (trusted_updates, untrusted_updates) =
+            tools_changed.iter().partition::<Vec<_>, _>(|update| {
+                let id = update.artifact.tool_spec.id();
+                tool_cache.is_trusted(id) || trust_manifest.is_trusted(id)
+            });
+        let installed_updates = trusted_updates
+            .into_iter()
+            .map(|update| {
+                let (source, pt, manifest) = (&source, &pt, &manifest);
+                async move {
+                    let installed = install_update(home, source, manifest, update, pt).await?;
+                    tool_storage.create_tool_link(&update.alias).await?;
+                    anyhow::Ok((update, installed))
                 }
             })
-            .collect::<Vec<_>>();
-        let bullet = style("•").dim();
-        let arrow = style("→").dim();
+            .collect::<FuturesUnordered<_>>()
+            .try_collect::<Vec<_>>()
+            .await?;
 
-        let updated_tool_lines = tools_changed
-            .iter()
-            .map(|(alias, spec_old, spec_new)| {
-                format!(
-                    "{bullet} {} {} {arrow} {}",
-                    style(alias.to_string()).bold().cyan(),
-                    style(spec_old.version()).yellow(),
-                    style(spec_new.version()).bold().yellow()
-                )
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+        // 7. Lock the new versions of installed tools, replacing the previous versions
+        let mut new_lockfile = lockfile.clone().unwrap_or_default();
+        for (update, installed) in installed_updates {
+            let spec = &update.artifact.tool_spec;
+            if let Some(spec_old) = update.spec_old.as_ref().filter(|_| update.pinned) {
+                new_lockfile.unlock(&spec_old.clone().into());
+            }
+            new_lockfile.lock_resolved(update.requirement.clone(), spec.clone());
+            if let Some(InstalledArtifact {
+                url: Some(url),
+                checksum,
+                ..
+            }) = &installed
+            {
+                new_lockfile.lock_artifact(spec, url, checksum);
+            }
+        }
+        let is_unchanged = match &lockfile {
+            Some(existing) => existing == &new_lockfile,
+            None => new_lockfile == RokitLockfile::default(),
+        };
+        if !is_unchanged {
+            new_lockfile
+                .save(&manifest_path)
+                .await
+                .context("Failed to save lockfile")?;
+        }
 
-        if tools_changed.is_empty() {
-            pt.finish_with_message(format!(
-                "All tools are already up-to-date! {}",
-                pt.formatted_elapsed(),
-            ));
+        // 8. Finally, display a nice message to the user
+        let untrusted_note = if untrusted_updates.is_empty() {
+            String::new()
         } else {
-            pt.finish_with_message(format!(
-                "Updated versions for {} tool{} {}\
-                \n\n{updated_tool_lines}\n\n\
-                Run `{}` to install the updated tools.",
-                style(tools_changed.len()).bold().magenta(),
-                if tools_changed.len() == 1 { "" } else { "s" },
-                pt.formatted_elapsed(),
+            format!(
+                "\n\nSome tools are not trusted and were not installed.\
+                \nRun `{}` to trust and install them.",
                 style("rokit install").bold().green(),
-            ));
+            )
+        };
+        pt.finish_with_message(format!(
+            "Updated versions for {} tool{} {}\
+            \n\n{updated_tool_lines}{untrusted_note}",
+            style(tools_changed.len()).bold().magenta(),
+            if tools_changed.len() == 1 { "" } else { "s" },
+            pt.formatted_elapsed(),
+        ));
+
+        Ok(())
+    }
+}
+
+/**
+    A tool that has a new version available.
+
+    Pinned tools have their exact version written in the manifest, which
+    will be updated, while other tools use a version requirement instead.
+    The requirement is the one in the manifest after updating the tool.
+*/
+struct ToolUpdate {
+    alias: ToolAlias,
+    requirement: ToolSpecReq,
+    spec_old: Option<ToolSpec>,
+    release: Vec<Artifact>,
+    artifact: Artifact,
+    pinned: bool,
+}
+
+// Downloads and installs the new version of a tool, unless it is already installed
+async fn install_update(
+    home: &Home,
+    source: &ArtifactSource,
+    manifest: &RokitManifest,
+    update: &ToolUpdate,
+    pt: &CliProgressTracker,
+) -> Result<Option<InstalledArtifact>> {
+    let spec = &update.artifact.tool_spec;
+    if home.tool_cache().is_installed(spec) && home.tool_storage().tool_path(spec).exists() {
+        for _ in 0..INSTALL_SUBTASKS {
+            pt.subtask_completed();
         }
+        return Ok(None);
+    }
 
-        // FUTURE: Install the newly updated tools automatically
+    let alias = &update.alias;
+    let binary = manifest.get_tool_binary(alias);
+    let minisign_key = parse_minisign_key(alias, manifest.get_tool_minisign_key(alias).as_deref())?;
+    let artifact_pattern =
+        parse_artifact_pattern(alias, manifest.get_tool_artifact_pattern(alias).as_deref())?;
+    let tag_prefix = manifest.get_tool_tag_prefix(alias);
+    let verify = manifest.get_tool_verify(alias);
+    let post_install = manifest.get_tool_post_install(alias);
+    let options = InstallOptions {
+        binary: binary.as_deref(),
+        alias: Some(alias),
+        artifact_pattern: artifact_pattern.as_ref(),
+        minisign_key: minisign_key.as_ref(),
+        tag_prefix: tag_prefix.as_deref(),
+        verify_args: verify.as_ref().and_then(ToolVerify::args),
+        post_install: post_install.as_ref(),
+        release: Some(&update.release),
+        artifact: Some(&update.artifact),
+    };
 
-        Ok(())
+    let bar = pt.new_tool_bar(spec, "installing");
+    let installed = install_spec_with_options(home, source, spec, options, |p| {
+        bar.report_install(p);
+    })
+    .await
+    .with_context(|| format!("Failed to install {spec}"))?;
+    if let Some(e) = &installed.post_install_error {
+        pt.print_message(format!(
+            "{} {e}\nRun `{}` with `{}` to see its output.",
+            style("Warning:").bold().yellow(),
+            style("rokit install --reinstall").bold().green(),
+            style("--verbose").bold().green(),
+        ));
     }
+
+    Ok(Some(installed))
 }
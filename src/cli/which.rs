@@ -0,0 +1,272 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    process::exit,
+};
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use console::style;
+use serde_json::json;
+
+use rokit::{
+    discovery::{discover_all_manifests, ToolSource},
+    storage::Home,
+    system::current_dir,
+    tool::{ToolAlias, ToolSpec},
+};
+
+use crate::{
+    runner::{resolve_tool, ResolvedTool},
+    util::{CliEvent, OutputFormat},
+};
+
+/// Prints the path to the binary that Rokit would run for a tool.
+///
+/// The path is printed to stdout, and details about the tool to stderr.
+/// With `--json`, an object with the alias, spec, and absolute path of the
+/// tool is printed instead, or an object with an error if it was not found.
+/// With `--output json`, the same is instead printed as a JSON event.
+#[derive(Debug, Parser)]
+pub struct WhichSubcommand {
+    /// The alias of the tool to find.
+    pub alias: ToolAlias,
+    /// Also list every manifest the tool was found in,
+    /// to help debug which manifest takes precedence.
+    #[clap(long, conflicts_with = "json")]
+    pub all: bool,
+    /// Output the tool as JSON instead of human-readable text,
+    /// for use in editor integrations and other tooling.
+    #[clap(long)]
+    pub json: bool,
+}
+
+impl WhichSubcommand {
+    pub async fn run(self, home: &Home) -> Result<()> {
+        let alias = self.alias;
+        // NOTE: With the global JSON output format, errors
+        // are instead emitted as events, same as for other commands
+        let events = OutputFormat::is_json();
+
+        let found = match find_tool(home, &alias).await {
+            Ok(found) => found,
+            Err(e) if self.json && !events => {
+                let value = json!({
+                    "alias": alias.to_string(),
+                    "error": e.to_string(),
+                });
+                println!("{}", serde_json::to_string_pretty(&value)?);
+                exit(1);
+            }
+            Err(e) => return Err(e),
+        };
+
+        if self.json || events {
+            let path = normalize_path(&found.path, &current_dir().await);
+            if events {
+                CliEvent::ToolFound {
+                    alias: alias.to_string(),
+                    spec: found.spec().map(ToString::to_string),
+                    path: path.display().to_string(),
+                    installed: path.exists(),
+                }
+                .emit();
+            } else {
+                let value = found_tool_to_json(&alias, found.spec(), &path);
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            }
+            return Ok(());
+        }
+
+        println!("{}", found.path.display());
+        match &found.kind {
+            FoundKind::Managed(spec) => {
+                eprintln!("{} {spec}", style("→").dim());
+                if !found.path.exists() {
+                    eprintln!(
+                        "{} This tool is not installed yet, run `{}` to install it.",
+                        style("Warning:").bold().yellow(),
+                        style("rokit install").bold().green(),
+                    );
+                }
+            }
+            FoundKind::Local => {
+                eprintln!("{} locally built", style("→").dim());
+                if !found.path.exists() {
+                    eprintln!(
+                        "{} This tool has not been built yet.",
+                        style("Warning:").bold().yellow(),
+                    );
+                }
+            }
+            FoundKind::NonRokit => {
+                eprintln!("{} system PATH, not managed by Rokit", style("→").dim());
+            }
+        }
+
+        if self.all {
+            print_candidate_manifests(home, &alias).await;
+        }
+
+        Ok(())
+    }
+}
+
+/*
+    A tool found for an alias, with where it comes from, and the path to its binary.
+*/
+struct FoundTool {
+    kind: FoundKind,
+    path: PathBuf,
+}
+
+enum FoundKind {
+    Managed(ToolSpec),
+    Local,
+    NonRokit,
+}
+
+impl FoundTool {
+    fn spec(&self) -> Option<&ToolSpec> {
+        match &self.kind {
+            FoundKind::Managed(spec) => Some(spec),
+            _ => None,
+        }
+    }
+}
+
+// Finds the binary that Rokit would run for the given alias, same as the runner
+async fn find_tool(home: &Home, alias: &ToolAlias) -> Result<FoundTool> {
+    let (kind, path) = match resolve_tool(home, alias).await? {
+        ResolvedTool::Managed {
+            requirement,
+            locked,
+            installed,
+            ..
+        } => {
+            // NOTE: Tools that are not installed yet are still shown,
+            // if we know which version the runner would install for them
+            let Some(spec) = installed
+                .or(locked.map(|spec| *spec))
+                .or_else(|| requirement.exact())
+            else {
+                bail!(
+                    "No installed version of tool '{alias}' matches '{}'.\
+                    \nRun 'rokit install' to install a matching version.",
+                    requirement.version_req()
+                );
+            };
+            let path = home.tool_storage().tool_path(&spec);
+            (FoundKind::Managed(spec), path)
+        }
+        ResolvedTool::Local(path) => (FoundKind::Local, path),
+        ResolvedTool::NonRokit(path) => (FoundKind::NonRokit, path),
+    };
+    Ok(FoundTool { kind, path })
+}
+
+fn found_tool_to_json(
+    alias: &ToolAlias,
+    spec: Option<&ToolSpec>,
+    path: &Path,
+) -> serde_json::Value {
+    json!({
+        "alias": alias.to_string(),
+        "spec": spec.map(ToString::to_string),
+        "path": path.display().to_string(),
+        "installed": path.exists(),
+    })
+}
+
+/*
+    Makes the given path absolute, and removes any `.` and `..` components,
+    without touching the filesystem, so that paths to missing tools also work.
+*/
+fn normalize_path(path: &Path, cwd: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    dunce::simplified(&normalized).to_path_buf()
+}
+
+// Lists all manifests containing the given alias, in order of precedence
+async fn print_candidate_manifests(home: &Home, alias: &ToolAlias) {
+    let bullet = style("•").dim();
+
+    let candidates = discover_all_manifests(false, false)
+        .await
+        .into_iter()
+        .filter_map(|manifest| {
+            let requirement = match &manifest.entries.get(alias)?.source {
+                ToolSource::Spec(spec) => spec.to_string(),
+                ToolSource::Requirement(requirement) => requirement.to_string(),
+                ToolSource::Local(local) => format!("path:{}", local.display()),
+            };
+            Some((manifest.path, requirement))
+        })
+        .collect::<Vec<_>>();
+
+    eprintln!("\nFound in {} manifest(s):", candidates.len());
+    for (index, (path, requirement)) in candidates.into_iter().enumerate() {
+        let path = home
+            .display_path(&path)
+            .unwrap_or_else(|| path.display().to_string());
+        let note = if index == 0 {
+            style(" (used)").green().to_string()
+        } else {
+            style(" (shadowed)").dim().to_string()
+        };
+        eprintln!("  {bullet} {requirement} {}{note}", style(path).dim());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::cli::{Cli, Subcommand};
+
+    use super::*;
+
+    fn parse_which(args: &[&str]) -> WhichSubcommand {
+        let cli = Cli::try_parse_from(["rokit", "which"].iter().chain(args)).unwrap();
+        match cli.subcommand {
+            Some(Subcommand::Which(which)) => which,
+            other => panic!("expected which subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn json_flag() {
+        assert!(parse_which(&["tool", "--json"]).json);
+        assert!(!parse_which(&["tool"]).json);
+        assert!(parse_which(&["tool", "--json", "--output", "json"]).json);
+        assert!(Cli::try_parse_from(["rokit", "which", "tool", "--json", "--all"]).is_err());
+    }
+
+    #[test]
+    fn found_tool_json_shape() {
+        let alias = "tool".parse().unwrap();
+        let spec = "author/tool@1.0.0".parse().unwrap();
+        let path = Path::new("missing").join("tool");
+        assert_eq!(
+            found_tool_to_json(&alias, Some(&spec), &path),
+            json!({
+                "alias": "tool",
+                "spec": "author/tool@1.0.0",
+                "path": path.display().to_string(),
+                "installed": false,
+            })
+        );
+        assert_eq!(
+            found_tool_to_json(&alias, None, &path)["spec"],
+            serde_json::Value::Null
+        );
+    }
+}
@@ -9,6 +9,7 @@ mod util;
 
 use self::cli::Cli;
 use self::runner::Runner;
+use self::util::{CliEvent, OutputFormat};
 
 #[tokio::main]
 async fn main() {
@@ -37,7 +38,11 @@ async fn main() {
         respective `run` methods for the `Cli` and `Runner` structs.
     */
     if let Err(e) = result {
-        error!("{e:?}");
+        if OutputFormat::is_json() {
+            CliEvent::error(&e).emit();
+        } else {
+            error!("{e:?}");
+        }
         exit(1);
     }
 }
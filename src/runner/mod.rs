@@ -1,16 +1,26 @@
-use std::{env::args, process::exit, str::FromStr};
+use std::{
+    env::{args_os, var_os},
+    ffi::OsString,
+    path::PathBuf,
+    process::exit,
+    str::FromStr,
+};
 
-use anyhow::{bail, Error, Result};
-use tracing::level_filters::LevelFilter;
+use anyhow::{bail, Context, Error, Result};
+use tracing::{level_filters::LevelFilter, warn};
 
 use rokit::{
-    discovery::{discover_non_rokit_tool, discover_tool_spec},
+    discovery::{
+        discover_config, discover_lockfile_dir, discover_non_rokit_tool, discover_tool_entry,
+        discover_tool_env, ToolEntry, ToolSource,
+    },
+    manifests::RokitLockfile,
     storage::Home,
-    system::{current_exe_name, run_interruptible},
-    tool::ToolAlias,
+    system::{current_exe_name, run_interruptible_with_env},
+    tool::{ToolAlias, ToolSpec, ToolSpecReq},
 };
 
-use crate::util::init_tracing;
+use crate::util::{init_tracing, install_missing_tool};
 
 mod info;
 
@@ -23,11 +33,23 @@ pub struct Runner {
 
 impl Runner {
     pub fn new() -> Self {
+        Self::with_exe_name(current_exe_name())
+    }
+
+    /**
+        Creates a runner for the given executable name, instead of the name
+        of the current executable, which is useful for debugging links.
+    */
+    pub fn with_exe_name(exe_name: impl Into<String>) -> Self {
         Self {
-            exe_name: current_exe_name(),
+            exe_name: exe_name.into(),
         }
     }
 
+    pub fn exe_name(&self) -> &str {
+        &self.exe_name
+    }
+
     pub fn should_run(&self) -> bool {
         self.exe_name != env!("CARGO_BIN_NAME")
     }
@@ -36,32 +58,14 @@ impl Runner {
         // Always log at INFO level when running a managed program
         // unless the user has explicitly set a different level
         // using the RUST_LOG environment variable.
-        init_tracing(LevelFilter::INFO);
+        init_tracing(LevelFilter::INFO, None);
 
         let alias = ToolAlias::from_str(&self.exe_name)?;
-
         let home = Home::load_from_env().await?;
-        let spec = discover_tool_spec(&alias, false, false).await;
-
-        let program_args = args().skip(1).collect::<Vec<_>>();
-        let program_path = match spec {
-            // TODO: Prompt for trust and install tool if not already installed
-            Some(spec) => home.tool_storage().tool_path(&spec),
-            // FUTURE: Maybe we should add some kind of "fall-through" setting in
-            // Rokit manifests instead of always falling through to non-rokit tools?
-            None => match discover_non_rokit_tool(&home, &alias).await {
-                Some(path) => path,
-                None => bail!(
-                    "Failed to find tool '{alias}' in any project manifest file.\
-                    \nAdd the tool to a project using 'rokit add' before running it."
-                ),
-            },
-        };
 
-        let code = run_interruptible(&program_path, &program_args)
-            .await
-            .map_err(Error::from)
-            .inspect_err(|e| inform_user_about_potential_fixes(&alias, e))?;
+        // NOTE: Arguments are forwarded as-is, even if they are not valid UTF-8
+        let program_args = args_os().skip(1).collect::<Vec<_>>();
+        let code = run_tool(&home, &alias, &program_args).await?;
 
         exit(code);
     }
@@ -72,3 +76,143 @@ impl Default for Runner {
         Self::new()
     }
 }
+
+/**
+    How a tool alias resolves to a binary, see [`resolve_tool`].
+*/
+#[derive(Debug, Clone)]
+pub enum ResolvedTool {
+    /// A tool from a project manifest, along with its manifest entry, the
+    /// version its requirement is locked to in the lockfile, if any, and the
+    /// installed version that matches its requirement, if any is installed.
+    Managed {
+        requirement: Box<ToolSpecReq>,
+        entry: Box<ToolEntry>,
+        locked: Option<Box<ToolSpec>>,
+        installed: Option<ToolSpec>,
+    },
+    /// A locally built tool, which is run directly from its path.
+    Local(PathBuf),
+    /// A binary found on the system PATH, which is not managed by Rokit.
+    NonRokit(PathBuf),
+}
+
+/**
+    Resolves the tool with the given alias the same way as running its link
+    would, using the project manifests for the current directory, but without
+    installing the tool or making any network requests.
+*/
+pub async fn resolve_tool(home: &Home, alias: &ToolAlias) -> Result<ResolvedTool> {
+    if let Some(entry) = discover_tool_entry(alias, false, false).await {
+        let requirement = match &entry.source {
+            ToolSource::Spec(spec) => ToolSpecReq::from(spec.clone()),
+            ToolSource::Requirement(requirement) => requirement.clone(),
+            // NOTE: Locally built tools are run directly from their path, so
+            // that rebuilding the tool does not require reinstalling it
+            ToolSource::Local(path) => return Ok(ResolvedTool::Local(path.clone())),
+        };
+        // NOTE: Version requirements are resolved using the lockfile, same as
+        // `rokit install`, and otherwise against installed versions, since we
+        // should avoid making network requests when running tools
+        let locked = match requirement.exact() {
+            Some(_) => None,
+            None => find_locked(&requirement).await?.map(Box::new),
+        };
+        let installed = match requirement.exact().or_else(|| locked.as_deref().cloned()) {
+            Some(spec) => Some(spec),
+            None => home.tool_cache().find_installed_matching(&requirement),
+        }
+        .filter(|spec| home.tool_storage().tool_path(spec).exists());
+        return Ok(ResolvedTool::Managed {
+            requirement: Box::new(requirement),
+            entry: Box::new(entry),
+            locked,
+            installed,
+        });
+    }
+
+    // NOTE: Falling back to tools not managed by Rokit is opt-in, since
+    // running some other binary with the same name may be surprising
+    let config = discover_config(home).await?;
+    if !config.path_fallback.unwrap_or_default() {
+        bail!(
+            "Failed to find tool '{alias}' in any project manifest file.\
+            \nAdd the tool to a project using 'rokit add' before running it.\
+            \nTo instead run a '{alias}' binary found on the system PATH, which is \
+            not managed by Rokit, set 'path_fallback = true' in a Rokit config file."
+        );
+    }
+    match discover_non_rokit_tool(home, alias).await {
+        Some(path) => Ok(ResolvedTool::NonRokit(path)),
+        None => bail!(
+            "Failed to find tool '{alias}' in any project manifest file, \
+            or on the system PATH.\
+            \nAdd the tool to a project using 'rokit add' before running it."
+        ),
+    }
+}
+
+/*
+    Finds the version that a requirement is locked to, in the lockfile
+    next to the closest Rokit manifest, if there is such a lockfile.
+*/
+async fn find_locked(requirement: &ToolSpecReq) -> Result<Option<ToolSpec>> {
+    let Some(dir) = discover_lockfile_dir(false).await else {
+        return Ok(None);
+    };
+    let lockfile = RokitLockfile::load(&dir)
+        .await
+        .context("Failed to load lockfile")?;
+    Ok(lockfile.and_then(|lockfile| lockfile.get_resolved(requirement).cloned()))
+}
+
+/**
+    Runs the tool with the given alias, the same way as running its link would,
+    and returns the exit code of the tool, which should be forwarded by the caller.
+
+    The tool is found using the project manifests for the current directory,
+    and installed first if necessary, which also saves the given `Home`.
+*/
+pub async fn run_tool(home: &Home, alias: &ToolAlias, program_args: &[OsString]) -> Result<i32> {
+    let program_path = match resolve_tool(home, alias).await? {
+        ResolvedTool::Managed {
+            installed: Some(spec),
+            ..
+        } => home.tool_storage().tool_path(&spec),
+        ResolvedTool::Managed {
+            requirement,
+            entry,
+            locked,
+            installed: None,
+        } => {
+            // NOTE: Locked versions are installed as-is, without resolving them again
+            let requirement = locked.map_or(*requirement, |spec| ToolSpecReq::from(*spec));
+            let spec = install_missing_tool(home, alias, &requirement, Some(&entry)).await?;
+            home.save().await?;
+            home.tool_storage().tool_path(&spec)
+        }
+        ResolvedTool::Local(path) if path.exists() => path,
+        ResolvedTool::Local(path) => bail!(
+            "Failed to find locally built tool '{alias}' at '{}'.\
+            \nBuild the tool, or check the path in your manifest.",
+            path.display()
+        ),
+        ResolvedTool::NonRokit(path) => path,
+    };
+
+    // NOTE: Conflicts are not fatal, since the existing environment
+    // intentionally takes precedence, but may be surprising to users
+    let (program_env, conflicts) = discover_tool_env(alias, false, false)
+        .await
+        .resolve(|name| var_os(name));
+    for conflict in conflicts {
+        warn!("{conflict}");
+    }
+
+    let code = run_interruptible_with_env(&program_path, program_args, program_env)
+        .await
+        .map_err(Error::from)
+        .inspect_err(|e| inform_user_about_potential_fixes(alias, e))?;
+
+    Ok(code)
+}
@@ -1,12 +1,47 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 
 use rokit::{
-    descriptor::{Arch, OS},
-    sources::Artifact,
-    tool::ToolId,
+    descriptor::{Arch, Descriptor, OS},
+    sources::{Artifact, ArtifactPattern, MinisignPublicKey},
+    tool::{ToolAlias, ToolId},
 };
 
-pub fn find_most_compatible_artifact(artifacts: &[Artifact], tool_id: &ToolId) -> Result<Artifact> {
+// Number of artifacts to log scores for when none are compatible
+const MAX_SCORED_ARTIFACTS: usize = 5;
+
+pub fn find_most_compatible_artifact(
+    artifacts: &[Artifact],
+    tool_id: &ToolId,
+    pattern: Option<&ArtifactPattern>,
+) -> Result<Artifact> {
+    // An explicit pattern always takes priority over the heuristics,
+    // and failing to match it should be a hard error, since the user
+    // explicitly told us which artifact to look for
+    if let Some(pattern) = pattern {
+        let artifact = Artifact::filter_by_pattern(artifacts, pattern)
+            .into_iter()
+            .next();
+        tracing::debug!(
+            %tool_id,
+            %pattern,
+            name = %artifact.as_ref().and_then(|a| a.name.as_deref()).unwrap_or("N/A"),
+            "selected artifact matching pattern for tool",
+        );
+        return artifact.with_context(|| {
+            let artifact_names = artifacts
+                .iter()
+                .filter(|artifact| !artifact.is_checksum_file() && !artifact.is_signature_file())
+                .filter_map(|artifact| artifact.file_name.as_deref())
+                .map(|name| format!("\n  - {name}"))
+                .collect::<Vec<_>>();
+            format!(
+                "No artifact matching '{pattern}' found for {tool_id}\
+                \nAvailable artifacts:{}",
+                artifact_names.join("")
+            )
+        });
+    }
+
     let mut artifact_opt = Artifact::sort_by_system_compatibility(artifacts)
         .first()
         .cloned();
@@ -36,7 +71,151 @@ pub fn find_most_compatible_artifact(artifacts: &[Artifact], tool_id: &ToolId) -
         }
     }
 
+    // Emulated binaries work, but let the user know why they may be slow
+    if let Some(artifact) = artifact_opt
+        .as_ref()
+        .filter(|a| a.is_emulated_on_current_system())
+    {
+        tracing::warn!(
+            "No native artifact was found for {tool_id}, using '{}' instead.\
+            \nThis artifact will run through emulation (Rosetta 2) and may be slower.",
+            artifact.name.as_deref().unwrap_or("N/A"),
+        );
+    }
+
     // If we did not find a compatible artifact, either directly
     // or through a fallback mechanism, this should be a hard error
-    artifact_opt.with_context(|| format!("No compatible artifact found for {tool_id}"))
+    artifact_opt.with_context(|| {
+        incompatible_artifacts_message(artifacts, tool_id, Descriptor::current_system())
+    })
+}
+
+/**
+    Finds the most compatible artifact for the given target system, which
+    may be a different system than the current one, such as when downloading
+    artifacts to be used on another platform.
+*/
+pub fn find_artifact_for_target(
+    artifacts: &[Artifact],
+    tool_id: &ToolId,
+    pattern: Option<&ArtifactPattern>,
+    target: Descriptor,
+) -> Result<Artifact> {
+    let artifact = match pattern {
+        Some(pattern) => Artifact::filter_by_pattern_for(artifacts, pattern, target)
+            .into_iter()
+            .next(),
+        None => Artifact::sort_by_compatibility_with(artifacts, target)
+            .into_iter()
+            .next()
+            .or_else(|| Artifact::find_partially_compatible_fallback_for(artifacts, target)),
+    };
+    artifact.with_context(|| incompatible_artifacts_message(artifacts, tool_id, target))
+}
+
+/*
+    Creates an error message for when no artifact compatible with the
+    target system was found, listing all of the available artifacts and
+    the systems they were detected as, to make it clear why none was picked.
+
+    The highest scoring artifacts are also logged, for `--verbose` output.
+*/
+fn incompatible_artifacts_message(
+    artifacts: &[Artifact],
+    tool_id: &ToolId,
+    target: Descriptor,
+) -> String {
+    let mut candidates = artifacts
+        .iter()
+        .filter(|artifact| !artifact.is_checksum_file() && !artifact.is_signature_file())
+        .filter_map(|artifact| {
+            let name = artifact.name.as_deref()?;
+            let score = artifact.compatibility_score_with(target);
+            Some((name, score))
+        })
+        .collect::<Vec<_>>();
+
+    candidates.sort_by(|(name_a, score_a), (name_b, score_b)| {
+        score_b.cmp(score_a).then_with(|| name_a.cmp(name_b))
+    });
+    for (name, score) in candidates.iter().take(MAX_SCORED_ARTIFACTS) {
+        tracing::debug!(%tool_id, name, score, "scored incompatible artifact");
+    }
+
+    let system = if target == Descriptor::current_system() {
+        "Your system"
+    } else {
+        "Target system"
+    };
+    if candidates.is_empty() {
+        return format!(
+            "No compatible artifact found for {tool_id}\
+            \n{system}: {target}\
+            \nThe release of this tool has no artifacts to choose from."
+        );
+    }
+
+    let artifact_lines = candidates
+        .iter()
+        .map(|(name, _)| match Descriptor::detect(name) {
+            Some(desc) => format!("\n  - {name} ({desc})"),
+            None => format!("\n  - {name} (unknown system)"),
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "No compatible artifact found for {tool_id}\
+        \n{system}: {target}\
+        \nAvailable artifacts:{}\
+        \nThis tool may not support your system, or it may use artifact names that\
+        \nRokit does not recognize. If one of the artifacts above does support your\
+        \nsystem, set an `artifact` pattern for the tool in your manifest to use it.",
+        artifact_lines.join("")
+    )
+}
+
+/**
+    Parses a target system, such as `x86_64-pc-windows-msvc` or `windows-x64`.
+*/
+pub fn parse_target(s: &str) -> Result<Descriptor> {
+    let target = s
+        .parse::<Descriptor>()
+        .ok()
+        .with_context(|| format!("Unknown operating system in target '{s}'"))?;
+    if target.arch().is_none() {
+        bail!("Unknown architecture in target '{s}', expected a target such as `windows-x64`");
+    }
+    Ok(target)
+}
+
+/**
+    Parses the artifact name pattern declared for a tool in a manifest, if any.
+*/
+pub fn parse_artifact_pattern(
+    alias: &ToolAlias,
+    pattern: Option<&str>,
+) -> Result<Option<ArtifactPattern>> {
+    pattern
+        .map(|pattern| {
+            pattern
+                .parse::<ArtifactPattern>()
+                .with_context(|| format!("Invalid artifact pattern for tool '{alias}'"))
+        })
+        .transpose()
+}
+
+/**
+    Parses the minisign public key declared for a tool in a manifest, if any.
+
+    A key that fails to parse is a hard error, since the user
+    explicitly asked for the tool to be verified using it.
+*/
+pub fn parse_minisign_key(
+    alias: &ToolAlias,
+    key: Option<&str>,
+) -> Result<Option<MinisignPublicKey>> {
+    key.map(|key| {
+        key.parse::<MinisignPublicKey>()
+            .with_context(|| format!("Invalid minisign public key for tool '{alias}'"))
+    })
+    .transpose()
 }
@@ -0,0 +1,26 @@
+use std::env::var_os;
+
+use console::{
+    colors_enabled, colors_enabled_stderr, set_colors_enabled, set_colors_enabled_stderr,
+};
+
+/**
+    Decides if styled output should use colors, and applies that
+    decision to all `console::style` calls, progress bars, and logs.
+
+    Colors are disabled if the `NO_COLOR` environment variable is set to
+    a non-empty value, or if either stdout or stderr is not a terminal.
+
+    Styled text may be written to either stdout or stderr, so
+    colors are only used when both of them are terminals, or
+    when forced using the `CLICOLOR_FORCE` environment variable.
+
+    Returns `true` if colors are enabled.
+*/
+pub fn init() -> bool {
+    let no_color = var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let enabled = !no_color && colors_enabled() && colors_enabled_stderr();
+    set_colors_enabled(enabled);
+    set_colors_enabled_stderr(enabled);
+    enabled
+}
@@ -0,0 +1,105 @@
+use std::io::{stderr, IsTerminal};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+
+use rokit::{
+    discovery::ToolEntry,
+    install::{install_spec_with_options, InstallOptions},
+    manifests::ToolVerify,
+    storage::Home,
+    tool::{ToolAlias, ToolSpec, ToolSpecReq},
+};
+
+use super::{
+    find_most_compatible_artifact, parse_artifact_pattern, parse_minisign_key, prompt_for_trust,
+    CliProgressTracker, INSTALL_SUBTASKS,
+};
+
+/**
+    Installs a tool that is not yet installed, so that it can be run
+    directly without having to run `rokit install` first.
+
+    Untrusted tools will prompt the user for trust, unless the terminal is not
+    interactive, in which case the user is told how to trust the tool instead.
+
+    Settings such as the binary to extract, and any verification or post-install
+    commands, are taken from the manifest entry for the tool, if given,
+    otherwise the tool is installed using defaults.
+*/
+pub async fn install_missing_tool(
+    home: &Home,
+    alias: &ToolAlias,
+    requirement: &ToolSpecReq,
+    entry: Option<&ToolEntry>,
+) -> Result<ToolSpec> {
+    let binary = entry.and_then(|entry| entry.binary.as_deref());
+    let tag_prefix = entry.and_then(|entry| entry.tag_prefix.as_deref());
+    let minisign_key = entry.and_then(|entry| entry.minisign_key.as_deref());
+    let minisign_key = parse_minisign_key(alias, minisign_key)?;
+    let artifact_pattern = entry.and_then(|entry| entry.artifact_pattern.as_deref());
+    let artifact_pattern = parse_artifact_pattern(alias, artifact_pattern)?;
+    let verify_args = entry
+        .and_then(|entry| entry.verify.as_ref())
+        .and_then(ToolVerify::args);
+    let post_install = entry.and_then(|entry| entry.post_install.as_ref());
+    let tool_cache = home.tool_cache();
+    let id = requirement.id();
+
+    // 1. Make sure the tool is trusted, without hanging on non-interactive terminals
+    if !tool_cache.is_trusted(id) && !home.trust_manifest().await?.is_trusted(id) {
+        if !stderr().is_terminal() {
+            bail!(
+                "Tool '{alias}' ({id}) is not installed, and has not been marked as trusted.\
+                \nRun `rokit trust {id}` to trust it, or run `rokit install` in an interactive terminal."
+            );
+        }
+        eprintln!("Tool '{alias}' is not installed yet, and needs your approval.");
+        if !prompt_for_trust(id.clone()).await? {
+            bail!("Tool {id} was not trusted and will not be installed.");
+        }
+        let _ = tool_cache.add_trust(id.clone());
+    }
+
+    // 2. Resolve any version requirement and find a compatible artifact
+    let pt = CliProgressTracker::new_with_message_and_subtasks("Installing", 1, INSTALL_SUBTASKS);
+    let source = home.artifact_source().await?;
+    let spec = source.resolve_requirement(requirement).await?;
+
+    let artifacts = source
+        .get_specific_release_with_tag_prefix(&spec, tag_prefix)
+        .await?;
+    let artifact = find_most_compatible_artifact(&artifacts, spec.id(), artifact_pattern.as_ref())?;
+
+    // 3. Download, verify, extract and store the tool, so that it can be found
+    // next time it runs, and run any verification and post-install commands
+    let options = InstallOptions {
+        binary,
+        alias: Some(alias),
+        artifact_pattern: artifact_pattern.as_ref(),
+        minisign_key: minisign_key.as_ref(),
+        tag_prefix,
+        verify_args,
+        post_install,
+        release: Some(&artifacts),
+        artifact: Some(&artifact),
+    };
+    let bar = pt.new_tool_bar(&spec, "installing");
+    let installed = install_spec_with_options(home, &source, &spec, options, |p| {
+        bar.report_install(p);
+    })
+    .await
+    .with_context(|| format!("Failed to install {spec}"))?;
+    drop(bar);
+    if let Some(e) = installed.post_install_error {
+        pt.print_message(format!("{} {e}", style("Warning:").bold().yellow()));
+    }
+
+    pt.finish_with_message(format!(
+        "Installed {} {}",
+        style(&spec).bold().magenta(),
+        pt.formatted_elapsed(),
+    ));
+
+    Ok(spec)
+}
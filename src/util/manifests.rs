@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use rokit::discovery::{discover_all_manifests, load_manifests, DiscoveredManifest};
+
+/**
+    Loads the manifests at the given paths, if any were given using `--manifest`,
+    or discovers all manifests from the current directory if none were given.
+*/
+pub async fn load_or_discover_manifests(paths: &[PathBuf]) -> Result<Vec<DiscoveredManifest>> {
+    if paths.is_empty() {
+        return Ok(discover_all_manifests(false, false).await);
+    }
+    load_manifests(paths)
+        .await
+        .context("Failed to load the given manifests")
+}
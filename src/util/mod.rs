@@ -1,14 +1,28 @@
 mod alias_or_id_or_spec;
 mod artifacts;
+mod colors;
 mod constants;
 mod id_or_spec;
+mod install;
+mod manifests;
+mod output;
 mod progress;
 mod prompts;
 mod tracing;
 
 pub use self::alias_or_id_or_spec::ToolAliasOrIdOrSpec;
-pub use self::artifacts::find_most_compatible_artifact;
+pub use self::artifacts::{
+    find_artifact_for_target, find_most_compatible_artifact, parse_artifact_pattern,
+    parse_minisign_key, parse_target,
+};
+pub use self::colors::init as init_colors;
 pub use self::id_or_spec::ToolIdOrSpec;
-pub use self::progress::CliProgressTracker;
-pub use self::prompts::{prompt_for_trust, prompt_for_trust_specs};
+pub use self::install::install_missing_tool;
+pub use self::manifests::load_or_discover_manifests;
+pub use self::output::{print_json, print_message, CliEvent, OutputFormat};
+pub use self::progress::{CliProgressTracker, INSTALL_SUBTASKS};
+pub use self::prompts::{
+    prompt_for_changelog, prompt_for_profile_change, prompt_for_removal, prompt_for_repair,
+    prompt_for_token, prompt_for_trust, prompt_for_trust_specs,
+};
 pub use self::tracing::init as init_tracing;
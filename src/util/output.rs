@@ -0,0 +1,184 @@
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use rokit::tool::ToolSpec;
+
+/**
+    The version of the JSON event schema.
+
+    Must be incremented whenever events are removed or changed in
+    a way that is not backwards compatible, so that consumers can adapt.
+    Adding new events or new fields to existing events is fine.
+*/
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/**
+    The output format used by the Rokit CLI.
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output, with progress bars and styled messages.
+    #[default]
+    Human,
+    /// Newline-delimited JSON events, printed to stdout.
+    Json,
+}
+
+impl OutputFormat {
+    /**
+        Sets the output format for the current process.
+
+        This should be called once, before any output is displayed.
+    */
+    pub fn set(self) {
+        let _ = OUTPUT_FORMAT.set(self);
+    }
+
+    /**
+        Gets the output format for the current process.
+    */
+    pub fn get() -> Self {
+        OUTPUT_FORMAT.get().copied().unwrap_or_default()
+    }
+
+    pub fn is_json() -> bool {
+        Self::get() == Self::Json
+    }
+}
+
+/**
+    A machine-readable event, emitted as a single line of JSON
+    to stdout when using the JSON output format.
+*/
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CliEvent<'a> {
+    TaskStart {
+        message: &'a str,
+        total: usize,
+    },
+    TaskMessage {
+        message: &'a str,
+    },
+    TaskProgress {
+        completed: u64,
+        total: u64,
+    },
+    TaskFinish {
+        message: Option<String>,
+    },
+    Message {
+        message: String,
+    },
+    DownloadStart {
+        tool: String,
+        version: String,
+    },
+    DownloadFinish {
+        tool: String,
+        version: String,
+        bytes: u64,
+    },
+    Error {
+        message: String,
+        causes: Vec<String>,
+    },
+    ToolFound {
+        alias: String,
+        spec: Option<String>,
+        path: String,
+        installed: bool,
+    },
+    Data {
+        data: serde_json::Value,
+    },
+}
+
+impl CliEvent<'_> {
+    pub fn download_start(spec: &ToolSpec) -> Self {
+        Self::DownloadStart {
+            tool: spec.id().to_string(),
+            version: spec.version().to_string(),
+        }
+    }
+
+    pub fn download_finish(spec: &ToolSpec, bytes: u64) -> Self {
+        Self::DownloadFinish {
+            tool: spec.id().to_string(),
+            version: spec.version().to_string(),
+            bytes,
+        }
+    }
+
+    pub fn error(error: &anyhow::Error) -> Self {
+        Self::Error {
+            message: error.to_string(),
+            causes: error.chain().skip(1).map(ToString::to_string).collect(),
+        }
+    }
+
+    /**
+        Emits this event as a single line of JSON to stdout,
+        if the JSON output format is being used.
+    */
+    pub fn emit(&self) {
+        #[derive(Serialize)]
+        struct Envelope<'a, 'b> {
+            schema: u32,
+            #[serde(flatten)]
+            event: &'a CliEvent<'b>,
+        }
+
+        if !OutputFormat::is_json() {
+            return;
+        }
+
+        let envelope = Envelope {
+            schema: EVENT_SCHEMA_VERSION,
+            event: self,
+        };
+        if let Ok(line) = serde_json::to_string(&envelope) {
+            println!("{line}");
+        }
+    }
+}
+
+/**
+    Prints a message to stdout, or emits it as a [`CliEvent::Message`] without
+    any styling when using the JSON output format, so that stdout only ever
+    contains JSON events, which can be parsed line by line.
+*/
+pub fn print_message(message: impl AsRef<str>) {
+    if OutputFormat::is_json() {
+        CliEvent::Message {
+            message: strip_styles(message),
+        }
+        .emit();
+    } else {
+        println!("{}", message.as_ref());
+    }
+}
+
+/**
+    Prints a JSON value to stdout, for commands with their own `--json` flag,
+    or emits it as a [`CliEvent::Data`] when using the JSON output format.
+*/
+pub fn print_json(value: serde_json::Value) -> serde_json::Result<()> {
+    if OutputFormat::is_json() {
+        CliEvent::Data { data: value }.emit();
+    } else {
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    }
+    Ok(())
+}
+
+/**
+    Strips any styling from a message, so that it can be included in events.
+*/
+pub fn strip_styles(message: impl AsRef<str>) -> String {
+    console::strip_ansi_codes(message.as_ref()).into_owned()
+}
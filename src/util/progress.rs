@@ -1,26 +1,102 @@
-use std::{fmt::Write, time::Duration};
+use std::{
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
 
 use console::style;
-use indicatif::{ProgressBar, ProgressState, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressState, ProgressStyle};
+
+use rokit::{
+    install::InstallProgress,
+    result::RokitResult,
+    sources::{Artifact, ArtifactSource},
+    tool::ToolSpec,
+};
+
+use super::output::{strip_styles, CliEvent, OutputFormat};
 
 const PROGRESS_BAR_CHARACTERS: &str = "█▉▊▋▌▍▎▏ ";
 const PROGRESS_BAR_TICKERS: &str = "⠙⠹⠸⠼⠴⠦⠧⠇⠏ ";
 
 const PROGRESS_TEMPLATE_DEFAULT: &str =
     "{spinner:.bold.cyan} {msg:11.bold.cyan} [{bar:32.bold}] {current_task:>2} / {total_tasks:2}";
+const PROGRESS_TEMPLATE_DOWNLOAD: &str =
+    "  {spinner:.dim} {prefix:.dim} [{bar:24}] {bytes} / {total_bytes} {bytes_per_sec:.dim}";
+const PROGRESS_TEMPLATE_DOWNLOAD_UNKNOWN: &str =
+    "  {spinner:.dim} {prefix:.dim} {bytes} {bytes_per_sec:.dim}";
+const PROGRESS_TEMPLATE_TOOL: &str = "  {spinner:.dim} {prefix:.dim} {msg:.dim}";
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/**
+    The number of subtasks that installing a single tool is split into,
+    when reporting its progress using [`CliToolBar::report_install`].
+*/
+pub const INSTALL_SUBTASKS: usize = 5;
 
 /**
     A styled progress bar for the Rokit CLI.
 
     Tracks subtasks (partial tasks) and a more granular progress
     bar while still only displaying the main task count to the user.
+
+    When using the JSON output format, progress bars are hidden,
+    and progress is instead emitted as [`CliEvent`]s.
+
+    When quiet, progress bars and intermediate messages are
+    hidden, and only the final message is printed, if any.
 */
 pub struct CliProgressTracker {
+    multi: MultiProgress,
     inner: ProgressBar,
+    main: MainProgress,
     num_subtasks: Option<usize>,
+    download_weight: u64,
+    json: bool,
+    quiet: bool,
+}
+
+/*
+    The main progress bar of a tracker, along with how many units of progress
+    each task is worth, which download bars share to credit progress towards it.
+*/
+#[derive(Clone)]
+struct MainProgress {
+    inner: ProgressBar,
+    units_per_task: u64,
+    json: bool,
+}
+
+impl MainProgress {
+    // Increments the progress bar, and emits an event whenever a main task completes
+    fn increment(&self, delta: u64) {
+        let before = self.inner.position() / self.units_per_task;
+        self.inner.inc(delta);
+        let after = self.inner.position() / self.units_per_task;
+        if self.json && after != before {
+            CliEvent::TaskProgress {
+                completed: after,
+                total: self.inner.length().unwrap_or_default() / self.units_per_task,
+            }
+            .emit();
+        }
+    }
 }
 
 impl CliProgressTracker {
+    /**
+        Sets whether all progress trackers in the current process should be quiet.
+
+        This should be called once, before any progress tracker is created.
+    */
+    pub fn set_quiet(quiet: bool) {
+        QUIET.store(quiet, Ordering::Relaxed);
+    }
+
     /**
         Create a new progress tracker with a message and a number of tasks.
 
@@ -31,10 +107,7 @@ impl CliProgressTracker {
         num_tasks: usize,
         subtasks_per_task: usize,
     ) -> Self {
-        Self {
-            inner: new_progress_bar(message, num_tasks, subtasks_per_task),
-            num_subtasks: Some(subtasks_per_task),
-        }
+        Self::new_inner(message.into(), num_tasks, Some(subtasks_per_task))
     }
 
     /**
@@ -43,20 +116,70 @@ impl CliProgressTracker {
         Does not have any subtasks.
     */
     pub fn new_with_message(message: impl Into<String>, num_tasks: usize) -> Self {
+        Self::new_inner(message.into(), num_tasks, None)
+    }
+
+    fn new_inner(message: String, num_tasks: usize, num_subtasks: Option<usize>) -> Self {
+        let json = OutputFormat::is_json();
+        let quiet = QUIET.load(Ordering::Relaxed);
+        let multi = if json || quiet {
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+        } else {
+            MultiProgress::new()
+        };
+        CliEvent::TaskStart {
+            message: &message,
+            total: num_tasks,
+        }
+        .emit();
+        let units_per_task = num_subtasks.unwrap_or(1);
+        let inner = new_progress_bar(&multi, message, num_tasks, units_per_task);
         Self {
-            inner: new_progress_bar(message, num_tasks, 1),
-            num_subtasks: None,
+            main: MainProgress {
+                inner: inner.clone(),
+                units_per_task: units_per_task as u64,
+                json,
+            },
+            inner,
+            multi,
+            num_subtasks,
+            download_weight: 0,
+            json,
+            quiet,
         }
     }
 
+    /**
+        Gives downloads a weight of the given number of subtasks, in addition to the
+        subtasks per task, which is filled in as bytes are downloaded, instead of
+        all at once - downloads usually take most of the time spent on each task.
+
+        Download progress is credited by download bars, using the content length of
+        each download, and the full weight is credited once a download bar is dropped.
+        Tasks that never download anything must call [`CliProgressTracker::download_skipped`].
+    */
+    #[must_use]
+    pub fn with_download_weight(mut self, subtasks: usize) -> Self {
+        assert!(
+            self.num_subtasks.is_some(),
+            "with_download_weight called without subtasks"
+        );
+        let num_tasks = self.inner.length().unwrap_or_default() / self.main.units_per_task;
+        self.download_weight = subtasks as u64;
+        self.main.units_per_task += self.download_weight;
+        self.inner.set_length(num_tasks * self.main.units_per_task);
+        self.inner.set_style(new_progress_style(
+            usize::try_from(num_tasks).unwrap_or_default(),
+            usize::try_from(self.main.units_per_task).unwrap_or_default(),
+        ));
+        self
+    }
+
     /**
         Increments the main task count.
     */
     pub fn task_completed(&self) {
-        match self.num_subtasks {
-            Some(n) => self.inner.inc(n as u64),
-            None => self.inner.inc(1),
-        }
+        self.main.increment(self.main.units_per_task);
     }
 
     /**
@@ -70,7 +193,77 @@ impl CliProgressTracker {
             self.num_subtasks.is_some(),
             "subtask_completed called without subtasks"
         );
-        self.inner.inc(1);
+        self.main.increment(1);
+    }
+
+    /**
+        Credits the download weight for a task that did not download anything,
+        such as a task that was skipped, see [`CliProgressTracker::with_download_weight`].
+    */
+    pub fn download_skipped(&self) {
+        self.main.increment(self.download_weight);
+    }
+
+    /**
+        Creates a new progress bar for a single download, displayed below the main
+        progress bar, which shows the number of bytes downloaded for the given tool.
+
+        The download bar is removed when it is dropped.
+    */
+    pub fn new_download_bar(&self, spec: &ToolSpec) -> CliDownloadBar {
+        let pb = ProgressBar::new_spinner()
+            .with_style(new_download_style(PROGRESS_TEMPLATE_DOWNLOAD_UNKNOWN))
+            .with_prefix(spec.to_string());
+        let pb = self.multi.add(pb);
+        pb.enable_steady_tick(Duration::from_millis(50));
+        CliEvent::download_start(spec).emit();
+        CliDownloadBar {
+            inner: pb,
+            spec: spec.clone(),
+            clear_on_drop: true,
+            credit: DownloadCredit::new(&self.main, self.download_weight),
+        }
+    }
+
+    /**
+        Downloads the contents of the given artifact, displaying its progress
+        in a download bar, see [`CliProgressTracker::new_download_bar`].
+    */
+    pub async fn download_artifact(
+        &self,
+        source: &ArtifactSource,
+        artifact: &Artifact,
+    ) -> RokitResult<Vec<u8>> {
+        let download = self.new_download_bar(&artifact.tool_spec);
+        source
+            .download_artifact_contents_with_progress(artifact, |downloaded, total| {
+                download.set_progress(downloaded, total);
+            })
+            .await
+    }
+
+    /**
+        Creates a new line for a single tool, displayed below the main progress bar,
+        which shows the current stage of the tool, such as downloading or extracting.
+
+        This is meant for tools that are processed in parallel, and should only
+        be created once work on the tool has started, so that only tools that are
+        currently being processed are displayed. The line is removed when dropped.
+    */
+    pub fn new_tool_bar(&self, spec: &ToolSpec, stage: &'static str) -> CliToolBar {
+        let pb = ProgressBar::new_spinner()
+            .with_style(new_download_style(PROGRESS_TEMPLATE_TOOL))
+            .with_prefix(spec.to_string())
+            .with_message(stage);
+        let pb = self.multi.add(pb);
+        pb.enable_steady_tick(Duration::from_millis(50));
+        CliToolBar {
+            inner: pb,
+            spec: spec.clone(),
+            main: self.main.clone(),
+            download_weight: self.download_weight,
+            download: Mutex::new(None),
+        }
     }
 
     /**
@@ -89,15 +282,35 @@ impl CliProgressTracker {
         Updates the message in front of the progress bar.
     */
     pub fn update_message(&self, message: impl Into<String>) {
-        self.inner.set_message(message.into());
+        let message = message.into();
+        if self.json && self.inner.message() != message {
+            CliEvent::TaskMessage { message: &message }.emit();
+        }
+        self.inner.set_message(message);
     }
 
     /**
         Prints a message above the current progress bar.
     */
-    #[allow(dead_code)]
     pub fn print_message(&self, message: impl Into<String>) {
-        self.inner.println(message.into());
+        let message = message.into();
+        if self.json {
+            CliEvent::Message {
+                message: strip_styles(&message),
+            }
+            .emit();
+        }
+        self.inner.println(message);
+    }
+
+    /**
+        Finishes the progress tracker without a final message.
+
+        This will clear the progress bar and display nothing else.
+    */
+    pub fn finish_and_clear(&self) {
+        CliEvent::TaskFinish { message: None }.emit();
+        self.inner.finish_and_clear();
     }
 
     /**
@@ -115,15 +328,191 @@ impl CliProgressTracker {
         This will clear the progress bar and display the final message given.
     */
     pub fn finish_with_emoji_and_message(&self, emoji: &str, final_message: impl Into<String>) {
-        self.inner.println(format!(
-            "{} {}",
-            style(emoji).bold().green(),
-            final_message.into()
-        ));
+        let final_message = final_message.into();
+        if self.json {
+            CliEvent::TaskFinish {
+                message: Some(strip_styles(&final_message)),
+            }
+            .emit();
+            self.inner.finish_and_clear();
+            return;
+        }
+        let final_message = format!("{} {}", style(emoji).bold().green(), final_message);
+        if self.quiet {
+            // NOTE: The progress bar is hidden, so println would do nothing
+            eprintln!("{final_message}");
+        } else {
+            self.inner.println(final_message);
+        }
+        self.inner.finish_and_clear();
+    }
+}
+
+/**
+    A progress bar for a single download, created using [`CliProgressTracker::new_download_bar`].
+
+    Displays a spinner until the total size of the download is known.
+*/
+pub struct CliDownloadBar {
+    inner: ProgressBar,
+    spec: ToolSpec,
+    clear_on_drop: bool,
+    credit: DownloadCredit,
+}
+
+impl CliDownloadBar {
+    /**
+        Updates the number of bytes downloaded, and the total number of bytes, if known.
+    */
+    pub fn set_progress(&self, downloaded: u64, total: Option<u64>) {
+        if let Some(total) = total {
+            if self.inner.length() != Some(total) {
+                self.inner.set_length(total);
+                self.inner
+                    .set_style(new_download_style(PROGRESS_TEMPLATE_DOWNLOAD));
+            }
+        }
+        self.inner.set_position(downloaded);
+        if let Some(total) = total.filter(|total| *total > 0) {
+            self.credit.credit_fraction(downloaded.min(total), total);
+        }
+    }
+}
+
+impl Drop for CliDownloadBar {
+    fn drop(&mut self) {
+        self.credit.credit_fraction(1, 1);
+        CliEvent::download_finish(&self.spec, self.inner.position()).emit();
+        if self.clear_on_drop {
+            self.inner.finish_and_clear();
+        }
+    }
+}
+
+/**
+    A line for a single tool, created using [`CliProgressTracker::new_tool_bar`].
+
+    Displays the current stage of the tool, and its download progress while downloading.
+*/
+pub struct CliToolBar {
+    inner: ProgressBar,
+    spec: ToolSpec,
+    main: MainProgress,
+    download_weight: u64,
+    download: Mutex<Option<CliDownloadBar>>,
+}
+
+impl CliToolBar {
+    /**
+        Updates the stage displayed for the tool, such as `extracting`.
+    */
+    pub fn set_stage(&self, stage: &'static str) {
+        self.inner
+            .set_style(new_download_style(PROGRESS_TEMPLATE_TOOL));
+        self.inner.set_message(stage);
+    }
+
+    /**
+        Starts displaying download progress for the tool, on the same line.
+
+        The line goes back to displaying stages once the download bar
+        is dropped, and the next stage is set using [`CliToolBar::set_stage`].
+    */
+    pub fn new_download_bar(&self) -> CliDownloadBar {
+        self.inner
+            .set_style(new_download_style(PROGRESS_TEMPLATE_DOWNLOAD_UNKNOWN));
+        self.inner.set_position(0);
+        CliEvent::download_start(&self.spec).emit();
+        CliDownloadBar {
+            inner: self.inner.clone(),
+            spec: self.spec.clone(),
+            clear_on_drop: false,
+            credit: DownloadCredit::new(&self.main, self.download_weight),
+        }
+    }
+
+    /**
+        Reports progress of installing the tool using [`rokit::install`],
+        completing a subtask for each finished stage, and displaying download
+        progress on the same line while the tool is being downloaded.
+
+        The tracker that created this line must have [`INSTALL_SUBTASKS`] subtasks per task.
+    */
+    pub fn report_install(&self, progress: InstallProgress) {
+        let mut download = self.download.lock().unwrap();
+        match progress {
+            InstallProgress::FetchedRelease => {
+                self.main.increment(1);
+                self.set_stage("finding artifact");
+            }
+            InstallProgress::FoundArtifact => {
+                self.main.increment(1);
+                *download = Some(self.new_download_bar());
+            }
+            InstallProgress::Downloading { downloaded, total } => {
+                if let Some(download) = download.as_ref() {
+                    download.set_progress(downloaded, total);
+                }
+            }
+            InstallProgress::Downloaded => {
+                download.take();
+                self.set_stage("verifying");
+            }
+            InstallProgress::Verified => {
+                self.main.increment(1);
+                self.set_stage("extracting");
+            }
+            InstallProgress::Extracted => {
+                self.main.increment(1);
+                self.set_stage("installing");
+            }
+            InstallProgress::RunningPostInstall => self.set_stage("running post-install"),
+            InstallProgress::Installed => self.main.increment(1),
+        }
+    }
+}
+
+impl Drop for CliToolBar {
+    fn drop(&mut self) {
         self.inner.finish_and_clear();
     }
 }
 
+/*
+    Credits the progress of a single download towards the main progress bar,
+    proportionally to the number of bytes downloaded, up to the download weight.
+*/
+struct DownloadCredit {
+    main: MainProgress,
+    weight: u64,
+    credited: AtomicU64,
+}
+
+impl DownloadCredit {
+    fn new(main: &MainProgress, weight: u64) -> Self {
+        Self {
+            main: main.clone(),
+            weight,
+            credited: AtomicU64::new(0),
+        }
+    }
+
+    fn credit_fraction(&self, done: u64, total: u64) {
+        let target = self.weight * done / total;
+        let credited = self.credited.fetch_max(target, Ordering::SeqCst);
+        if target > credited {
+            self.main.increment(target - credited);
+        }
+    }
+}
+
+fn new_download_style(template: &str) -> ProgressStyle {
+    ProgressStyle::with_template(template)
+        .unwrap()
+        .progress_chars(PROGRESS_BAR_CHARACTERS)
+        .tick_chars(PROGRESS_BAR_TICKERS)
+}
+
 fn new_progress_style(num_tasks: usize, subtasks_per_task: usize) -> ProgressStyle {
     ProgressStyle::with_template(PROGRESS_TEMPLATE_DEFAULT)
         .unwrap()
@@ -145,7 +534,10 @@ fn new_progress_style(num_tasks: usize, subtasks_per_task: usize) -> ProgressSty
         .tick_chars(PROGRESS_BAR_TICKERS)
 }
 
+// NOTE: The progress bar must be added to the multi progress before it
+// starts ticking, otherwise it would draw even if the multi is hidden
 fn new_progress_bar(
+    multi: &MultiProgress,
     message: impl Into<String>,
     num_tasks: usize,
     subtasks_per_task: usize,
@@ -153,6 +545,7 @@ fn new_progress_bar(
     let pb = ProgressBar::new_spinner()
         .with_style(new_progress_style(num_tasks, subtasks_per_task))
         .with_message(message.into());
+    let pb = multi.add(pb);
 
     pb.enable_steady_tick(Duration::from_millis(50));
     pb.set_length((num_tasks * subtasks_per_task) as u64);
@@ -1,66 +1,149 @@
 use std::{
-    collections::BTreeSet,
-    io::{stderr, IsTerminal},
+    collections::{BTreeMap, BTreeSet},
+    env::var_os,
+    io::{stderr, stdin, IsTerminal},
 };
 
 use anyhow::{bail, Context, Result};
 use console::{style, Style};
 use dialoguer::theme::ColorfulTheme;
 use rokit::tool::{ToolId, ToolSpec};
+use semver::Version;
 use tokio::task::spawn_blocking;
 
-#[derive(Debug, Clone, Copy)]
-pub enum TrustPromptKind {
-    Install,
-    InstallMany,
-}
-
 pub async fn prompt_for_trust(tool_id: ToolId) -> Result<bool> {
-    spawn_blocking(move || prompt_for_install_trust_inner(TrustPromptKind::Install, &tool_id))
-        .await?
+    spawn_blocking(move || prompt_for_install_trust_inner(&tool_id)).await?
 }
 
 pub async fn prompt_for_trust_specs(tool_specs: Vec<ToolSpec>) -> Result<Vec<ToolSpec>> {
     spawn_blocking(move || {
-        if tool_specs.is_empty() {
-            Ok(Vec::new())
-        } else if tool_specs.len() == 1 {
-            println!("A tool is not yet trusted and needs your approval.");
-            let spec = tool_specs.first().unwrap();
-            if prompt_for_install_trust_inner(TrustPromptKind::Install, spec.id())? {
-                Ok(vec![spec.clone()])
+        let ids_to_prompt_for = tool_specs
+            .iter()
+            .map(|spec| spec.id().clone())
+            .collect::<BTreeSet<_>>();
+        let newly_trusted_ids = if ids_to_prompt_for.is_empty() {
+            BTreeSet::new()
+        } else if ids_to_prompt_for.len() == 1 {
+            eprintln!("A tool is not yet trusted and needs your approval.");
+            let id = ids_to_prompt_for.first().unwrap();
+            if prompt_for_install_trust_inner(id)? {
+                ids_to_prompt_for
             } else {
-                Ok(Vec::new())
+                BTreeSet::new()
             }
         } else {
-            println!(
-                "Some tools are not yet trusted and need your approval.\
-                \nYou will be prompted for each tool individually, and \
-                any tool you do not trust will not be installed."
-            );
-            let ids_to_prompt_for = tool_specs
-                .iter()
-                .map(|spec| spec.id().clone())
-                .collect::<BTreeSet<_>>();
-
-            let mut newly_trusted_ids = Vec::new();
-            for id in ids_to_prompt_for {
-                if prompt_for_install_trust_inner(TrustPromptKind::InstallMany, &id)? {
-                    newly_trusted_ids.push(id);
-                }
-            }
+            prompt_for_many_trust_inner(&ids_to_prompt_for)?
+        };
+        let newly_trusted_specs = tool_specs
+            .into_iter()
+            .filter(|spec| newly_trusted_ids.contains(spec.id()))
+            .collect();
+        Ok(newly_trusted_specs)
+    })
+    .await?
+}
 
-            let newly_trusted_specs = tool_specs
-                .into_iter()
-                .filter(|spec| newly_trusted_ids.contains(spec.id()))
-                .collect();
-            Ok(newly_trusted_specs)
+pub async fn prompt_for_repair(num_problems: usize) -> Result<bool> {
+    spawn_blocking(move || {
+        // Repairs are never made without confirmation, so if the terminal isn't
+        // interactive, the user needs to explicitly opt in using a flag instead
+        if !stderr().is_terminal() {
+            return Ok(false);
+        }
+
+        let repaired = dialoguer::Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(if num_problems == 1 {
+                String::from("Repair 1 problem automatically?")
+            } else {
+                format!("Repair {num_problems} problems automatically?")
+            })
+            .default(true)
+            .interact_opt()?
+            .unwrap_or(false);
+
+        Ok(repaired)
+    })
+    .await?
+}
+
+pub async fn prompt_for_removal(prompt: String) -> Result<bool> {
+    spawn_blocking(move || {
+        // Files are never removed without confirmation, so if the terminal isn't
+        // interactive, the user needs to explicitly opt in using a flag instead
+        if !stderr().is_terminal() {
+            return Ok(false);
+        }
+
+        let confirmed = dialoguer::Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .default(false)
+            .interact_opt()?
+            .unwrap_or(false);
+
+        Ok(confirmed)
+    })
+    .await?
+}
+
+pub async fn prompt_for_profile_change(prompt: String) -> Result<bool> {
+    spawn_blocking(move || {
+        // Shell profiles are never changed without confirmation, so if the terminal
+        // isn't interactive, the user needs to explicitly opt in using a flag instead
+        if !stderr().is_terminal() {
+            return Ok(false);
+        }
+
+        let confirmed = dialoguer::Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .default(true)
+            .interact_opt()?
+            .unwrap_or(false);
+
+        Ok(confirmed)
+    })
+    .await?
+}
+
+pub async fn prompt_for_changelog(version: Version) -> Result<bool> {
+    spawn_blocking(move || {
+        // Release notes are only offered when someone is there to answer, since
+        // this prompt is optional and must never block automated updates
+        if !is_interactive() {
+            return Ok(false);
+        }
+
+        let confirmed = dialoguer::Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Show the release notes for version {version}?"))
+            .default(false)
+            .interact_opt()?
+            .unwrap_or(false);
+
+        Ok(confirmed)
+    })
+    .await?
+}
+
+pub async fn prompt_for_token(provider_name: String) -> Result<String> {
+    spawn_blocking(move || {
+        // Tokens must never be read from a non-interactive terminal by accident,
+        // such as when piping, so the user must pass them explicitly instead
+        if !stderr().is_terminal() {
+            bail!(
+                "No token was given, and the terminal is not interactive.\
+                \nUse the `--token` flag to give a token for {provider_name}."
+            );
         }
+
+        let token = dialoguer::Password::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Paste your {provider_name} token"))
+            .interact()?;
+
+        Ok(token.trim().to_string())
     })
     .await?
 }
 
-fn prompt_for_install_trust_inner(kind: TrustPromptKind, tool_id: &ToolId) -> Result<bool> {
+fn prompt_for_install_trust_inner(tool_id: &ToolId) -> Result<bool> {
     let theme = ColorfulTheme {
         active_item_prefix: style("🔒 ".to_string()),
         prompt_style: Style::new(),
@@ -79,15 +162,136 @@ fn prompt_for_install_trust_inner(kind: TrustPromptKind, tool_id: &ToolId) -> Re
     // Since the terminal is interactive, ask the user
     // if they're sure they want to install this tool.
     let trusted = dialoguer::Confirm::with_theme(&theme)
-        .with_prompt(match kind {
-            TrustPromptKind::Install => format!("Trust and install {tool_id}?"),
-            TrustPromptKind::InstallMany => format!("Trust {tool_id}?"),
-        })
+        .with_prompt(format!("Trust and install {tool_id}?"))
         .interact_opt()?
-        .with_context(|| match kind {
-            TrustPromptKind::Install => format!("Exited without trusting tool {tool_id}"),
-            TrustPromptKind::InstallMany => String::from("Exited without trusting tools"),
-        })?;
+        .with_context(|| format!("Exited without trusting tool {tool_id}"))?;
 
     Ok(trusted)
 }
+
+#[derive(Debug, Clone, Copy)]
+enum TrustChoice {
+    All,
+    Owners,
+    Tools,
+    None,
+}
+
+/*
+    Asks the user which of several tools to trust, all at once - the user may
+    trust all or none of the tools, or choose either owners or individual tools,
+    where choosing an owner trusts every tool from that owner in the list.
+*/
+fn prompt_for_many_trust_inner(tool_ids: &BTreeSet<ToolId>) -> Result<BTreeSet<ToolId>> {
+    let by_owner = group_by_owner(tool_ids);
+
+    // Just like for single tools, trust is never given without
+    // an interactive terminal, and all untrusted tools are listed
+    if !stderr().is_terminal() {
+        bail!(
+            "The following tools have not been marked as trusted:\n{}\
+            \nRun `rokit install` in an interactive terminal to trust these tools, \
+            or use `--no-trust-check` to skip trust checks entirely.",
+            tool_ids
+                .iter()
+                .map(|id| format!("  - {id}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    eprintln!(
+        "Some tools are not yet trusted and need your approval.\
+        \nAny tool you do not trust will not be installed.\n"
+    );
+    for (owner, ids) in &by_owner {
+        eprintln!(
+            "  {} {}",
+            style(owner).bold(),
+            style(format!(
+                "({})",
+                ids.iter().map(ToolId::name).collect::<Vec<_>>().join(", ")
+            ))
+            .dim()
+        );
+    }
+    eprintln!();
+
+    let theme = ColorfulTheme::default();
+    let exited = || anyhow::anyhow!("Exited without trusting tools");
+    let mut choices = vec![(
+        format!("Trust all {} tools", tool_ids.len()),
+        TrustChoice::All,
+    )];
+    if by_owner.len() > 1 {
+        choices.push((String::from("Choose owners to trust"), TrustChoice::Owners));
+    }
+    choices.push((String::from("Choose tools to trust"), TrustChoice::Tools));
+    choices.push((String::from("Trust none"), TrustChoice::None));
+
+    let choice = dialoguer::Select::with_theme(&theme)
+        .with_prompt("Trust these tools?")
+        .items(&choices.iter().map(|(label, _)| label).collect::<Vec<_>>())
+        .default(0)
+        .interact_opt()?
+        .ok_or_else(exited)?;
+
+    let trusted = match choices[choice].1 {
+        TrustChoice::All => tool_ids.clone(),
+        TrustChoice::None => BTreeSet::new(),
+        TrustChoice::Owners => {
+            let owners = by_owner.keys().collect::<Vec<_>>();
+            let selected = dialoguer::MultiSelect::with_theme(&theme)
+                .with_prompt("Select owners to trust all of their tools (space to select)")
+                .items(&owners)
+                .interact_opt()?
+                .ok_or_else(exited)?;
+            selected
+                .into_iter()
+                .flat_map(|index| by_owner[owners[index]].iter().cloned())
+                .collect()
+        }
+        TrustChoice::Tools => {
+            let ids = tool_ids.iter().collect::<Vec<_>>();
+            let selected = dialoguer::MultiSelect::with_theme(&theme)
+                .with_prompt("Select tools to trust (space to select)")
+                .items(&ids)
+                .interact_opt()?
+                .ok_or_else(exited)?;
+            selected
+                .into_iter()
+                .map(|index| ids[index].clone())
+                .collect()
+        }
+    };
+
+    Ok(trusted)
+}
+
+/*
+    Groups tools by their owner, which is everything in the tool id except for the
+    name, so that owners with the same name on different hosts are kept apart.
+*/
+fn group_by_owner(tool_ids: &BTreeSet<ToolId>) -> BTreeMap<String, Vec<ToolId>> {
+    let mut by_owner = BTreeMap::<String, Vec<ToolId>>::new();
+    for id in tool_ids {
+        let id_string = id.to_string();
+        let owner = id_string
+            .rsplit_once('/')
+            .map_or(id.author(), |(owner, _)| owner);
+        by_owner
+            .entry(owner.to_string())
+            .or_default()
+            .push(id.clone());
+    }
+    by_owner
+}
+
+/*
+    Checks if the user can answer optional prompts - both stdin and stderr
+    must be terminals, and the process must not be running in CI, where
+    some providers allocate a pseudo-terminal even though nobody is there.
+*/
+fn is_interactive() -> bool {
+    stdin().is_terminal() && stderr().is_terminal() && var_os("CI").is_none()
+}
@@ -1,33 +1,55 @@
-use std::io::stderr;
+use std::{env::var, io::stderr};
 
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::EnvFilter;
 
+use super::init_colors;
+
 #[cfg(debug_assertions)]
 const FMT_PRETTY: bool = true;
 
 #[cfg(not(debug_assertions))]
 const FMT_PRETTY: bool = false;
 
-pub fn init(default_level_filter: LevelFilter) {
-    let tracing_env_filter = EnvFilter::builder()
-        .with_default_directive(default_level_filter.into())
-        .from_env_lossy()
-        // Adding the below extra directives will let us debug
-        // Rokit easier using RUST_LOG=debug or RUST_LOG=trace
-        .add_directive("reqwest=info".parse().unwrap())
-        .add_directive("rustls=info".parse().unwrap())
-        .add_directive("tokio_util=info".parse().unwrap())
-        .add_directive("goblin=info".parse().unwrap())
-        .add_directive("tower=info".parse().unwrap())
-        .add_directive("hyper=info".parse().unwrap())
-        .add_directive("h2=info".parse().unwrap());
+const LOG_FILTER_ENV_VAR: &str = "ROKIT_LOG";
+
+/*
+    Initializes tracing with the given default level filter.
+
+    Filter directives, using the same syntax as `RUST_LOG`, are read from
+    the given filter, the `ROKIT_LOG` environment variable, or `RUST_LOG`,
+    in that order - the first one that is set is used.
+*/
+pub fn init(default_level_filter: LevelFilter, filter: Option<&str>) {
+    let env_filter = filter
+        .map(ToString::to_string)
+        .or_else(|| var(LOG_FILTER_ENV_VAR).ok())
+        .filter(|directives| !directives.trim().is_empty());
+    let builder = EnvFilter::builder().with_default_directive(default_level_filter.into());
+    let tracing_env_filter = match env_filter {
+        // NOTE: Directives without a target replace the default level, while
+        // any other directives are used in addition to the default level
+        Some(directives) => builder.parse_lossy(format!("{default_level_filter},{directives}")),
+        None => builder.from_env_lossy(),
+    }
+    // Adding the below extra directives will let us debug
+    // Rokit easier using RUST_LOG=debug or RUST_LOG=trace
+    .add_directive("reqwest=info".parse().unwrap())
+    .add_directive("rustls=info".parse().unwrap())
+    .add_directive("tokio_util=info".parse().unwrap())
+    .add_directive("goblin=info".parse().unwrap())
+    .add_directive("tower=info".parse().unwrap())
+    .add_directive("hyper=info".parse().unwrap())
+    .add_directive("h2=info".parse().unwrap());
+
+    let ansi = init_colors();
 
     // Show the target module in the tracing output during development
     // so that we can track down issues and trace origins faster.
     tracing_subscriber::fmt()
         .with_env_filter(tracing_env_filter)
         .with_writer(stderr)
+        .with_ansi(ansi)
         .with_target(FMT_PRETTY)
         .without_time()
         .init();
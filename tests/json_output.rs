@@ -0,0 +1,49 @@
+use std::{path::Path, process::Command};
+
+use tempfile::tempdir;
+
+const MANIFEST: &str = r#"
+[tools]
+tool = "author/tool@1.0.0"
+"#;
+
+const COMMANDS: &[&[&str]] = &[
+    &["list"],
+    &["list", "--installed"],
+    &["tree"],
+    &["cache", "list"],
+    &["fmt", "--offline", "--check"],
+    &["trust", "--list"],
+    &["auth", "status", "--skip-verify"],
+    &["system-info"],
+];
+
+fn run_json(root: &Path, cwd: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_rokit"))
+        .args(["--output", "json"])
+        .args(args)
+        .current_dir(cwd)
+        .env("ROKIT_ROOT", root)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run rokit");
+    String::from_utf8(output.stdout).expect("stdout should be valid UTF-8")
+}
+
+#[test]
+fn json_output_only_writes_json_to_stdout() {
+    let root = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    std::fs::write(project.path().join("rokit.toml"), MANIFEST).unwrap();
+
+    for args in COMMANDS {
+        let stdout = run_json(root.path(), project.path(), args);
+        for line in stdout.lines().filter(|line| !line.trim().is_empty()) {
+            assert!(
+                serde_json::from_str::<serde_json::Value>(line).is_ok(),
+                "`rokit {}` wrote a non-JSON line to stdout: {line}",
+                args.join(" ")
+            );
+        }
+    }
+}